@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::exporters;
+use crate::models::{AppError, BatchFileResult, BatchReport, ExportOptions};
+use crate::parsers::{self, SUPPORTED_EXTENSIONS};
+use crate::storage;
+
+/// フォルダ内のBOMを一括で読み込み、指定した形式で出力する
+///
+/// サブフォルダも含めて走査し（出力フォルダ・シンボリックリンクのフォルダは除く）、出力先には同じフォルダ構成で
+/// `元のファイル名.出力形式の拡張子` として書き込む。
+/// 1つのファイルの失敗では中断せず、ファイルごとの結果を返す。
+///
+/// # 引数
+/// * `input_dir` - 入力フォルダ
+/// * `output_dir` - 出力フォルダ（存在しない場合は作成）
+/// * `target_format` - 出力形式（"csv", "eco", "ccf" など `export_bom_file` と同じ）
+///
+/// # 戻り値
+/// ファイルごとの結果と件数（出力形式・入力フォルダが不正な場合はエラー）
+pub fn batch_convert(
+    input_dir: &Path,
+    output_dir: &Path,
+    target_format: &str,
+) -> Result<BatchReport, AppError> {
+    let extension = exporters::file_extension(target_format).ok_or_else(|| {
+        AppError::unsupported_format(format!("未対応のエクスポート形式です: {}", target_format))
+    })?;
+    if !input_dir.is_dir() {
+        return Err(AppError::not_found(format!(
+            "入力フォルダが見つかりません: {}",
+            input_dir.display()
+        )));
+    }
+
+    // 出力フォルダが入力フォルダの中にある場合、前回の出力を読み込まないように除く
+    let skip_dir = output_dir.canonicalize().ok();
+    let mut input_files = Vec::new();
+    collect_files(input_dir, skip_dir.as_deref(), &mut input_files)?;
+    input_files.sort();
+
+    let options = ExportOptions {
+        format: target_format.to_string(),
+        ..ExportOptions::default()
+    };
+    let mut report = BatchReport::default();
+    let mut written: HashSet<PathBuf> = HashSet::new();
+
+    for input_path in input_files {
+        let supported = input_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false);
+        if !supported {
+            report.skipped += 1;
+            report.files.push(BatchFileResult {
+                input_path: input_path.display().to_string(),
+                output_path: None,
+                severity: "info".to_string(),
+                message: Some("対応していないファイル形式のためスキップしました。".to_string()),
+                warning_count: 0,
+            });
+            continue;
+        }
+
+        let relative = input_path.strip_prefix(input_dir).unwrap_or(&input_path);
+        let output_path = output_dir.join(relative).with_extension(extension);
+
+        let result = if output_path == input_path {
+            Err(AppError::validation("出力先が入力ファイルと同じです。"))
+        } else if !written.insert(output_path.clone()) {
+            Err(AppError::validation(format!(
+                "出力先 {} が他のファイルの出力と重複しています。",
+                output_path.display()
+            )))
+        } else {
+            convert_file(&input_path, &output_path, &options)
+        };
+
+        match result {
+            Ok(warning_count) => {
+                report.succeeded += 1;
+                report.files.push(BatchFileResult {
+                    input_path: input_path.display().to_string(),
+                    output_path: Some(output_path.display().to_string()),
+                    severity: "success".to_string(),
+                    message: None,
+                    warning_count,
+                });
+            }
+            Err(err) => {
+                report.failed += 1;
+                report.files.push(BatchFileResult {
+                    input_path: input_path.display().to_string(),
+                    output_path: None,
+                    severity: "error".to_string(),
+                    message: Some(err.to_string()),
+                    warning_count: 0,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// 1ファイルを読み込んで出力し、読み込み時の警告の件数を返す
+fn convert_file(
+    input_path: &Path,
+    output_path: &Path,
+    options: &ExportOptions,
+) -> Result<usize, AppError> {
+    let parse = parsers::parse_bom_file(input_path.display().to_string())?;
+    let content = exporters::export_bom_bytes(&parse, options)?;
+
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| AppError::io(format!("出力フォルダの作成に失敗しました: {err}")))?;
+    }
+    storage::atomic::write_atomic(output_path, &content)
+        .map_err(|err| AppError::io(format!("ファイルの書き込みに失敗しました: {err}")))?;
+
+    Ok(parse.errors.len())
+}
+
+/// フォルダ内のファイルを再帰的に列挙
+///
+/// `skip_dir`（正規化済みのパス）のフォルダと、シンボリックリンクのフォルダはたどらない
+/// （リンクの循環や入力フォルダ外のファイルを読み込まないため）
+fn collect_files(
+    dir: &Path,
+    skip_dir: Option<&Path>,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), AppError> {
+    let entries = fs::read_dir(dir).map_err(|err| {
+        AppError::io(format!(
+            "フォルダの読み込みに失敗しました（{}）: {err}",
+            dir.display()
+        ))
+    })?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let canonical = path.canonicalize().ok();
+            if skip_dir.is_some() && canonical.as_deref() == skip_dir {
+                continue;
+            }
+            collect_files(&path, skip_dir, files)?;
+        } else if file_type.is_symlink() && path.is_dir() {
+            continue;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("bomsync_batch_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_batch_convert_collects_results_per_file() {
+        let input_dir = temp_dir("input");
+        let output_dir = temp_dir("output");
+        fs::write(
+            input_dir.join("a.eco"),
+            "*PADS-ECO*\n*PART*\nC1 GRM155\nR1 RC0402\n*END*\n",
+        )
+        .unwrap();
+        fs::create_dir_all(input_dir.join("sub")).unwrap();
+        fs::write(input_dir.join("sub/b.pws"), "74HC08:U1;\n").unwrap();
+        fs::write(input_dir.join("broken.xlsx"), "not an excel file").unwrap();
+        fs::write(input_dir.join("notes.md"), "# memo").unwrap();
+
+        let report = batch_convert(&input_dir, &output_dir, "csv").unwrap();
+
+        assert_eq!(report.succeeded, 2);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.skipped, 1);
+        let severities: Vec<&str> = report.files.iter().map(|f| f.severity.as_str()).collect();
+        assert_eq!(severities, vec!["success", "error", "info", "success"]);
+
+        let content = fs::read_to_string(output_dir.join("a.csv")).unwrap();
+        assert!(content.contains("C1,GRM155"));
+        let content = fs::read_to_string(output_dir.join("sub/b.csv")).unwrap();
+        assert!(content.contains("U1,74HC08"));
+
+        assert!(batch_convert(&input_dir, &output_dir, "pdf").is_err());
+    }
+
+    #[test]
+    fn test_batch_convert_skips_output_dir_inside_input() {
+        let input_dir = temp_dir("nested_input");
+        fs::write(
+            input_dir.join("a.eco"),
+            "*PADS-ECO*\n*PART*\nC1 GRM155\n*END*\n",
+        )
+        .unwrap();
+        let output_dir = input_dir.join("out");
+
+        let first = batch_convert(&input_dir, &output_dir, "eco").unwrap();
+        assert_eq!(first.files.len(), 1);
+        // 2回目も前回の出力（out/a.eco）は入力として扱わない
+        let second = batch_convert(&input_dir, &output_dir, "eco").unwrap();
+        assert_eq!(second.files.len(), 1);
+        assert!(!output_dir.join("out").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_batch_convert_does_not_follow_symlinked_dirs() {
+        let input_dir = temp_dir("symlink_input");
+        let output_dir = temp_dir("symlink_output");
+        fs::write(
+            input_dir.join("a.eco"),
+            "*PADS-ECO*\n*PART*\nC1 GRM155\n*END*\n",
+        )
+        .unwrap();
+        // 自身を指すリンク（たどると循環する）
+        std::os::unix::fs::symlink(&input_dir, input_dir.join("loop")).unwrap();
+
+        let report = batch_convert(&input_dir, &output_dir, "csv").unwrap();
+        assert_eq!(report.files.len(), 1);
+        assert!(report.files[0].input_path.ends_with("a.eco"));
+    }
+}
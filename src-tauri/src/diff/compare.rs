@@ -1,6 +1,24 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::models::{DiffRow, ParseResult};
+use super::KeyNormalizer;
+use crate::models::{
+    AppError, CompareOptions, CompareResult, DiffRow, FieldChange, ParseResult, MATCH_KEY_SEPARATOR,
+};
+use crate::utils::eng::{parse_eng_value, values_equivalent};
+use crate::utils::header::{matches_quantity_header, normalize_header};
+use crate::utils::progress::{OperationContext, ProgressReporter};
+use crate::utils::text::normalize_width;
+
+/// 梱包コードとみなす末尾の英字の最大文字数
+const MAX_PACKAGING_SUFFIX_LEN: usize = 3;
+
+/// 区切り文字なしで数字の直後に付く梱包コード（"LM358DR" の "DR" など）
+///
+/// 改版・特性を表す英字（"LM358A" の "A" など）を梱包コードとみなさないよう、既知のものに限る
+const PACKAGING_SUFFIXES: &[&str] = &["R", "T", "TR", "DR", "CT", "PT", "RL", "TL"];
+
+/// 許容差を指定したときに数値として比較する実装位置の役割
+const PLACEMENT_ROLES: [&str; 3] = ["pos_x", "pos_y", "rotation"];
 
 /// 2つのBOMを比較して差分を検出
 ///
@@ -22,18 +40,163 @@ use crate::models::{DiffRow, ParseResult};
 /// - "added": Bにのみ存在
 /// - "removed": Aにのみ存在
 /// - "modified": 両方に存在するが内容が異なる
+/// - "likely_same": 部品型番の梱包コードのみが異なる（`fuzzy_part_threshold` 指定時）
+/// - "comment_changed": 両方に存在し、コメント列のみが異なる（優先度の低い変更）
+/// - "dnp_changed": 実装・未実装（DNP）が切り替わった（ほかの列の変更より優先）
+/// - "rotated": 実装位置の回転角のみが異なる（`position_tolerance` 指定時）
+/// - "moved_xy": 実装位置の座標（と回転角）のみが異なる（`position_tolerance` 指定時）
 /// - "unchanged": 両方に存在し内容が同一
 pub fn compare_boms(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<DiffRow> {
+    compare_boms_with_options(parse_a, parse_b, &CompareOptions::default())
+}
+
+/// オプションを指定して2つのBOMを比較
+///
+/// # 引数
+/// * `parse_a` - データセットAのパース結果
+/// * `parse_b` - データセットBのパース結果
+/// * `options` - 比較オプション
+///
+/// # 戻り値
+/// 差分行のリスト（ステータス付き）
+pub fn compare_boms_with_options(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    options: &CompareOptions,
+) -> Vec<DiffRow> {
+    // 中断指示がないため失敗しない
+    compare_boms_with_progress(parse_a, parse_b, options, &OperationContext::default())
+        .unwrap_or_default()
+}
+
+/// 進捗を通知しながら2つのBOMを比較
+///
+/// 比較結果は `compare_boms_with_options` と同じ。
+/// 進捗はBの行の索引作成とAの行の比較を合わせた行数で通知する
+///
+/// # 戻り値
+/// 差分行のリスト（中断が指示された場合は `AppError::cancelled()`）
+pub fn compare_boms_with_progress(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    options: &CompareOptions,
+    context: &OperationContext,
+) -> Result<Vec<DiffRow>, AppError> {
+    compare_keyed_rows(parse_a, parse_b, options, context, None)
+}
+
+/// 一部のReferenceの行のみを比較し直して、前回の差分結果を更新
+///
+/// 編集中のBOMを比較し直す場合に、全体を比較せずに結果を更新する。
+/// `changed_refs` のReferenceの行（編集前・編集後の両方のReferenceを渡す）と、
+/// それらと同じ対応付けキーの行のみを比較し、それ以外の行は前回の結果をそのまま使う。
+/// 影響する行の結果は `compare_boms_with_options` と同じになる。
+///
+/// 行の追加・削除で行インデックスが変わった場合は前回の結果を使えないため、全体を比較すること
+///
+/// # 引数
+/// * `previous` - 前回の差分結果（同じ行の並びのBOMを同じオプションで比較したもの）
+/// * `parse_a` - データセットA
+/// * `parse_b` - データセットB
+/// * `changed_refs` - 編集された行のReference
+/// * `options` - 比較オプション
+///
+/// # 戻り値
+/// 更新後の差分行のリスト（Aの行順、続けてBのみの行をBの行順）
+pub fn update_diff(
+    previous: &[DiffRow],
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    changed_refs: &[String],
+    options: &CompareOptions,
+) -> Vec<DiffRow> {
+    let key_roles = options.match_key.roles();
+    let normalizer = KeyNormalizer::from_compare_options(options);
+    let changed: HashSet<String> = changed_refs
+        .iter()
+        .map(|reference| normalizer.normalize(reference))
+        .filter(|reference| !reference.is_empty())
+        .collect();
+    if changed.is_empty() {
+        return previous.to_vec();
+    }
+
+    // 編集された行と同じキーの行は対応付けが変わりうるため、まとめて比較し直す
+    let row_key = |parse: &ParseResult, idx: usize| {
+        normalizer.normalize_key(&parse.get_match_key(idx, &key_roles))
+    };
+    let mut selected: HashSet<String> = HashSet::new();
+    for parse in [parse_a, parse_b] {
+        for idx in 0..parse.rows.len() {
+            if changed.contains(&normalizer.normalize(&parse.get_ref(idx))) {
+                selected.insert(row_key(parse, idx));
+            }
+        }
+    }
+    selected.remove("");
+
+    let affected = |diff: &DiffRow| {
+        changed.contains(&normalizer.normalize(&diff.ref_value))
+            || diff
+                .a_index
+                .filter(|&idx| idx < parse_a.rows.len())
+                .is_some_and(|idx| selected.contains(&row_key(parse_a, idx)))
+            || diff
+                .b_index
+                .filter(|&idx| idx < parse_b.rows.len())
+                .is_some_and(|idx| selected.contains(&row_key(parse_b, idx)))
+    };
+    let mut diffs: Vec<DiffRow> = previous
+        .iter()
+        .filter(|diff| !affected(diff))
+        .cloned()
+        .collect();
+    // 中断指示がないため失敗しない
+    diffs.extend(
+        compare_keyed_rows(
+            parse_a,
+            parse_b,
+            options,
+            &OperationContext::default(),
+            Some(&selected),
+        )
+        .unwrap_or_default(),
+    );
+    // 全体を比較した場合と同じく、Aの行順に並べてからBのみの行をBの行順で続ける
+    diffs.sort_by_key(|diff| (diff.a_index.is_none(), diff.a_index, diff.b_index));
+    diffs
+}
+
+/// 対応付けキーで行を対応付けて比較（`selected` 指定時はそのキーの行のみ）
+fn compare_keyed_rows(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    options: &CompareOptions,
+    context: &OperationContext,
+    selected: Option<&HashSet<String>>,
+) -> Result<Vec<DiffRow>, AppError> {
+    let is_selected = |key: &str| selected.is_none_or(|keys| keys.contains(key));
+    let total = parse_b.rows.len() + parse_a.rows.len();
+    let progress = ProgressReporter::new("compare_boms", total, context);
+
     // ------------------------------------------------------------------------
     // ステップ1: Reference値でインデックスマッピングを作成
     // ------------------------------------------------------------------------
 
-    // データセットB: Reference → 行インデックス
+    let key_roles = options.match_key.roles();
+    let normalizer = KeyNormalizer::from_compare_options(options);
+    let schema = SchemaComparison::new(parse_a, parse_b, options);
+
+    // データセットB: 対応付けキー（比較キー） → 行インデックス
     let mut map_b: HashMap<String, VecDeque<usize>> = HashMap::new();
     for (idx, _) in parse_b.rows.iter().enumerate() {
-        let ref_value = parse_b.get_ref(idx);
-        if !ref_value.is_empty() {
-            map_b.entry(ref_value).or_default().push_back(idx);
+        progress.tick(idx)?;
+        let key = parse_b.get_match_key(idx, &key_roles);
+        if !key.is_empty() {
+            let key = normalizer.normalize_key(&key);
+            if is_selected(&key) {
+                map_b.entry(key).or_default().push_back(idx);
+            }
         }
     }
 
@@ -44,15 +207,31 @@ pub fn compare_boms(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<DiffRow
     // ------------------------------------------------------------------------
 
     for (idx_a, _) in parse_a.rows.iter().enumerate() {
-        let ref_a = parse_a.get_ref(idx_a);
-        if ref_a.is_empty() {
-            continue; // Referenceが空の行はスキップ
+        progress.tick(parse_b.rows.len() + idx_a)?;
+        let key_a = parse_a.get_match_key(idx_a, &key_roles);
+        if key_a.is_empty() {
+            continue; // キーが空の行はスキップ
         }
+        if !is_selected(&normalizer.normalize_key(&key_a)) {
+            continue;
+        }
+        let ref_a = display_ref(parse_a, idx_a, &key_a);
 
-        if let Some(queue) = map_b.get_mut(&ref_a) {
+        if let Some(queue) = map_b.get_mut(&normalizer.normalize_key(&key_a)) {
             if let Some(idx_b) = queue.pop_front() {
                 // 両方に存在 → 内容を比較
-                let (status, changed_columns) = compare_rows(parse_a, idx_a, parse_b, idx_b);
+                let (status, changed_columns, changes) = compare_rows(
+                    parse_a,
+                    idx_a,
+                    parse_b,
+                    idx_b,
+                    options,
+                    &normalizer,
+                    &schema,
+                );
+                if status == "unchanged" && !options.include_unchanged {
+                    continue;
+                }
 
                 diffs.push(DiffRow {
                     status,
@@ -60,6 +239,7 @@ pub fn compare_boms(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<DiffRow
                     b_index: Some(idx_b),
                     ref_value: ref_a,
                     changed_columns,
+                    changes,
                 });
             } else {
                 // 対応するBの行が残っていない → 削除扱い
@@ -69,6 +249,7 @@ pub fn compare_boms(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<DiffRow
                     b_index: None,
                     ref_value: ref_a,
                     changed_columns: vec![],
+                    changes: vec![],
                 });
             }
         } else {
@@ -79,27 +260,270 @@ pub fn compare_boms(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<DiffRow
                 b_index: None,
                 ref_value: ref_a,
                 changed_columns: vec![],
+                changes: vec![],
             });
         }
     }
 
+    progress.tick(total)?;
+
     // ------------------------------------------------------------------------
     // ステップ3: データセットBのみに存在する行（追加）
     // ------------------------------------------------------------------------
 
-    for (ref_value, mut indices) in map_b.into_iter() {
-        while let Some(idx_b) = indices.pop_front() {
-            diffs.push(DiffRow {
-                status: "added".to_string(),
-                a_index: None,
-                b_index: Some(idx_b),
-                ref_value: ref_value.clone(),
-                changed_columns: vec![],
-            });
+    // 結果が毎回同じ順序になるよう、Bの行順に並べる
+    let mut added: Vec<usize> = map_b.into_values().flatten().collect();
+    added.sort_unstable();
+    for idx_b in added {
+        // 表示用には比較キーではなく元の表記を返す
+        let key_b = parse_b.get_match_key(idx_b, &key_roles);
+        diffs.push(DiffRow {
+            status: "added".to_string(),
+            a_index: None,
+            b_index: Some(idx_b),
+            ref_value: display_ref(parse_b, idx_b, &key_b),
+            changed_columns: vec![],
+            changes: vec![],
+        });
+    }
+
+    Ok(diffs)
+}
+
+/// 列構成を確認してから2つのBOMを比較
+///
+/// 比較結果は `compare_boms_with_progress` と同じ。片方のBOMにしかない役割や、
+/// 列構成の違いで列の位置による比較ができない場合は、その内容を警告として返す
+///
+/// # 戻り値
+/// 差分行のリストと列構成の警告（中断が指示された場合は `AppError::cancelled()`）
+pub fn compare_boms_with_report(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    options: &CompareOptions,
+    context: &OperationContext,
+) -> Result<CompareResult, AppError> {
+    let warnings = check_schema_compatibility(parse_a, parse_b);
+    let diffs = compare_boms_with_progress(parse_a, parse_b, options, context)?;
+    Ok(CompareResult { diffs, warnings })
+}
+
+/// 2つのBOMの列構成の違いを確認
+///
+/// # 戻り値
+/// 片方のBOMにしかない役割ごとの警告と、列の位置で比較できない場合の警告
+pub fn check_schema_compatibility(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<String> {
+    let roles_a = present_roles(parse_a);
+    let roles_b = present_roles(parse_b);
+    let mut warnings = Vec::new();
+
+    for (roles, others, side, other_side) in [
+        (&roles_a, &roles_b, "A", "B"),
+        (&roles_b, &roles_a, "B", "A"),
+    ] {
+        for role in roles.iter().filter(|role| !others.contains(role)) {
+            warnings.push(format!(
+                "BOM {side}には{}列がありますが、BOM {other_side}にはないため比較しません。",
+                role_label(role)
+            ));
         }
     }
 
-    diffs
+    if !SchemaComparison::new(parse_a, parse_b, &CompareOptions::default()).same_layout {
+        warnings.push(
+            "BOM AとBOM Bの列構成が異なるため、両方にある役割の列のみを比較します。".to_string(),
+        );
+    }
+    warnings
+}
+
+/// 2つのBOMの列構成（行の比較方法の決定に使う）
+struct SchemaComparison {
+    /// 列数と、両方にある役割の列が一致し、列の位置で比較できるか
+    same_layout: bool,
+    /// 両方のBOMにある役割（名前順）
+    shared_roles: Vec<String>,
+    /// 数値として比較する数量列（A・Bの列インデックス、部品型番で対応付ける場合のみ）
+    quantity_columns: Option<(usize, usize)>,
+    /// 全列の比較から除外する列のインデックス（`CompareOptions::ignore_columns`）
+    ignored_columns: HashSet<usize>,
+}
+
+impl SchemaComparison {
+    fn new(parse_a: &ParseResult, parse_b: &ParseResult, options: &CompareOptions) -> Self {
+        let roles_b = present_roles(parse_b);
+        let shared_roles: Vec<String> = present_roles(parse_a)
+            .into_iter()
+            .filter(|role| roles_b.contains(role))
+            .collect();
+        // 末尾の空の列（Excelで書式だけ設定された列など）は列構成の違いとみなさない。
+        // 値のある列数が異なっても、その範囲のヘッダー名が同じであれば同じ列構成とする
+        let (count_a, count_b) = (
+            effective_column_count(parse_a),
+            effective_column_count(parse_b),
+        );
+        let header = |parse: &ParseResult, idx: usize| {
+            parse.headers.get(idx).map(|name| normalize_header(name))
+        };
+        let same_columns = count_a == count_b
+            || (0..count_a.max(count_b)).all(|idx| header(parse_a, idx) == header(parse_b, idx));
+        let same_layout = same_columns
+            && shared_roles
+                .iter()
+                .all(|role| parse_a.column_roles.get(role) == parse_b.column_roles.get(role));
+        // 部品型番ごとに数量を集計した購買用BOMでは、数量の違いが主な差分になる
+        let quantity_columns = if options
+            .match_key
+            .roles()
+            .iter()
+            .any(|role| role == "part_no")
+        {
+            quantity_column(parse_a).zip(quantity_column(parse_b))
+        } else {
+            None
+        };
+        Self {
+            same_layout,
+            shared_roles,
+            quantity_columns,
+            ignored_columns: ignored_column_indices(parse_a, parse_b, &options.ignore_columns),
+        }
+    }
+
+    fn has_role(&self, role: &str) -> bool {
+        self.shared_roles.iter().any(|shared| shared == role)
+    }
+}
+
+/// 末尾の、全ての行で空欄の列を除いた列数
+fn effective_column_count(parse: &ParseResult) -> usize {
+    let mut count = parse.column_count();
+    while count > 0
+        && !parse.rows.iter().any(|row| {
+            row.get(count - 1)
+                .is_some_and(|value| !value.trim().is_empty())
+        })
+    {
+        count -= 1;
+    }
+    count
+}
+
+/// 除外する列（列IDまたはヘッダー名）をインデックスに変換
+///
+/// ヘッダー名はA・Bのどちらかで一致すれば除外する（前後の空白・大文字小文字は区別しない）
+fn ignored_column_indices(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    ignore_columns: &[String],
+) -> HashSet<usize> {
+    let mut ignored = HashSet::new();
+    for column in ignore_columns {
+        let column = column.trim();
+        if column.is_empty() {
+            continue;
+        }
+        if let Some(idx) = column
+            .strip_prefix("col-")
+            .and_then(|num| num.parse::<usize>().ok())
+        {
+            ignored.insert(idx);
+            continue;
+        }
+        let normalized = normalize_header(column);
+        for parse in [parse_a, parse_b] {
+            ignored.extend(
+                parse
+                    .headers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, header)| normalize_header(header) == normalized)
+                    .map(|(idx, _)| idx),
+            );
+        }
+    }
+    ignored
+}
+
+/// 列が割り当てられている役割（名前順）
+fn present_roles(parse: &ParseResult) -> Vec<String> {
+    let mut roles: Vec<String> = parse
+        .column_roles
+        .iter()
+        .filter(|(_, col_ids)| !col_ids.is_empty())
+        .map(|(role, _)| role.clone())
+        .collect();
+    roles.sort();
+    roles
+}
+
+/// 数量列のインデックス（役割 "quantity" の列、なければ数量らしいヘッダーの列）
+fn quantity_column(parse: &ParseResult) -> Option<usize> {
+    parse
+        .get_column_indices("quantity")
+        .first()
+        .copied()
+        .or_else(|| {
+            parse
+                .headers
+                .iter()
+                .position(|header| matches_quantity_header(&normalize_header(header)))
+        })
+}
+
+/// 数量が等しいか（"1,000" と "1000" のように数値として等しい場合も含む）
+fn quantities_equal(quantity_a: &str, quantity_b: &str) -> bool {
+    let parse = |value: &str| value.trim().replace(',', "").parse::<f64>().ok();
+    match (parse(quantity_a), parse(quantity_b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => quantity_a.trim() == quantity_b.trim(),
+    }
+}
+
+/// 座標（"12.5"、"12.5mm"）が許容差の範囲で等しいか（数値でない場合は文字列として比較）
+fn coordinates_equal(value_a: &str, value_b: &str, tolerance: f64) -> bool {
+    match (parse_placement(value_a), parse_placement(value_b)) {
+        (Some(a), Some(b)) => (a - b).abs() <= tolerance,
+        _ => value_a.trim() == value_b.trim(),
+    }
+}
+
+/// 回転角が許容差の範囲で等しいか（360度の違いは同じ向きとみなす: "270" と "-90"）
+fn angles_equal(value_a: &str, value_b: &str, tolerance: f64) -> bool {
+    match (parse_placement(value_a), parse_placement(value_b)) {
+        (Some(a), Some(b)) => {
+            let diff = (a - b).rem_euclid(360.0);
+            diff.min(360.0 - diff) <= tolerance
+        }
+        _ => value_a.trim() == value_b.trim(),
+    }
+}
+
+/// 実装位置の値を数値に変換（末尾の単位 "mm"・"deg"・"°" は除く）
+fn parse_placement(value: &str) -> Option<f64> {
+    let lower = value.trim().to_ascii_lowercase();
+    let number = ["mm", "deg", "°"]
+        .iter()
+        .find_map(|unit| lower.strip_suffix(unit))
+        .unwrap_or(&lower);
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+/// 警告に使う役割の表示名
+fn role_label(role: &str) -> &str {
+    match role {
+        "ref" => "Reference",
+        "part_no" => "部品型番",
+        "manufacturer" => "メーカー",
+        "value" => "Value",
+        "comment" => "コメント",
+        "dnp" => "DNP（未実装）",
+        other => other,
+    }
 }
 
 /// 2つの行を比較して差分を検出
@@ -109,15 +533,21 @@ pub fn compare_boms(parse_a: &ParseResult, parse_b: &ParseResult) -> Vec<DiffRow
 /// * `idx_a` - データセットAの行インデックス
 /// * `parse_b` - データセットB
 /// * `idx_b` - データセットBの行インデックス
+/// * `options` - 比較オプション
+/// * `normalizer` - 比較値の正規化
+/// * `schema` - 2つのBOMの列構成（片方にしかない役割は比較しない）
 ///
 /// # 戻り値
-/// (ステータス, 変更された列IDのリスト)
+/// (ステータス, 変更された列IDのリスト, 変更された列ごとの値)
 fn compare_rows(
     parse_a: &ParseResult,
     idx_a: usize,
     parse_b: &ParseResult,
     idx_b: usize,
-) -> (String, Vec<String>) {
+    options: &CompareOptions,
+    normalizer: &KeyNormalizer,
+    schema: &SchemaComparison,
+) -> (String, Vec<String>, Vec<FieldChange>) {
     let mut changed_columns = Vec::new();
 
     // ------------------------------------------------------------------------
@@ -127,7 +557,11 @@ fn compare_rows(
     let part_no_a = parse_a.get_part_no(idx_a);
     let part_no_b = parse_b.get_part_no(idx_b);
 
-    if part_no_a != part_no_b {
+    let part_no_key_a = normalizer.normalize(&part_no_a);
+    let part_no_key_b = normalizer.normalize(&part_no_b);
+    let part_no_changed = schema.has_role("part_no") && part_no_key_a != part_no_key_b;
+
+    if part_no_changed {
         // Part_No列が変更された
         if let Some(col_ids) = parse_a.column_roles.get("part_no") {
             changed_columns.extend(col_ids.clone());
@@ -137,7 +571,9 @@ fn compare_rows(
     let manufacturer_a = parse_a.get_manufacturer(idx_a);
     let manufacturer_b = parse_b.get_manufacturer(idx_b);
 
-    if manufacturer_a != manufacturer_b {
+    if schema.has_role("manufacturer")
+        && normalizer.normalize(&manufacturer_a) != normalizer.normalize(&manufacturer_b)
+    {
         if let Some(col_ids) = parse_a.column_roles.get("manufacturer") {
             changed_columns.extend(col_ids.clone());
         }
@@ -147,28 +583,93 @@ fn compare_rows(
     // 全列の比較（より詳細な差分検出）
     // ------------------------------------------------------------------------
 
-    let row_a = &parse_a.rows[idx_a];
-    let row_b = &parse_b.rows[idx_b];
-
-    let min_len = row_a.len().min(row_b.len());
-
-    for col_idx in 0..min_len {
-        let val_a = row_a.get(col_idx).map(|s| s.trim()).unwrap_or("");
-        let val_b = row_b.get(col_idx).map(|s| s.trim()).unwrap_or("");
-
-        if val_a != val_b {
-            let col_id = format!("col-{}", col_idx);
+    if schema.same_layout {
+        for col_id in compare_columns(parse_a, idx_a, parse_b, idx_b, options, normalizer, schema) {
             // 既に記録されている列はスキップ
             if !changed_columns.contains(&col_id) {
                 changed_columns.push(col_id);
             }
         }
+    } else {
+        // 列の位置が対応しないため、両方にある役割の値のみを比較
+        for role in &schema.shared_roles {
+            if role == "part_no" || role == "manufacturer" {
+                continue;
+            }
+            let value_a = parse_a.get_values(idx_a, role).join(", ");
+            let value_b = parse_b.get_values(idx_b, role).join(", ");
+            let equal = if role == "value" && options.normalize_eng_values {
+                eng_values_equal(&value_a, &value_b, normalizer)
+            } else {
+                normalizer.normalize(value_a.trim()) == normalizer.normalize(value_b.trim())
+            };
+            if !equal {
+                for col_id in parse_a.column_roles.get(role).into_iter().flatten() {
+                    if !changed_columns.contains(col_id) {
+                        changed_columns.push(col_id.clone());
+                    }
+                }
+            }
+        }
     }
 
-    // 列数が異なる場合も変更とみなす
-    if row_a.len() != row_b.len() {
-        for col_idx in min_len..row_a.len().max(row_b.len()) {
-            changed_columns.push(format!("col-{}", col_idx));
+    // 数量は数値として比較（列の位置による比較の結果を置き換える）
+    if let Some((qty_a, qty_b)) = schema
+        .quantity_columns
+        .filter(|(qty_a, _)| !schema.ignored_columns.contains(qty_a))
+    {
+        let col_id = format!("col-{qty_a}");
+        changed_columns.retain(|changed| *changed != col_id);
+        let cell = |parse: &ParseResult, idx: usize, col_idx: usize| {
+            parse.rows[idx].get(col_idx).cloned().unwrap_or_default()
+        };
+        if !quantities_equal(&cell(parse_a, idx_a, qty_a), &cell(parse_b, idx_b, qty_b)) {
+            changed_columns.push(col_id);
+        }
+    }
+
+    // 未実装（DNP）の指定は表記（"x" と "DNP" など）ではなく、実装するかどうかで比較
+    let mut dnp_changed = false;
+    if schema.has_role("dnp") {
+        let col_ids = parse_a.column_roles.get("dnp").cloned().unwrap_or_default();
+        changed_columns.retain(|changed| !col_ids.contains(changed));
+        dnp_changed = parse_a.is_dnp(idx_a) != parse_b.is_dnp(idx_b);
+        if dnp_changed {
+            changed_columns.extend(col_ids);
+        }
+    }
+
+    // 実装位置は許容差の範囲で数値として比較（両方のBOMに座標・回転の役割がある場合のみ）
+    let mut position_columns = Vec::new();
+    let mut rotation_columns = Vec::new();
+    if let Some(tolerance) = options.position_tolerance {
+        for role in PLACEMENT_ROLES {
+            if !schema.has_role(role) {
+                continue;
+            }
+            let col_ids = parse_a.column_roles.get(role).cloned().unwrap_or_default();
+            changed_columns.retain(|changed| !col_ids.contains(changed));
+            let value = |parse: &ParseResult, idx: usize| {
+                parse
+                    .get_values(idx, role)
+                    .first()
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let (value_a, value_b) = (value(parse_a, idx_a), value(parse_b, idx_b));
+            let equal = if role == "rotation" {
+                angles_equal(&value_a, &value_b, tolerance)
+            } else {
+                coordinates_equal(&value_a, &value_b, tolerance)
+            };
+            if !equal {
+                changed_columns.extend(col_ids.iter().cloned());
+            }
+            if role == "rotation" {
+                rotation_columns = col_ids;
+            } else {
+                position_columns.extend(col_ids);
+            }
         }
     }
 
@@ -176,19 +677,242 @@ fn compare_rows(
     // ステータスを決定
     // ------------------------------------------------------------------------
 
+    let comment_columns = parse_a
+        .column_roles
+        .get("comment")
+        .cloned()
+        .unwrap_or_default();
+    let part_no_columns = parse_a
+        .column_roles
+        .get("part_no")
+        .cloned()
+        .unwrap_or_default();
+    let only_changed_in = |allowed: &[&Vec<String>]| {
+        changed_columns
+            .iter()
+            .all(|col_id| allowed.iter().any(|cols| cols.contains(col_id)))
+    };
+
+    let likely_same_part = part_no_changed
+        && options.fuzzy_part_threshold.is_some_and(|threshold| {
+            is_likely_same_part(&part_no_key_a, &part_no_key_b, threshold)
+        });
+
     let status = if changed_columns.is_empty() {
         "unchanged".to_string()
+    } else if dnp_changed {
+        "dnp_changed".to_string()
+    } else if !rotation_columns.is_empty() && only_changed_in(&[&rotation_columns]) {
+        "rotated".to_string()
+    } else if !position_columns.is_empty()
+        && only_changed_in(&[&position_columns, &rotation_columns])
+    {
+        "moved_xy".to_string()
+    } else if likely_same_part && only_changed_in(&[&part_no_columns, &comment_columns]) {
+        "likely_same".to_string()
+    } else if !comment_columns.is_empty() && only_changed_in(&[&comment_columns]) {
+        "comment_changed".to_string()
     } else {
         "modified".to_string()
     };
 
-    (status, changed_columns)
+    let changes = field_changes(parse_a, idx_a, parse_b, idx_b, &changed_columns, schema);
+    (status, changed_columns, changes)
+}
+
+/// 変更された列ごとに、A・Bの値を取得
+///
+/// Bの値は、両方にある役割の列であれば同じ役割の列（複数列の場合は同じ順番の列）から、
+/// それ以外は列構成が同じ場合のみ同じ位置の列から取得する
+fn field_changes(
+    parse_a: &ParseResult,
+    idx_a: usize,
+    parse_b: &ParseResult,
+    idx_b: usize,
+    changed_columns: &[String],
+    schema: &SchemaComparison,
+) -> Vec<FieldChange> {
+    let cell = |parse: &ParseResult, idx: usize, col_id: &str| {
+        col_id
+            .strip_prefix("col-")
+            .and_then(|col_idx| col_idx.parse::<usize>().ok())
+            .and_then(|col_idx| parse.rows[idx].get(col_idx))
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    changed_columns
+        .iter()
+        .map(|col_id| {
+            // 複数の役割を持つ列は名前順で最初の役割
+            let role = parse_a
+                .column_roles
+                .iter()
+                .filter(|(_, col_ids)| col_ids.contains(col_id))
+                .map(|(role, _)| role.clone())
+                .min();
+            let col_id_b = match &role {
+                Some(role) if schema.has_role(role) => {
+                    let position = parse_a.column_roles[role]
+                        .iter()
+                        .position(|id| id == col_id)
+                        .unwrap_or(0);
+                    parse_b
+                        .column_roles
+                        .get(role)
+                        .and_then(|col_ids| col_ids.get(position).or(col_ids.first()))
+                        .cloned()
+                }
+                _ => schema.same_layout.then(|| col_id.clone()),
+            };
+            FieldChange {
+                column_id: col_id.clone(),
+                role,
+                a_value: cell(parse_a, idx_a, col_id),
+                b_value: col_id_b
+                    .map(|col_id_b| cell(parse_b, idx_b, &col_id_b))
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// 列構成が同じBOMの行を列の位置ごとに比較（除外する列は比較しない）
+///
+/// # 戻り値
+/// 値が異なる列IDのリスト
+fn compare_columns(
+    parse_a: &ParseResult,
+    idx_a: usize,
+    parse_b: &ParseResult,
+    idx_b: usize,
+    options: &CompareOptions,
+    normalizer: &KeyNormalizer,
+    schema: &SchemaComparison,
+) -> Vec<String> {
+    let mut changed_columns = Vec::new();
+    let row_a = &parse_a.rows[idx_a];
+    let row_b = &parse_b.rows[idx_b];
+
+    // 末尾の空欄はセルがないものとみなす（列数の異なる行は、ない方のセルを空欄として比較）
+    let compared_len = trimmed_row_len(row_a).max(trimmed_row_len(row_b));
+
+    // Value列は必要に応じて工学表記の値として比較
+    let eng_value_columns = if options.normalize_eng_values {
+        parse_a.get_column_indices("value")
+    } else {
+        vec![]
+    };
+
+    for col_idx in 0..compared_len {
+        if schema.ignored_columns.contains(&col_idx) {
+            continue;
+        }
+        let val_a = row_a.get(col_idx).map(|s| s.trim()).unwrap_or("");
+        let val_b = row_b.get(col_idx).map(|s| s.trim()).unwrap_or("");
+
+        let equal = if eng_value_columns.contains(&col_idx) {
+            eng_values_equal(val_a, val_b, normalizer)
+        } else {
+            normalizer.normalize(val_a) == normalizer.normalize(val_b)
+        };
+
+        if !equal {
+            changed_columns.push(format!("col-{}", col_idx));
+        }
+    }
+
+    changed_columns
+}
+
+/// Value列の値を工学表記の値として比較
+///
+/// 接頭辞の大文字・小文字には意味がある（"1m" と "1M"）ため、工学表記として解析する値には
+/// 全角・半角の同一視と前後の空白の除去のみを適用する。
+/// 解析できない値は正規化した文字列として比較する
+fn eng_values_equal(value_a: &str, value_b: &str, normalizer: &KeyNormalizer) -> bool {
+    let fold = |value: &str| {
+        let value = value.trim();
+        if normalizer.width_fold {
+            normalize_width(value).trim().to_string()
+        } else {
+            value.to_string()
+        }
+    };
+    let (folded_a, folded_b) = (fold(value_a), fold(value_b));
+    if parse_eng_value(&folded_a).is_some() && parse_eng_value(&folded_b).is_some() {
+        values_equivalent(&folded_a, &folded_b)
+    } else {
+        normalizer.normalize(value_a.trim()) == normalizer.normalize(value_b.trim())
+    }
+}
+
+/// 末尾の空欄（空白のみのセルを含む）を除いた行の長さ
+fn trimmed_row_len(row: &[String]) -> usize {
+    row.iter()
+        .rposition(|value| !value.trim().is_empty())
+        .map_or(0, |idx| idx + 1)
+}
+
+/// 梱包コードの違いのみと考えられる部品型番の変更か
+///
+/// 末尾の梱包コード（区切り文字の後に続く最大 `MAX_PACKAGING_SUFFIX_LEN` 文字の英字、
+/// または数字の直後の `PACKAGING_SUFFIXES`）を
+/// 除いた型番が一致し、かつ元の型番同士の正規化した編集距離による類似度が
+/// `threshold` 以上の場合に真。型番の途中（定数・特性コードなど）が異なる場合は偽
+fn is_likely_same_part(part_a: &str, part_b: &str, threshold: f64) -> bool {
+    let part_a = part_a.trim().to_uppercase();
+    let part_b = part_b.trim().to_uppercase();
+    if part_a.is_empty() || part_b.is_empty() {
+        return false;
+    }
+
+    // 片方にのみ梱包コードが付いている場合も含める
+    let base_a = strip_packaging_suffix(&part_a);
+    let base_b = strip_packaging_suffix(&part_b);
+    let same_base = base_a == base_b || base_a == part_b || base_b == part_a;
+    if base_a.is_empty() || base_b.is_empty() || !same_base {
+        return false;
+    }
+
+    strsim::normalized_levenshtein(&part_a, &part_b) >= threshold.clamp(0.0, 1.0)
+}
+
+/// 末尾の梱包コードを除去（"LM358DR" → "LM358"、"RC0402-TR" → "RC0402"、"LM358A" はそのまま）
+fn strip_packaging_suffix(part: &str) -> &str {
+    let suffix_len = part
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .count();
+    if suffix_len == 0 || suffix_len > MAX_PACKAGING_SUFFIX_LEN {
+        return part;
+    }
+
+    let (base, suffix) = part.split_at(part.len() - suffix_len);
+    if base.ends_with(['-', '/', '#']) {
+        return base.trim_end_matches(['-', '/', '#']);
+    }
+    if base.ends_with(|c: char| c.is_ascii_digit()) && PACKAGING_SUFFIXES.contains(&suffix) {
+        return base;
+    }
+    part
+}
+
+/// 差分行に返すReference値（Referenceが空の場合は対応付けキー）
+fn display_ref(parse: &ParseResult, row_index: usize, key: &str) -> String {
+    let ref_value = parse.get_ref(row_index);
+    if ref_value.is_empty() {
+        key.replace(MATCH_KEY_SEPARATOR, " / ")
+    } else {
+        ref_value
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::ColumnMeta;
+    use crate::models::{MatchKey, NormalizeOptions};
     use std::collections::HashMap;
 
     #[test]
@@ -198,27 +922,12 @@ mod tests {
         column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
         column_roles.insert("part_no".to_string(), vec!["col-1".to_string()]);
 
-        let parse_a = ParseResult {
-            rows: vec![vec!["C1".to_string(), "0603B104K".to_string()]],
-            column_roles: column_roles.clone(),
-            column_order: vec!["col-0".to_string(), "col-1".to_string()],
-            guessed_columns: HashMap::new(),
-            guessed_roles: HashMap::new(),
-            errors: vec![],
-            headers: vec!["Ref".to_string(), "Part".to_string()],
-            columns: vec![
-                ColumnMeta {
-                    id: "col-0".to_string(),
-                    name: "Ref".to_string(),
-                },
-                ColumnMeta {
-                    id: "col-1".to_string(),
-                    name: "Part".to_string(),
-                },
-            ],
-            row_numbers: vec![1],
-            structured_errors: None,
-        };
+        let parse_a = ParseResult::builder()
+            .headers(vec!["Ref".to_string(), "Part".to_string()])
+            .rows(vec![vec!["C1".to_string(), "0603B104K".to_string()]])
+            .column_roles(column_roles.clone())
+            .row_numbers(vec![1])
+            .build();
 
         let parse_b = parse_a.clone();
 
@@ -235,27 +944,12 @@ mod tests {
         column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
         column_roles.insert("part_no".to_string(), vec!["col-1".to_string()]);
 
-        let parse_a = ParseResult {
-            rows: vec![vec!["C1".to_string(), "0603B104K".to_string()]],
-            column_roles: column_roles.clone(),
-            column_order: vec!["col-0".to_string(), "col-1".to_string()],
-            guessed_columns: HashMap::new(),
-            guessed_roles: HashMap::new(),
-            errors: vec![],
-            headers: vec!["Ref".to_string(), "Part".to_string()],
-            columns: vec![
-                ColumnMeta {
-                    id: "col-0".to_string(),
-                    name: "Ref".to_string(),
-                },
-                ColumnMeta {
-                    id: "col-1".to_string(),
-                    name: "Part".to_string(),
-                },
-            ],
-            row_numbers: vec![1],
-            structured_errors: None,
-        };
+        let parse_a = ParseResult::builder()
+            .headers(vec!["Ref".to_string(), "Part".to_string()])
+            .rows(vec![vec!["C1".to_string(), "0603B104K".to_string()]])
+            .column_roles(column_roles.clone())
+            .row_numbers(vec![1])
+            .build();
 
         let mut parse_b = parse_a.clone();
         parse_b
@@ -268,4 +962,912 @@ mod tests {
         assert_eq!(diffs[0].status, "unchanged"); // C1
         assert_eq!(diffs[1].status, "added"); // C2
     }
+
+    #[test]
+    fn test_compare_eng_values_opt_in() {
+        let mut column_roles = HashMap::new();
+        column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
+        column_roles.insert("value".to_string(), vec!["col-1".to_string()]);
+
+        let build = |values: [&str; 3]| {
+            ParseResult::builder()
+                .headers(vec!["Ref".to_string(), "Value".to_string()])
+                .rows(
+                    ["C1", "R1", "R2"]
+                        .iter()
+                        .zip(values)
+                        .map(|(reference, value)| vec![reference.to_string(), value.to_string()])
+                        .collect(),
+                )
+                .column_roles(column_roles.clone())
+                .row_numbers(vec![1, 2, 3])
+                .build()
+        };
+
+        let parse_a = build(["0.1uF", "4R7", "10k"]);
+        let parse_b = build(["100nF", "4.7", "1k"]);
+
+        let literal = compare_boms(&parse_a, &parse_b);
+        assert!(literal.iter().all(|diff| diff.status == "modified"));
+
+        let options = CompareOptions {
+            normalize_eng_values: true,
+            ..CompareOptions::default()
+        };
+        let statuses: Vec<String> = compare_boms_with_options(&parse_a, &parse_b, &options)
+            .into_iter()
+            .map(|diff| diff.status)
+            .collect();
+        assert_eq!(statuses, vec!["unchanged", "unchanged", "modified"]);
+    }
+
+    #[test]
+    fn test_compare_eng_values_with_case_insensitive() {
+        let build = |headers: &[&str], rows: &[&[&str]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = headers.iter().map(|name| name.to_string()).collect();
+            parse.rebuild_columns_from_headers();
+            parse
+                .column_roles
+                .insert("ref".to_string(), vec!["col-0".to_string()]);
+            let value_idx = headers.iter().position(|name| *name == "Value").unwrap();
+            parse
+                .column_roles
+                .insert("value".to_string(), vec![format!("col-{value_idx}")]);
+            parse
+        };
+        let parse_a = build(
+            &["Ref", "Value"],
+            &[&["C1", "0.1uF"], &["R1", "1m"], &["U1", "opamp"]],
+        );
+        let parse_b = build(
+            &["Ref", "Value"],
+            &[&["C1", "100nF"], &["R1", "1M"], &["U1", "OPAMP"]],
+        );
+        // 列の位置が異なるBOM（役割の値のみを比較する経路）
+        let parse_c = build(
+            &["Ref", "Note", "Value"],
+            &[
+                &["C1", "", "100nF"],
+                &["R1", "", "1M"],
+                &["U1", "", "OPAMP"],
+            ],
+        );
+
+        let options = CompareOptions {
+            normalize_eng_values: true,
+            normalize: NormalizeOptions {
+                case_insensitive: true,
+                ..NormalizeOptions::default()
+            },
+            ..CompareOptions::default()
+        };
+        for parse_b in [&parse_b, &parse_c] {
+            let statuses: Vec<String> = compare_boms_with_options(&parse_a, parse_b, &options)
+                .into_iter()
+                .map(|diff| diff.status)
+                .collect();
+            // 接頭辞の大文字・小文字は区別し、工学表記でない値のみ大文字・小文字を無視する
+            assert_eq!(statuses, vec!["unchanged", "modified", "unchanged"]);
+        }
+    }
+
+    #[test]
+    fn test_compare_normalize_width_keeps_original_text() {
+        let mut column_roles = HashMap::new();
+        column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
+        column_roles.insert("part_no".to_string(), vec!["col-1".to_string()]);
+
+        let build = |rows: Vec<[&str; 2]>| {
+            ParseResult::builder()
+                .headers(vec!["Ref".to_string(), "Part".to_string()])
+                .row_numbers((1..=rows.len()).collect())
+                .rows(
+                    rows.into_iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .column_roles(column_roles.clone())
+                .build()
+        };
+
+        let parse_a = build(vec![["Ｃ１", "ＧＲＭ１５５"], ["R1", "RC0402"]]);
+        let parse_b = build(vec![["C1", "GRM155"], ["Ｒ２", "RC0402"]]);
+
+        // 既定では別の部品として扱う
+        let literal = compare_boms(&parse_a, &parse_b);
+        assert!(literal.iter().all(|diff| diff.status != "unchanged"));
+
+        let options = CompareOptions {
+            normalize_width: true,
+            ..CompareOptions::default()
+        };
+        let diffs = compare_boms_with_options(&parse_a, &parse_b, &options);
+        let summary: Vec<(&str, &str)> = diffs
+            .iter()
+            .map(|diff| (diff.status.as_str(), diff.ref_value.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("unchanged", "Ｃ１"), ("removed", "R1"), ("added", "Ｒ２")]
+        );
+        // 元データは変更しない
+        assert_eq!(parse_a.rows[0][0], "Ｃ１");
+    }
+
+    #[test]
+    fn test_compare_comment_only_change_is_low_priority() {
+        let mut column_roles = HashMap::new();
+        column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
+        column_roles.insert("part_no".to_string(), vec!["col-1".to_string()]);
+        column_roles.insert("comment".to_string(), vec!["col-2".to_string()]);
+
+        let headers = ["Ref", "Part", "Notes"];
+        let build = |rows: Vec<[&str; 3]>| {
+            ParseResult::builder()
+                .headers(headers.iter().map(|name| name.to_string()).collect())
+                .row_numbers((1..=rows.len()).collect())
+                .rows(
+                    rows.into_iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .column_roles(column_roles.clone())
+                .build()
+        };
+
+        let parse_a = build(vec![["C1", "GRM155", "DNP"], ["R1", "RC0402", ""]]);
+        let parse_b = build(vec![["C1", "GRM155", "実装"], ["R1", "RC0603", "代替"]]);
+
+        let diffs = compare_boms(&parse_a, &parse_b);
+        assert_eq!(diffs[0].status, "comment_changed");
+        assert_eq!(diffs[0].changed_columns, vec!["col-2"]);
+        // コメント以外も変わっていれば通常の変更
+        assert_eq!(diffs[1].status, "modified");
+    }
+
+    #[test]
+    fn test_is_likely_same_part() {
+        assert!(is_likely_same_part("BSS138CT", "BSS138TR", 0.7));
+        assert!(is_likely_same_part(
+            "RC0402FR-0710K-TR",
+            "RC0402FR-0710K",
+            0.7
+        ));
+        // 型番の途中が異なる（容量コードの変更）
+        assert!(!is_likely_same_part(
+            "GRM155R71C104KA",
+            "GRM155R71C105KA",
+            0.7
+        ));
+        assert!(!is_likely_same_part(
+            "RC0402FR-0710KL",
+            "RC0402FR-0747KL",
+            0.7
+        ));
+        // 短い型番では類似度がしきい値に届かない
+        assert!(!is_likely_same_part("R1", "R1ABC", 0.7));
+        assert!(!is_likely_same_part("BSS138CT", "BSS138TR", 0.9));
+        assert!(is_likely_same_part("LM358DR", "LM358", 0.7));
+        assert!(is_likely_same_part("LM358/TR", "LM358", 0.6));
+        // 改版・特性を表す末尾の英字は梱包コードではない
+        assert!(!is_likely_same_part("LM358", "LM358A", 0.7));
+        assert!(!is_likely_same_part("LM358ADR", "LM358DR", 0.7));
+    }
+
+    #[test]
+    fn test_compare_fuzzy_part_threshold_opt_in() {
+        let mut column_roles = HashMap::new();
+        column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
+        column_roles.insert("part_no".to_string(), vec!["col-1".to_string()]);
+
+        let build = |rows: Vec<[&str; 2]>| {
+            ParseResult::builder()
+                .headers(vec!["Ref".to_string(), "Part".to_string()])
+                .row_numbers((1..=rows.len()).collect())
+                .rows(
+                    rows.into_iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .column_roles(column_roles.clone())
+                .build()
+        };
+
+        let parse_a = build(vec![["Q1", "BSS138CT"], ["C1", "GRM155R71C104KA"]]);
+        let parse_b = build(vec![["Q1", "BSS138TR"], ["C1", "GRM155R71C105KA"]]);
+
+        let literal = compare_boms(&parse_a, &parse_b);
+        assert!(literal.iter().all(|diff| diff.status == "modified"));
+
+        let options = CompareOptions {
+            fuzzy_part_threshold: Some(0.7),
+            ..CompareOptions::default()
+        };
+        let statuses: Vec<String> = compare_boms_with_options(&parse_a, &parse_b, &options)
+            .into_iter()
+            .map(|diff| diff.status)
+            .collect();
+        assert_eq!(statuses, vec!["likely_same", "modified"]);
+    }
+
+    #[test]
+    fn test_compare_match_key_ref_and_value() {
+        let mut column_roles = HashMap::new();
+        column_roles.insert("ref".to_string(), vec!["col-0".to_string()]);
+        column_roles.insert("value".to_string(), vec!["col-1".to_string()]);
+
+        let build = |rows: Vec<[&str; 2]>| {
+            ParseResult::builder()
+                .headers(vec!["Ref".to_string(), "Value".to_string()])
+                .row_numbers((1..=rows.len()).collect())
+                .rows(
+                    rows.into_iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .column_roles(column_roles.clone())
+                .build()
+        };
+
+        let parse_a = build(vec![["R1", "10k"], ["R2", "4.7k"]]);
+        let parse_b = build(vec![["R1", "10k"], ["R2", "1k"]]);
+
+        let options = CompareOptions {
+            match_key: MatchKey::Multiple(vec!["ref".to_string(), "value".to_string()]),
+            ..CompareOptions::default()
+        };
+        let summary: Vec<(String, String)> =
+            compare_boms_with_options(&parse_a, &parse_b, &options)
+                .into_iter()
+                .map(|diff| (diff.status, diff.ref_value))
+                .collect();
+        // 値が変わった行は別の部品（削除 + 追加）として扱う
+        assert_eq!(
+            summary,
+            vec![
+                ("unchanged".to_string(), "R1".to_string()),
+                ("removed".to_string(), "R2".to_string()),
+                ("added".to_string(), "R2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_match_key_part_no_skips_empty_keys() {
+        let mut column_roles = HashMap::new();
+        column_roles.insert("part_no".to_string(), vec!["col-0".to_string()]);
+        column_roles.insert("quantity".to_string(), vec!["col-1".to_string()]);
+
+        let build = |rows: Vec<[&str; 2]>| {
+            ParseResult::builder()
+                .headers(vec!["Part".to_string(), "Qty".to_string()])
+                .row_numbers((1..=rows.len()).collect())
+                .rows(
+                    rows.into_iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .column_roles(column_roles.clone())
+                .build()
+        };
+
+        let parse_a = build(vec![["GRM155", "3"], ["", "1"]]);
+        let parse_b = build(vec![["GRM155", "4"]]);
+
+        let options = CompareOptions {
+            match_key: MatchKey::Single("part_no".to_string()),
+            ..CompareOptions::default()
+        };
+        let diffs = compare_boms_with_options(&parse_a, &parse_b, &options);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].status, "modified");
+        // Referenceがない場合はキーを表示用の値にする
+        assert_eq!(diffs[0].ref_value, "GRM155");
+        assert_eq!(diffs[0].changed_columns, vec!["col-1"]);
+    }
+
+    #[test]
+    fn test_compare_boms_with_missing_role() {
+        // A: ネットリスト（Reference・部品型番のみ）、B: メーカー列のあるBOM
+        let build = |headers: &[&str], rows: &[&[&str]], roles: &[(&str, &str)]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = headers.iter().map(|header| header.to_string()).collect();
+            parse.rebuild_columns_from_headers();
+            for (role, col_id) in roles {
+                parse
+                    .column_roles
+                    .insert(role.to_string(), vec![col_id.to_string()]);
+            }
+            parse
+        };
+        let parse_a = build(
+            &["Ref", "Part"],
+            &[&["C1", "GRM155"], &["R1", "RC0402"]],
+            &[("ref", "col-0"), ("part_no", "col-1")],
+        );
+        let parse_b = build(
+            &["Ref", "Maker", "Part"],
+            &[&["C1", "Murata", "GRM155"], &["R1", "Yageo", "RC0603"]],
+            &[
+                ("ref", "col-0"),
+                ("manufacturer", "col-1"),
+                ("part_no", "col-2"),
+            ],
+        );
+
+        let result = compare_boms_with_report(
+            &parse_a,
+            &parse_b,
+            &CompareOptions::default(),
+            &OperationContext::default(),
+        )
+        .unwrap();
+        let statuses: Vec<&str> = result
+            .diffs
+            .iter()
+            .map(|diff| diff.status.as_str())
+            .collect();
+        assert_eq!(statuses, vec!["unchanged", "modified"]);
+        assert_eq!(result.diffs[1].changed_columns, vec!["col-1"]);
+
+        assert_eq!(result.warnings.len(), 2);
+        assert!(result.warnings[0].contains("BOM Bにはメーカー列"));
+        assert!(result.warnings[1].contains("列構成が異なる"));
+
+        assert!(check_schema_compatibility(&parse_a, &parse_a).is_empty());
+    }
+
+    #[test]
+    fn test_compare_purchasing_boms_by_part_no() {
+        // 部品型番・数量のみの購買用BOM（Referenceなし、列構成も異なる）
+        let build = |headers: &[&str], rows: &[&[&str]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = headers.iter().map(|header| header.to_string()).collect();
+            parse.rebuild_columns_from_headers();
+            parse
+                .column_roles
+                .insert("part_no".to_string(), vec!["col-0".to_string()]);
+            parse
+        };
+        let parse_a = build(
+            &["Part", "Qty"],
+            &[&["GRM155", "10"], &["RC0402", "1,000"], &["LM358", "2"]],
+        );
+        let parse_b = build(
+            &["Part", "Description", "Q'ty"],
+            &[
+                &["GRM155", "100nF", "12"],
+                &["RC0402", "10k", "1000"],
+                &["NE555", "Timer", "1"],
+            ],
+        );
+
+        let options = CompareOptions {
+            match_key: MatchKey::Single("part_no".to_string()),
+            ..CompareOptions::default()
+        };
+        let diffs = compare_boms_with_options(&parse_a, &parse_b, &options);
+        let summary: Vec<(&str, &str, Vec<String>)> = diffs
+            .iter()
+            .map(|diff| {
+                (
+                    diff.ref_value.as_str(),
+                    diff.status.as_str(),
+                    diff.changed_columns.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("GRM155", "modified", vec!["col-1".to_string()]),
+                ("RC0402", "unchanged", vec![]),
+                ("LM358", "removed", vec![]),
+                ("NE555", "added", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_ignore_columns() {
+        let build = |rows: &[[&str; 3]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = vec!["Ref".to_string(), "Part".to_string(), "Updated".to_string()];
+            parse.rebuild_columns_from_headers();
+            parse
+                .column_roles
+                .insert("ref".to_string(), vec!["col-0".to_string()]);
+            parse
+                .column_roles
+                .insert("part_no".to_string(), vec!["col-1".to_string()]);
+            parse
+        };
+        let parse_a = build(&[
+            ["C1", "GRM155", "2024-01-10"],
+            ["R1", "RC0402", "2024-01-10"],
+        ]);
+        let parse_b = build(&[
+            ["C1", "GRM155", "2024-03-02"],
+            ["R1", "RC0603", "2024-03-02"],
+        ]);
+
+        let statuses = |options: &CompareOptions| -> Vec<(String, Vec<String>)> {
+            compare_boms_with_options(&parse_a, &parse_b, options)
+                .into_iter()
+                .map(|diff| (diff.status, diff.changed_columns))
+                .collect()
+        };
+
+        // 除外しない場合は日付列の違いで全行が変更になる
+        let all = statuses(&CompareOptions::default());
+        assert!(all.iter().all(|(status, _)| status == "modified"));
+
+        for ignore in ["Updated", "col-2", " updated "] {
+            let options = CompareOptions {
+                ignore_columns: vec![ignore.to_string()],
+                ..CompareOptions::default()
+            };
+            assert_eq!(
+                statuses(&options),
+                vec![
+                    ("unchanged".to_string(), vec![]),
+                    ("modified".to_string(), vec!["col-1".to_string()]),
+                ],
+                "{ignore}"
+            );
+        }
+
+        // 役割の列は除外しても比較する
+        let options = CompareOptions {
+            ignore_columns: vec!["Part".to_string(), "Updated".to_string()],
+            ..CompareOptions::default()
+        };
+        assert_eq!(statuses(&options)[1].0, "modified");
+    }
+
+    #[test]
+    fn test_compare_ignores_blank_extra_columns() {
+        let build = |rows: Vec<Vec<&str>>| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.into_iter()
+                        .map(|row| row.into_iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = vec!["Ref".to_string(), "Part".to_string(), "Value".to_string()];
+            parse.rebuild_columns_from_headers();
+            parse
+                .column_roles
+                .insert("ref".to_string(), vec!["col-0".to_string()]);
+            parse
+                .column_roles
+                .insert("part_no".to_string(), vec!["col-1".to_string()]);
+            parse
+        };
+        let parse_a = build(vec![
+            vec!["C1", "GRM155", "0.1uF"],
+            vec!["R1", "RC0402", "10k"],
+            vec!["U1", "LM358", ""],
+        ]);
+        // Bは末尾に空の列がある（Excelで書式だけ設定された列など）
+        let parse_b = build(vec![
+            vec!["C1", "GRM155", "0.1uF", ""],
+            vec!["R1 ", " RC0402", "10k ", "   "],
+            vec!["U1", "LM358", "OPAMP", ""],
+        ]);
+        assert!(check_schema_compatibility(&parse_a, &parse_b).is_empty());
+
+        let diffs = compare_boms(&parse_a, &parse_b);
+        let statuses: Vec<(&str, &[String])> = diffs
+            .iter()
+            .map(|diff| (diff.status.as_str(), diff.changed_columns.as_slice()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("unchanged", &[][..]),
+                ("unchanged", &[][..]),
+                ("modified", &["col-2".to_string()][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_ragged_trailing_blanks() {
+        let mut parse_a = ParseResult::builder()
+            .rows(vec![
+                vec!["C1".to_string(), "GRM155".to_string(), String::new()],
+                vec!["R1".to_string(), "RC0402".to_string()],
+                vec!["U1".to_string(), "LM358".to_string()],
+                vec!["L1".to_string()],
+            ])
+            .build();
+        parse_a.headers = vec!["Ref".to_string(), "Part".to_string(), "Note".to_string()];
+        parse_a.rebuild_columns_from_headers();
+        parse_a
+            .column_roles
+            .insert("ref".to_string(), vec!["col-0".to_string()]);
+
+        let mut parse_b = parse_a.clone();
+        parse_b.rows = vec![
+            vec!["C1".to_string(), "GRM155".to_string()],
+            vec![
+                "R1".to_string(),
+                "RC0402".to_string(),
+                " ".to_string(),
+                String::new(),
+            ],
+            vec!["U1".to_string(), "LM358".to_string(), "DNP".to_string()],
+            vec!["L1".to_string(), "BLM18".to_string(), String::new()],
+        ];
+
+        let diffs = compare_boms(&parse_a, &parse_b);
+        let changed: Vec<(&str, &[String])> = diffs
+            .iter()
+            .map(|diff| (diff.ref_value.as_str(), diff.changed_columns.as_slice()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                ("C1", &[][..]),
+                ("R1", &[][..]),
+                ("U1", &["col-2".to_string()][..]),
+                ("L1", &["col-1".to_string()][..]),
+            ]
+        );
+        assert_eq!(diffs[0].status, "unchanged");
+        assert_eq!(diffs[1].status, "unchanged");
+    }
+
+    #[test]
+    fn test_compare_placement_with_tolerance() {
+        let build = |rows: &[[&str; 5]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = ["Ref", "Val", "PosX", "PosY", "Rot"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+            parse.rebuild_columns_from_headers();
+            for (role, idx) in [
+                ("ref", 0),
+                ("value", 1),
+                ("pos_x", 2),
+                ("pos_y", 3),
+                ("rotation", 4),
+            ] {
+                parse
+                    .column_roles
+                    .insert(role.to_string(), vec![format!("col-{idx}")]);
+            }
+            parse
+        };
+        let parse_a = build(&[
+            ["C1", "100nF", "10.000", "20.000", "0"],
+            ["C2", "100nF", "15.000", "20.000", "0"],
+            ["R1", "10k", "30.000", "5.000", "180"],
+            ["R2", "10k", "40.000", "5.000", "270"],
+        ]);
+        let parse_b = build(&[
+            ["C1", "100nF", "10.000", "20.000", "90"],
+            ["C2", "100nF", "15.500", "20.004", "0"],
+            ["R1", "22k", "30.000", "5.000", "180"],
+            ["R2", "10k", "40.005mm", "5.000", "-90"],
+        ]);
+        let statuses = |options: &CompareOptions| -> Vec<(String, Vec<String>)> {
+            compare_boms_with_options(&parse_a, &parse_b, options)
+                .into_iter()
+                .map(|diff| (diff.status, diff.changed_columns))
+                .collect()
+        };
+
+        // 既定では位置の違いも通常の変更（文字列として比較）
+        let default = statuses(&CompareOptions::default());
+        assert_eq!(default[0].0, "modified");
+        assert_eq!(default[3].0, "modified");
+
+        let options = CompareOptions {
+            position_tolerance: Some(0.01),
+            ..CompareOptions::default()
+        };
+        assert_eq!(
+            statuses(&options),
+            vec![
+                ("rotated".to_string(), vec!["col-4".to_string()]),
+                ("moved_xy".to_string(), vec!["col-2".to_string()]),
+                ("modified".to_string(), vec!["col-1".to_string()]),
+                // 許容差内の座標と、同じ向きの回転角は変更としない
+                ("unchanged".to_string(), vec![]),
+            ]
+        );
+
+        // 片方のBOMに回転の役割がない場合は回転を比較しない
+        let mut parse_b = parse_b.clone();
+        parse_b.column_roles.remove("rotation");
+        let diffs = compare_boms_with_options(&parse_a, &parse_b, &options);
+        assert_ne!(diffs[0].status, "rotated");
+    }
+
+    #[test]
+    fn test_compare_dnp_toggle() {
+        let build = |rows: &[[&str; 3]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = vec!["Ref".to_string(), "Part".to_string(), "DNP".to_string()];
+            parse.rebuild_columns_from_headers();
+            for (role, idx) in [("ref", 0), ("part_no", 1), ("dnp", 2)] {
+                parse
+                    .column_roles
+                    .insert(role.to_string(), vec![format!("col-{idx}")]);
+            }
+            parse
+        };
+        // Rev.A → Rev.B（C1を未実装に、R1を実装に、C2は表記のみ変更、U1は型番と実装有無を変更）
+        let parse_a = build(&[
+            ["C1", "GRM155", ""],
+            ["R1", "RC0402", "DNP"],
+            ["C2", "GRM155", "x"],
+            ["U1", "LM358", ""],
+        ]);
+        let parse_b = build(&[
+            ["C1", "GRM155", "DNP"],
+            ["R1", "RC0402", ""],
+            ["C2", "GRM155", "未実装"],
+            ["U1", "LM358B", "x"],
+        ]);
+
+        let diffs = compare_boms(&parse_a, &parse_b);
+        let statuses: Vec<(&str, &str)> = diffs
+            .iter()
+            .map(|diff| (diff.ref_value.as_str(), diff.status.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("C1", "dnp_changed"),
+                ("R1", "dnp_changed"),
+                ("C2", "unchanged"),
+                ("U1", "dnp_changed"),
+            ]
+        );
+        assert_eq!(diffs[0].changed_columns, vec!["col-2"]);
+        assert_eq!(diffs[3].changed_columns, vec!["col-1", "col-2"]);
+
+        // Rev.B → Rev.A に戻しても切り替わりとして検出される
+        let reverted = compare_boms(&parse_b, &parse_a);
+        assert_eq!(reverted[1].status, "dnp_changed");
+    }
+
+    #[test]
+    fn test_compare_records_field_changes() {
+        let build = |headers: &[&str], rows: &[&[&str]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = headers.iter().map(|name| name.to_string()).collect();
+            parse.rebuild_columns_from_headers();
+            parse
+        };
+        let mut parse_a = build(
+            &["Ref", "Part", "Note"],
+            &[&["C1", "GRM155", "a"], &["R1", "RC0402", ""]],
+        );
+        parse_a
+            .column_roles
+            .insert("ref".to_string(), vec!["col-0".to_string()]);
+        parse_a
+            .column_roles
+            .insert("part_no".to_string(), vec!["col-1".to_string()]);
+        // Bは列の並びが異なる（部品型番が3列目）
+        let mut parse_b = build(
+            &["Ref", "Note", "Part"],
+            &[&["C1", "b", "GRM188"], &["R1", "", "RC0402"]],
+        );
+        parse_b
+            .column_roles
+            .insert("ref".to_string(), vec!["col-0".to_string()]);
+        parse_b
+            .column_roles
+            .insert("part_no".to_string(), vec!["col-2".to_string()]);
+
+        let diffs = compare_boms(&parse_a, &parse_b);
+        assert_eq!(diffs[0].status, "modified");
+        assert_eq!(diffs[0].changed_columns, vec!["col-1"]);
+        assert_eq!(
+            diffs[0].changes,
+            vec![FieldChange {
+                column_id: "col-1".to_string(),
+                role: Some("part_no".to_string()),
+                a_value: "GRM155".to_string(),
+                b_value: "GRM188".to_string(),
+            }]
+        );
+        // 一致した行には値を持たない
+        assert_eq!(diffs[1].status, "unchanged");
+        assert!(diffs[1].changes.is_empty());
+    }
+
+    #[test]
+    fn test_compare_include_unchanged() {
+        let build = |rows: &[[&str; 2]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = vec!["Ref".to_string(), "Part".to_string()];
+            parse.rebuild_columns_from_headers();
+            parse
+                .column_roles
+                .insert("ref".to_string(), vec!["col-0".to_string()]);
+            parse
+                .column_roles
+                .insert("part_no".to_string(), vec!["col-1".to_string()]);
+            parse
+        };
+        let parse_a = build(&[
+            ["C1", "GRM155"],
+            ["C2", "GRM155"],
+            ["C3", "GRM155"],
+            ["R1", "RC0402"],
+        ]);
+        let parse_b = build(&[
+            ["C1", "GRM155"],
+            ["C2", "GRM155"],
+            ["C3", "GRM188"],
+            ["U1", "LM358"],
+        ]);
+        let count = |diffs: &[DiffRow], status: &str| {
+            diffs.iter().filter(|diff| diff.status == status).count()
+        };
+
+        // 既定では一致した行も含める
+        let all = compare_boms_with_options(&parse_a, &parse_b, &CompareOptions::default());
+        assert_eq!(all.len(), 5);
+        assert_eq!(count(&all, "unchanged"), 2);
+
+        let options = CompareOptions {
+            include_unchanged: false,
+            ..CompareOptions::default()
+        };
+        let changed = compare_boms_with_options(&parse_a, &parse_b, &options);
+        assert_eq!(changed.len(), 3);
+        assert_eq!(count(&changed, "unchanged"), 0);
+        assert_eq!(count(&changed, "modified"), 1);
+        assert_eq!(count(&changed, "removed"), 1);
+        assert_eq!(count(&changed, "added"), 1);
+    }
+
+    #[test]
+    fn test_update_diff_matches_full_compare() {
+        let build = |rows: &[[&str; 2]]| {
+            let mut parse = ParseResult::builder()
+                .rows(
+                    rows.iter()
+                        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                        .collect(),
+                )
+                .build();
+            parse.headers = vec!["Ref".to_string(), "Part".to_string()];
+            parse.rebuild_columns_from_headers();
+            parse
+                .column_roles
+                .insert("ref".to_string(), vec!["col-0".to_string()]);
+            parse
+                .column_roles
+                .insert("part_no".to_string(), vec!["col-1".to_string()]);
+            parse
+        };
+        type Summary = (String, Option<usize>, Option<usize>, String, Vec<String>);
+        // 順序も含めて比較する
+        let summarize = |diffs: &[DiffRow]| -> Vec<Summary> {
+            diffs
+                .iter()
+                .map(|diff| {
+                    (
+                        diff.ref_value.clone(),
+                        diff.a_index,
+                        diff.b_index,
+                        diff.status.clone(),
+                        diff.changed_columns.clone(),
+                    )
+                })
+                .collect()
+        };
+        let options = CompareOptions::default();
+        let parse_a = build(&[
+            ["C1", "GRM155"],
+            ["C2", "GRM155"],
+            ["R1", "RC0402"],
+            ["R2", "RC0402"],
+        ]);
+        let mut parse_b = build(&[
+            ["C1", "GRM155"],
+            ["C2", "GRM155"],
+            ["R1", "RC0603"],
+            ["U1", "LM358"],
+            ["U2", "LM324"],
+            ["D1", "RB751"],
+            ["D2", "RB751"],
+        ]);
+        let previous = compare_boms_with_options(&parse_a, &parse_b, &options);
+
+        // 1つのセルを編集（C2の部品型番）
+        parse_b.rows[1][1] = "GRM188".to_string();
+        let updated = update_diff(&previous, &parse_a, &parse_b, &["C2".to_string()], &options);
+        let full = compare_boms_with_options(&parse_a, &parse_b, &options);
+        assert_eq!(summarize(&updated), summarize(&full));
+        assert_eq!(updated[1].status, "modified");
+        // Aの行順に並び、Bのみの行がBの行順で最後
+        let added: Vec<&str> = updated
+            .iter()
+            .filter(|diff| diff.a_index.is_none())
+            .map(|diff| diff.ref_value.as_str())
+            .collect();
+        assert_eq!(added, vec!["U1", "U2", "D1", "D2"]);
+
+        // Bのみの行を編集
+        parse_b.rows[5][1] = "RB520".to_string();
+        let updated = update_diff(&updated, &parse_a, &parse_b, &["D1".to_string()], &options);
+        let full = compare_boms_with_options(&parse_a, &parse_b, &options);
+        assert_eq!(summarize(&updated), summarize(&full));
+
+        // Referenceの変更（編集前・編集後の両方を渡す）
+        parse_b.rows[3][0] = "R2".to_string();
+        let renamed = update_diff(
+            &updated,
+            &parse_a,
+            &parse_b,
+            &["U1".to_string(), "R2".to_string()],
+            &options,
+        );
+        let full = compare_boms_with_options(&parse_a, &parse_b, &options);
+        assert_eq!(summarize(&renamed), summarize(&full));
+        assert_eq!(
+            renamed.iter().filter(|diff| diff.status == "added").count(),
+            3
+        );
+    }
 }
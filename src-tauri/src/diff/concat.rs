@@ -0,0 +1,228 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::models::{AppError, ParseResult};
+use crate::parsers::build_column_order;
+use crate::utils::header::normalize_header;
+
+/// 連結後の列
+struct UnionColumn {
+    header: String,
+    roles: BTreeSet<String>,
+}
+
+/// 複数のBOMの行をそのまま縦に連結
+///
+/// マージと異なりReferenceでの対応付けは行わない。列は役割が同じ列、
+/// 次にヘッダー名（空白・大文字小文字を無視）が同じ列にまとめ、
+/// どれにも該当しない列は末尾に追加する。列の少ない行は空欄で埋める。
+///
+/// # 引数
+/// * `parts` - 連結するBOM（この順に行を並べる）
+///
+/// # 戻り値
+/// 連結したBOM（行番号はそれぞれの元ファイルでの行番号）。
+/// 同じヘッダー名の列に異なる役割が割り当てられている場合はエラー
+pub fn concat_boms(parts: &[ParseResult]) -> Result<ParseResult, AppError> {
+    if parts.is_empty() {
+        return Err(AppError::validation("連結するBOMがありません。"));
+    }
+
+    let mut union: Vec<UnionColumn> = Vec::new();
+    let mut rows = Vec::new();
+    let mut row_numbers = Vec::new();
+
+    for (part_idx, part) in parts.iter().enumerate() {
+        let column_roles = roles_by_column(part);
+        let mut mapping = Vec::with_capacity(part.column_count());
+        let mut used = HashSet::new();
+
+        for col_idx in 0..part.column_count() {
+            let header = part.headers.get(col_idx).cloned().unwrap_or_default();
+            let roles = column_roles.get(&col_idx).cloned().unwrap_or_default();
+            let target = find_union_column(&union, &used, &header, &roles).map_err(|other| {
+                AppError::validation(format!(
+                    "{}番目のBOMの列 '{}' の役割（{}）が、他のBOMの同じ名前の列の役割（{}）と一致しません。",
+                    part_idx + 1,
+                    header,
+                    join_roles(&roles),
+                    join_roles(&union[other].roles)
+                ))
+            })?;
+
+            let target = target.unwrap_or_else(|| {
+                union.push(UnionColumn {
+                    header: header.clone(),
+                    roles: BTreeSet::new(),
+                });
+                union.len() - 1
+            });
+            union[target].roles.extend(roles);
+            used.insert(target);
+            mapping.push(target);
+        }
+
+        for (row_idx, row) in part.rows.iter().enumerate() {
+            let mut new_row = vec![String::new(); union.len()];
+            for (col_idx, cell) in row.iter().enumerate() {
+                new_row[mapping[col_idx]] = cell.clone();
+            }
+            rows.push(new_row);
+            row_numbers.push(part.source_row_number(row_idx));
+        }
+    }
+
+    // 後のBOMで追加された列の分だけ、先のBOMの行を空欄で埋める
+    for row in &mut rows {
+        row.resize(union.len(), String::new());
+    }
+
+    let mut column_roles: HashMap<String, Vec<String>> = HashMap::new();
+    for (idx, column) in union.iter().enumerate() {
+        for role in &column.roles {
+            column_roles
+                .entry(role.clone())
+                .or_default()
+                .push(format!("col-{}", idx));
+        }
+    }
+
+    let mut result = ParseResult::builder()
+        .rows(rows)
+        .row_numbers(row_numbers)
+        .build();
+    result.column_order = build_column_order(&column_roles, union.len());
+    result.column_roles = column_roles;
+    result.headers = union.into_iter().map(|column| column.header).collect();
+    result.rebuild_columns_from_headers();
+
+    Ok(result)
+}
+
+/// 列インデックス → その列の役割
+fn roles_by_column(parse: &ParseResult) -> HashMap<usize, BTreeSet<String>> {
+    let mut roles: HashMap<usize, BTreeSet<String>> = HashMap::new();
+    for role in parse.column_roles.keys() {
+        for col_idx in parse.get_column_indices(role) {
+            roles.entry(col_idx).or_default().insert(role.clone());
+        }
+    }
+    roles
+}
+
+/// 列をまとめる連結後の列を探す
+///
+/// # 戻り値
+/// 該当する列（ない場合はNone）。同じヘッダー名の列の役割が異なる場合は、その列を `Err` で返す
+fn find_union_column(
+    union: &[UnionColumn],
+    used: &HashSet<usize>,
+    header: &str,
+    roles: &BTreeSet<String>,
+) -> Result<Option<usize>, usize> {
+    let normalized = normalize_header(header);
+    let name_match = (!normalized.is_empty())
+        .then(|| {
+            (0..union.len()).find(|idx| {
+                !used.contains(idx) && normalize_header(&union[*idx].header) == normalized
+            })
+        })
+        .flatten();
+
+    // 役割が共通する列を優先し、複数ある場合はヘッダー名が同じ列を選ぶ
+    let role_matches: Vec<usize> = (0..union.len())
+        .filter(|idx| !used.contains(idx) && !union[*idx].roles.is_disjoint(roles))
+        .collect();
+    if let Some(&first) = role_matches.first() {
+        return Ok(Some(
+            name_match
+                .filter(|idx| role_matches.contains(idx))
+                .unwrap_or(first),
+        ));
+    }
+
+    match name_match {
+        Some(idx) if !roles.is_empty() && !union[idx].roles.is_empty() => Err(idx),
+        other => Ok(other),
+    }
+}
+
+fn join_roles(roles: &BTreeSet<String>) -> String {
+    if roles.is_empty() {
+        "なし".to_string()
+    } else {
+        roles.iter().cloned().collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(headers: &[&str], roles: &[(&str, usize)], rows: &[&[&str]]) -> ParseResult {
+        let mut parse = ParseResult::builder()
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .row_numbers((2..rows.len() + 2).collect())
+            .build();
+        parse.headers = headers.iter().map(|header| header.to_string()).collect();
+        parse.rebuild_columns_from_headers();
+        for (role, col_idx) in roles {
+            parse
+                .column_roles
+                .insert(role.to_string(), vec![format!("col-{}", col_idx)]);
+        }
+        parse
+    }
+
+    #[test]
+    fn test_concat_two_and_three_column_boms() {
+        let board_a = build(
+            &["Ref", "Part No"],
+            &[("ref", 0), ("part_no", 1)],
+            &[&["C1", "GRM155"], &["R1", "RC0402"]],
+        );
+        // 役割が同じ列は名前が違ってもまとめ、役割のない列は名前でまとめる
+        let board_b = build(
+            &["Maker", "RefDes", "品番"],
+            &[("ref", 1), ("part_no", 2)],
+            &[&["Murata", "C11", "GRM188"]],
+        );
+
+        let result = concat_boms(&[board_a, board_b]).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part No", "Maker"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec!["C1", "GRM155", ""],
+                vec!["R1", "RC0402", ""],
+                vec!["C11", "GRM188", "Murata"],
+            ]
+        );
+        assert_eq!(result.row_numbers, vec![2, 3, 2]);
+        assert_eq!(result.get_column_indices("ref"), vec![0]);
+        assert_eq!(result.get_column_indices("part_no"), vec![1]);
+        assert!(result.columns_consistent());
+    }
+
+    #[test]
+    fn test_concat_conflicting_roles() {
+        let board_a = build(
+            &["Ref", "Value"],
+            &[("ref", 0), ("value", 1)],
+            &[&["C1", "0.1uF"]],
+        );
+        let board_b = build(
+            &["Ref", "Value"],
+            &[("ref", 0), ("part_no", 1)],
+            &[&["C2", "GRM155"]],
+        );
+
+        let error = concat_boms(&[board_a, board_b]).unwrap_err();
+        assert!(error.message.contains("2番目のBOMの列 'Value'"));
+
+        assert!(concat_boms(&[]).is_err());
+    }
+}
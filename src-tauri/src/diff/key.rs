@@ -0,0 +1,162 @@
+use crate::models::{CompareOptions, NormalizeOptions, MATCH_KEY_SEPARATOR};
+use crate::utils::text::normalize_width;
+
+/// 対応付けキー・比較値の正規化
+///
+/// 比較（`compare_boms`）・マージ（`update_and_append_boms`）・マスタの照合で同じ規則を使うため、
+/// キーの正規化はすべてこの構造体を通して行う
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyNormalizer {
+    pub case_insensitive: bool,
+    pub width_fold: bool,
+    pub trim: bool,
+    pub collapse_space: bool,
+}
+
+impl Default for KeyNormalizer {
+    fn default() -> Self {
+        Self::new(&NormalizeOptions::default())
+    }
+}
+
+impl KeyNormalizer {
+    /// 正規化オプションから作成
+    pub fn new(options: &NormalizeOptions) -> Self {
+        Self {
+            case_insensitive: options.case_insensitive,
+            width_fold: options.width_fold,
+            trim: options.trim,
+            collapse_space: options.collapse_space,
+        }
+    }
+
+    /// 登録名マスタの条件・例外マスタの照合用（前後の空白を除き、大文字小文字を区別しない）
+    pub fn for_matching() -> Self {
+        Self {
+            case_insensitive: true,
+            width_fold: false,
+            trim: true,
+            collapse_space: false,
+        }
+    }
+
+    /// 比較オプションから作成（従来の `normalize_width` も全角・半角の同一視として扱う）
+    pub fn from_compare_options(options: &CompareOptions) -> Self {
+        let mut normalizer = Self::new(&options.normalize);
+        normalizer.width_fold |= options.normalize_width;
+        normalizer
+    }
+
+    /// 値を正規化
+    pub fn normalize(&self, value: &str) -> String {
+        let mut normalized = if self.width_fold {
+            normalize_width(value)
+        } else {
+            value.to_string()
+        };
+        if self.collapse_space {
+            normalized = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
+        } else if self.trim {
+            normalized = normalized.trim().to_string();
+        }
+        if self.case_insensitive {
+            normalized = normalized.to_uppercase();
+        }
+        normalized
+    }
+
+    /// 対応付けキー（役割ごとの値を区切り文字で連結したもの）を正規化
+    pub fn normalize_key(&self, key: &str) -> String {
+        key.split(MATCH_KEY_SEPARATOR)
+            .map(|part| self.normalize(part))
+            .collect::<Vec<_>>()
+            .join(MATCH_KEY_SEPARATOR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compare::compare_boms_with_options;
+    use crate::diff::merge::update_and_append_boms_with_normalizer;
+    use crate::models::{MatchKey, ParseResult};
+
+    fn build(rows: &[[&str; 2]]) -> ParseResult {
+        let headers = vec!["Ref".to_string(), "Part".to_string()];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers((1..=rows.len()).collect())
+            .build()
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalizer = KeyNormalizer::new(&NormalizeOptions {
+            case_insensitive: true,
+            width_fold: true,
+            trim: true,
+            collapse_space: true,
+        });
+        assert_eq!(normalizer.normalize("  ｇｒｍ　155  x "), "GRM 155 X");
+
+        let default = KeyNormalizer::default();
+        assert_eq!(default.normalize(" c1 "), "c1");
+        assert_eq!(
+            default.normalize_key(&format!(" C1 {MATCH_KEY_SEPARATOR} 10k ")),
+            format!("C1{MATCH_KEY_SEPARATOR}10k")
+        );
+    }
+
+    #[test]
+    fn test_same_options_match_same_rows_in_compare_and_merge() {
+        let parse_a = build(&[["c1", "GRM155"], ["R1", "RC0402"]]);
+        let parse_b = build(&[["Ｃ１", "grm155"], ["r1", "RC0603"], ["U1", "74HC08"]]);
+        let normalize = NormalizeOptions {
+            case_insensitive: true,
+            width_fold: true,
+            ..NormalizeOptions::default()
+        };
+
+        let options = CompareOptions {
+            normalize: normalize.clone(),
+            ..CompareOptions::default()
+        };
+        let diffs = compare_boms_with_options(&parse_a, &parse_b, &options);
+        let statuses: Vec<(&str, Option<usize>, Option<usize>)> = diffs
+            .iter()
+            .map(|diff| (diff.status.as_str(), diff.a_index, diff.b_index))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("unchanged", Some(0), Some(0)),
+                ("modified", Some(1), Some(1)),
+                ("added", None, Some(2)),
+            ]
+        );
+
+        // 比較で対応付いた行はマージでも同じ行が更新され、Bのみの行だけが追加される
+        let merged = update_and_append_boms_with_normalizer(
+            &parse_a,
+            &parse_b,
+            &MatchKey::default(),
+            &KeyNormalizer::new(&normalize),
+        )
+        .unwrap();
+        assert_eq!(
+            merged.rows,
+            vec![
+                vec!["Ｃ１", "grm155"],
+                vec!["r1", "RC0603"],
+                vec!["U1", "74HC08"],
+            ]
+        );
+    }
+}
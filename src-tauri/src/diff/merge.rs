@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::models::{AppError, ParseResult};
+use super::KeyNormalizer;
+use crate::models::{AppError, MatchKey, ParseResult};
 
 /// BOM AをBOM Bで更新し、Bの新規行を追加
 ///
@@ -18,19 +19,56 @@ pub fn update_and_append_boms(
     parse_a: &ParseResult,
     parse_b: &ParseResult,
 ) -> Result<ParseResult, AppError> {
+    update_and_append_boms_with_key(parse_a, parse_b, &MatchKey::default())
+}
+
+/// 対応付けキーを指定してBOM AをBOM Bで更新し、Bの新規行を追加
+///
+/// # 引数
+/// * `parse_a` - 更新元のBOM
+/// * `parse_b` - 更新内容のBOM
+/// * `match_key` - 行を対応付けるキーの役割（"part_no"、["ref", "value"] など）
+///
+/// # 戻り値
+/// マージ後のParseResult（Aの構造を維持、Bの内容で更新）
+///
+/// 行番号はAの行ではAでの行番号、追加したBの行ではBでの行番号を引き継ぐ
+pub fn update_and_append_boms_with_key(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    match_key: &MatchKey,
+) -> Result<ParseResult, AppError> {
+    update_and_append_boms_with_normalizer(parse_a, parse_b, match_key, &KeyNormalizer::default())
+}
+
+/// 対応付けキーと正規化を指定してBOM AをBOM Bで更新し、Bの新規行を追加
+///
+/// `normalizer` は対応付けキーの比較にのみ使用し、マージ結果の値は元の表記のまま
+pub fn update_and_append_boms_with_normalizer(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    match_key: &MatchKey,
+    normalizer: &KeyNormalizer,
+) -> Result<ParseResult, AppError> {
+    let key_roles = match_key.roles();
+
     // ------------------------------------------------------------------------
     // ステップ1: Bのマッピングを作成
     // ------------------------------------------------------------------------
 
     let mut map_b: HashMap<String, VecDeque<usize>> = HashMap::new();
     for (idx, _) in parse_b.rows.iter().enumerate() {
-        let ref_value = parse_b.get_ref(idx);
-        if !ref_value.is_empty() {
-            map_b.entry(ref_value).or_default().push_back(idx);
+        let key = parse_b.get_match_key(idx, &key_roles);
+        if !key.is_empty() {
+            map_b
+                .entry(normalizer.normalize_key(&key))
+                .or_default()
+                .push_back(idx);
         }
     }
 
     let mut merged_rows = Vec::new();
+    let mut row_numbers = Vec::new();
     let mut used_indices: HashSet<usize> = HashSet::new();
 
     // ------------------------------------------------------------------------
@@ -38,9 +76,10 @@ pub fn update_and_append_boms(
     // ------------------------------------------------------------------------
 
     for (idx_a, row_a) in parse_a.rows.iter().enumerate() {
-        let ref_a = parse_a.get_ref(idx_a);
+        let key_a = parse_a.get_match_key(idx_a, &key_roles);
+        row_numbers.push(parse_a.source_row_number(idx_a));
 
-        if let Some(queue) = map_b.get_mut(&ref_a) {
+        if let Some(queue) = map_b.get_mut(&normalizer.normalize_key(&key_a)) {
             if let Some(idx_b) = queue.pop_front() {
                 // Bに対応する行がある → Bの値で更新
                 used_indices.insert(idx_b);
@@ -82,6 +121,7 @@ pub fn update_and_append_boms(
     for (idx_b, row_b) in parse_b.rows.iter().enumerate() {
         if !used_indices.contains(&idx_b) {
             merged_rows.push(row_b.clone());
+            row_numbers.push(parse_b.source_row_number(idx_b));
         }
     }
 
@@ -89,20 +129,64 @@ pub fn update_and_append_boms(
     // ステップ4: マージ結果のParseResultを作成
     // ------------------------------------------------------------------------
 
-    let row_count = merged_rows.len();
-
-    Ok(ParseResult {
-        rows: merged_rows,
-        column_roles: parse_a.column_roles.clone(),
-        column_order: parse_a.column_order.clone(),
-        #[allow(deprecated)]
-        guessed_columns: HashMap::new(),
-        #[allow(deprecated)]
-        guessed_roles: HashMap::new(),
-        errors: vec![],
-        headers: parse_a.headers.clone(),
-        columns: parse_a.columns.clone(),
-        row_numbers: (1..=row_count).collect(),
-        structured_errors: None,
-    })
+    // AとBの行が混在するため、元ファイルでのヘッダー行・データ開始行の位置は引き継がない
+    Ok(ParseResult::builder()
+        .schema_from(parse_a)
+        .rows(merged_rows)
+        .row_numbers(row_numbers)
+        .header_row_index(None)
+        .data_start_index(0)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(rows: &[[&str; 3]]) -> ParseResult {
+        let headers: Vec<String> = ["Ref", "Part", "Value"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .role("value", &[2])
+            .row_numbers((1..=rows.len()).collect())
+            .build()
+    }
+
+    #[test]
+    fn test_update_and_append_keyed_by_part_no() {
+        // 部品型番ごとに集約されたBOM（Referenceは行ごとに異なる表記）
+        let parse_a = build(&[["C1-C3", "GRM155", "0.1uF"], ["R1", "RC0402", "10k"]]);
+        let parse_b = build(&[["C1-C4", "GRM155", "0.1uF"], ["U1", "74HC08", ""]]);
+
+        let merged = update_and_append_boms_with_key(
+            &parse_a,
+            &parse_b,
+            &MatchKey::Single("part_no".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            merged.rows,
+            vec![
+                vec!["C1-C4", "GRM155", "0.1uF"],
+                vec!["R1", "RC0402", "10k"],
+                vec!["U1", "74HC08", ""],
+            ]
+        );
+        // 追加したBの行はBでの行番号を引き継ぐ
+        assert_eq!(merged.row_numbers, vec![1, 2, 2]);
+
+        // 既定のReferenceキーでは別の行として追加される
+        let merged = update_and_append_boms(&parse_a, &parse_b).unwrap();
+        assert_eq!(merged.rows.len(), 4);
+    }
 }
@@ -1,2 +1,7 @@
 pub mod compare;
+pub mod concat;
+pub mod key;
 pub mod merge;
+pub mod three_way;
+
+pub use key::KeyNormalizer;
@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::models::{AppError, MergeConflict, MergeResult, ParseResult};
+use crate::utils::header::normalize_header;
+
+/// 共通の祖先を使って2つのBOMを3方向マージ
+///
+/// # マージ戦略
+/// Referenceで行を対応付け、列ごとに以下の規則で値を決める。
+/// 1. A・Bの値が同じ → その値
+/// 2. 片方のみがbaseから変更 → 変更された値
+/// 3. 両方が異なる値に変更 → 競合（マージ結果はAの値）
+///
+/// 行の削除は、もう一方が変更していなければ反映し、変更されていれば競合とする。
+/// B・baseの列は列名（見つからなければ同じ位置）でAの列に対応付け、
+/// Aにない列は出力しない。
+/// 行番号はAの行ではAでの行番号、Bのみの行ではBでの行番号を引き継ぐ。
+///
+/// # 引数
+/// * `base` - 共通の祖先となるBOM
+/// * `parse_a` - 編集済みのBOM A（列構造の基準）
+/// * `parse_b` - 編集済みのBOM B
+///
+/// # 戻り値
+/// マージ後のBOMと競合のリスト
+pub fn three_way_merge(
+    base: &ParseResult,
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+) -> Result<MergeResult, AppError> {
+    if parse_a.get_column_indices("ref").is_empty() {
+        return Err(AppError::validation(
+            "BOM AのReference列が設定されていないため、3方向マージできません。",
+        ));
+    }
+
+    let column_count = parse_a.column_count();
+    let base_rows = align_rows(base, parse_a, column_count);
+    let b_rows = align_rows(parse_b, parse_a, column_count);
+
+    let mut base_map = ref_map(base);
+    let mut b_map = ref_map(parse_b);
+    let mut used_b: HashSet<usize> = HashSet::new();
+
+    let mut merged_rows: Vec<Vec<String>> = Vec::new();
+    let mut row_numbers: Vec<usize> = Vec::new();
+    let mut conflicts: Vec<MergeConflict> = Vec::new();
+
+    // ------------------------------------------------------------------------
+    // ステップ1: Aの行を基準にマージ
+    // ------------------------------------------------------------------------
+
+    for (idx_a, row_a) in parse_a.rows.iter().enumerate() {
+        let ref_value = parse_a.get_ref(idx_a);
+        let row_a = pad_row(row_a, column_count);
+
+        if ref_value.is_empty() {
+            merged_rows.push(row_a);
+            row_numbers.push(parse_a.source_row_number(idx_a));
+            continue;
+        }
+
+        let idx_base = pop_front(&mut base_map, &ref_value);
+        let idx_b = pop_front(&mut b_map, &ref_value);
+        if let Some(idx_b) = idx_b {
+            used_b.insert(idx_b);
+        }
+
+        let merged_index = merged_rows.len();
+        match (idx_base, idx_b) {
+            (Some(idx_base), Some(idx_b)) => {
+                let (row, row_conflicts) = merge_row(
+                    parse_a,
+                    &ref_value,
+                    merged_index,
+                    Some(&base_rows[idx_base]),
+                    &row_a,
+                    &b_rows[idx_b],
+                    "modified",
+                );
+                merged_rows.push(row);
+                row_numbers.push(parse_a.source_row_number(idx_a));
+                conflicts.extend(row_conflicts);
+            }
+            (None, Some(idx_b)) => {
+                // 両方で追加された行
+                let (row, row_conflicts) = merge_row(
+                    parse_a,
+                    &ref_value,
+                    merged_index,
+                    None,
+                    &row_a,
+                    &b_rows[idx_b],
+                    "added_both",
+                );
+                merged_rows.push(row);
+                row_numbers.push(parse_a.source_row_number(idx_a));
+                conflicts.extend(row_conflicts);
+            }
+            (Some(idx_base), None) => {
+                // Bで削除された行：Aが変更していなければ削除を反映
+                if row_a == base_rows[idx_base] {
+                    continue;
+                }
+                conflicts.push(row_conflict("deleted_in_b", &ref_value, merged_index));
+                merged_rows.push(row_a);
+                row_numbers.push(parse_a.source_row_number(idx_a));
+            }
+            (None, None) => {
+                // Aで追加された行
+                merged_rows.push(row_a);
+                row_numbers.push(parse_a.source_row_number(idx_a));
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // ステップ2: Aに存在しないBの行
+    // ------------------------------------------------------------------------
+
+    for (idx_b, row_b) in b_rows.iter().enumerate() {
+        if used_b.contains(&idx_b) {
+            continue;
+        }
+        let ref_value = parse_b.get_ref(idx_b);
+
+        match pop_front(&mut base_map, &ref_value).filter(|_| !ref_value.is_empty()) {
+            Some(idx_base) => {
+                // Aで削除された行：Bが変更していなければ削除を反映
+                if *row_b == base_rows[idx_base] {
+                    continue;
+                }
+                conflicts.push(row_conflict("deleted_in_a", &ref_value, merged_rows.len()));
+                merged_rows.push(row_b.clone());
+                row_numbers.push(parse_b.source_row_number(idx_b));
+            }
+            None => {
+                // Bで追加された行
+                merged_rows.push(row_b.clone());
+                row_numbers.push(parse_b.source_row_number(idx_b));
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // ステップ3: マージ結果のParseResultを作成
+    // ------------------------------------------------------------------------
+
+    // 行はA・Bの両方から作るため、Aの書式・読み込み時の値は引き継がない
+    let mut merged = ParseResult::builder()
+        .schema_from(parse_a)
+        .rows(merged_rows)
+        .row_numbers(row_numbers)
+        .header_row_index(None)
+        .data_start_index(0)
+        .build();
+    merged.rebuild_columns_from_headers();
+
+    Ok(MergeResult { merged, conflicts })
+}
+
+/// 1行を列ごとに3方向マージ
+fn merge_row(
+    parse_a: &ParseResult,
+    ref_value: &str,
+    merged_index: usize,
+    row_base: Option<&Vec<String>>,
+    row_a: &[String],
+    row_b: &[String],
+    conflict_kind: &str,
+) -> (Vec<String>, Vec<MergeConflict>) {
+    let mut merged = row_a.to_vec();
+    let mut conflicts = Vec::new();
+
+    for (col_idx, cell) in merged.iter_mut().enumerate() {
+        let value_a = row_a[col_idx].trim();
+        let value_b = row_b[col_idx].trim();
+        let value_base = row_base.map(|row| row[col_idx].trim()).unwrap_or("");
+
+        if value_a == value_b || value_b == value_base {
+            continue;
+        }
+        if value_a == value_base {
+            *cell = row_b[col_idx].clone();
+            continue;
+        }
+
+        conflicts.push(MergeConflict {
+            kind: conflict_kind.to_string(),
+            ref_value: ref_value.to_string(),
+            merged_index,
+            column_id: Some(format!("col-{}", col_idx)),
+            column_name: parse_a.headers.get(col_idx).cloned(),
+            base_value: row_base.map(|_| value_base.to_string()),
+            a_value: Some(value_a.to_string()),
+            b_value: Some(value_b.to_string()),
+        });
+    }
+
+    (merged, conflicts)
+}
+
+/// 行単位の競合（削除と変更）
+fn row_conflict(kind: &str, ref_value: &str, merged_index: usize) -> MergeConflict {
+    MergeConflict {
+        kind: kind.to_string(),
+        ref_value: ref_value.to_string(),
+        merged_index,
+        column_id: None,
+        column_name: None,
+        base_value: None,
+        a_value: None,
+        b_value: None,
+    }
+}
+
+/// Reference → 行インデックスのキュー（重複するReferenceは出現順に対応付ける）
+fn ref_map(parse: &ParseResult) -> HashMap<String, VecDeque<usize>> {
+    let mut map: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for idx in 0..parse.rows.len() {
+        let ref_value = parse.get_ref(idx);
+        if !ref_value.is_empty() {
+            map.entry(ref_value).or_default().push_back(idx);
+        }
+    }
+    map
+}
+
+fn pop_front(map: &mut HashMap<String, VecDeque<usize>>, key: &str) -> Option<usize> {
+    map.get_mut(key).and_then(|queue| queue.pop_front())
+}
+
+fn pad_row(row: &[String], column_count: usize) -> Vec<String> {
+    let mut padded = row.to_vec();
+    padded.resize(column_count, String::new());
+    padded
+}
+
+/// 行データをAの列構造に並べ替える
+///
+/// 列名（正規化後）が一致する列、なければ同じ位置の列を対応付ける
+fn align_rows(parse: &ParseResult, target: &ParseResult, column_count: usize) -> Vec<Vec<String>> {
+    let source_headers: Vec<String> = parse.headers.iter().map(|h| normalize_header(h)).collect();
+
+    let mapping: Vec<Option<usize>> = (0..column_count)
+        .map(|col_idx| {
+            let header = target
+                .headers
+                .get(col_idx)
+                .map(|h| normalize_header(h))
+                .filter(|h| !h.is_empty());
+            match header {
+                Some(header) => source_headers
+                    .iter()
+                    .position(|source| *source == header)
+                    .or(Some(col_idx)),
+                None => Some(col_idx),
+            }
+        })
+        .collect();
+
+    parse
+        .rows
+        .iter()
+        .map(|row| {
+            mapping
+                .iter()
+                .map(|source_idx| {
+                    source_idx
+                        .and_then(|idx| row.get(idx))
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(headers: &[&str], rows: &[&[&str]]) -> ParseResult {
+        let headers: Vec<String> = headers.iter().map(|name| name.to_string()).collect();
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers((1..=rows.len()).collect())
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build()
+    }
+
+    const HEADERS: &[&str] = &["Ref", "Part", "Maker"];
+
+    fn base() -> ParseResult {
+        build(
+            HEADERS,
+            &[
+                &["C1", "GRM155", "Murata"],
+                &["C2", "GRM155", "Murata"],
+                &["R1", "RC0402", "Yageo"],
+                &["R2", "RC0402", "Yageo"],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_three_way_merge_applies_both_sides() {
+        let mut a = build(
+            HEADERS,
+            &[
+                &["C1", "GRM188", "Murata"],
+                &["C2", "GRM155", "Murata"],
+                &["R1", "RC0402", "Yageo"],
+                &["R2", "RC0402", "Yageo"],
+                &["U1", "74HC08", "TI"],
+            ],
+        );
+        // Bは列の順序が異なり、R2を削除してR1のメーカーを変更
+        let mut b = build(
+            &["Maker", "Ref", "Part"],
+            &[
+                &["Murata", "C1", "GRM155"],
+                &["Murata", "C2", "GRM155"],
+                &["KOA", "R1", "RC0402"],
+                &["ROHM", "D1", "RB751"],
+            ],
+        );
+        b.column_roles = HashMap::from([
+            ("ref".to_string(), vec!["col-1".to_string()]),
+            ("part_no".to_string(), vec!["col-2".to_string()]),
+        ]);
+
+        a.original_rows = Some(a.rows.clone());
+
+        let result = three_way_merge(&base(), &a, &b).unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged.headers, vec!["Ref", "Part", "Maker"]);
+        // Aの読み込み時の値はマージ後の行と対応しないため引き継がない
+        assert_eq!(result.merged.original_rows, None);
+        assert_eq!(
+            result.merged.rows,
+            vec![
+                vec!["C1", "GRM188", "Murata"],
+                vec!["C2", "GRM155", "Murata"],
+                vec!["R1", "RC0402", "KOA"],
+                vec!["U1", "74HC08", "TI"],
+                vec!["D1", "RB751", "ROHM"],
+            ]
+        );
+        assert_eq!(result.merged.row_numbers, vec![1, 2, 3, 5, 4]);
+    }
+
+    #[test]
+    fn test_three_way_merge_reports_conflicts() {
+        let a = build(
+            HEADERS,
+            &[
+                &["C1", "GRM188", "Murata"],
+                &["C2", "GRM155", "Murata"],
+                &["R2", "RC0603", "Yageo"],
+            ],
+        );
+        let b = build(
+            HEADERS,
+            &[
+                &["C1", "CL05B104", "Samsung"],
+                &["R1", "RC0402", "KOA"],
+                &["R2", "RC0402", "Yageo"],
+            ],
+        );
+
+        let result = three_way_merge(&base(), &a, &b).unwrap();
+
+        // C1 の部品型番は両方が変更 → Aの値を保持して競合
+        assert_eq!(result.merged.rows[0], vec!["C1", "GRM188", "Samsung"]);
+        let part_conflict = &result.conflicts[0];
+        assert_eq!(part_conflict.kind, "modified");
+        assert_eq!(part_conflict.ref_value, "C1");
+        assert_eq!(part_conflict.column_id.as_deref(), Some("col-1"));
+        assert_eq!(part_conflict.column_name.as_deref(), Some("Part"));
+        assert_eq!(part_conflict.base_value.as_deref(), Some("GRM155"));
+        assert_eq!(part_conflict.a_value.as_deref(), Some("GRM188"));
+        assert_eq!(part_conflict.b_value.as_deref(), Some("CL05B104"));
+
+        // C2 はBで削除されAは未変更 → 削除を反映
+        assert!(result.merged.rows.iter().all(|row| row[0] != "C2"));
+
+        // R1 はAで削除されBで変更 → Bの行を保持して競合
+        let kinds: Vec<(&str, &str)> = result
+            .conflicts
+            .iter()
+            .map(|conflict| (conflict.kind.as_str(), conflict.ref_value.as_str()))
+            .collect();
+        assert_eq!(kinds, vec![("modified", "C1"), ("deleted_in_a", "R1")]);
+        let r1 = &result.conflicts[1];
+        assert_eq!(result.merged.rows[r1.merged_index][0], "R1");
+        assert_eq!(r1.column_id, None);
+    }
+}
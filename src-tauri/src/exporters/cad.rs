@@ -1,114 +1,208 @@
-use super::diff_comment;
-use crate::models::{AppError, ParseResult};
+use super::RowStatuses;
+use crate::models::{
+    AppError, CadIndent, EcoTemplate, GroupBy, ParseResult, UNSPECIFIED_GROUP_KEY,
+};
+use crate::processors::reference::individual_references;
 use std::collections::HashMap;
 
+/// 字下げに指定できる最大の空白の数
+const MAX_CAD_INDENT: usize = 64;
+
+/// CCFの先頭（DEFINITIONセクションの開始まで）
+const CCF_HEADER: &str = "$CCF{\n     DEFINITION{\n";
+
+/// CCFの末尾（DEFINITIONセクションの終了と、空のNETセクション）
+const CCF_FOOTER: &str = "               }\n     NET{\n        }\n    }\n";
+
+/// MSFの先頭（SHAPEセクションの開始まで）
+const MSF_HEADER: &str = "$MSF {\n     SHAPE {\n";
+
+/// MSFの末尾（SHAPEセクションとファイルの終了）
+const MSF_FOOTER: &str = "           }\n      }\n";
+
 /// PADS-ECO形式でエクスポート
+///
+/// テンプレートがある場合は、そのヘッダー・フッターの行で部品データを挟んで出力する。
+/// 1つのセルに複数のReference（"C1,C2"・"C1-C3"）がある行は、Referenceごとに1行ずつ出力する。
+/// `extra_roles` の値は部品型番の後（差分コメントの前）に空白区切りで出力する
 pub fn export_eco(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    statuses: &RowStatuses,
     include_comments: bool,
+    template: Option<&EcoTemplate>,
+    extra_roles: &[String],
 ) -> Result<String, AppError> {
-    let mut lines = vec!["*PADS-ECO*".to_string(), "*PART*".to_string()];
+    // BOMに列がない役割は出力しない
+    let extra_roles: Vec<&str> = extra_roles
+        .iter()
+        .map(|role| role.trim())
+        .filter(|role| !parse.get_column_indices(role).is_empty())
+        .collect();
+
+    let mut lines = match template {
+        Some(template) if !template.header.is_empty() => template.header.clone(),
+        _ => vec!["*PADS-ECO*".to_string()],
+    };
+    if !lines.iter().any(|line| line.trim() == "*PART*") {
+        lines.push("*PART*".to_string());
+    }
 
     for (idx, _) in parse.rows.iter().enumerate() {
         let ref_value = parse.get_ref(idx);
         let part_no = parse.get_part_no(idx);
 
-        let mut line = format!("{} {}", ref_value, part_no);
+        let mut attributes = String::new();
+        for role in &extra_roles {
+            attributes.push(' ');
+            attributes.push_str(&eco_token(&parse.get_values(idx, role)));
+        }
         if include_comments {
-            let comment = diff_comment(&ref_value, diff_map);
+            let comment = statuses.comment(idx);
             if !comment.is_empty() {
-                line.push_str(&format!(" {}", comment));
+                attributes.push_str(&format!(" {}", comment));
             }
         }
-        lines.push(line);
+
+        // PADSは1行に1つのReferenceのみ受け付けるため、"C1,C2" のようなセルは1つずつ出力する
+        let refs = individual_references(&ref_value)?;
+        if refs.is_empty() {
+            lines.push(format!("{} {}{}", ref_value, part_no, attributes));
+        }
+        for reference in refs {
+            lines.push(format!("{} {}{}", reference, part_no, attributes));
+        }
     }
 
-    lines.push("*END*".to_string());
+    if let Some(template) = template {
+        lines.extend(template.footer.iter().cloned());
+    }
+    if lines.last().map(|line| line.trim()) != Some("*END*") {
+        lines.push("*END*".to_string());
+    }
     Ok(lines.join("\n"))
 }
 
+/// 役割の値をECOの1トークンに変換
+///
+/// 複数列の値は "/" で連結し、空白は "_" に置き換える。
+/// 値がない場合は列の位置がずれないよう "-" を出力する
+fn eco_token(values: &[String]) -> String {
+    let joined = values
+        .iter()
+        .map(|value| value.split_whitespace().collect::<Vec<_>>().join("_"))
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if joined.is_empty() {
+        "-".to_string()
+    } else {
+        joined
+    }
+}
+
 /// CCF形式でエクスポート
+///
+/// DEFINITIONセクションに "型番:Ref1,Ref2;" の形式で出力し、末尾に空のNETセクションを付ける
 pub fn export_ccf(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    statuses: &RowStatuses,
     include_comments: bool,
+    group_by: GroupBy,
+    indent: &CadIndent,
 ) -> Result<String, AppError> {
-    let grouped = group_by_part_no(parse, diff_map, include_comments);
-    let mut content = String::from("$CCF{\n     DEFINITION{\n");
-
-    // 品番でソート
-    let mut sorted_part_nos: Vec<_> = grouped.keys().collect();
-    sorted_part_nos.sort();
-
-    for part_no in sorted_part_nos {
-        let refs = grouped.get(part_no).unwrap();
-        content.push_str(&format!("                {}:{}", part_no, refs[0]));
-        for i in 1..refs.len() {
-            content.push_str(&format!(",\n                         {}", refs[i]));
-        }
-        content.push_str(";\n");
-    }
-
-    content.push_str("               }\n     NET{\n        }\n    }\n");
-    Ok(content)
+    let grouped = group_by_role(parse, group_by.role_key(), statuses, include_comments);
+    let entries = definition_entries(&grouped, indent)?;
+    Ok(format!("{CCF_HEADER}{entries}{CCF_FOOTER}"))
 }
 
 /// MSF形式でエクスポート
+///
+/// SHAPEセクションに "型番:Ref1,Ref2;" の形式で出力する
 pub fn export_msf(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    statuses: &RowStatuses,
     include_comments: bool,
+    group_by: GroupBy,
+    indent: &CadIndent,
 ) -> Result<String, AppError> {
-    let grouped = group_by_part_no(parse, diff_map, include_comments);
-    let mut content = String::from("$MSF {\n     SHAPE {\n");
-
-    // 品番でソート
-    let mut sorted_part_nos: Vec<_> = grouped.keys().collect();
-    sorted_part_nos.sort();
-
-    for part_no in sorted_part_nos {
-        let refs = grouped.get(part_no).unwrap();
-        content.push_str(&format!("                {}:{}", part_no, refs[0]));
-        for i in 1..refs.len() {
-            content.push_str(&format!(",\n                         {}", refs[i]));
+    let grouped = group_by_role(parse, group_by.role_key(), statuses, include_comments);
+    let entries = definition_entries(&grouped, indent)?;
+    Ok(format!("{MSF_HEADER}{entries}{MSF_FOOTER}"))
+}
+
+/// CCF/MSFのエントリ（グループのキー順、2つ目以降のReferenceは継続行）
+fn definition_entries(
+    grouped: &HashMap<String, Vec<String>>,
+    indent: &CadIndent,
+) -> Result<String, AppError> {
+    if indent.entry > MAX_CAD_INDENT || indent.continuation > MAX_CAD_INDENT {
+        return Err(AppError::validation(format!(
+            "字下げは{MAX_CAD_INDENT}文字以下で指定してください。"
+        )));
+    }
+    let entry_indent = " ".repeat(indent.entry);
+    let continuation_indent = " ".repeat(indent.continuation);
+
+    // グループのキーでソート
+    let mut sorted_keys: Vec<_> = grouped.keys().collect();
+    sorted_keys.sort();
+
+    let mut content = String::new();
+    for key in sorted_keys {
+        let refs = &grouped[key];
+        content.push_str(&format!("{entry_indent}{}:{}", key, refs[0]));
+        for reference in &refs[1..] {
+            content.push_str(&format!(",\n{continuation_indent}{}", reference));
         }
         content.push_str(";\n");
     }
-
-    content.push_str("           }\n      }\n");
     Ok(content)
 }
 
-/// 品番でグルーピング
-fn group_by_part_no(
+/// 指定した役割の値でグルーピング
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `role` - グループ化する役割（"part_no", "value", "manufacturer"）
+/// * `statuses` - 行ごとの差分ステータス
+/// * `include_comments` - 差分コメントを含めるか
+///
+/// # 戻り値
+/// 役割の値（空の場合は "(未指定)"） → Referenceのリスト
+fn group_by_role(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    role: &str,
+    statuses: &RowStatuses,
     include_comments: bool,
 ) -> HashMap<String, Vec<String>> {
     let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
 
     for (idx, _) in parse.rows.iter().enumerate() {
         let ref_value = parse.get_ref(idx);
-        let mut part_no = parse.get_part_no(idx);
+        // Referenceが空の行は出力すると "品番:;" のような不正なエントリになるため除外
+        if ref_value.is_empty() {
+            continue;
+        }
+        let mut key = parse
+            .get_values(idx, role)
+            .into_iter()
+            .find(|value| !value.trim().is_empty())
+            .unwrap_or_default();
 
         // 空の場合は "(未指定)" とする
-        if part_no.is_empty() {
-            part_no = "(未指定)".to_string();
+        if key.is_empty() {
+            key = UNSPECIFIED_GROUP_KEY.to_string();
         }
 
         let mut ref_with_comment = ref_value.clone();
         if include_comments {
-            let comment = diff_comment(&ref_value, diff_map);
+            let comment = statuses.comment(idx);
             if !comment.is_empty() {
                 ref_with_comment.push_str(&format!(" {}", comment));
             }
         }
 
-        grouped
-            .entry(part_no)
-            .or_insert_with(Vec::new)
-            .push(ref_with_comment);
+        grouped.entry(key).or_default().push(ref_with_comment);
     }
 
     grouped
@@ -116,20 +210,21 @@ fn group_by_part_no(
 /// PWS形式でエクスポート
 pub fn export_pws(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    statuses: &RowStatuses,
     include_comments: bool,
+    group_by: GroupBy,
 ) -> Result<String, AppError> {
-    let grouped = group_by_part_no(parse, diff_map, include_comments);
+    let grouped = group_by_role(parse, group_by.role_key(), statuses, include_comments);
     let mut lines = Vec::new();
 
-    // 品番でソート
-    let mut sorted_part_nos: Vec<_> = grouped.keys().collect();
-    sorted_part_nos.sort();
+    // グループのキーでソート
+    let mut sorted_keys: Vec<_> = grouped.keys().collect();
+    sorted_keys.sort();
 
-    for part_no in sorted_part_nos {
-        let refs = grouped.get(part_no).unwrap();
+    for key in sorted_keys {
+        let refs = grouped.get(key).unwrap();
         let refs_joined = refs.join(",");
-        lines.push(format!("{}:{};", part_no, refs_joined));
+        lines.push(format!("{}:{};", key, refs_joined));
     }
 
     Ok(lines.join("\n"))
@@ -138,7 +233,7 @@ pub fn export_pws(
 /// BD形式でエクスポート
 pub fn export_bd(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    statuses: &RowStatuses,
     include_comments: bool,
 ) -> Result<String, AppError> {
     let mut lines = Vec::new();
@@ -149,7 +244,7 @@ pub fn export_bd(
 
         let mut line = format!("{} {}", ref_value, part_no);
         if include_comments {
-            let comment = diff_comment(&ref_value, diff_map);
+            let comment = statuses.comment(idx);
             if !comment.is_empty() {
                 line.push_str(&format!(" {}", comment));
             }
@@ -163,7 +258,7 @@ pub fn export_bd(
 /// PADSレポート形式でエクスポート
 pub fn export_pads_report(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
+    statuses: &RowStatuses,
     include_comments: bool,
 ) -> Result<String, AppError> {
     let mut lines = vec![
@@ -178,7 +273,7 @@ pub fn export_pads_report(
 
         let mut line = format!("{:<12} XXX            {}", ref_value, part_no);
         if include_comments {
-            let comment = diff_comment(&ref_value, diff_map);
+            let comment = statuses.comment(idx);
             if !comment.is_empty() {
                 line.push_str(&format!(" {}", comment));
             }
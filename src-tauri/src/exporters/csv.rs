@@ -1,46 +1,282 @@
-use super::diff_comment;
-use crate::models::{AppError, ParseResult};
-use csv::WriterBuilder;
-use std::collections::HashMap;
+use super::RowStatuses;
+use crate::models::{AppError, ExportOptions, LineEnding, ParseResult};
+use csv::{Terminator, WriterBuilder};
 
 /// CSVエクスポート
+///
+/// `options.headers` が空の場合は全列を元の順序で出力し、
+/// 指定がある場合はその列のみを指定順で出力します（列名または列IDで指定）
+///
+/// `options.extra_roles` の役割を持つ列が出力対象に含まれない場合は末尾に追加します
+///
+/// BOMの有無・改行コード・区切り文字は
+/// `options.write_bom` / `options.line_ending` / `options.delimiter` に従います
 pub fn export_csv(
     parse: &ParseResult,
-    diff_map: &HashMap<String, String>,
-    include_comments: bool,
+    statuses: &RowStatuses,
+    options: &ExportOptions,
 ) -> Result<String, AppError> {
+    let terminator = match options.line_ending {
+        LineEnding::Lf => Terminator::Any(b'\n'),
+        LineEnding::Crlf => Terminator::CRLF,
+    };
+
+    let delimiter = validate_delimiter(options.delimiter)?;
+
     let mut writer = WriterBuilder::new()
         .has_headers(false)
+        .delimiter(delimiter)
+        .terminator(terminator)
         .from_writer(Vec::new());
 
+    for record in export_table(parse, statuses, options)? {
+        writer
+            .write_record(&record)
+            .map_err(|e| AppError::new(format!("CSV書き込みエラー: {}", e)))?;
+    }
+
+    let data = writer
+        .into_inner()
+        .map_err(|e| AppError::new(format!("CSVバッファ取得エラー: {}", e)))?;
+
+    let csv_string =
+        String::from_utf8(data).map_err(|e| AppError::new(format!("UTF-8変換エラー: {}", e)))?;
+
+    // UTF-8 BOMを先頭に追加
+    if options.write_bom {
+        Ok(format!("\u{FEFF}{}", csv_string))
+    } else {
+        Ok(csv_string)
+    }
+}
+
+/// 表形式（CSV・XLSX）で出力するヘッダー行とデータ行
+///
+/// 出力列は `options.headers`・`options.extra_roles` に従い、
+/// `options.include_diff_comments` の場合は末尾に差分コメント列を追加します
+pub(super) fn export_table(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    options: &ExportOptions,
+) -> Result<Vec<Vec<String>>, AppError> {
+    let include_comments = options.include_diff_comments;
+    let mut column_indices = resolve_export_columns(parse, &options.headers)?;
+    for role in &options.extra_roles {
+        for col_idx in parse.get_column_indices(role.trim()) {
+            if !column_indices.contains(&col_idx) {
+                column_indices.push(col_idx);
+            }
+        }
+    }
+
     // ヘッダー行
-    let mut header_row = parse.headers.clone();
+    let mut header_row: Vec<String> = column_indices
+        .iter()
+        .map(|&idx| parse.headers.get(idx).cloned().unwrap_or_default())
+        .collect();
     if include_comments {
         header_row.push("差分コメント".to_string());
     }
-    writer
-        .write_record(&header_row)
-        .map_err(|e| AppError::new(format!("CSV書き込みエラー: {}", e)))?;
+    let mut table = Vec::with_capacity(parse.rows.len() + 1);
+    table.push(header_row);
 
     // データ行
     for (idx, row) in parse.rows.iter().enumerate() {
-        let mut output_row = row.clone();
+        let mut output_row: Vec<String> = column_indices
+            .iter()
+            .map(|&col_idx| row.get(col_idx).cloned().unwrap_or_default())
+            .collect();
         if include_comments {
-            let ref_value = parse.get_ref(idx);
-            output_row.push(diff_comment(&ref_value, diff_map));
+            output_row.push(statuses.comment(idx));
         }
-        writer
-            .write_record(&output_row)
-            .map_err(|e| AppError::new(format!("CSV書き込みエラー: {}", e)))?;
+        table.push(output_row);
     }
+    Ok(table)
+}
 
-    let data = writer
-        .into_inner()
-        .map_err(|e| AppError::new(format!("CSVバッファ取得エラー: {}", e)))?;
+/// 区切り文字を検証してバイト値に変換
+///
+/// 引用符・改行・非ASCII文字は出力の引用処理を壊すため拒否します
+fn validate_delimiter(delimiter: char) -> Result<u8, AppError> {
+    if !delimiter.is_ascii() || matches!(delimiter, '"' | '\r' | '\n') {
+        return Err(AppError::validation(format!(
+            "CSVの区切り文字として使用できない文字です: {:?}",
+            delimiter
+        )));
+    }
+    Ok(delimiter as u8)
+}
 
-    let csv_string =
-        String::from_utf8(data).map_err(|e| AppError::new(format!("UTF-8変換エラー: {}", e)))?;
+/// 出力対象の列インデックスを解決
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `headers` - 出力する列名または列ID（"col-0" など）のリスト
+///
+/// # 戻り値
+/// 出力順に並んだ列インデックス（`headers` が空の場合は全列）
+fn resolve_export_columns(parse: &ParseResult, headers: &[String]) -> Result<Vec<usize>, AppError> {
+    let column_count = parse
+        .rows
+        .iter()
+        .map(|row| row.len())
+        .chain(std::iter::once(parse.headers.len()))
+        .max()
+        .unwrap_or(0);
 
-    // UTF-8 BOMを先頭に追加
-    Ok(format!("\u{FEFF}{}", csv_string))
+    if headers.is_empty() {
+        return Ok((0..column_count).collect());
+    }
+
+    headers
+        .iter()
+        .map(|requested| {
+            let requested = requested.trim();
+
+            // 列IDで検索（"col-0" など）
+            if let Some(idx) = parse
+                .columns
+                .iter()
+                .position(|column| column.id == requested)
+            {
+                return Ok(idx);
+            }
+
+            // 列名で検索
+            parse
+                .headers
+                .iter()
+                .position(|header| header.trim() == requested)
+                .ok_or_else(|| {
+                    AppError::validation(format!(
+                        "エクスポート対象の列が見つかりません: {}",
+                        requested
+                    ))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DiffRow;
+
+    fn sample_parse() -> ParseResult {
+        let headers = vec!["Ref".to_string(), "Part".to_string(), "Maker".to_string()];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(vec![
+                vec!["C1".to_string(), "GRM155".to_string(), "Murata".to_string()],
+                vec!["R1".to_string(), "RC0402".to_string(), "Yageo".to_string()],
+            ])
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers(vec![1, 2])
+            .build()
+    }
+
+    #[test]
+    fn test_export_csv_all_columns_by_default() {
+        let csv = export_csv(
+            &sample_parse(),
+            &RowStatuses::default(),
+            &ExportOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            csv,
+            "\u{FEFF}Ref,Part,Maker\nC1,GRM155,Murata\nR1,RC0402,Yageo\n"
+        );
+    }
+
+    #[test]
+    fn test_export_csv_selected_columns_reordered() {
+        let options = ExportOptions {
+            include_diff_comments: true,
+            headers: vec!["Maker".to_string(), "col-0".to_string()],
+            ..ExportOptions::default()
+        };
+        let diffs = vec![DiffRow {
+            status: "modified".to_string(),
+            a_index: Some(0),
+            b_index: Some(0),
+            ref_value: "C1".to_string(),
+            changed_columns: vec!["col-1".to_string()],
+            changes: vec![],
+        }];
+        let statuses = RowStatuses::new(&sample_parse(), &diffs, None);
+        let csv = export_csv(&sample_parse(), &statuses, &options).unwrap();
+        assert_eq!(
+            csv,
+            "\u{FEFF}Maker,Ref,差分コメント\nMurata,C1,←変更\nYageo,R1,\n"
+        );
+    }
+
+    #[test]
+    fn test_export_csv_unknown_header_errors() {
+        let options = ExportOptions {
+            headers: vec!["Ref".to_string(), "Qty".to_string()],
+            ..ExportOptions::default()
+        };
+        let err = export_csv(&sample_parse(), &RowStatuses::default(), &options).unwrap_err();
+        assert!(err.message.contains("Qty"));
+    }
+
+    #[test]
+    fn test_export_csv_bom_and_line_ending_combinations() {
+        let cases = [
+            (true, LineEnding::Lf, "\u{FEFF}Ref", "Yageo\n"),
+            (true, LineEnding::Crlf, "\u{FEFF}Ref", "Yageo\r\n"),
+            (false, LineEnding::Lf, "Ref", "Yageo\n"),
+            (false, LineEnding::Crlf, "Ref", "Yageo\r\n"),
+        ];
+
+        for (write_bom, line_ending, prefix, suffix) in cases {
+            let options = ExportOptions {
+                write_bom,
+                line_ending,
+                ..ExportOptions::default()
+            };
+            let csv = export_csv(&sample_parse(), &RowStatuses::default(), &options).unwrap();
+            let bytes = csv.as_bytes();
+
+            assert!(csv.starts_with(prefix), "prefix: {:?}", csv);
+            assert!(csv.ends_with(suffix), "suffix: {:?}", csv);
+            assert_eq!(bytes.starts_with(&[0xEF, 0xBB, 0xBF]), write_bom);
+            if line_ending == LineEnding::Lf {
+                assert!(!csv.contains('\r'));
+            } else {
+                assert_eq!(csv.matches("\r\n").count(), 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_tsv_quotes_cells_containing_delimiter() {
+        let mut parse = sample_parse();
+        parse.rows[0][2] = "Murata\tMfg".to_string();
+        let options = ExportOptions {
+            delimiter: '\t',
+            write_bom: false,
+            ..ExportOptions::default()
+        };
+
+        let tsv = export_csv(&parse, &RowStatuses::default(), &options).unwrap();
+        assert_eq!(
+            tsv,
+            "Ref\tPart\tMaker\nC1\tGRM155\t\"Murata\tMfg\"\nR1\tRC0402\tYageo\n"
+        );
+    }
+
+    #[test]
+    fn test_export_csv_rejects_quote_delimiter() {
+        for delimiter in ['"', '\n', '、'] {
+            let options = ExportOptions {
+                delimiter,
+                ..ExportOptions::default()
+            };
+            assert!(export_csv(&sample_parse(), &RowStatuses::default(), &options).is_err());
+        }
+    }
 }
@@ -0,0 +1,181 @@
+use super::diff_report::{build_report_entries, DiffReportEntry, REPORT_HEADERS};
+use crate::models::{DiffRow, ParseResult};
+use crate::utils::text::status_to_color;
+
+/// 差分レポートをHTMLの表（単体で表示できる断片）として出力
+///
+/// 行はステータスに応じて色分けする（色は `color_to_status` と同じ配色）。
+/// セルの内容はHTMLエスケープする
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - `compare_boms` の結果
+pub fn export_diff_html(parse_a: &ParseResult, parse_b: &ParseResult, diffs: &[DiffRow]) -> String {
+    let mut html = String::from(
+        "<table class=\"bom-diff\" style=\"border-collapse:collapse;font-family:sans-serif;font-size:13px\">\n",
+    );
+    html.push_str("  <thead>\n    <tr>");
+    for header in REPORT_HEADERS {
+        html.push_str(&format!(
+            "<th style=\"border:1px solid #ccc;padding:4px 8px;text-align:left\">{}</th>",
+            escape_html(header)
+        ));
+    }
+    html.push_str("</tr>\n  </thead>\n  <tbody>\n");
+
+    for entry in build_report_entries(parse_a, parse_b, diffs) {
+        html.push_str(&format!("    <tr{}>", row_style(&entry.status)));
+        for value in entry_cells(&entry) {
+            html.push_str(&format!(
+                "<td style=\"border:1px solid #ccc;padding:4px 8px\">{}</td>",
+                escape_html(value)
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("  </tbody>\n</table>\n");
+    html
+}
+
+/// 差分レポートをMarkdownの表として出力
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - `compare_boms` の結果
+pub fn export_diff_markdown(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    diffs: &[DiffRow],
+) -> String {
+    let mut lines = vec![
+        format!("| {} |", REPORT_HEADERS.join(" | ")),
+        format!("|{}", "---|".repeat(REPORT_HEADERS.len())),
+    ];
+
+    for entry in build_report_entries(parse_a, parse_b, diffs) {
+        let cells: Vec<String> = entry_cells(&entry)
+            .iter()
+            .map(|value| escape_markdown(value))
+            .collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn entry_cells(entry: &DiffReportEntry) -> [&str; 5] {
+    [
+        &entry.reference,
+        &entry.status,
+        &entry.field,
+        &entry.value_a,
+        &entry.value_b,
+    ]
+}
+
+/// ステータスに応じた行の背景色（変更系のステータスは "modified" の色）
+fn row_style(status: &str) -> String {
+    let color = match status {
+        "added" | "removed" | "modified" | "unchanged" => status_to_color(status),
+        _ => status_to_color("modified"),
+    };
+    color
+        .map(|color| format!(" style=\"background-color:{color}33\""))
+        .unwrap_or_default()
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 表の区切り（|）と改行を無害化
+fn escape_markdown(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(rows: &[[&str; 2]]) -> ParseResult {
+        let headers = vec!["Ref".to_string(), "Part".to_string()];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers((1..=rows.len()).collect())
+            .build()
+    }
+
+    fn sample() -> (ParseResult, ParseResult, Vec<DiffRow>) {
+        let parse_a = build(&[["C1", "<script>alert(1)</script>"]]);
+        let parse_b = build(&[["C1", "A|B & \"C\""], ["R1", "RC0402"]]);
+        let diffs = vec![
+            DiffRow {
+                status: "modified".to_string(),
+                a_index: Some(0),
+                b_index: Some(0),
+                ref_value: "C1".to_string(),
+                changed_columns: vec!["col-1".to_string()],
+                changes: vec![],
+            },
+            DiffRow {
+                status: "added".to_string(),
+                a_index: None,
+                b_index: Some(1),
+                ref_value: "R1".to_string(),
+                changed_columns: vec![],
+                changes: vec![],
+            },
+        ];
+        (parse_a, parse_b, diffs)
+    }
+
+    #[test]
+    fn test_export_diff_html_escapes_and_colors_rows() {
+        let (parse_a, parse_b, diffs) = sample();
+        let html = export_diff_html(&parse_a, &parse_b, &diffs);
+
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("A|B &amp; &quot;C&quot;"));
+        assert!(html.contains("<tr style=\"background-color:#ffb34733\">"));
+        assert!(html.contains("<tr style=\"background-color:#00b89433\">"));
+        assert_eq!(html.matches("<tr").count(), 3);
+    }
+
+    #[test]
+    fn test_export_diff_markdown() {
+        let (parse_a, parse_b, diffs) = sample();
+        let markdown = export_diff_markdown(&parse_a, &parse_b, &diffs);
+        assert_eq!(
+            markdown,
+            "| Ref | Status | Field | A | B |\n\
+             |---|---|---|---|---|\n\
+             | C1 | modified | Part | <script>alert(1)</script> | A\\|B & \"C\" |\n\
+             | R1 | added | Part |  | RC0402 |\n"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use crate::models::{AppError, DiffRow, ParseResult};
+use csv::WriterBuilder;
+
+/// 差分レポートのヘッダー行
+pub const REPORT_HEADERS: [&str; 5] = ["Ref", "Status", "Field", "A", "B"];
+
+/// 差分レポートの1行（変更された項目ごと）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReportEntry {
+    pub reference: String,
+    pub status: String,
+    pub field: String,
+    pub value_a: String,
+    pub value_b: String,
+}
+
+/// 差分情報から差分レポートの行を作成
+///
+/// # 出力内容
+/// - 変更された行: 変更された列ごとに1行（列名、Aの値、Bの値）。
+///   Bの値は `DiffRow.changes` を優先し、無い場合は役割でBの列を解決する
+/// - 追加・削除された行: 1行（部品型番列があればその値）
+/// - 一致した行: 出力しない
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - `compare_boms` の結果
+pub fn build_report_entries(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    diffs: &[DiffRow],
+) -> Vec<DiffReportEntry> {
+    let mut entries = Vec::new();
+
+    for diff in diffs {
+        let entry = |field: String, value_a: String, value_b: String| DiffReportEntry {
+            reference: diff.ref_value.clone(),
+            status: diff.status.clone(),
+            field,
+            value_a,
+            value_b,
+        };
+
+        match (diff.a_index, diff.b_index) {
+            (Some(idx_a), Some(idx_b)) => {
+                for col_id in &diff.changed_columns {
+                    let Some(col_idx) = parse_column_index(col_id) else {
+                        continue;
+                    };
+                    let (value_a, value_b) = match diff
+                        .changes
+                        .iter()
+                        .find(|change| &change.column_id == col_id)
+                    {
+                        Some(change) => (change.a_value.clone(), change.b_value.clone()),
+                        None => (
+                            cell(parse_a, idx_a, col_idx),
+                            column_in_b(parse_a, parse_b, col_id, col_idx)
+                                .map(|col_idx_b| cell(parse_b, idx_b, col_idx_b))
+                                .unwrap_or_default(),
+                        ),
+                    };
+                    entries.push(entry(
+                        field_name(parse_a, col_idx, col_id),
+                        value_a,
+                        value_b,
+                    ));
+                }
+            }
+            (Some(idx_a), None) => entries.push(entry(
+                part_field_name(parse_a),
+                parse_a.get_part_no(idx_a),
+                String::new(),
+            )),
+            (None, Some(idx_b)) => entries.push(entry(
+                part_field_name(parse_b),
+                String::new(),
+                parse_b.get_part_no(idx_b),
+            )),
+            (None, None) => {}
+        }
+    }
+
+    entries
+}
+
+/// 差分レポート（変更された項目ごとに1行）をCSVで出力
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - `compare_boms` の結果
+///
+/// # 戻り値
+/// UTF-8 BOM付きのCSV文字列
+pub fn export_diff_report(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    diffs: &[DiffRow],
+) -> Result<String, AppError> {
+    let mut writer = WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    let write_error = |e: csv::Error| AppError::new(format!("CSV書き込みエラー: {}", e));
+
+    writer.write_record(REPORT_HEADERS).map_err(write_error)?;
+    for entry in build_report_entries(parse_a, parse_b, diffs) {
+        writer
+            .write_record([
+                &entry.reference,
+                &entry.status,
+                &entry.field,
+                &entry.value_a,
+                &entry.value_b,
+            ])
+            .map_err(write_error)?;
+    }
+
+    let data = writer
+        .into_inner()
+        .map_err(|e| AppError::new(format!("CSVバッファ取得エラー: {}", e)))?;
+    let csv_string =
+        String::from_utf8(data).map_err(|e| AppError::new(format!("UTF-8変換エラー: {}", e)))?;
+
+    Ok(format!("\u{FEFF}{}", csv_string))
+}
+
+fn parse_column_index(col_id: &str) -> Option<usize> {
+    col_id.strip_prefix("col-")?.parse().ok()
+}
+
+/// Aの列に対応するBの列番号
+///
+/// 役割を持つ列は同じ役割の同じ位置の列、役割の無い列は同じ列名の列
+fn column_in_b(
+    parse_a: &ParseResult,
+    parse_b: &ParseResult,
+    col_id: &str,
+    col_idx: usize,
+) -> Option<usize> {
+    let role = parse_a
+        .column_roles
+        .iter()
+        .filter(|(_, col_ids)| col_ids.iter().any(|id| id == col_id))
+        .map(|(role, _)| role)
+        .min();
+    if let Some((role, col_ids_b)) =
+        role.and_then(|role| Some((role, parse_b.column_roles.get(role)?)))
+    {
+        let position = parse_a.column_roles[role]
+            .iter()
+            .position(|id| id == col_id)
+            .unwrap_or(0);
+        return col_ids_b
+            .get(position)
+            .or(col_ids_b.first())
+            .and_then(|id| parse_column_index(id));
+    }
+
+    let name = parse_a.headers.get(col_idx)?.trim();
+    parse_b
+        .headers
+        .iter()
+        .position(|header| header.trim() == name)
+}
+
+/// 列名（空の場合は列ID）
+fn field_name(parse: &ParseResult, col_idx: usize, col_id: &str) -> String {
+    parse
+        .headers
+        .get(col_idx)
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| col_id.to_string())
+}
+
+/// 追加・削除行の項目名（部品型番列名、なければ空）
+fn part_field_name(parse: &ParseResult) -> String {
+    parse
+        .get_column_indices("part_no")
+        .first()
+        .map(|&idx| field_name(parse, idx, &format!("col-{}", idx)))
+        .unwrap_or_default()
+}
+
+fn cell(parse: &ParseResult, row_idx: usize, col_idx: usize) -> String {
+    parse
+        .rows
+        .get(row_idx)
+        .and_then(|row| row.get(col_idx))
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::compare::compare_boms;
+
+    fn build(rows: &[[&str; 3]]) -> ParseResult {
+        build_with_headers(["Ref", "Part", "Maker"], rows)
+    }
+
+    fn build_with_headers(names: [&str; 3], rows: &[[&str; 3]]) -> ParseResult {
+        let role_of = |name: &str| match name {
+            "Ref" => "ref",
+            "Part" => "part_no",
+            _ => "manufacturer",
+        };
+        let headers: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .column_roles(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, name)| (role_of(name).to_string(), vec![format!("col-{}", idx)]))
+                    .collect(),
+            )
+            .row_numbers((1..=rows.len()).collect())
+            .build()
+    }
+
+    #[test]
+    fn test_export_diff_report_one_row_per_changed_field() {
+        let parse_a = build(&[
+            ["C1", "GRM155", "Murata"],
+            ["R1", "RC0402", "Yageo"],
+            ["R2", "RC0402", "Yageo"],
+        ]);
+        let parse_b = build(&[["C1", "CL05B104", "Samsung"], ["R1", "RC0402", "Yageo"]]);
+        let diffs = compare_boms(&parse_a, &parse_b);
+
+        let report = export_diff_report(&parse_a, &parse_b, &diffs).unwrap();
+        assert_eq!(
+            report,
+            "\u{FEFF}Ref,Status,Field,A,B\n\
+             C1,modified,Part,GRM155,CL05B104\n\
+             C1,modified,Maker,Murata,Samsung\n\
+             R2,removed,Part,RC0402,\n"
+        );
+    }
+
+    #[test]
+    fn test_build_report_entries_resolves_b_column_by_role() {
+        let parse_a = build(&[["C1", "GRM155", "Murata"]]);
+        let parse_b = build_with_headers(["Ref", "Maker", "Part"], &[["C1", "Murata", "GRM188"]]);
+        let diffs = compare_boms(&parse_a, &parse_b);
+        let expected = vec![DiffReportEntry {
+            reference: "C1".to_string(),
+            status: "modified".to_string(),
+            field: "Part".to_string(),
+            value_a: "GRM155".to_string(),
+            value_b: "GRM188".to_string(),
+        }];
+
+        assert_eq!(build_report_entries(&parse_a, &parse_b, &diffs), expected);
+
+        // changes を持たない差分（フロントエンドから渡された場合など）も役割で解決
+        let diffs_without_changes: Vec<DiffRow> = diffs
+            .into_iter()
+            .map(|diff| DiffRow {
+                changes: vec![],
+                ..diff
+            })
+            .collect();
+        assert_eq!(
+            build_report_entries(&parse_a, &parse_b, &diffs_without_changes),
+            expected
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use crate::models::{AppError, ParseResult};
+
+/// BOMデータをJSON形式でエクスポート
+///
+/// `ParseResult` をそのまま出力するため、列の役割・表示順序・行番号も含めて
+/// `parse_bom_file` で元の状態に読み込み直せる
+pub fn export_json(parse: &ParseResult) -> Result<String, AppError> {
+    serde_json::to_string_pretty(parse)
+        .map_err(|err| AppError::new(format!("JSONの出力に失敗しました: {err}")))
+}
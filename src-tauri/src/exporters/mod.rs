@@ -1,54 +1,818 @@
 pub mod cad;
 pub mod csv;
+pub mod diff_html;
+pub mod diff_report;
+pub mod json;
+pub mod xlsx;
 
 use std::collections::HashMap;
 
-use crate::models::{AppError, DiffRow, ParseResult};
+use crate::models::{AppError, DiffRow, DiffSide, ExportOptions, ParseResult};
 
 /// BOMファイルをエクスポート
 ///
-/// ParseResultから指定されたフォーマットで出力します
-pub fn export_bom_file(
-    parse: &ParseResult,
-    format: &str,
-    diffs: Option<&[DiffRow]>,
-    include_comments: bool,
-) -> Result<String, AppError> {
-    let mut diff_map: HashMap<String, String> = HashMap::new();
-    if let Some(diffs) = diffs {
-        for diff in diffs {
-            diff_map.insert(diff.ref_value.clone(), diff.status.clone());
+/// ParseResultから`ExportOptions`で指定されたフォーマットで出力します
+///
+/// XLSXは文字列で表せないため、`export_bom_bytes` を使用してください
+pub fn export_bom_file(parse: &ParseResult, options: &ExportOptions) -> Result<String, AppError> {
+    with_filtered_rows(parse, options, |parse, statuses| {
+        export_text(parse, statuses, options)
+    })
+}
+
+/// BOMファイルをバイト列でエクスポート（XLSXを含む全形式に対応）
+pub fn export_bom_bytes(parse: &ParseResult, options: &ExportOptions) -> Result<Vec<u8>, AppError> {
+    with_filtered_rows(parse, options, |parse, statuses| {
+        if is_xlsx(&options.format) {
+            xlsx::export_xlsx(parse, statuses, options)
+        } else {
+            export_text(parse, statuses, options).map(String::into_bytes)
         }
+    })
+}
+
+/// 指定した行（`row_indices`）を差分ステータスで絞り込み、必要に応じて未実装（DNP）の行を
+/// 除いた行に対して出力処理を行う
+fn with_filtered_rows<T>(
+    parse: &ParseResult,
+    options: &ExportOptions,
+    export: impl FnOnce(&ParseResult, &RowStatuses) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    check_required_roles(parse, options)?;
+    // 差分情報とは元の行の並びで対応付けてから、出力する行を選ぶ
+    let statuses = RowStatuses::new(
+        parse,
+        options.diffs.as_deref().unwrap_or_default(),
+        options.diff_side,
+    );
+
+    let selected = match &options.row_indices {
+        Some(indices) => Some(select_rows(parse, &statuses, indices)?),
+        None => None,
+    };
+    let (parse, statuses) = match &selected {
+        Some((selected_parse, selected_map)) => (selected_parse, selected_map),
+        None => (parse, &statuses),
+    };
+
+    let filtered = filter_rows_by_status(parse, statuses, options.filter.as_deref());
+    let (parse, statuses) = match &filtered {
+        Some((filtered_parse, filtered_map)) => (filtered_parse, filtered_map),
+        None => (parse, statuses),
+    };
+    if options.exclude_dnp {
+        let (placed, placed_statuses) = filter_rows(parse, statuses, |idx| !parse.is_dnp(idx));
+        return export(&placed, &placed_statuses);
     }
+    export(parse, statuses)
+}
 
-    match format.to_uppercase().as_str() {
-        "CSV" => csv::export_csv(parse, &diff_map, include_comments),
-        "ECO" => cad::export_eco(parse, &diff_map, include_comments),
-        "CCF" => cad::export_ccf(parse, &diff_map, include_comments),
-        "MSF" => cad::export_msf(parse, &diff_map, include_comments),
-        "PWS" => cad::export_pws(parse, &diff_map, include_comments),
-        "BD" => cad::export_bd(parse, &diff_map, include_comments),
+/// 出力形式に必要な役割の列があるか確認
+///
+/// CAD形式はReference・部品型番（CCF/MSF/PWSはグループ化の役割）の列がないと
+/// 空の値だけのファイルになるため、出力前にエラーにする。CSV・XLSX・JSONは全列を出力するため確認しない
+fn check_required_roles(parse: &ParseResult, options: &ExportOptions) -> Result<(), AppError> {
+    let format = options.format.to_uppercase();
+    let required: &[&str] = match format.as_str() {
+        "ECO" | "BD" | "PADSREPORT" | "PADS_REPORT" | "RPT" => &["ref", "part_no"],
+        "CCF" | "MSF" | "PWS" => &["ref", options.group_by.role_key()],
+        _ => &[],
+    };
+    match required
+        .iter()
+        .find(|role| parse.get_column_indices(role).is_empty())
+    {
+        Some(role) => Err(AppError::validation(format!(
+            "{}列が未指定のため{format}出力できません。列の役割を指定してください。",
+            required_role_label(role)
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// エラーメッセージに使う役割の表示名
+fn required_role_label(role: &str) -> &str {
+    match role {
+        "ref" => "Reference",
+        "part_no" => "部品型番",
+        "value" => "Value",
+        "manufacturer" => "メーカー",
+        other => other,
+    }
+}
+
+fn is_xlsx(format: &str) -> bool {
+    format.eq_ignore_ascii_case("XLSX")
+}
+
+/// テキスト形式での出力
+fn export_text(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    options: &ExportOptions,
+) -> Result<String, AppError> {
+    let include_comments = options.include_diff_comments;
+    let group_by = options.group_by;
+
+    match options.format.to_uppercase().as_str() {
+        "CSV" => csv::export_csv(parse, statuses, options),
+        "ECO" => {
+            let template = options
+                .eco_template
+                .as_ref()
+                .or(parse.eco_template.as_ref());
+            cad::export_eco(
+                parse,
+                statuses,
+                include_comments,
+                template,
+                &options.extra_roles,
+            )
+        }
+        "CCF" => cad::export_ccf(
+            parse,
+            statuses,
+            include_comments,
+            group_by,
+            &options.cad_indent,
+        ),
+        "MSF" => cad::export_msf(
+            parse,
+            statuses,
+            include_comments,
+            group_by,
+            &options.cad_indent,
+        ),
+        "PWS" => cad::export_pws(parse, statuses, include_comments, group_by),
+        "BD" => cad::export_bd(parse, statuses, include_comments),
+        "JSON" => json::export_json(parse),
         "PADSREPORT" | "PADS_REPORT" | "RPT" => {
-            cad::export_pads_report(parse, &diff_map, include_comments)
+            cad::export_pads_report(parse, statuses, include_comments)
         }
-        other => Err(AppError::new(format!(
+        "XLSX" => Err(AppError::unsupported_format(
+            "XLSXは文字列として出力できません。ファイルに直接書き出してください。",
+        )),
+        other => Err(AppError::unsupported_format(format!(
             "未対応のエクスポート形式です: {}",
             other
         ))),
     }
 }
 
-/// 差分コメントを生成
-pub fn diff_comment(ref_value: &str, diff_map: &HashMap<String, String>) -> String {
-    if let Some(status) = diff_map.get(ref_value) {
-        match status.as_str() {
-            "added" | "追加" => "←追加".to_string(),
-            "removed" | "削除" => "←削除".to_string(),
-            "modified" | "変更" => "←変更".to_string(),
-            "unchanged" | "同一" => String::new(),
-            other => format!("←{}", other),
+/// エクスポート形式に対応するファイルの拡張子（未対応の形式の場合はNone）
+pub fn file_extension(format: &str) -> Option<&'static str> {
+    match format.to_uppercase().as_str() {
+        "CSV" => Some("csv"),
+        "ECO" => Some("eco"),
+        "CCF" => Some("ccf"),
+        "MSF" => Some("msf"),
+        "PWS" => Some("pws"),
+        "BD" => Some("bd"),
+        "JSON" => Some("json"),
+        "PADSREPORT" | "PADS_REPORT" | "RPT" => Some("rpt"),
+        "XLSX" => Some("xlsx"),
+        _ => None,
+    }
+}
+
+/// 行ごとの差分ステータス（`parse.rows` と同じ並び）
+///
+/// 出力する側（`DiffSide`）を指定した場合は、差分情報のその側の行インデックスで対応付ける
+/// （Bを出力する場合の "removed" のように、その側に行のない差分は使わない）。
+///
+/// 出力する側を指定しない場合と、行インデックスのない差分はReferenceで対応付ける。
+/// 同じReferenceの行が複数ある場合は、n回目に出現した行に、そのReferenceの差分情報のうち
+/// n番目のステータスを割り当てる（差分情報の方が少ない場合は最後のステータス）。
+#[derive(Default)]
+pub struct RowStatuses {
+    statuses: Vec<Option<String>>,
+}
+
+impl RowStatuses {
+    /// 差分情報から各行のステータスを求める
+    pub fn new(parse: &ParseResult, diffs: &[DiffRow], side: Option<DiffSide>) -> Self {
+        let mut statuses: Vec<Option<String>> = vec![None; parse.rows.len()];
+        let mut by_ref: HashMap<&str, Vec<&str>> = HashMap::new();
+        for diff in diffs {
+            let has_index = diff.a_index.is_some() || diff.b_index.is_some();
+            match side {
+                Some(side) if has_index => {
+                    let index = match side {
+                        DiffSide::A => diff.a_index,
+                        DiffSide::B => diff.b_index,
+                    };
+                    if let Some(slot) = index.and_then(|idx| statuses.get_mut(idx)) {
+                        *slot = Some(diff.status.clone());
+                    }
+                }
+                _ => by_ref
+                    .entry(diff.ref_value.as_str())
+                    .or_default()
+                    .push(diff.status.as_str()),
+            }
+        }
+
+        if !by_ref.is_empty() {
+            let mut occurrences: HashMap<String, usize> = HashMap::new();
+            for (idx, slot) in statuses.iter_mut().enumerate() {
+                if slot.is_some() {
+                    continue;
+                }
+                let ref_value = parse.get_ref(idx);
+                let Some(ref_statuses) = by_ref.get(ref_value.as_str()) else {
+                    continue;
+                };
+                let occurrence = occurrences.entry(ref_value).or_insert(0);
+                *slot = ref_statuses
+                    .get(*occurrence)
+                    .or(ref_statuses.last())
+                    .map(|status| status.to_string());
+                *occurrence += 1;
+            }
+        }
+
+        Self { statuses }
+    }
+
+    /// 行の差分ステータス
+    pub fn status(&self, row_idx: usize) -> Option<&str> {
+        self.statuses
+            .get(row_idx)
+            .and_then(|status| status.as_deref())
+    }
+
+    /// 行の差分コメント（差分がない・同一の場合は空文字）
+    pub fn comment(&self, row_idx: usize) -> String {
+        self.status(row_idx).map(diff_comment).unwrap_or_default()
+    }
+}
+
+/// 差分ステータスで行を絞り込む
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `statuses` - 行ごとの差分ステータス
+/// * `filter` - 出力するステータス（None または "all" の場合は絞り込みなし）
+///
+/// # 戻り値
+/// 絞り込み後のBOMデータと行ごとの差分ステータス（絞り込み不要の場合はNone）
+fn filter_rows_by_status(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    filter: Option<&str>,
+) -> Option<(ParseResult, RowStatuses)> {
+    let status = filter.map(|f| f.trim().to_lowercase())?;
+    if status.is_empty() || status == "all" {
+        return None;
+    }
+
+    Some(filter_rows(parse, statuses, |idx| {
+        statuses
+            .status(idx)
+            .map(|s| s.eq_ignore_ascii_case(&status))
+            .unwrap_or(false)
+    }))
+}
+
+/// 条件を満たす行（行インデックスで判定）と、その行の差分ステータスを残す
+fn filter_rows(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    keep: impl Fn(usize) -> bool,
+) -> (ParseResult, RowStatuses) {
+    take_rows(
+        parse,
+        statuses,
+        (0..parse.rows.len()).filter(|&idx| keep(idx)),
+    )
+}
+
+/// 指定した行のみを指定した順に残す（範囲外の行インデックスはエラー）
+fn select_rows(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    indices: &[usize],
+) -> Result<(ParseResult, RowStatuses), AppError> {
+    if let Some(&idx) = indices.iter().find(|&&idx| idx >= parse.rows.len()) {
+        return Err(AppError::validation(format!(
+            "出力する行の番号 {idx} が範囲外です（行数: {}）。",
+            parse.rows.len()
+        )));
+    }
+    Ok(take_rows(parse, statuses, indices.iter().copied()))
+}
+
+/// 行インデックスの順に行と差分ステータスを取り出す
+///
+/// 行に対応する行番号・書式・読み込み時の値も同じ行を取り出す。
+/// 行ごとのエラー・検証結果は取り出した行と対応しないためクリアする
+fn take_rows(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    indices: impl IntoIterator<Item = usize>,
+) -> (ParseResult, RowStatuses) {
+    let indices: Vec<usize> = indices.into_iter().collect();
+    let row_count = parse.rows.len();
+    fn select<T: Clone>(items: &[T], indices: &[usize]) -> Vec<T> {
+        indices.iter().map(|&idx| items[idx].clone()).collect()
+    }
+
+    let cell_styles = parse
+        .cell_styles
+        .as_ref()
+        .filter(|styles| styles.len() == row_count)
+        .map(|styles| select(styles, &indices));
+    let original_rows = parse
+        .original_rows
+        .as_ref()
+        .filter(|rows| rows.len() == row_count)
+        .map(|rows| select(rows, &indices));
+
+    let filtered = ParseResult::builder()
+        .schema_from(parse)
+        .rows(select(&parse.rows, &indices))
+        .row_numbers(
+            indices
+                .iter()
+                .map(|&idx| parse.source_row_number(idx))
+                .collect(),
+        )
+        .cell_styles(cell_styles)
+        .original_rows(original_rows)
+        .build();
+    let filtered_statuses = indices
+        .iter()
+        .map(|&idx| statuses.status(idx).map(str::to_string))
+        .collect();
+
+    (
+        filtered,
+        RowStatuses {
+            statuses: filtered_statuses,
+        },
+    )
+}
+
+/// 差分ステータスを差分コメントに変換
+pub fn diff_comment(status: &str) -> String {
+    match status {
+        "added" | "追加" => "←追加".to_string(),
+        "removed" | "削除" => "←削除".to_string(),
+        "modified" | "変更" => "←変更".to_string(),
+        "likely_same" => "←型番類似".to_string(),
+        "comment_changed" => "←コメント変更".to_string(),
+        "rotated" => "←回転変更".to_string(),
+        "moved_xy" => "←位置変更".to_string(),
+        "dnp_changed" => "←実装有無変更".to_string(),
+        "unchanged" | "同一" => String::new(),
+        other => format!("←{}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CadIndent, CellStyle, DiffRow, ErrorKind, GroupBy};
+
+    fn sample_parse() -> ParseResult {
+        ParseResult::builder()
+            .headers(vec!["Ref".to_string(), "Part".to_string()])
+            .rows(vec![
+                vec!["C1".to_string(), "GRM155".to_string()],
+                vec!["C2".to_string(), "GRM188".to_string()],
+            ])
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers(vec![1, 2])
+            .build()
+    }
+
+    fn diff(ref_value: &str, status: &str) -> DiffRow {
+        DiffRow {
+            status: status.to_string(),
+            a_index: Some(0),
+            b_index: Some(0),
+            ref_value: ref_value.to_string(),
+            changed_columns: vec![],
+            changes: vec![],
         }
-    } else {
-        String::new()
+    }
+
+    #[test]
+    fn test_export_with_options_filters_by_status() {
+        let options = ExportOptions {
+            format: "eco".to_string(),
+            include_diff_comments: true,
+            filter: Some("modified".to_string()),
+            headers: vec![],
+            diffs: Some(vec![diff("C1", "unchanged"), diff("C2", "modified")]),
+            ..ExportOptions::default()
+        };
+
+        let content = export_bom_file(&sample_parse(), &options).unwrap();
+        assert_eq!(content, "*PADS-ECO*\n*PART*\nC2 GRM188 ←変更\n*END*");
+    }
+
+    #[test]
+    fn test_export_excludes_dnp_rows() {
+        let mut parse = sample_parse();
+        parse.rows[0].push("DNP".to_string());
+        parse
+            .column_roles
+            .insert("dnp".to_string(), vec!["col-2".to_string()]);
+
+        let options = ExportOptions {
+            format: "eco".to_string(),
+            include_diff_comments: true,
+            diffs: Some(vec![diff("C1", "dnp_changed"), diff("C2", "modified")]),
+            ..ExportOptions::default()
+        };
+        let content = export_bom_file(&parse, &options).unwrap();
+        assert!(content.contains("C1 GRM155 ←実装有無変更"));
+
+        let options = ExportOptions {
+            exclude_dnp: true,
+            ..options
+        };
+        let content = export_bom_file(&parse, &options).unwrap();
+        assert_eq!(content, "*PADS-ECO*\n*PART*\nC2 GRM188 ←変更\n*END*");
+    }
+
+    #[test]
+    fn test_export_selected_row_indices() {
+        let mut parse = sample_parse();
+        parse
+            .rows
+            .push(vec!["C3".to_string(), "GRM155".to_string()]);
+        parse.row_numbers.push(3);
+
+        let options = ExportOptions {
+            include_diff_comments: true,
+            diffs: Some(vec![diff("C1", "removed"), diff("C3", "added")]),
+            row_indices: Some(vec![2, 0]),
+            ..ExportOptions::default()
+        };
+        let content = export_bom_file(&parse, &options).unwrap();
+        assert_eq!(
+            content,
+            "\u{FEFF}Ref,Part,差分コメント\nC3,GRM155,←追加\nC1,GRM155,←削除\n"
+        );
+
+        // ステータスでの絞り込みと組み合わせられる
+        let options = ExportOptions {
+            filter: Some("added".to_string()),
+            ..options
+        };
+        let content = export_bom_file(&parse, &options).unwrap();
+        assert_eq!(content, "\u{FEFF}Ref,Part,差分コメント\nC3,GRM155,←追加\n");
+
+        let options = ExportOptions {
+            row_indices: Some(vec![0, 3]),
+            ..ExportOptions::default()
+        };
+        let error = export_bom_file(&parse, &options).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Validation);
+    }
+
+    #[test]
+    fn test_export_requires_ref_and_part_roles() {
+        let mut parse = sample_parse();
+        parse.column_roles.remove("ref");
+        for format in ["eco", "ccf", "msf"] {
+            let options = ExportOptions {
+                format: format.to_string(),
+                ..ExportOptions::default()
+            };
+            let error = export_bom_file(&parse, &options).unwrap_err();
+            assert_eq!(error.kind, ErrorKind::Validation, "{format}");
+            assert_eq!(
+                error.message,
+                format!(
+                    "Reference列が未指定のため{}出力できません。列の役割を指定してください。",
+                    format.to_uppercase()
+                )
+            );
+            assert!(export_bom_bytes(&parse, &options).is_err());
+        }
+        // CSVは全列を出力するため役割がなくても出力できる
+        assert!(export_bom_file(&parse, &ExportOptions::default()).is_ok());
+
+        // グループ化の役割がない場合もCCFは出力できない
+        let options = ExportOptions {
+            format: "ccf".to_string(),
+            group_by: GroupBy::Value,
+            ..ExportOptions::default()
+        };
+        let error = export_bom_file(&sample_parse(), &options).unwrap_err();
+        assert!(error.message.starts_with("Value列が未指定"));
+    }
+
+    #[test]
+    fn test_export_eco_splits_multi_reference_cells() {
+        let mut parse = sample_parse();
+        parse.rows[0][0] = "C1,C2,C3".to_string();
+        parse.rows[1][0] = "R1; R2-R3".to_string();
+        let options = ExportOptions {
+            format: "eco".to_string(),
+            include_diff_comments: true,
+            diffs: Some(vec![diff("C1,C2,C3", "modified")]),
+            ..ExportOptions::default()
+        };
+
+        let content = export_bom_file(&parse, &options).unwrap();
+        assert_eq!(
+            content,
+            concat!(
+                "*PADS-ECO*\n*PART*\n",
+                "C1 GRM155 ←変更\n",
+                "C2 GRM155 ←変更\n",
+                "C3 GRM155 ←変更\n",
+                "R1 GRM188\n",
+                "R2 GRM188\n",
+                "R3 GRM188\n",
+                "*END*"
+            )
+        );
+
+        parse.rows[1][0] = "R1-R999999999".to_string();
+        let error = export_bom_file(&parse, &options).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Validation);
+    }
+
+    #[test]
+    fn test_export_ccf_msf_snapshot() {
+        let mut parse = sample_parse();
+        parse
+            .rows
+            .push(vec!["C3".to_string(), "GRM155".to_string()]);
+        let export = |format: &str, cad_indent: CadIndent| {
+            let options = ExportOptions {
+                format: format.to_string(),
+                cad_indent,
+                ..ExportOptions::default()
+            };
+            export_bom_file(&parse, &options).unwrap()
+        };
+
+        assert_eq!(
+            export("ccf", CadIndent::default()),
+            concat!(
+                "$CCF{\n",
+                "     DEFINITION{\n",
+                "                GRM155:C1,\n",
+                "                         C3;\n",
+                "                GRM188:C2;\n",
+                "               }\n",
+                "     NET{\n",
+                "        }\n",
+                "    }\n",
+            )
+        );
+        assert_eq!(
+            export("msf", CadIndent::default()),
+            concat!(
+                "$MSF {\n",
+                "     SHAPE {\n",
+                "                GRM155:C1,\n",
+                "                         C3;\n",
+                "                GRM188:C2;\n",
+                "           }\n",
+                "      }\n",
+            )
+        );
+
+        let indent = CadIndent {
+            entry: 2,
+            continuation: 4,
+        };
+        assert!(export("ccf", indent).contains("\n  GRM155:C1,\n    C3;\n  GRM188:C2;\n"));
+
+        let options = ExportOptions {
+            format: "msf".to_string(),
+            cad_indent: CadIndent {
+                entry: 1000,
+                continuation: 25,
+            },
+            ..ExportOptions::default()
+        };
+        assert!(export_bom_file(&parse, &options).is_err());
+    }
+
+    #[test]
+    fn test_export_with_options_all_filter_keeps_rows() {
+        let options = ExportOptions {
+            format: "csv".to_string(),
+            include_diff_comments: false,
+            filter: Some("all".to_string()),
+            headers: vec!["Part".to_string()],
+            diffs: None,
+            ..ExportOptions::default()
+        };
+
+        let content = export_bom_file(&sample_parse(), &options).unwrap();
+        assert_eq!(content, "\u{FEFF}Part\nGRM155\nGRM188\n");
+    }
+
+    #[test]
+    fn test_export_group_by_value_and_part_no() {
+        let mut parse = sample_parse();
+        parse.rows = vec![
+            vec!["C1".to_string(), "GRM155".to_string(), "0.1uF".to_string()],
+            vec![
+                "C2".to_string(),
+                "CL05B104".to_string(),
+                "0.1uF".to_string(),
+            ],
+            vec!["C3".to_string(), "GRM155".to_string(), String::new()],
+        ];
+        parse
+            .column_roles
+            .insert("value".to_string(), vec!["col-2".to_string()]);
+
+        let export = |group_by: GroupBy| {
+            let options = ExportOptions {
+                format: "pws".to_string(),
+                group_by,
+                ..ExportOptions::default()
+            };
+            export_bom_file(&parse, &options).unwrap()
+        };
+
+        assert_eq!(export(GroupBy::PartNo), "CL05B104:C2;\nGRM155:C1,C3;");
+        assert_eq!(export(GroupBy::Value), "(未指定):C3;\n0.1uF:C1,C2;");
+    }
+
+    #[test]
+    fn test_export_extra_roles() {
+        let mut parse = sample_parse();
+        parse
+            .headers
+            .extend(["Maker".to_string(), "Note".to_string()]);
+        parse.rows[0].extend(["Murata".to_string(), "DNP 要確認".to_string()]);
+        parse.rows[1].extend(["Murata".to_string(), String::new()]);
+        parse.column_roles.extend([
+            ("manufacturer".to_string(), vec!["col-2".to_string()]),
+            ("comment".to_string(), vec!["col-3".to_string()]),
+        ]);
+        parse.rebuild_columns_from_headers();
+
+        let extra_roles = vec![
+            "manufacturer".to_string(),
+            "comment".to_string(),
+            "value".to_string(),
+        ];
+        let export = |format: &str, headers: Vec<String>| {
+            let options = ExportOptions {
+                format: format.to_string(),
+                headers,
+                extra_roles: extra_roles.clone(),
+                diffs: Some(vec![diff("C1", "modified")]),
+                include_diff_comments: true,
+                ..ExportOptions::default()
+            };
+            export_bom_file(&parse, &options).unwrap()
+        };
+
+        assert_eq!(
+            export("eco", vec![]),
+            "*PADS-ECO*\n*PART*\nC1 GRM155 Murata DNP_要確認 ←変更\nC2 GRM188 Murata -\n*END*"
+        );
+        assert_eq!(
+            export("csv", vec!["Ref".to_string(), "Part".to_string()]),
+            "\u{FEFF}Ref,Part,Maker,Note,差分コメント\nC1,GRM155,Murata,DNP 要確認,←変更\nC2,GRM188,Murata,,\n"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_refs_use_status_by_occurrence() {
+        let mut parse = sample_parse();
+        parse.rows = vec![
+            vec!["C1".to_string(), "GRM155".to_string()],
+            vec!["C1".to_string(), "GRM188".to_string()],
+            vec!["C1".to_string(), "GRM033".to_string()],
+            vec!["C2".to_string(), "GRM155".to_string()],
+        ];
+        let diffs = vec![diff("C1", "unchanged"), diff("C1", "modified")];
+
+        let statuses = RowStatuses::new(&parse, &diffs, None);
+        assert_eq!(statuses.status(0), Some("unchanged"));
+        assert_eq!(statuses.status(1), Some("modified"));
+        // 差分情報が足りない場合は最後のステータス
+        assert_eq!(statuses.status(2), Some("modified"));
+        assert_eq!(statuses.status(3), None);
+
+        let options = ExportOptions {
+            format: "bd".to_string(),
+            include_diff_comments: true,
+            filter: Some("modified".to_string()),
+            diffs: Some(diffs),
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            export_bom_file(&parse, &options).unwrap(),
+            "C1 GRM188 ←変更\nC1 GRM033 ←変更"
+        );
+    }
+
+    #[test]
+    fn test_export_json_selected_rows_keep_side_data_aligned() {
+        let mut parse = sample_parse();
+        parse.original_rows = Some(vec![
+            vec!["C1".to_string(), "grm155 ".to_string()],
+            vec!["C2".to_string(), "grm188 ".to_string()],
+        ]);
+        parse.cell_styles = Some(vec![
+            vec![CellStyle::default(); 2],
+            vec![
+                CellStyle {
+                    fill_color: Some("#ffff00".to_string()),
+                    strikethrough: false,
+                };
+                2
+            ],
+        ]);
+        parse.structured_errors = Some(vec![crate::models::ParseError {
+            message: "1行目の警告".to_string(),
+            row: Some(1),
+            column: None,
+            severity: "warning".to_string(),
+        }]);
+
+        let options = ExportOptions {
+            format: "json".to_string(),
+            row_indices: Some(vec![1]),
+            ..ExportOptions::default()
+        };
+        let content = export_bom_bytes(&parse, &options).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("bomsync_export_rows_{}.json", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        let imported = crate::parsers::parse_bom_file(path.display().to_string()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(imported.rows, vec![vec!["C2", "GRM188"]]);
+        assert_eq!(imported.row_numbers, vec![2]);
+        assert_eq!(
+            imported.cell_styles.as_ref().unwrap()[0][0]
+                .fill_color
+                .as_deref(),
+            Some("#ffff00")
+        );
+        assert!(imported.structured_errors.is_none());
+        let reverted = crate::processors::cleaner::revert_cell(&imported, 0, 1).unwrap();
+        assert_eq!(reverted.rows[0][1], "grm188 ");
+    }
+
+    #[test]
+    fn test_row_statuses_use_index_of_exported_side() {
+        // Ref+値で比較した結果、R2 は A の行が "removed"、B の行が "added" になる
+        let mut parse = sample_parse();
+        parse.rows = vec![
+            vec!["R1".to_string(), "10k".to_string()],
+            vec!["R2".to_string(), "22k".to_string()],
+        ];
+        let diffs = vec![
+            DiffRow {
+                a_index: Some(0),
+                b_index: Some(0),
+                ..diff("R1", "unchanged")
+            },
+            DiffRow {
+                a_index: Some(1),
+                b_index: None,
+                ..diff("R2", "removed")
+            },
+            DiffRow {
+                a_index: None,
+                b_index: Some(1),
+                ..diff("R2", "added")
+            },
+        ];
+
+        let statuses_b = RowStatuses::new(&parse, &diffs, Some(DiffSide::B));
+        assert_eq!(statuses_b.status(0), Some("unchanged"));
+        assert_eq!(statuses_b.status(1), Some("added"));
+        let statuses_a = RowStatuses::new(&parse, &diffs, Some(DiffSide::A));
+        assert_eq!(statuses_a.status(1), Some("removed"));
+
+        // 行インデックスのない差分はReferenceで対応付ける
+        let without_index = vec![DiffRow {
+            a_index: None,
+            b_index: None,
+            ..diff("R2", "modified")
+        }];
+        let statuses = RowStatuses::new(&parse, &without_index, Some(DiffSide::B));
+        assert_eq!(statuses.status(0), None);
+        assert_eq!(statuses.status(1), Some("modified"));
+
+        let options = ExportOptions {
+            format: "bd".to_string(),
+            include_diff_comments: true,
+            filter: Some("added".to_string()),
+            diffs: Some(diffs),
+            diff_side: Some(DiffSide::B),
+            ..ExportOptions::default()
+        };
+        assert_eq!(export_bom_file(&parse, &options).unwrap(), "R2 22k ←追加");
     }
 }
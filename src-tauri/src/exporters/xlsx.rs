@@ -0,0 +1,151 @@
+use std::io::{Cursor, Write};
+
+use quick_xml::escape::escape;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::csv::export_table;
+use super::RowStatuses;
+use crate::models::{AppError, ExportOptions, ParseResult};
+
+/// ワークシート名
+const SHEET_NAME: &str = "BOM";
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+
+const ROOT_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+/// XLSXエクスポート
+///
+/// 出力列はCSVと同じ（`options.headers`・`options.extra_roles`・差分コメント列）。
+/// 部品番号の先頭の0などが失われないよう、全てのセルを文字列として書き込みます
+pub fn export_xlsx(
+    parse: &ParseResult,
+    statuses: &RowStatuses,
+    options: &ExportOptions,
+) -> Result<Vec<u8>, AppError> {
+    let table = export_table(parse, statuses, options)?;
+    let workbook_xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{SHEET_NAME}" sheetId="1" r:id="rId1"/></sheets></workbook>"#
+    );
+    let files = [
+        ("[Content_Types].xml", CONTENT_TYPES_XML.to_string()),
+        ("_rels/.rels", ROOT_RELS_XML.to_string()),
+        ("xl/workbook.xml", workbook_xml),
+        ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS_XML.to_string()),
+        ("xl/worksheets/sheet1.xml", sheet_xml(&table)),
+    ];
+
+    let to_error = |err: &dyn std::fmt::Display| {
+        AppError::new(format!("XLSXファイルの作成に失敗しました: {err}"))
+    };
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let file_options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in files {
+        zip.start_file(name, file_options)
+            .map_err(|err| to_error(&err))?;
+        zip.write_all(content.as_bytes())
+            .map_err(|err| to_error(&err))?;
+    }
+    let cursor = zip.finish().map_err(|err| to_error(&err))?;
+    Ok(cursor.into_inner())
+}
+
+/// ワークシートのXML（セルはインライン文字列）
+fn sheet_xml(table: &[Vec<String>]) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    );
+    for (row_idx, row) in table.iter().enumerate() {
+        let row_number = row_idx + 1;
+        xml.push_str(&format!(r#"<row r="{row_number}">"#));
+        for (col_idx, value) in row.iter().enumerate() {
+            if value.is_empty() {
+                continue;
+            }
+            xml.push_str(&format!(
+                r#"<c r="{}{row_number}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                column_letter(col_idx),
+                escape(&xml_safe(value))
+            ));
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// 列番号（0始まり）をExcelの列名（A, B, ..., Z, AA, ...）に変換
+fn column_letter(mut col_idx: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'A' + (col_idx % 26) as u8);
+        if col_idx < 26 {
+            break;
+        }
+        col_idx = col_idx / 26 - 1;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap_or_default()
+}
+
+/// XMLに書き込めない制御文字を取り除く
+fn xml_safe(value: &str) -> String {
+    value
+        .chars()
+        .filter(|&c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::parse_bom_file;
+    use std::fs;
+
+    #[test]
+    fn test_column_letter() {
+        assert_eq!(column_letter(0), "A");
+        assert_eq!(column_letter(25), "Z");
+        assert_eq!(column_letter(26), "AA");
+        assert_eq!(column_letter(701), "ZZ");
+        assert_eq!(column_letter(702), "AAA");
+    }
+
+    #[test]
+    fn test_export_xlsx_round_trip() {
+        let mut parse = ParseResult::builder()
+            .rows(vec![
+                vec![
+                    "C1".to_string(),
+                    "00123".to_string(),
+                    "<Murata & Co>".to_string(),
+                ],
+                vec!["R1".to_string(), String::new(), "Yageo\u{1}".to_string()],
+            ])
+            .row_numbers(vec![1, 2])
+            .build();
+        parse.headers = vec!["Ref".to_string(), "Part".to_string(), "Maker".to_string()];
+        parse.rebuild_columns_from_headers();
+
+        let bytes =
+            export_xlsx(&parse, &RowStatuses::default(), &ExportOptions::default()).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("bomsync_export_xlsx_{}.xlsx", std::process::id()));
+        fs::write(&path, &bytes).unwrap();
+        let loaded = parse_bom_file(path.display().to_string()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.headers, vec!["Ref", "Part", "Maker"]);
+        assert_eq!(loaded.rows[0], vec!["C1", "00123", "<Murata & Co>"]);
+        assert_eq!(loaded.rows[1][0], "R1");
+        assert_eq!(loaded.rows[1][2], "Yageo");
+    }
+}
@@ -1,46 +1,211 @@
+//! BOMSyncTool のバックエンド
+//!
+//! 読み込み（`parsers`）・比較とマージ（`diff`）・前処理（`processors`）・
+//! IPC登録名の適用（`matchers`）・出力（`exporters`）・一括変換（`batch`）はTauriに依存しない関数として公開し、
+//! `#[tauri::command]` はそれらを呼び出すだけの薄いラッパーとする。
+//! 辞書・自動保存（`storage`）はアプリの設定ディレクトリを使う関数のほかに、
+//! ディレクトリを指定する `*_in` 関数を持つ。
+
+pub mod batch;
+pub mod diff;
+pub mod exporters;
+pub mod matchers;
 pub mod models;
+pub mod parsers;
+pub mod processors;
+pub mod storage;
 pub mod utils;
 
-mod diff;
-mod exporters;
-mod matchers;
-mod parsers;
-mod processors;
-mod storage;
+#[cfg(test)]
+mod roundtrip_tests;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
 
-use models::{AppError, DiffRow, ExceptionMasterEntry, FormatOptions, IpcMasterRule, ParseResult};
+use models::{
+    AppError, AutosaveEntry, BatchReport, BomRow, CellEdit, CleanseOptions, CompareOptions,
+    CompareResult, DedupResult, DictionaryImportMode, DictionaryImportReport, DictionaryLoadResult,
+    DiffRow, ExceptionMasterEntry, ExpandOptions, ExportOptions, FormatOptions, GapReport,
+    IpcApplyResult, IpcMasterRule, ManufacturerAliasEntry, MatchKey, MergeResult, NormalizeOptions,
+    ParseError, ParseOptions, ParseResult, PartSummary, PipelineStep, Progress, ProjectTransfer,
+    ProjectWindowInfo, RenumberResult, TransferAck, Transform, TransformResult,
+};
 use serde::Deserialize;
-use serde_json;
-use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Emitter, Listener, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use utils::progress::{OperationContext, OperationRegistry};
+use utils::window_label::ProjectWindowRegistry;
 
 #[tauri::command]
 fn parse_bom_file(path: String) -> Result<ParseResult, AppError> {
     parsers::parse_bom_file(path)
 }
 
+/// オプションを指定してBOMファイルを読み込む
+///
+/// # 引数
+/// * `path` - ファイルパス
+/// * `options` - 読み込みオプション（許可文字リスト、読み込み時の値の保持、CSVの引用符・エスケープ文字、ヘッダー行の扱いなど）
+///
+/// # 戻り値
+/// パース結果
+#[tauri::command]
+fn parse_bom_file_with_options(
+    path: String,
+    options: ParseOptions,
+) -> Result<ParseResult, AppError> {
+    parsers::parse_bom_file_with_options(path, &options)
+}
+
+/// BOMファイルの形式を判定
+///
+/// 拡張子がない・未知・".txt" の場合は先頭の内容から判定する
+///
+/// # 引数
+/// * `path` - ファイルパス
+///
+/// # 戻り値
+/// 形式名（"csv", "xlsx", "json", "eco", "msf", "ccf", "pws", "bd", "rpt" など）
+#[tauri::command]
+fn detect_format(path: String) -> Result<String, AppError> {
+    parsers::detect_format(Path::new(&path))
+}
+
 /// 2つのBOMを比較し、差分を返す
 ///
 /// # 引数
 /// * `parse_a` - BOM A（比較元）
 /// * `parse_b` - BOM B（比較先）
+/// * `options` - 比較オプション（省略時は文字列として比較）
+///   - `fuzzyPartThreshold`: 部品型番のあいまい比較のしきい値（0.0～1.0）。
+///     梱包コードのみが異なり、類似度がこの値以上の型番変更を "likely_same" とする。
+///     値を大きくするほど判定が厳しくなる（目安: 0.8）。省略時は無効
+///   - `matchKey`: 行を対応付けるキーの役割（"part_no"、["ref", "value"] など）。省略時は "ref"。
+///     "part_no" を含む場合は数量列（役割 "quantity" または "Qty" などのヘッダー）を数値として比較する
+///   - `normalize`: 対応付けキー・列の値の正規化（`update_and_append_boms` と共通）
+///   - `ignoreColumns`: 全列の比較から除外する列（列IDまたはヘッダー名）。
+///     役割の列の比較には影響せず、除外した列のみが異なる行は "unchanged" になる
+///   - `positionTolerance`: 実装位置（座標・回転）の許容差（mm・度、目安: 0.01）。
+///     両方のBOMに座標・回転の役割があれば数値として比較し、回転角のみの変更を "rotated"、
+///     座標のみの変更を "moved_xy" とする。省略時は無効
+///   - `includeUnchanged`: 内容が同一の行も返すか（既定: true）。
+///     false の場合は変更・追加・削除の行のみを返す
+///
+/// * `operation_id` - 中断に使う処理ID（`cancel_operation` に渡す、省略時は中断不可）
 ///
 /// # 戻り値
-/// 差分情報のリスト（追加/削除/変更/一致）
-#[tauri::command]
-fn compare_boms(parse_a: ParseResult, parse_b: ParseResult) -> Vec<DiffRow> {
-    diff::compare::compare_boms(&parse_a, &parse_b)
+/// 差分情報のリスト（追加/削除/変更/一致）。中断された場合は kind が "cancelled" のエラー
+///
+/// 処理中は `bom-progress` イベントで進捗（stage: "compare_boms"）を通知する
+#[tauri::command(async)]
+fn compare_boms(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, OperationRegistry>,
+    parse_a: ParseResult,
+    parse_b: ParseResult,
+    options: Option<CompareOptions>,
+    operation_id: Option<String>,
+) -> Result<Vec<DiffRow>, AppError> {
+    let options = options.unwrap_or_default();
+    operations.run(operation_id.as_deref(), |cancel| {
+        let on_progress = |progress: &Progress| emit_progress(&app, progress);
+        let context = OperationContext {
+            on_progress: &on_progress,
+            cancel,
+        };
+        diff::compare::compare_boms_with_progress(&parse_a, &parse_b, &options, &context)
+    })
+}
+
+/// 列構成を確認してから2つのBOMを比較する
+///
+/// 引数・進捗の通知・中断は `compare_boms` と同じ
+///
+/// # 戻り値
+/// 差分情報のリストと列構成の警告（片方のBOMにしかない役割、列の位置で比較できない場合）
+#[tauri::command(async)]
+fn compare_boms_with_report(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, OperationRegistry>,
+    parse_a: ParseResult,
+    parse_b: ParseResult,
+    options: Option<CompareOptions>,
+    operation_id: Option<String>,
+) -> Result<CompareResult, AppError> {
+    let options = options.unwrap_or_default();
+    operations.run(operation_id.as_deref(), |cancel| {
+        let on_progress = |progress: &Progress| emit_progress(&app, progress);
+        let context = OperationContext {
+            on_progress: &on_progress,
+            cancel,
+        };
+        diff::compare::compare_boms_with_report(&parse_a, &parse_b, &options, &context)
+    })
+}
+
+/// 編集された行のみを比較し直して、前回の差分結果を更新する
+///
+/// # 引数
+/// * `previous` - 前回の `compare_boms` の結果（同じオプションで比較したもの）
+/// * `parse_a` - BOM A（比較元）
+/// * `parse_b` - BOM B（比較先）
+/// * `changed_refs` - 編集された行のReference（Referenceを変更した場合は変更前・変更後の両方）
+/// * `options` - 比較オプション（`compare_boms` と同じ）
+///
+/// # 戻り値
+/// 更新後の差分情報のリスト。行の追加・削除で行の並びが変わった場合は `compare_boms` を使うこと
+#[tauri::command(async)]
+fn update_diff(
+    previous: Vec<DiffRow>,
+    parse_a: ParseResult,
+    parse_b: ParseResult,
+    changed_refs: Vec<String>,
+    options: Option<CompareOptions>,
+) -> Vec<DiffRow> {
+    diff::compare::update_diff(
+        &previous,
+        &parse_a,
+        &parse_b,
+        &changed_refs,
+        &options.unwrap_or_default(),
+    )
 }
 
 /// Reference列を展開する（例: "C1-C5" → 5行に分割）
 ///
 /// # 引数
 /// * `parse` - 元のBOMデータ
+/// * `options` - 展開オプション（降順の範囲の扱い、1セルあたりの展開数の上限）
 ///
 /// # 戻り値
 /// 展開後のBOMデータ
 #[tauri::command]
-fn expand_reference(parse: ParseResult) -> Result<ParseResult, AppError> {
-    processors::reference::expand_reference(&parse)
+fn expand_reference(
+    parse: ParseResult,
+    options: Option<ExpandOptions>,
+) -> Result<ParseResult, AppError> {
+    processors::reference::expand_reference_with_options(&parse, &options.unwrap_or_default())
+}
+
+/// Reference列を展開し、元に戻すための変更記録も返す
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `options` - 展開オプション（`expand_reference` と同じ）
+///
+/// # 戻り値
+/// 展開後のBOMデータと変更記録（`undo_transform` に渡すと展開前に戻せる）
+#[tauri::command]
+fn expand_reference_with_transform(
+    parse: ParseResult,
+    options: Option<ExpandOptions>,
+) -> Result<TransformResult, AppError> {
+    processors::reference::expand_reference_transform_with_options(
+        &parse,
+        &options.unwrap_or_default(),
+    )
 }
 
 /// Reference列を分割する（例: "C1,C2,C3" を含む1行 → 3行に分割）
@@ -55,6 +220,72 @@ fn split_reference_rows(parse: ParseResult) -> Result<ParseResult, AppError> {
     processors::reference::split_reference_rows(&parse)
 }
 
+/// 指定した接頭辞のReferenceを連番で付け直す（例: "C1, C3, C4" → "C1, C2, C3"）
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `prefix` - 付け直す接頭辞（"C" など、ほかの接頭辞のReferenceは変更しない）
+/// * `start` - 最初に割り当てる番号
+///
+/// # 戻り値
+/// 付け直したBOMデータと、変更したReferenceの一覧（変更前 → 変更後）
+#[tauri::command]
+fn renumber_references(
+    parse: ParseResult,
+    prefix: String,
+    start: u32,
+) -> Result<RenumberResult, AppError> {
+    processors::reference::renumber_references(&parse, &prefix, start)
+}
+
+/// キーが重複する行を削除する
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `key_roles` - キーに使う役割（空の場合は行全体の値で判定）
+/// * `prefer_complete` - 値のあるセルが最も多い行を残すか（省略時は最初の行を残す）
+///
+/// # 戻り値
+/// 重複を削除したBOMデータと削除した行数
+#[tauri::command]
+fn deduplicate_rows(
+    parse: ParseResult,
+    key_roles: Vec<String>,
+    prefer_complete: Option<bool>,
+) -> DedupResult {
+    processors::dedup::deduplicate_rows(&parse, &key_roles, prefer_complete.unwrap_or(false))
+}
+
+/// 指定した役割の列の値で行を並べ替える（値が空の行は末尾）
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `by_role` - 並べ替えに使う役割（"ref", "part_no" など）
+/// * `natural` - Referenceを自然順（"C2" < "C10"）で並べるか
+///
+/// # 戻り値
+/// 並べ替えたBOMデータ（列構造は変更しない）
+#[tauri::command]
+fn sort_rows(parse: ParseResult, by_role: String, natural: bool) -> ParseResult {
+    processors::sort::sort_rows(&parse, &by_role, natural)
+}
+
+/// メーカー列の表記ゆれを正式名に置き換える（辞書にない値はそのまま）
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `aliases` - メーカー名エイリアス（辞書 "manufacturer_alias" の内容）
+///
+/// # 戻り値
+/// メーカー列を置き換えたBOMデータ
+#[tauri::command]
+fn apply_manufacturer_aliases(
+    parse: ParseResult,
+    aliases: Vec<ManufacturerAliasEntry>,
+) -> ParseResult {
+    processors::manufacturer::apply_manufacturer_aliases(&parse, &aliases)
+}
+
 /// 空欄セルを上の行の値で埋める
 ///
 /// # 引数
@@ -67,6 +298,31 @@ fn fill_blank_cells(parse: ParseResult) -> Result<ParseResult, AppError> {
     processors::cleaner::fill_blank_cells(&parse)
 }
 
+/// 空欄セルを上の行の値で埋め、元に戻すための変更記録も返す
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+///
+/// # 戻り値
+/// 空欄補完後のBOMデータと変更記録（`undo_transform` に渡すと補完前に戻せる）
+#[tauri::command]
+fn fill_blank_cells_with_transform(parse: ParseResult) -> Result<TransformResult, AppError> {
+    processors::cleaner::fill_blank_cells_with_transform(&parse)
+}
+
+/// 変更記録を使って処理前の状態に戻す
+///
+/// # 引数
+/// * `parse` - 処理後のBOMデータ
+/// * `transform` - 処理が返した変更記録
+///
+/// # 戻り値
+/// 処理前のBOMデータ
+#[tauri::command]
+fn undo_transform(parse: ParseResult, transform: Transform) -> Result<ParseResult, AppError> {
+    processors::transform::undo_transform(&parse, &transform)
+}
+
 /// 書式整形ルールを適用する
 ///
 /// # 引数
@@ -85,6 +341,8 @@ fn apply_format_rules(parse: ParseResult, options: FormatOptions) -> Result<Pars
 /// # 引数
 /// * `parse_a` - 更新元のBOM
 /// * `parse_b` - 更新内容のBOM
+/// * `match_key` - 行を対応付けるキーの役割（"part_no"、["ref", "value"] など、省略時は "ref"）
+/// * `normalize` - 対応付けキーの正規化（`compare_boms` の `normalize` と共通、省略時は前後の空白のみ無視）
 ///
 /// # 戻り値
 /// マージ後のBOMデータ
@@ -92,8 +350,51 @@ fn apply_format_rules(parse: ParseResult, options: FormatOptions) -> Result<Pars
 fn update_and_append_boms(
     parse_a: ParseResult,
     parse_b: ParseResult,
+    match_key: Option<MatchKey>,
+    normalize: Option<NormalizeOptions>,
 ) -> Result<ParseResult, AppError> {
-    diff::merge::update_and_append_boms(&parse_a, &parse_b)
+    match (match_key, normalize) {
+        (match_key, Some(normalize)) => diff::merge::update_and_append_boms_with_normalizer(
+            &parse_a,
+            &parse_b,
+            &match_key.unwrap_or_default(),
+            &diff::KeyNormalizer::new(&normalize),
+        ),
+        (Some(match_key), None) => {
+            diff::merge::update_and_append_boms_with_key(&parse_a, &parse_b, &match_key)
+        }
+        (None, None) => diff::merge::update_and_append_boms(&parse_a, &parse_b),
+    }
+}
+
+/// 複数のBOMの行をそのまま縦に連結する（Referenceでの対応付けは行わない）
+///
+/// # 引数
+/// * `parts` - 連結するBOM（この順に行を並べる）
+///
+/// # 戻り値
+/// 連結したBOMデータ（列は役割・ヘッダー名でまとめる）
+#[tauri::command]
+fn concat_boms(parts: Vec<ParseResult>) -> Result<ParseResult, AppError> {
+    diff::concat::concat_boms(&parts)
+}
+
+/// 共通の祖先（base）を使ってBOM AとBOM Bを3方向マージ
+///
+/// # 引数
+/// * `base` - 共通の祖先となるBOM
+/// * `parse_a` - 編集済みのBOM A（列構造の基準）
+/// * `parse_b` - 編集済みのBOM B
+///
+/// # 戻り値
+/// マージ後のBOMと競合（Reference + 列ID）のリスト
+#[tauri::command]
+fn three_way_merge(
+    base: ParseResult,
+    parse_a: ParseResult,
+    parse_b: ParseResult,
+) -> Result<MergeResult, AppError> {
+    diff::three_way::three_way_merge(&base, &parse_a, &parse_b)
 }
 
 /// IPC登録名をBOMに適用
@@ -103,27 +404,222 @@ fn update_and_append_boms(
 /// * `ipc_rules` - IPC登録名ルール
 /// * `exceptions` - 例外マスタエントリ
 ///
+/// * `operation_id` - 中断に使う処理ID（`cancel_operation` に渡す、省略時は中断不可）
+///
 /// # 戻り値
-/// IPC登録名が適用されたBOMデータ
-#[tauri::command]
+/// IPC登録名が適用されたBOMデータ。中断された場合は kind が "cancelled" のエラー
+///
+/// 処理中は `bom-progress` イベントで進捗（stage: "apply_ipc_names"）を通知する
+#[tauri::command(async)]
 fn apply_ipc_names(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, OperationRegistry>,
     parse: ParseResult,
     ipc_rules: Vec<IpcMasterRule>,
     exceptions: Vec<ExceptionMasterEntry>,
+    operation_id: Option<String>,
+) -> Result<ParseResult, AppError> {
+    operations.run(operation_id.as_deref(), |cancel| {
+        let on_progress = |progress: &Progress| emit_progress(&app, progress);
+        let context = OperationContext {
+            on_progress: &on_progress,
+            cancel,
+        };
+        matchers::ipc::apply_ipc_names_with_progress(&parse, ipc_rules, exceptions, &context)
+    })
+}
+
+/// IPC登録名をBOMに適用し、適用状況を集計する
+///
+/// 引数・進捗の通知・中断は `apply_ipc_names` と同じ
+///
+/// # 戻り値
+/// IPC登録名が適用されたBOMデータと、ルール・例外マスタで登録名を付けた行数、
+/// 一致しなかった行数と部品型番（マスタへの追加候補）
+#[tauri::command(async)]
+fn apply_ipc_names_with_report(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, OperationRegistry>,
+    parse: ParseResult,
+    ipc_rules: Vec<IpcMasterRule>,
+    exceptions: Vec<ExceptionMasterEntry>,
+    operation_id: Option<String>,
+) -> Result<IpcApplyResult, AppError> {
+    operations.run(operation_id.as_deref(), |cancel| {
+        let on_progress = |progress: &Progress| emit_progress(&app, progress);
+        let context = OperationContext {
+            on_progress: &on_progress,
+            cancel,
+        };
+        matchers::ipc::apply_ipc_names_with_report(&parse, ipc_rules, exceptions, &context)
+    })
+}
+
+/// 複数の前処理をまとめて順に適用する（処理ごとのBOMデータのやり取りを省く）
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `steps` - 適用する処理（先頭から順に実行、例: [{"type": "expand"}, {"type": "fill_blanks"}]）
+/// * `operation_id` - 中断に使う処理ID（`cancel_operation` に渡す、省略時は中断不可）
+///
+/// # 戻り値
+/// 全ての処理を適用したBOMデータ。失敗した場合は何番目のどの処理で失敗したかを含むエラー
+///
+/// IPC登録名の適用中は `bom-progress` イベントで進捗（stage: "apply_ipc_names"）を通知する
+#[tauri::command(async)]
+fn run_pipeline(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, OperationRegistry>,
+    parse: ParseResult,
+    steps: Vec<PipelineStep>,
+    operation_id: Option<String>,
+) -> Result<ParseResult, AppError> {
+    operations.run(operation_id.as_deref(), |cancel| {
+        let on_progress = |progress: &Progress| emit_progress(&app, progress);
+        let context = OperationContext {
+            on_progress: &on_progress,
+            cancel,
+        };
+        processors::pipeline::run_pipeline(parse, steps, &context)
+    })
+}
+
+/// 実行中の処理（`compare_boms`・`apply_ipc_names`・`run_pipeline`）に中断を指示する
+///
+/// # 引数
+/// * `operation_id` - 処理の呼び出し時に指定した処理ID
+///
+/// # 戻り値
+/// 処理が実行中だった場合は true（既に終了していた場合は false）
+#[tauri::command]
+fn cancel_operation(operations: tauri::State<'_, OperationRegistry>, operation_id: String) -> bool {
+    operations.cancel(&operation_id)
+}
+
+/// 進捗を通知するイベント名
+const PROGRESS_EVENT: &str = "bom-progress";
+
+/// 進捗イベントを送信（送信に失敗しても処理は続ける）
+fn emit_progress(app: &tauri::AppHandle, progress: &Progress) {
+    let _ = app.emit(PROGRESS_EVENT, progress.clone());
+}
+
+/// Referenceの接頭辞が許可リストに含まれるか検証する
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `allowed` - 許可する接頭辞（例: ["C", "R", "L", "U", "IC"]）
+///
+/// # 戻り値
+/// 許可されていない接頭辞のReferenceごとの警告
+#[tauri::command]
+fn validate_ref_prefixes(parse: ParseResult, allowed: Vec<String>) -> Vec<ParseError> {
+    processors::validator::validate_ref_prefixes(&parse, &allowed)
+}
+
+/// Referenceの欠番を接頭辞ごとに検出する
+///
+/// # 戻り値
+/// 欠番がある接頭辞ごとの報告（使用数が3未満の接頭辞は除く）。範囲指定が大きすぎる場合はエラー
+#[tauri::command]
+fn find_reference_gaps(parse: ParseResult) -> Result<Vec<GapReport>, AppError> {
+    processors::validator::find_reference_gaps(&parse)
+}
+
+/// 部品型番ごとに実装数を集計する（範囲指定は展開して数える）
+///
+/// # 戻り値
+/// 部品型番ごとの実装数とReferenceのリスト（型番順）。範囲指定が大きすぎる場合はエラー
+#[tauri::command]
+fn summarize_by_part(parse: ParseResult) -> Result<Vec<PartSummary>, AppError> {
+    processors::reference::summarize_by_part(&parse)
+}
+
+/// BOMデータの内容を表すハッシュ値を返す（前回保存時から変更されたかの確認用）
+///
+/// # 戻り値
+/// ヘッダー・行データ・列の役割から計算したSHA-256の16進文字列
+/// （エラー・行番号などの付随情報は含めない）
+#[tauri::command]
+fn bom_fingerprint(parse: ParseResult) -> String {
+    parse.fingerprint()
+}
+
+/// 列の役割を手動で設定し、表示順序を再作成する
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `roles` - 役割名 → 列IDの配列（例: {"ref": ["col-0"], "part_no": ["col-2"]}）
+///
+/// # 戻り値
+/// 役割と表示順序を更新したBOMデータ
+#[tauri::command]
+fn set_column_roles(
+    parse: ParseResult,
+    roles: HashMap<String, Vec<String>>,
 ) -> Result<ParseResult, AppError> {
-    matchers::ipc::apply_ipc_names(&parse, ipc_rules, exceptions)
+    processors::roles::set_column_roles(&parse, roles)
+}
+
+/// BOMデータを役割ごとの平坦な行に変換する
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+///
+/// # 戻り値
+/// Reference・部品型番・その他の列を持つ行のリスト
+#[tauri::command]
+fn normalize_bom(parse: ParseResult) -> Vec<BomRow> {
+    processors::normalizer::normalize_bom_data(
+        &parse.rows,
+        &parse.headers,
+        &parse.column_roles,
+        &parse.row_numbers,
+    )
 }
 
 /// テキストデータをクレンジングする（空白削除、正規化）
 ///
 /// # 引数
 /// * `parse` - 元のBOMデータ
+/// * `options` - クレンジングオプション（省略時は括弧の削除と半角変換の両方を行う）
 ///
 /// # 戻り値
 /// クレンジング後のBOMデータ
 #[tauri::command]
-fn cleanse_text_data(parse: ParseResult) -> ParseResult {
-    processors::cleaner::cleanse_text_data(&parse)
+fn cleanse_text_data(parse: ParseResult, options: Option<CleanseOptions>) -> ParseResult {
+    match options {
+        Some(options) => processors::cleaner::cleanse_text_data_with_options(&parse, &options),
+        None => processors::cleaner::cleanse_text_data(&parse),
+    }
+}
+
+/// テキストクレンジングで変更されるセルを確認する（データは変更しない）
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `options` - クレンジングオプション（`cleanse_text_data` と同じ既定値）
+///
+/// # 戻り値
+/// 値が変わるセルの一覧（行・列インデックス、変更前後の値）
+#[tauri::command]
+fn preview_cleanse(parse: ParseResult, options: Option<CleanseOptions>) -> Vec<CellEdit> {
+    let options = options.unwrap_or_else(CleanseOptions::legacy);
+    processors::cleaner::preview_cleanse(&parse, &options)
+}
+
+/// 1つのセルを読み込み時の値に戻す
+///
+/// # 引数
+/// * `parse` - BOMデータ（`keepOriginalRows` を指定して読み込んだもの）
+/// * `row` - 行インデックス
+/// * `column` - 列インデックス
+///
+/// # 戻り値
+/// セルを戻したBOMデータ
+#[tauri::command]
+fn revert_cell(parse: ParseResult, row: usize, column: usize) -> Result<ParseResult, AppError> {
+    processors::cleaner::revert_cell(&parse, row, column)
 }
 
 #[tauri::command]
@@ -131,6 +627,39 @@ fn load_dictionary(app: tauri::AppHandle, dictionary_name: String) -> Result<Str
     storage::dictionary::load_dictionary(app, dictionary_name)
 }
 
+/// 辞書を読み込む（破損時にバックアップから復元した場合は警告付き）
+#[tauri::command]
+fn load_dictionary_with_status(
+    app: tauri::AppHandle,
+    dictionary_name: String,
+) -> Result<DictionaryLoadResult, AppError> {
+    storage::dictionary::load_dictionary_with_status(app, dictionary_name)
+}
+
+/// 現在の辞書ディレクトリを取得
+///
+/// # 戻り値
+/// 辞書ファイルを置くディレクトリのパス
+#[tauri::command]
+fn get_dictionary_dir(app: tauri::AppHandle) -> Result<String, AppError> {
+    storage::dictionary::get_dictionary_dir(app)
+}
+
+/// 辞書ディレクトリを変更（共有フォルダーやポータブル環境のフォルダーを指定する）
+///
+/// 環境変数 `BOMSYNC_DICTIONARY_DIR` が指定されている場合はそちらが優先される。
+///
+/// # 引数
+/// * `app` - Tauriアプリケーションハンドル
+/// * `path` - 辞書ディレクトリの絶対パス（None・空文字の場合は既定の場所に戻す）
+///
+/// # 戻り値
+/// 設定後に使われる辞書ディレクトリ（書き込めない場合はエラー）
+#[tauri::command]
+fn set_dictionary_dir(app: tauri::AppHandle, path: Option<String>) -> Result<String, AppError> {
+    storage::dictionary::set_dictionary_dir(app, path)
+}
+
 #[tauri::command]
 fn save_dictionary(
     app: tauri::AppHandle,
@@ -140,6 +669,25 @@ fn save_dictionary(
     storage::dictionary::save_dictionary(app, dictionary_name, content)
 }
 
+/// 外部ファイルの辞書内容を取り込む
+///
+/// # 引数
+/// * `dictionary_name` - 辞書名
+/// * `incoming_content` - 取り込む辞書の内容（JSON文字列）
+/// * `mode` - "replace" | "append" | "merge"
+///
+/// # 戻り値
+/// 追加・更新・スキップ件数と競合したエントリ
+#[tauri::command]
+fn import_dictionary(
+    app: tauri::AppHandle,
+    dictionary_name: String,
+    incoming_content: String,
+    mode: DictionaryImportMode,
+) -> Result<DictionaryImportReport, AppError> {
+    storage::dictionary::import_dictionary(app, dictionary_name, incoming_content, mode)
+}
+
 #[tauri::command]
 fn save_session_to_file(path: String, content: String) -> Result<(), AppError> {
     storage::session::save_session_to_file(path, content)
@@ -150,6 +698,27 @@ fn load_session_from_file(path: String) -> Result<String, AppError> {
     storage::session::load_session_from_file(path)
 }
 
+/// セッションを自動保存する（最新10件まで保持）
+#[tauri::command]
+fn save_session_autosave(
+    app: tauri::AppHandle,
+    content: String,
+) -> Result<AutosaveEntry, AppError> {
+    storage::session::save_session_autosave(app, content)
+}
+
+/// 自動保存の一覧を取得（新しい順）
+#[tauri::command]
+fn list_autosaves(app: tauri::AppHandle) -> Result<Vec<AutosaveEntry>, AppError> {
+    storage::session::list_autosaves(app)
+}
+
+/// 自動保存から復元する
+#[tauri::command]
+fn load_autosave(app: tauri::AppHandle, id: String) -> Result<String, AppError> {
+    storage::session::load_autosave(app, id)
+}
+
 /// BOMファイルをエクスポート
 ///
 /// # 引数
@@ -157,17 +726,139 @@ fn load_session_from_file(path: String) -> Result<String, AppError> {
 /// * `format` - 出力フォーマット（"csv", "eco", "ccf", "msf"）
 /// * `diffs` - 差分情報（差分コメント用）
 /// * `include_comments` - 差分コメントを含めるか
+/// * `headers` - 出力する列名または列ID（CSVのみ、省略時は全列）
 ///
 /// # 戻り値
 /// エクスポートされたファイル内容（文字列）
+///
+/// 後方互換性のため残しています。新規の呼び出しは
+/// `export_bom_file_with_options` を使用してください。
 #[tauri::command]
 fn export_bom_file(
     parse: ParseResult,
     format: String,
     diffs: Option<Vec<DiffRow>>,
     include_comments: bool,
+    headers: Option<Vec<String>>,
 ) -> Result<String, AppError> {
-    exporters::export_bom_file(&parse, &format, diffs.as_deref(), include_comments)
+    let options = ExportOptions {
+        format,
+        include_diff_comments: include_comments,
+        filter: None,
+        headers: headers.unwrap_or_default(),
+        diffs,
+        ..ExportOptions::default()
+    };
+    exporters::export_bom_file(&parse, &options)
+}
+
+/// BOMファイルをエクスポートオプション指定でエクスポート
+///
+/// # 引数
+/// * `parse` - エクスポートするBOMデータ
+/// * `options` - エクスポートオプション（形式、差分コメント、フィルタ、出力列、差分情報）
+///
+/// # 戻り値
+/// エクスポートされたファイル内容（文字列）。CAD形式でReference・部品型番
+/// （CCF/MSF/PWSはグループ化の役割）の列が未指定の場合は kind が "validation" のエラー
+#[tauri::command]
+fn export_bom_file_with_options(
+    parse: ParseResult,
+    options: ExportOptions,
+) -> Result<String, AppError> {
+    exporters::export_bom_file(&parse, &options)
+}
+
+/// BOMファイルをエクスポートして指定パスに書き込む
+///
+/// 一時ファイルに書き込んでから置き換えるため、途中で失敗しても既存ファイルは壊れない。
+/// 文字列で返せないXLSX形式にも対応する（プレビューには `export_bom_file_with_options` を使用）。
+///
+/// # 引数
+/// * `parse` - エクスポートするBOMデータ
+/// * `format` - 出力フォーマット（`options.format` より優先）
+/// * `options` - エクスポートオプション
+/// * `path` - 書き込み先のファイルパス
+///
+/// # 戻り値
+/// 書き込んだバイト数
+#[tauri::command(async)]
+fn export_bom_to_path(
+    parse: ParseResult,
+    format: String,
+    options: ExportOptions,
+    path: String,
+) -> Result<u64, AppError> {
+    let options = ExportOptions { format, ..options };
+    let content = exporters::export_bom_bytes(&parse, &options)?;
+    storage::atomic::write_atomic(Path::new(&path), &content)
+        .map_err(|err| AppError::io(format!("ファイルの書き込みに失敗しました: {err}")))?;
+    Ok(content.len() as u64)
+}
+
+/// フォルダ内のBOMを一括で変換する
+///
+/// # 引数
+/// * `input_dir` - 入力フォルダ（サブフォルダも含めて走査）
+/// * `output_dir` - 出力フォルダ
+/// * `target_format` - 出力形式（"csv", "eco", "ccf", "msf", "pws", "bd", "pads_report", "json"）
+///
+/// # 戻り値
+/// ファイルごとの成功・失敗・スキップの結果
+#[tauri::command(async)]
+fn batch_convert(
+    input_dir: String,
+    output_dir: String,
+    target_format: String,
+) -> Result<BatchReport, AppError> {
+    batch::batch_convert(
+        Path::new(&input_dir),
+        Path::new(&output_dir),
+        &target_format,
+    )
+}
+
+/// 差分レポート（変更された項目ごとに Ref, Status, Field, A, B の1行）をCSVで出力
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - 差分情報（`compare_boms` の結果）
+///
+/// # 戻り値
+/// CSV形式のレポート（文字列）
+#[tauri::command]
+fn export_diff_report(
+    parse_a: ParseResult,
+    parse_b: ParseResult,
+    diffs: Vec<DiffRow>,
+) -> Result<String, AppError> {
+    exporters::diff_report::export_diff_report(&parse_a, &parse_b, &diffs)
+}
+
+/// 差分レポートを色分けしたHTMLの表として出力
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - 差分情報（`compare_boms` の結果）
+///
+/// # 戻り値
+/// 単体で表示できるHTMLの断片（`<table>` 要素）
+#[tauri::command]
+fn export_diff_html(parse_a: ParseResult, parse_b: ParseResult, diffs: Vec<DiffRow>) -> String {
+    exporters::diff_html::export_diff_html(&parse_a, &parse_b, &diffs)
+}
+
+/// 差分レポートをMarkdownの表として出力
+///
+/// # 引数
+/// * `parse_a` - 比較元のBOM
+/// * `parse_b` - 比較先のBOM
+/// * `diffs` - 差分情報（`compare_boms` の結果）
+#[tauri::command]
+fn export_diff_markdown(parse_a: ParseResult, parse_b: ParseResult, diffs: Vec<DiffRow>) -> String {
+    exporters::diff_html::export_diff_markdown(&parse_a, &parse_b, &diffs)
 }
 
 #[derive(Deserialize)]
@@ -176,30 +867,67 @@ struct WindowPosition {
     y: f64,
 }
 
+/// プロジェクトを別ウィンドウで開く（既に開いている場合はそのウィンドウを前面に出す）
+///
+/// 位置の指定がない場合は、前回保存したウィンドウの位置とサイズを復元する。
+/// ウィンドウの配置はラベルではなくプロジェクトIDに対応付けて、閉じるときに保存する。
+///
+/// # 引数
+/// * `app` - Tauriアプリケーションハンドル
+/// * `windows` - プロジェクトID → ウィンドウのラベルの対応
+/// * `project_id` - 開くプロジェクトのID
+/// * `position` - ウィンドウの位置（省略時は保存済みの位置）
 #[tauri::command]
 fn open_project_window(
     app: tauri::AppHandle,
+    windows: tauri::State<'_, ProjectWindowRegistry>,
     project_id: String,
     position: Option<WindowPosition>,
 ) -> Result<(), AppError> {
-    let sanitized_base: String = project_id
-        .chars()
-        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
-        .collect::<String>()
-        .trim_matches('-')
-        .to_owned();
-
-    let base_label = if sanitized_base.is_empty() {
-        "project".to_string()
-    } else {
-        sanitized_base
-    };
+    focus_or_create_window(&app, &windows, project_id, position).map(|_| ())
+}
 
-    let mut label = base_label.clone();
-    let mut counter = 0usize;
-    while app.get_webview_window(&label).is_some() {
-        counter += 1;
-        label = format!("{}-{}", base_label, counter);
+/// プロジェクトのウィンドウを前面に出し、開いていない場合は新しく開く
+///
+/// # 引数
+/// * `app` - Tauriアプリケーションハンドル
+/// * `windows` - プロジェクトID → ウィンドウのラベルの対応
+/// * `project_id` - 開くプロジェクトのID
+/// * `position` - 新しく開く場合のウィンドウの位置（省略時は保存済みの位置）
+///
+/// # 戻り値
+/// ウィンドウのラベルと、新しく開いたかどうか
+#[tauri::command]
+fn focus_or_create_project_window(
+    app: tauri::AppHandle,
+    windows: tauri::State<'_, ProjectWindowRegistry>,
+    project_id: String,
+    position: Option<WindowPosition>,
+) -> Result<ProjectWindowInfo, AppError> {
+    focus_or_create_window(&app, &windows, project_id, position)
+}
+
+fn focus_or_create_window(
+    app: &tauri::AppHandle,
+    windows: &ProjectWindowRegistry,
+    project_id: String,
+    position: Option<WindowPosition>,
+) -> Result<ProjectWindowInfo, AppError> {
+    let (label, is_new) =
+        windows.claim(&project_id, |label| app.get_webview_window(label).is_some());
+
+    if !is_new {
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.unminimize();
+            let _ = window.show();
+            window.set_focus().map_err(|err| {
+                AppError::new(format!("ウィンドウを前面に出せませんでした: {err}"))
+            })?;
+            return Ok(ProjectWindowInfo {
+                label,
+                created: false,
+            });
+        }
     }
 
     let project_json = serde_json::to_string(&project_id).map_err(|err| {
@@ -209,7 +937,7 @@ fn open_project_window(
         ))
     })?;
 
-    let mut builder = WebviewWindowBuilder::new(&app, label, WebviewUrl::App("index.html".into()))
+    let mut builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App("index.html".into()))
         .title(format!("BOMSyncTool - {}", project_id))
         .initialization_script(&format!(
             "window.__INITIAL_PROJECT_ID__ = {}; window.dispatchEvent(new CustomEvent('initial-project-ready'));",
@@ -218,16 +946,81 @@ fn open_project_window(
 
     if let Some(pos) = position {
         builder = builder.position(pos.x, pos.y);
+    } else if let Some(state) = storage::window_state::load_window_state(app, &project_id) {
+        builder = builder
+            .position(state.x, state.y)
+            .inner_size(state.width, state.height);
     }
 
-    builder
-        .build()
-        .map_err(|err| AppError::new(format!("タブを開けませんでした: {}", err)))?;
+    let window = match builder.build() {
+        Ok(window) => window,
+        Err(err) => {
+            windows.release(&label);
+            return Err(AppError::new(format!("タブを開けませんでした: {}", err)));
+        }
+    };
 
-    Ok(())
+    let handle = app.clone();
+    let event_window = window.clone();
+    let event_label = label.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::CloseRequested { .. } => {
+            // 閉じる処理を妨げないよう、保存に失敗しても無視する
+            let _ = storage::window_state::save_window_state(&handle, &event_window, &project_id);
+        }
+        WindowEvent::Destroyed => {
+            handle
+                .state::<ProjectWindowRegistry>()
+                .release(&event_label);
+        }
+        _ => {}
+    });
+
+    Ok(ProjectWindowInfo {
+        label,
+        created: true,
+    })
 }
 
-/// プロジェクトデータを別ウィンドウに転送（元ウィンドウからは削除）
+/// 呼び出し元ウィンドウの位置とサイズをプロジェクトに対応付けて保存
+///
+/// 移動・リサイズ後に呼び出すと、次に `open_project_window` で開くときに復元される。
+///
+/// # 引数
+/// * `app` - Tauriアプリケーションハンドル
+/// * `window` - 呼び出し元のウィンドウ
+/// * `project_id` - プロジェクトID
+#[tauri::command]
+fn save_window_state(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    project_id: String,
+) -> Result<(), AppError> {
+    storage::window_state::save_window_state(&app, &window, &project_id)
+}
+
+/// プロジェクト転送イベント名（転送先ウィンドウに送信）
+const TRANSFER_EVENT: &str = "merge-project";
+
+/// プロジェクト転送の受信確認イベント名（転送先ウィンドウから送信）
+const TRANSFER_ACK_EVENT: &str = "merge-project-ack";
+
+/// 転送先の受信確認を待つ時間
+///
+/// 転送先は取り込み（保存を含む）を終えてから確認を返すため、大きなプロジェクトでも
+/// 間に合うよう長めにとる
+const TRANSFER_ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 転送ごとに割り当てるID（受信確認の照合に使用）
+static NEXT_TRANSFER_ID: AtomicU64 = AtomicU64::new(1);
+
+/// プロジェクトデータを別ウィンドウに転送
+///
+/// `ProjectTransfer`（形式のバージョン付き）を `merge-project` で送り、
+/// 転送先ウィンドウが `merge-project-ack` で受信を確認するまで待つ。
+/// 転送先は取り込みを終えてから確認を返す。
+/// 確認が届かない場合や転送先で取り込めなかった場合はエラーを返すため、
+/// 元ウィンドウは Ok を受け取ってからプロジェクトを削除すること。
 ///
 /// # 引数
 /// * `app` - Tauriアプリケーションハンドル
@@ -235,8 +1028,8 @@ fn open_project_window(
 /// * `project_data` - プロジェクトデータ（JSON文字列）
 ///
 /// # 戻り値
-/// 成功時は Ok(())
-#[tauri::command]
+/// 転送先が受信を確認した場合は Ok(())
+#[tauri::command(async)]
 fn transfer_project_to_window(
     app: tauri::AppHandle,
     target_label: String,
@@ -245,18 +1038,42 @@ fn transfer_project_to_window(
     // ターゲットウィンドウを取得
     let target_window = app
         .get_webview_window(&target_label)
-        .ok_or_else(|| AppError::new("ターゲットウィンドウが見つかりません".to_string()))?;
+        .ok_or_else(|| AppError::not_found("ターゲットウィンドウが見つかりません".to_string()))?;
+
+    // 不正なデータは転送せずに、元ウィンドウに残したままにする
+    let transfer_id = NEXT_TRANSFER_ID.fetch_add(1, Ordering::Relaxed);
+    let transfer = ProjectTransfer::from_json(transfer_id, &project_data)?;
 
-    // JavaScriptイベントを発火してプロジェクトデータを送信
-    target_window
-        .emit("merge-project", project_data)
+    // 受信確認を取りこぼさないよう、送信前に待ち受けを始める
+    let (sender, receiver) = mpsc::channel();
+    let listener = app.listen(TRANSFER_ACK_EVENT, move |event| {
+        if let Some(ack) = TransferAck::matching(event.payload(), transfer_id) {
+            let _ = sender.send(ack);
+        }
+    });
+
+    let result = target_window
+        .emit(TRANSFER_EVENT, transfer)
         .map_err(|err| {
             AppError::new(format!(
                 "プロジェクト転送イベントの送信に失敗しました: {}",
                 err
             ))
-        })?;
+        })
+        .and_then(|_| {
+            receiver.recv_timeout(TRANSFER_ACK_TIMEOUT).map_err(|_| {
+                AppError::new(
+                    "転送先ウィンドウから応答がありませんでした。プロジェクトは元のウィンドウに残しています。",
+                )
+            })
+        });
+    app.unlisten(listener);
 
+    if let Some(message) = result?.error {
+        return Err(AppError::new(format!(
+            "転送先ウィンドウでプロジェクトを取り込めませんでした: {message}"
+        )));
+    }
     Ok(())
 }
 
@@ -266,22 +1083,63 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(OperationRegistry::default())
+        .manage(ProjectWindowRegistry::default())
         .invoke_handler(tauri::generate_handler![
             parse_bom_file,
+            parse_bom_file_with_options,
             compare_boms,
+            compare_boms_with_report,
+            update_diff,
             expand_reference,
+            expand_reference_with_transform,
             split_reference_rows,
+            renumber_references,
+            deduplicate_rows,
+            sort_rows,
+            apply_manufacturer_aliases,
             fill_blank_cells,
+            fill_blank_cells_with_transform,
+            undo_transform,
             apply_format_rules,
             update_and_append_boms,
+            concat_boms,
+            three_way_merge,
             cleanse_text_data,
+            preview_cleanse,
+            revert_cell,
+            set_column_roles,
+            validate_ref_prefixes,
+            find_reference_gaps,
+            summarize_by_part,
+            bom_fingerprint,
+            normalize_bom,
             load_dictionary,
+            load_dictionary_with_status,
             save_dictionary,
+            get_dictionary_dir,
+            set_dictionary_dir,
+            import_dictionary,
             save_session_to_file,
             load_session_from_file,
+            save_session_autosave,
+            list_autosaves,
+            load_autosave,
             apply_ipc_names,
+            apply_ipc_names_with_report,
+            run_pipeline,
+            cancel_operation,
             export_bom_file,
+            export_bom_file_with_options,
+            export_bom_to_path,
+            detect_format,
+            batch_convert,
+            export_diff_report,
+            export_diff_html,
+            export_diff_markdown,
             open_project_window,
+            focus_or_create_project_window,
+            save_window_state,
             transfer_project_to_window
         ])
         .run(tauri::generate_context!())
@@ -1,5 +1,40 @@
+use std::collections::HashMap;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::diff::KeyNormalizer;
 use crate::models::{MasterCondition, ParseResult};
 
+/// 列名（`KeyNormalizer::for_matching` で正規化）→ その列名を持つ列のインデックス（列順）
+///
+/// 役割名以外のフィールドを列名で探す際に、行・条件ごとにヘッダーを走査しないよう
+/// BOMごとに1回だけ作成する
+pub struct HeaderIndex {
+    columns: HashMap<String, Vec<usize>>,
+}
+
+impl HeaderIndex {
+    pub fn new(parse: &ParseResult) -> Self {
+        let normalizer = KeyNormalizer::for_matching();
+        let mut columns: HashMap<String, Vec<usize>> = HashMap::new();
+        for (col_idx, header) in parse.headers.iter().enumerate() {
+            columns
+                .entry(normalizer.normalize(header))
+                .or_default()
+                .push(col_idx);
+        }
+        Self { columns }
+    }
+
+    /// 正規化した列名が一致する列のインデックス（同じ列名が複数ある場合は全て、列順）
+    fn get(&self, normalized: &str) -> &[usize] {
+        self.columns
+            .get(normalized)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
 /// 条件が行にマッチするかチェック
 ///
 /// # 引数
@@ -10,131 +45,290 @@ use crate::models::{MasterCondition, ParseResult};
 /// # 戻り値
 /// マッチする場合true
 pub fn condition_matches(parse: &ParseResult, row_idx: usize, condition: &MasterCondition) -> bool {
-    let target_value = match get_field_value(parse, row_idx, &condition.field) {
-        Some(value) => value,
-        None => return false,
-    };
+    condition_matches_with_index(parse, row_idx, condition, &HeaderIndex::new(parse))
+}
 
-    value_matches(&target_value, &condition.value, &condition.match_type)
+/// 作成済みの列名の索引を使って条件が行にマッチするかチェック（結果は `condition_matches` と同じ）
+pub fn condition_matches_with_index(
+    parse: &ParseResult,
+    row_idx: usize,
+    condition: &MasterCondition,
+    headers: &HeaderIndex,
+) -> bool {
+    // 同じ役割の列が複数ある場合（代替メーカー列など）は、いずれかの列が一致すればマッチ
+    let matched = get_field_values(parse, row_idx, &condition.field, headers)
+        .iter()
+        .any(|target_value| value_matches(target_value, &condition.value, &condition.match_type));
+    // 否定の条件は、どの列も一致しない場合（値が空の場合を含む）にマッチ
+    matched != condition.negate
 }
 
-/// 指定したフィールドの値を取得
+/// 指定したフィールドの値を全て取得
 ///
 /// # 引数
 /// * `parse` - BOMデータ
 /// * `row_idx` - 行インデックス
 /// * `field` - フィールド名（"ref", "part_no", または列名）
+/// * `headers` - 列名の索引（`HeaderIndex::new(parse)`）
 ///
 /// # 戻り値
-/// フィールドの値（見つからない場合はNone）
-fn get_field_value(parse: &ParseResult, row_idx: usize, field: &str) -> Option<String> {
-    let normalized = field.trim().to_lowercase();
+/// フィールドに対応する全ての列の値（前後の空白を除き、空の値は除外）。
+/// 列名で指定した場合、同じ列名の列が複数あれば列順で最初の空でない値のみ
+pub fn get_field_values(
+    parse: &ParseResult,
+    row_idx: usize,
+    field: &str,
+    headers: &HeaderIndex,
+) -> Vec<String> {
+    let normalized = KeyNormalizer::for_matching().normalize(field);
 
-    // 標準的な役割名でチェック
+    // 標準的な役割名でチェック（正規化で大文字になっている）
     match normalized.as_str() {
-        "ref" | "reference" => {
-            let ref_value = parse.get_ref(row_idx);
-            if ref_value.is_empty() {
-                None
-            } else {
-                Some(ref_value)
-            }
-        }
-        "part_no" | "partno" | "partnumber" | "部品型番" => {
-            let part_no = parse.get_part_no(row_idx);
-            if part_no.is_empty() {
-                None
-            } else {
-                Some(part_no)
-            }
-        }
-        "manufacturer" | "メーカー" => {
-            let mfr = parse.get_manufacturer(row_idx);
-            if mfr.is_empty() {
-                None
-            } else {
-                Some(mfr)
-            }
-        }
-        "value" | "値" => {
-            let value = parse.get_value(row_idx);
-            if value.is_empty() {
-                None
-            } else {
-                Some(value)
-            }
+        "REF" | "REFERENCE" => parse.get_values(row_idx, "ref"),
+        "PART_NO" | "PARTNO" | "PARTNUMBER" | "部品型番" => {
+            parse.get_values(row_idx, "part_no")
         }
+        "MANUFACTURER" | "メーカー" => parse.get_values(row_idx, "manufacturer"),
+        "VALUE" | "値" => parse.get_values(row_idx, "value"),
         _ => {
             // その他のフィールド名は列名として扱う
-            // ヘッダーから該当する列を探す
-            let row = parse.rows.get(row_idx)?;
-            for (col_idx, header) in parse.headers.iter().enumerate() {
-                if header.to_lowercase() == normalized {
-                    if let Some(value) = row.get(col_idx) {
-                        if !value.trim().is_empty() {
-                            return Some(value.clone());
-                        }
-                    }
-                }
-            }
-            None
+            // ヘッダーから該当する列を探す（同じ列名の列は最初の空でない値を使う）
+            let Some(row) = parse.rows.get(row_idx) else {
+                return vec![];
+            };
+            headers
+                .get(&normalized)
+                .iter()
+                .filter_map(|&col_idx| row.get(col_idx))
+                .map(|value| value.trim())
+                .find(|value| !value.is_empty())
+                .map(str::to_string)
+                .into_iter()
+                .collect()
         }
     }
 }
 
+/// 値が条件に一致するか（`KeyNormalizer::for_matching` で正規化して比較）
 pub fn value_matches(target: &str, pattern: &str, match_type: &str) -> bool {
-    let target_lower = target.to_lowercase();
-    let pattern_lower = pattern.to_lowercase();
+    let normalizer = KeyNormalizer::for_matching();
+    let target_key = normalizer.normalize(target);
+    let pattern_key = normalizer.normalize(pattern);
     match match_type.trim().to_lowercase().as_str() {
-        "equals" => target_lower == pattern_lower,
-        "contains" => target_lower.contains(&pattern_lower),
-        "starts_with" => target_lower.starts_with(&pattern_lower),
-        "ends_with" => target_lower.ends_with(&pattern_lower),
-        "wildcard" => wildcard_match(&target_lower, &pattern_lower),
+        "equals" => target_key == pattern_key,
+        "contains" => target_key.contains(&pattern_key),
+        "starts_with" => target_key.starts_with(&pattern_key),
+        "ends_with" => target_key.ends_with(&pattern_key),
+        "wildcard" => wildcard_match(&target_key, &pattern_key),
+        // 不正な正規表現は一致しないものとして扱う（保存時の検証で弾く）
+        "regex" => build_regex(pattern).is_ok_and(|regex| regex.is_match(target.trim())),
         _ => {
-            if pattern.contains('*') {
-                wildcard_match(&target_lower, &pattern_lower)
+            if pattern_key.contains('*') {
+                wildcard_match(&target_key, &pattern_key)
             } else {
-                target_lower == pattern_lower
+                target_key == pattern_key
             }
         }
     }
 }
 
+/// 条件の正規表現を作成（ほかの一致方法と同じく大文字小文字を区別せず、前後の空白は除く）
+pub fn build_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern.trim())
+        .case_insensitive(true)
+        .build()
+}
+
+/// `*` を任意の文字列として照合（`*` 以外は完全一致）
+///
+/// 最後に現れた `*` の位置だけを覚えて戻る貪欲法で照合するため、
+/// `*` が多いパターンでも計算量は対象文字列とパターンの長さの積で抑えられる
 pub fn wildcard_match(target: &str, pattern: &str) -> bool {
     if pattern == "*" {
         return true;
     }
-
-    let parts: Vec<&str> = pattern.split('*').collect();
-    if parts.len() == 1 {
+    if !pattern.contains('*') {
         return target == pattern;
     }
 
-    let mut current_index = 0usize;
-    for (i, part) in parts.iter().enumerate() {
-        if part.is_empty() {
-            continue;
-        }
+    // 比較はバイト単位（'*' はASCIIのため、UTF-8の文字の途中で一致することはない）
+    let target = target.as_bytes();
+    let pattern = pattern.as_bytes();
+    let (mut t, mut p) = (0, 0);
+    // 直前の '*' の位置と、その '*' に対応させ始めた対象文字列の位置
+    let mut star: Option<(usize, usize)> = None;
 
-        if i == 0 {
-            if !target.starts_with(part) {
-                return false;
-            }
-            current_index = part.len();
-            continue;
+    while t < target.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == target[t] {
+            t += 1;
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // '*' に対応させる範囲を1バイト広げてやり直す
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
         }
+    }
 
-        if i == parts.len() - 1 {
-            return target.ends_with(part);
-        }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
 
-        if let Some(found) = target[current_index..].find(part) {
-            current_index += found + part.len();
-        } else {
-            return false;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_with_two_makers(primary: &str, alternate: &str) -> ParseResult {
+        let headers = vec![
+            "Ref".to_string(),
+            "Part".to_string(),
+            "Maker".to_string(),
+            "Alt Maker".to_string(),
+        ];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(vec![vec![
+                "C1".to_string(),
+                "GRM155".to_string(),
+                primary.to_string(),
+                alternate.to_string(),
+            ]])
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .role("manufacturer", &[2, 3])
+            .row_numbers(vec![1])
+            .build()
+    }
+
+    fn condition(field: &str, match_type: &str, value: &str) -> MasterCondition {
+        MasterCondition {
+            field: field.to_string(),
+            match_type: match_type.to_string(),
+            value: value.to_string(),
+            negate: false,
         }
     }
 
-    true
+    #[test]
+    fn test_condition_matches_any_manufacturer_column() {
+        let parse = parse_with_two_makers("Murata", "TDK");
+
+        assert!(condition_matches(
+            &parse,
+            0,
+            &condition("manufacturer", "equals", "TDK")
+        ));
+        assert!(condition_matches(
+            &parse,
+            0,
+            &condition("メーカー", "equals", "Murata")
+        ));
+        assert!(!condition_matches(
+            &parse,
+            0,
+            &condition("manufacturer", "equals", "Yageo")
+        ));
+    }
+
+    #[test]
+    fn test_condition_matches_single_column_unchanged() {
+        let mut parse = parse_with_two_makers("Murata", "");
+        parse
+            .column_roles
+            .insert("manufacturer".to_string(), vec!["col-2".to_string()]);
+
+        assert!(condition_matches(
+            &parse,
+            0,
+            &condition("manufacturer", "contains", "mura")
+        ));
+        assert!(condition_matches(
+            &parse,
+            0,
+            &condition("part_no", "starts_with", "GRM")
+        ));
+        assert!(condition_matches(
+            &parse,
+            0,
+            &condition("Maker", "equals", "murata")
+        ));
+        // 空の列は条件に一致しない
+        assert!(!condition_matches(
+            &parse,
+            0,
+            &condition("Alt Maker", "wildcard", "*")
+        ));
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("grm155r71c104", "grm*"));
+        assert!(wildcard_match("grm155r71c104", "*104"));
+        assert!(wildcard_match("grm155r71c104", "grm*r7*104"));
+        assert!(wildcard_match("grm155", "grm**155"));
+        assert!(wildcard_match("", "*"));
+        assert!(wildcard_match("村田製作所", "村田*所"));
+        assert!(!wildcard_match("grm155", "grm"));
+        assert!(!wildcard_match("grm155", "*156"));
+        assert!(!wildcard_match("grm155r71", "grm*r8*"));
+        // 前後の部分が重なる場合は一致しない
+        assert!(!wildcard_match("ab", "ab*b"));
+    }
+
+    #[test]
+    fn test_wildcard_match_pathological_pattern() {
+        let target = "a".repeat(20_000);
+        let pattern = format!("{}b", "*a".repeat(200));
+
+        let started = std::time::Instant::now();
+        assert!(!wildcard_match(&target, &pattern));
+        assert!(wildcard_match(&format!("{target}b"), &pattern));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_value_matches_ignores_surrounding_spaces() {
+        assert!(value_matches("GRM155 ", "GRM155", "equals"));
+        assert!(value_matches("GRM155", " grm155", "equals"));
+        assert!(value_matches(" GRM155", "155", "ends_with"));
+        assert!(value_matches("GRM155\t", "GRM", "starts_with"));
+        assert!(value_matches("GRM155 ", "grm*5", "wildcard"));
+        assert!(value_matches(" GRM155 ", "^grm155$", "regex"));
+
+        // 列名で指定したフィールドの値も前後の空白を除く
+        let mut parse = parse_with_two_makers("Murata", "");
+        parse.rows[0][3] = "TDK ".to_string();
+        assert!(condition_matches(
+            &parse,
+            0,
+            &condition("Alt Maker", "regex", "^tdk$")
+        ));
+        assert_eq!(
+            get_field_values(&parse, 0, "alt maker", &HeaderIndex::new(&parse)),
+            vec!["TDK"]
+        );
+    }
+
+    #[test]
+    fn test_matching_uses_key_normalizer() {
+        // 列名・役割名・値のいずれも KeyNormalizer::for_matching の規則で照合する
+        let mut parse = parse_with_two_makers("Murata", "");
+        parse.headers[3] = " ALT MAKER ".to_string();
+        parse.rows[0][3] = "tdk".to_string();
+        let headers = HeaderIndex::new(&parse);
+        assert_eq!(
+            get_field_values(&parse, 0, "Alt Maker", &headers),
+            vec!["tdk"]
+        );
+        assert_eq!(
+            get_field_values(&parse, 0, " Manufacturer ", &headers),
+            vec!["Murata", "tdk"]
+        );
+        assert!(value_matches("ＧＲＭ155", "ＧＲＭ155", "equals"));
+        assert!(!value_matches("GRM155", "ＧＲＭ155", "equals"));
+    }
 }
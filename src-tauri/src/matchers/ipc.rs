@@ -1,8 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::models::{AppError, ExceptionMasterEntry, IpcMasterRule, ParseResult};
+use crate::diff::KeyNormalizer;
+use crate::models::{
+    AppError, ExceptionMasterEntry, IpcApplyReport, IpcApplyResult, IpcMasterRule, ParseResult,
+};
+use crate::utils::progress::{OperationContext, ProgressReporter};
 
-use super::helpers::condition_matches;
+use super::helpers::HeaderIndex;
+use super::template::CompiledRule;
 use super::ASSIGNED_NAME_KEY;
 
 /// IPC登録名をBOMに適用
@@ -10,7 +15,11 @@ use super::ASSIGNED_NAME_KEY;
 /// # 動作
 /// 1. 例外マスタで部品型番に直接マッチするものを優先適用
 /// 2. ルールマスタで条件にマッチするものを適用
-/// 3. 結果を"assigned_name"列に格納
+/// 3. 結果を"登録名"列に格納し、ルールの `outputs` の値もそれぞれの列に格納
+///    （役割名・ヘッダー名に一致する列がなければ列を追加する）
+///
+/// 登録名・`outputs` の値の `${1}`・`${name}` は、一致方法が "regex" の条件の
+/// グループで置き換える（`$$` は `$`）。条件にないグループを参照するルールはエラー。
 ///
 /// # 引数
 /// * `parse` - 元のBOMデータ
@@ -18,95 +27,167 @@ use super::ASSIGNED_NAME_KEY;
 /// * `exceptions` - 例外マスタエントリのリスト
 ///
 /// # 戻り値
-/// "assigned_name"列が追加されたBOMデータ
+/// "登録名"列（と `outputs` の列）が追加されたBOMデータ
 pub fn apply_ipc_names(
     parse: &ParseResult,
     ipc_rules: Vec<IpcMasterRule>,
     exceptions: Vec<ExceptionMasterEntry>,
 ) -> Result<ParseResult, AppError> {
+    apply_ipc_names_with_progress(parse, ipc_rules, exceptions, &OperationContext::default())
+}
+
+/// 進捗を行数で通知しながらIPC登録名を適用（結果は `apply_ipc_names` と同じ）
+///
+/// 中断が指示された場合は `AppError::cancelled()` を返す
+pub fn apply_ipc_names_with_progress(
+    parse: &ParseResult,
+    ipc_rules: Vec<IpcMasterRule>,
+    exceptions: Vec<ExceptionMasterEntry>,
+    context: &OperationContext,
+) -> Result<ParseResult, AppError> {
+    apply_ipc_names_with_report(parse, ipc_rules, exceptions, context).map(|applied| applied.result)
+}
+
+/// IPC登録名を適用し、ルール・例外マスタで登録名を付けた行数と
+/// 一致しなかった部品型番を集計する（適用結果は `apply_ipc_names` と同じ）
+///
+/// 中断が指示された場合は `AppError::cancelled()` を返す
+pub fn apply_ipc_names_with_report(
+    parse: &ParseResult,
+    ipc_rules: Vec<IpcMasterRule>,
+    exceptions: Vec<ExceptionMasterEntry>,
+    context: &OperationContext,
+) -> Result<IpcApplyResult, AppError> {
     // ------------------------------------------------------------------------
     // ステップ1: 例外マスタのマッピングを作成
     // ------------------------------------------------------------------------
 
+    let normalizer = KeyNormalizer::for_matching();
     let mut exception_map: HashMap<String, String> = HashMap::new();
     for entry in exceptions {
         if entry.part_no.trim().is_empty() {
             continue;
         }
-        exception_map.insert(entry.part_no.to_lowercase(), entry.output_name.clone());
+        exception_map.insert(
+            normalizer.normalize(&entry.part_no),
+            entry.output_name.clone(),
+        );
     }
 
+    // 正規表現・登録名テンプレートを先に解析（不正なルールがあれば適用前にエラー）
+    let compiled_rules = ipc_rules
+        .iter()
+        .map(CompiledRule::compile)
+        .collect::<Result<Vec<_>, _>>()?;
+    // 条件の列名の検索用（行・条件ごとにヘッダーを走査しない）
+    let header_index = HeaderIndex::new(parse);
+
     // ------------------------------------------------------------------------
-    // ステップ2: assigned_name列のインデックスを確認・作成
+    // ステップ2: 出力先の列（登録名列とルールの追加出力の列）を確認・作成
     // ------------------------------------------------------------------------
 
-    let mut column_roles = parse.column_roles.clone();
-    let mut headers = parse.headers.clone();
-    let assigned_col_idx: usize;
-
-    // 既存のassigned_name列を探す
-    if let Some(existing_indices) = column_roles.get(ASSIGNED_NAME_KEY) {
-        if let Some(first_idx_str) = existing_indices.first() {
-            assigned_col_idx = first_idx_str
-                .strip_prefix("col-")
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(headers.len());
-        } else {
-            assigned_col_idx = headers.len();
-        }
-    } else {
-        // 新しい列を追加
-        assigned_col_idx = headers.len();
-        headers.push(ASSIGNED_NAME_KEY.to_string());
-        column_roles.insert(
-            ASSIGNED_NAME_KEY.to_string(),
-            vec![format!("col-{}", assigned_col_idx)],
-        );
-    }
+    let mut columns = OutputColumns {
+        column_roles: parse.column_roles.clone(),
+        headers: parse.headers.clone(),
+        column_order: parse.column_order.clone(),
+        next_idx: parse.column_count(),
+        insert_at: None,
+    };
+    let assigned_col_idx = columns.ensure(ASSIGNED_NAME_KEY);
+
+    let mut output_keys: Vec<&str> = ipc_rules
+        .iter()
+        .flat_map(|rule| rule.outputs.keys())
+        .map(|key| key.trim())
+        .filter(|key| !key.is_empty() && *key != ASSIGNED_NAME_KEY)
+        .collect();
+    output_keys.sort_unstable();
+    output_keys.dedup();
+    let output_columns: HashMap<&str, usize> = output_keys
+        .into_iter()
+        .map(|key| (key, columns.ensure(key)))
+        .collect();
+    let max_col_idx = output_columns
+        .values()
+        .copied()
+        .chain(std::iter::once(assigned_col_idx))
+        .max()
+        .unwrap_or(assigned_col_idx);
 
     // ------------------------------------------------------------------------
     // ステップ3: 各行にIPC登録名を適用
     // ------------------------------------------------------------------------
 
     let mut new_rows = Vec::with_capacity(parse.rows.len());
+    let mut report = IpcApplyReport::default();
+    let mut seen_unmatched: HashSet<String> = HashSet::new();
+    let progress = ProgressReporter::new("apply_ipc_names", parse.rows.len(), context);
 
     for (row_idx, row) in parse.rows.iter().enumerate() {
+        progress.tick(row_idx)?;
         let mut new_row = row.clone();
 
-        // assigned_name列の値を空にしておく
-        while new_row.len() <= assigned_col_idx {
+        // 出力先の列まで行を広げておく
+        while new_row.len() <= max_col_idx {
             new_row.push(String::new());
         }
 
         // 部品型番を取得
         let part_no = parse.get_part_no(row_idx);
         if !part_no.is_empty() {
-            let part_lower = part_no.to_lowercase();
-
             // 例外マスタでチェック
-            if let Some(output_name) = exception_map.get(&part_lower) {
+            if let Some(output_name) = exception_map.get(&normalizer.normalize(&part_no)) {
                 new_row[assigned_col_idx] = output_name.clone();
+                report.matched_by_exception += 1;
                 new_rows.push(new_row);
                 continue;
             }
 
             // ルールマスタでチェック
-            if let Some(output_name) = find_matching_ipc_name(parse, row_idx, &ipc_rules) {
+            if let Some((output_name, outputs)) =
+                find_matching_rule(parse, row_idx, &compiled_rules, &header_index)
+            {
                 new_row[assigned_col_idx] = output_name;
+                for (key, value) in outputs {
+                    let key = key.trim();
+                    let col_idx = if key == ASSIGNED_NAME_KEY {
+                        Some(assigned_col_idx)
+                    } else {
+                        output_columns.get(key).copied()
+                    };
+                    if let Some(col_idx) = col_idx {
+                        new_row[col_idx] = value;
+                    }
+                }
+                report.matched_by_rule += 1;
+                new_rows.push(new_row);
+                continue;
             }
         }
 
+        report.unmatched += 1;
+        if !part_no.is_empty() && seen_unmatched.insert(part_no.clone()) {
+            report.unmatched_parts.push(part_no);
+        }
         new_rows.push(new_row);
     }
 
+    progress.tick(parse.rows.len())?;
+
     // ------------------------------------------------------------------------
     // ステップ4: 結果のParseResultを作成
     // ------------------------------------------------------------------------
 
-    Ok(ParseResult {
+    let OutputColumns {
+        column_roles,
+        headers,
+        column_order,
+        ..
+    } = columns;
+    let mut result = ParseResult {
         rows: new_rows,
         column_roles,
-        column_order: parse.column_order.clone(),
+        column_order,
         #[allow(deprecated)]
         guessed_columns: HashMap::new(),
         #[allow(deprecated)]
@@ -116,33 +197,420 @@ pub fn apply_ipc_names(
         columns: parse.columns.clone(),
         row_numbers: parse.row_numbers.clone(),
         structured_errors: parse.structured_errors.clone(),
-    })
+        validation_summary: parse.validation_summary.clone(),
+        header_row_index: parse.header_row_index,
+        data_start_index: parse.data_start_index,
+        cell_styles: parse.cell_styles.clone(),
+        eco_template: parse.eco_template.clone(),
+        original_rows: parse.original_rows.clone(),
+    };
+    // 列の追加・行の拡張に合わせて列メタデータを揃える
+    result.rebuild_columns_from_headers();
+
+    Ok(IpcApplyResult { result, report })
+}
+
+/// 出力先の列の構成（列の追加に合わせて役割・ヘッダー・表示順序を更新する）
+struct OutputColumns {
+    column_roles: HashMap<String, Vec<String>>,
+    headers: Vec<String>,
+    column_order: Vec<String>,
+    /// 次に追加する列のインデックス
+    next_idx: usize,
+    /// 次に追加する列の表示順序での位置
+    insert_at: Option<usize>,
+}
+
+impl OutputColumns {
+    /// 役割名（なければヘッダー名）が `key` の列を探し、なければ末尾に追加してインデックスを返す
+    ///
+    /// ヘッダー名で見つけた既存の列には役割を付けない。追加した列は役割 `key` を持ち、表示順序は部品型番列の直後
+    /// （部品型番列がなければ末尾）に、追加した順で並べる
+    fn ensure(&mut self, key: &str) -> usize {
+        if let Some(col_ids) = self.column_roles.get(key) {
+            return col_ids
+                .first()
+                .and_then(|col_id| col_id.strip_prefix("col-"))
+                .and_then(|idx| idx.parse().ok())
+                .unwrap_or(self.headers.len());
+        }
+        if let Some(idx) = self.headers.iter().position(|header| header.trim() == key) {
+            return idx;
+        }
+
+        // 新しい列を末尾に追加（行データがヘッダーより長い場合も既存の列と重ならない位置）
+        let idx = self.next_idx;
+        self.next_idx += 1;
+        let col_id = format!("col-{}", idx);
+
+        self.headers.resize(idx, String::new());
+        self.headers.push(key.to_string());
+
+        let insert_at = self.insert_at.unwrap_or_else(|| {
+            self.column_roles
+                .get("part_no")
+                .and_then(|part_cols| {
+                    self.column_order
+                        .iter()
+                        .rposition(|col_id| part_cols.contains(col_id))
+                })
+                .map(|pos| pos + 1)
+                .unwrap_or(self.column_order.len())
+        });
+        self.column_order.insert(insert_at, col_id.clone());
+        self.insert_at = Some(insert_at + 1);
+
+        self.column_roles.insert(key.to_string(), vec![col_id]);
+        idx
+    }
 }
 
-/// ルールマスタから一致するIPC登録名を検索
+/// ルールマスタから一致するルールを検索
 ///
 /// # 引数
 /// * `parse` - BOMデータ
 /// * `row_idx` - 行インデックス
-/// * `rules` - ルールのリスト
+/// * `rules` - 解析済みのルールのリスト
+/// * `headers` - 元のBOMデータの列名の索引
 ///
 /// # 戻り値
-/// 一致したルールの登録名（見つからない場合はNone）
-fn find_matching_ipc_name(
+/// 一致したルールの登録名と追加出力（正規表現のグループは置き換え済み、見つからない場合はNone）
+fn find_matching_rule<'a>(
     parse: &ParseResult,
     row_idx: usize,
-    rules: &[IpcMasterRule],
-) -> Option<String> {
+    rules: &[CompiledRule<'a>],
+    headers: &HeaderIndex,
+) -> Option<(String, Vec<(&'a str, String)>)> {
     // ルールは逆順（後ろから）でチェック（優先度の高いものを後で定義する想定）
-    for rule in rules.iter().rev() {
-        if rule
-            .conditions
+    rules
+        .iter()
+        .rev()
+        .find_map(|rule| rule.evaluate(parse, row_idx, headers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MasterCondition;
+
+    fn sample_parse() -> ParseResult {
+        let headers: Vec<String> = ["Ref", "Part", "Maker"]
             .iter()
-            .all(|condition| condition_matches(parse, row_idx, condition))
-        {
-            return Some(rule.output_name.clone());
+            .map(|name| name.to_string())
+            .collect();
+        ParseResult::builder()
+            .headers(headers)
+            .rows(vec![
+                vec!["C1".to_string(), "GRM155".to_string(), "Murata".to_string()],
+                vec!["R1".to_string(), "RC0402".to_string(), "Yageo".to_string()],
+            ])
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .role("manufacturer", &[2])
+            .row_numbers(vec![2, 3])
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build()
+    }
+
+    #[test]
+    fn test_apply_ipc_names_keeps_columns_in_sync() {
+        let exceptions = vec![ExceptionMasterEntry {
+            part_no: "grm155".to_string(),
+            output_name: "CAP_0402".to_string(),
+        }];
+        let result = apply_ipc_names(&sample_parse(), vec![], exceptions).unwrap();
+
+        assert_eq!(result.headers.len(), result.columns.len());
+        assert_eq!(result.columns[3].id, "col-3");
+        assert_eq!(result.columns[3].name, ASSIGNED_NAME_KEY);
+        assert_eq!(
+            result.column_order,
+            vec!["col-0", "col-1", "col-3", "col-2"]
+        );
+        assert_eq!(result.get_values(0, ASSIGNED_NAME_KEY), vec!["CAP_0402"]);
+
+        // 再適用しても列は増えない
+        let again = apply_ipc_names(&result, vec![], vec![]).unwrap();
+        assert_eq!(again.headers.len(), result.headers.len());
+        assert_eq!(again.column_order, result.column_order);
+    }
+
+    #[test]
+    fn test_apply_ipc_names_with_multiple_outputs() {
+        let condition = |value: &str| MasterCondition {
+            field: "part_no".to_string(),
+            match_type: "starts_with".to_string(),
+            value: value.to_string(),
+            negate: false,
+        };
+        let rules = vec![
+            IpcMasterRule {
+                rule_name: "capacitor".to_string(),
+                conditions: vec![condition("GRM")],
+                output_name: "CAP_0402".to_string(),
+                outputs: HashMap::from([
+                    ("category".to_string(), "Capacitor".to_string()),
+                    ("Maker".to_string(), "Murata".to_string()),
+                ]),
+            },
+            IpcMasterRule {
+                rule_name: "resistor".to_string(),
+                conditions: vec![condition("RC")],
+                output_name: "RES_0402".to_string(),
+                outputs: HashMap::from([
+                    ("category".to_string(), "Resistor".to_string()),
+                    ("package".to_string(), "0402".to_string()),
+                ]),
+            },
+        ];
+
+        let result = apply_ipc_names(&sample_parse(), rules.clone(), vec![]).unwrap();
+        assert!(result.columns_consistent());
+        assert_eq!(
+            result.headers[3..],
+            [ASSIGNED_NAME_KEY, "category", "package"]
+        );
+        assert_eq!(
+            result.column_order,
+            vec!["col-0", "col-1", "col-3", "col-4", "col-5", "col-2"]
+        );
+        assert_eq!(result.column_roles["category"], vec!["col-4"]);
+        // ヘッダー名で見つけた既存の列は役割を変えない
+        assert!(!result.column_roles.contains_key("Maker"));
+        assert_eq!(result.column_roles, {
+            let mut roles = sample_parse().column_roles;
+            for (role, col_id) in [
+                (ASSIGNED_NAME_KEY, "col-3"),
+                ("category", "col-4"),
+                ("package", "col-5"),
+            ] {
+                roles.insert(role.to_string(), vec![col_id.to_string()]);
+            }
+            roles
+        });
+
+        assert_eq!(result.rows[0][2..], ["Murata", "CAP_0402", "Capacitor", ""]);
+        assert_eq!(
+            result.rows[1][2..],
+            ["Yageo", "RES_0402", "Resistor", "0402"]
+        );
+
+        // 再適用しても列は増えない
+        let again = apply_ipc_names(&result, rules, vec![]).unwrap();
+        assert_eq!(again.headers, result.headers);
+        assert_eq!(again.rows, result.rows);
+    }
+
+    #[test]
+    fn test_apply_ipc_names_with_regex_captures() {
+        let rules = vec![IpcMasterRule {
+            rule_name: "murata".to_string(),
+            conditions: vec![
+                MasterCondition {
+                    field: "part_no".to_string(),
+                    match_type: "regex".to_string(),
+                    value: r"^GRM(?P<size>\d{2})(\d)".to_string(),
+                    negate: false,
+                },
+                MasterCondition {
+                    field: "manufacturer".to_string(),
+                    match_type: "equals".to_string(),
+                    value: "murata".to_string(),
+                    negate: false,
+                },
+            ],
+            output_name: "CAP_${size}_${2}$$".to_string(),
+            outputs: HashMap::from([("package".to_string(), "${size}${2}".to_string())]),
+        }];
+
+        let result = apply_ipc_names(&sample_parse(), rules.clone(), vec![]).unwrap();
+        assert_eq!(result.get_values(0, ASSIGNED_NAME_KEY), vec!["CAP_15_5$"]);
+        assert_eq!(result.get_values(0, "package"), vec!["155"]);
+        // 条件に一致しない行は空のまま
+        assert!(result.get_values(1, ASSIGNED_NAME_KEY).is_empty());
+
+        let mut unknown = rules;
+        unknown[0].output_name = "CAP_${pitch}".to_string();
+        let err = apply_ipc_names(&sample_parse(), unknown, vec![]).unwrap_err();
+        assert!(err.message.contains("${pitch}"));
+    }
+
+    #[test]
+    fn test_apply_ipc_names_with_report() {
+        let mut parse = sample_parse();
+        parse.rows.extend([
+            vec!["R2".to_string(), "RC0402".to_string(), "Yageo".to_string()],
+            vec!["U1".to_string(), "74HC08".to_string(), "TI".to_string()],
+            vec!["TP1".to_string(), String::new(), String::new()],
+        ]);
+        parse.row_numbers.extend([4, 5, 6]);
+        let rules = vec![IpcMasterRule {
+            rule_name: "ti".to_string(),
+            conditions: vec![MasterCondition {
+                field: "manufacturer".to_string(),
+                match_type: "equals".to_string(),
+                value: "TI".to_string(),
+                negate: false,
+            }],
+            output_name: "LOGIC".to_string(),
+            outputs: HashMap::new(),
+        }];
+        let exceptions = vec![ExceptionMasterEntry {
+            part_no: "GRM155".to_string(),
+            output_name: "CAP_0402".to_string(),
+        }];
+
+        let applied =
+            apply_ipc_names_with_report(&parse, rules, exceptions, &OperationContext::default())
+                .unwrap();
+        assert_eq!(
+            applied.report,
+            IpcApplyReport {
+                matched_by_rule: 1,
+                matched_by_exception: 1,
+                unmatched: 3,
+                unmatched_parts: vec!["RC0402".to_string()],
+            }
+        );
+        assert_eq!(
+            applied.result.get_values(3, ASSIGNED_NAME_KEY),
+            vec!["LOGIC"]
+        );
+    }
+
+    #[test]
+    fn test_apply_ipc_names_custom_field_large_rule_set() {
+        let mut parse = sample_parse();
+        // 同じ列名の列が複数ある場合は、列順で最初の空でない値で判定する
+        parse
+            .headers
+            .extend(["Package".to_string(), "PACKAGE".to_string()]);
+        parse.rebuild_columns_from_headers();
+        parse.rows = (0..500)
+            .map(|idx| {
+                let (first, second) = if idx % 2 == 0 {
+                    // 後の列の値は使わない（使うと全ての行が rule0 に一致する）
+                    (format!("size{}", idx % 250), "SIZE0".to_string())
+                } else {
+                    (String::new(), format!("SIZE{}", idx % 250))
+                };
+                vec![
+                    format!("C{idx}"),
+                    format!("PART{idx}"),
+                    String::new(),
+                    first,
+                    second,
+                ]
+            })
+            .collect();
+        parse.row_numbers = (1..=500).collect();
+
+        let rules: Vec<IpcMasterRule> = (0..200)
+            .map(|idx| IpcMasterRule {
+                rule_name: format!("rule{idx}"),
+                conditions: vec![MasterCondition {
+                    field: " package ".to_string(),
+                    match_type: "equals".to_string(),
+                    value: format!("SIZE{idx}"),
+                    negate: false,
+                }],
+                output_name: format!("NAME{idx}"),
+                outputs: HashMap::new(),
+            })
+            .collect();
+
+        let result = apply_ipc_names(&parse, rules, vec![]).unwrap();
+        for row_idx in 0..parse.rows.len() {
+            let size = row_idx % 250;
+            let expected: Vec<String> = if size < 200 {
+                vec![format!("NAME{size}")]
+            } else {
+                vec![]
+            };
+            assert_eq!(result.get_values(row_idx, ASSIGNED_NAME_KEY), expected);
         }
     }
 
-    None
+    #[test]
+    fn test_apply_ipc_names_with_negated_condition() {
+        let mut parse = sample_parse();
+        parse.rows.push(vec![
+            "C2".to_string(),
+            "CL05B104".to_string(),
+            "Samsung".to_string(),
+        ]);
+        parse
+            .rows
+            .push(vec!["C3".to_string(), "GRM155".to_string(), String::new()]);
+        parse.row_numbers.extend([4, 5]);
+
+        // 部品型番が "1" を含むが "0402" を含まず、メーカーが Murata ではない
+        let rules = vec![IpcMasterRule {
+            rule_name: "not_murata".to_string(),
+            conditions: vec![
+                MasterCondition {
+                    field: "part_no".to_string(),
+                    match_type: "contains".to_string(),
+                    value: "1".to_string(),
+                    negate: false,
+                },
+                MasterCondition {
+                    field: "part_no".to_string(),
+                    match_type: "contains".to_string(),
+                    value: "0402".to_string(),
+                    negate: true,
+                },
+                MasterCondition {
+                    field: "manufacturer".to_string(),
+                    match_type: "equals".to_string(),
+                    value: "murata".to_string(),
+                    negate: true,
+                },
+            ],
+            output_name: "CAP_OTHER".to_string(),
+            outputs: HashMap::new(),
+        }];
+
+        let result = apply_ipc_names(&parse, rules, vec![]).unwrap();
+        let names: Vec<Vec<String>> = (0..result.rows.len())
+            .map(|idx| result.get_values(idx, ASSIGNED_NAME_KEY))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                vec![],
+                vec![],
+                vec!["CAP_OTHER".to_string()],
+                // メーカーが空の行は「Murataではない」に一致する
+                vec!["CAP_OTHER".to_string()],
+            ]
+        );
+
+        let json = r#"{"field": "part_no", "matchType": "contains", "value": "X"}"#;
+        let condition: MasterCondition = serde_json::from_str(json).unwrap();
+        assert!(!condition.negate);
+    }
+
+    #[test]
+    fn test_apply_ipc_names_with_negated_regex_condition() {
+        let rules = vec![IpcMasterRule {
+            rule_name: "not_grm".to_string(),
+            conditions: vec![MasterCondition {
+                field: "part_no".to_string(),
+                match_type: "regex".to_string(),
+                value: "^grm".to_string(),
+                negate: true,
+            }],
+            output_name: "OTHER".to_string(),
+            outputs: HashMap::new(),
+        }];
+
+        let result = apply_ipc_names(&sample_parse(), rules, vec![]).unwrap();
+        assert_eq!(
+            result.get_values(0, ASSIGNED_NAME_KEY),
+            Vec::<String>::new()
+        );
+        assert_eq!(result.get_values(1, ASSIGNED_NAME_KEY), vec!["OTHER"]);
+    }
 }
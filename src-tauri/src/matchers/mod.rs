@@ -1,4 +1,5 @@
 pub mod helpers;
 pub mod ipc;
+pub mod template;
 
 pub const ASSIGNED_NAME_KEY: &str = "登録名";
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::models::{AppError, IpcMasterRule, ParseResult};
+
+use super::helpers::{build_regex, condition_matches_with_index, get_field_values, HeaderIndex};
+
+/// 正規表現の条件で使う一致方法
+const REGEX_MATCH_TYPE: &str = "regex";
+
+/// 登録名テンプレートの構成要素
+#[derive(Debug, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    /// 番号のグループ参照（`${1}`）
+    Index(usize),
+    /// 名前付きグループ参照（`${size}`）
+    Name(String),
+}
+
+/// 登録名テンプレート（`${1}`・`${name}` を条件の正規表現のグループで置き換え、`$$` は `$`）
+#[derive(Debug, PartialEq)]
+pub struct OutputTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl OutputTemplate {
+    /// テンプレートを解析（`${` が閉じられていない場合はエラー）
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(pos) = rest.find('$') {
+            literal.push_str(&rest[..pos]);
+            let after = &rest[pos + 1..];
+            if let Some(after) = after.strip_prefix('$') {
+                literal.push('$');
+                rest = after;
+            } else if let Some(after) = after.strip_prefix('{') {
+                let end = after
+                    .find('}')
+                    .ok_or_else(|| format!("'${{' が閉じられていません: {template}"))?;
+                let group = after[..end].trim();
+                if group.is_empty() {
+                    return Err(format!("グループ名が空の参照があります: {template}"));
+                }
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(match group.parse::<usize>() {
+                    Ok(index) => TemplatePart::Index(index),
+                    Err(_) => TemplatePart::Name(group.to_string()),
+                });
+                rest = &after[end + 1..];
+            } else {
+                // "$" の後に "{" も "$" もない場合はそのまま
+                literal.push('$');
+                rest = after;
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// 参照しているグループのうち、どの正規表現にもないものを返す
+    fn unknown_group(&self, regexes: &[Regex]) -> Option<String> {
+        self.parts.iter().find_map(|part| match part {
+            TemplatePart::Index(index)
+                if !regexes.iter().any(|regex| regex.captures_len() > *index) =>
+            {
+                Some(format!("${{{index}}}"))
+            }
+            TemplatePart::Name(name)
+                if !regexes
+                    .iter()
+                    .any(|regex| regex.capture_names().flatten().any(|n| n == name)) =>
+            {
+                Some(format!("${{{name}}}"))
+            }
+            _ => None,
+        })
+    }
+
+    /// グループの値で置き換えた文字列（一致しなかったグループは空文字）
+    ///
+    /// 同じグループを持つ正規表現が複数ある場合は、条件の順で最初のものを使う
+    fn render(&self, captures: &[CaptureSet]) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(text) => text.as_str(),
+                TemplatePart::Index(index) => captures
+                    .iter()
+                    .find_map(|set| set.groups.get(*index))
+                    .and_then(|value| value.as_deref())
+                    .unwrap_or(""),
+                TemplatePart::Name(name) => captures
+                    .iter()
+                    .find_map(|set| set.names.get(name))
+                    .and_then(|value| value.as_deref())
+                    .unwrap_or(""),
+            })
+            .collect()
+    }
+}
+
+/// 1つの正規表現の条件で取り出したグループの値
+struct CaptureSet {
+    groups: Vec<Option<String>>,
+    names: HashMap<String, Option<String>>,
+}
+
+/// 正規表現・テンプレートを解析済みのルール
+pub struct CompiledRule<'a> {
+    pub rule: &'a IpcMasterRule,
+    /// 条件ごとの正規表現（一致方法が "regex" の条件のみ。否定の条件も含む）
+    regexes: Vec<Option<Regex>>,
+    output_name: OutputTemplate,
+    outputs: Vec<(&'a str, OutputTemplate)>,
+}
+
+impl<'a> CompiledRule<'a> {
+    /// ルールの正規表現とテンプレートを解析
+    ///
+    /// 正規表現が不正な場合や、テンプレートが条件にないグループを参照している場合はエラー
+    pub fn compile(rule: &'a IpcMasterRule) -> Result<Self, AppError> {
+        let name = rule.rule_name.trim();
+        let regexes = rule
+            .conditions
+            .iter()
+            .map(|condition| {
+                if !is_regex_condition(&condition.match_type) {
+                    return Ok(None);
+                }
+                build_regex(&condition.value).map(Some).map_err(|err| {
+                    AppError::validation(format!(
+                        "ルール'{name}'の正規表現 '{}' が不正です: {err}",
+                        condition.value
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 否定の条件はグループを取り出さないため、テンプレートが参照できるのは否定でない条件のみ
+        let patterns: Vec<Regex> = rule
+            .conditions
+            .iter()
+            .zip(&regexes)
+            .filter(|(condition, _)| !condition.negate)
+            .filter_map(|(_, regex)| regex.clone())
+            .collect();
+        let parse_template = |template: &str| {
+            let parsed = OutputTemplate::parse(template).map_err(|message| {
+                AppError::validation(format!("ルール'{name}'の登録名: {message}"))
+            })?;
+            if let Some(group) = parsed.unknown_group(&patterns) {
+                return Err(AppError::validation(format!(
+                    "ルール'{name}'の登録名 '{template}' の {group} に対応するグループが条件の正規表現にありません。"
+                )));
+            }
+            Ok(parsed)
+        };
+
+        let output_name = parse_template(&rule.output_name)?;
+        let mut outputs = Vec::with_capacity(rule.outputs.len());
+        for (key, value) in &rule.outputs {
+            outputs.push((key.as_str(), parse_template(value)?));
+        }
+
+        Ok(Self {
+            rule,
+            regexes,
+            output_name,
+            outputs,
+        })
+    }
+
+    /// 行が全ての条件に一致する場合、登録名と追加出力（出力先 → 値）を返す
+    pub fn evaluate(
+        &self,
+        parse: &ParseResult,
+        row_idx: usize,
+        headers: &HeaderIndex,
+    ) -> Option<(String, Vec<(&'a str, String)>)> {
+        let mut captures = Vec::new();
+        for (condition, regex) in self.rule.conditions.iter().zip(&self.regexes) {
+            match regex {
+                // 否定の条件は、どの列も一致しない場合（値が空の場合を含む）にマッチ
+                Some(regex) if condition.negate => {
+                    let values = get_field_values(parse, row_idx, &condition.field, headers);
+                    if values.iter().any(|value| regex.is_match(value)) {
+                        return None;
+                    }
+                }
+                Some(regex) => {
+                    // 同じ役割の列が複数ある場合は、最初に一致した列の値からグループを取り出す
+                    let values = get_field_values(parse, row_idx, &condition.field, headers);
+                    let set = values.iter().find_map(|value| {
+                        let caps = regex.captures(value)?;
+                        Some(CaptureSet {
+                            groups: caps
+                                .iter()
+                                .map(|group| group.map(|m| m.as_str().to_string()))
+                                .collect(),
+                            names: regex
+                                .capture_names()
+                                .flatten()
+                                .map(|name| {
+                                    let value = caps.name(name).map(|m| m.as_str().to_string());
+                                    (name.to_string(), value)
+                                })
+                                .collect(),
+                        })
+                    })?;
+                    captures.push(set);
+                }
+                None => {
+                    if !condition_matches_with_index(parse, row_idx, condition, headers) {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|(key, template)| (*key, template.render(&captures)))
+            .collect();
+        Some((self.output_name.render(&captures), outputs))
+    }
+}
+
+fn is_regex_condition(match_type: &str) -> bool {
+    match_type.trim().eq_ignore_ascii_case(REGEX_MATCH_TYPE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_template() {
+        let template = OutputTemplate::parse("CAP_${size}_$${1}_${1}$").unwrap();
+        assert_eq!(
+            template.parts,
+            vec![
+                TemplatePart::Literal("CAP_".to_string()),
+                TemplatePart::Name("size".to_string()),
+                TemplatePart::Literal("_${1}_".to_string()),
+                TemplatePart::Index(1),
+                TemplatePart::Literal("$".to_string()),
+            ]
+        );
+        assert!(OutputTemplate::parse("CAP_${1").is_err());
+        assert!(OutputTemplate::parse("CAP_${}").is_err());
+    }
+
+    #[test]
+    fn test_compile_negated_regex_condition() {
+        let condition = |value: &str, negate: bool| crate::models::MasterCondition {
+            field: "part_no".to_string(),
+            match_type: "regex".to_string(),
+            value: value.to_string(),
+            negate,
+        };
+        let rule = |conditions, output_name: &str| IpcMasterRule {
+            rule_name: "cap".to_string(),
+            conditions,
+            output_name: output_name.to_string(),
+            outputs: HashMap::new(),
+        };
+
+        // 否定の条件の正規表現も解析済みで持つ（行ごとに作り直さない）
+        let negated = rule(
+            vec![
+                condition("^GRM(?<size>\\d+)", false),
+                condition("X7R", true),
+            ],
+            "CAP_${size}",
+        );
+        let compiled = CompiledRule::compile(&negated).unwrap();
+        assert!(compiled.regexes.iter().all(Option::is_some));
+
+        // 否定の条件のグループはテンプレートから参照できない
+        let group_in_negated = rule(vec![condition("^GRM(?<size>\\d+)", true)], "CAP_${size}");
+        assert!(CompiledRule::compile(&group_in_negated).is_err());
+
+        // 否定の条件でも不正な正規表現は解析時にエラー
+        let invalid = rule(vec![condition("(", true)], "CAP");
+        assert!(CompiledRule::compile(&invalid).is_err());
+    }
+}
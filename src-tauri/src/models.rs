@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
+use crate::utils::text::is_dnp_marker;
+
 // ============================================================================
 // 列メタデータ
 // ============================================================================
@@ -94,10 +97,72 @@ pub struct ParseResult {
     pub columns: Vec<ColumnMeta>,
 
     /// 元ファイルでの行番号（1始まり）
+    ///
+    /// Reference展開・分割やマージで作られた行は元の行の番号を引き継ぐ
     pub row_numbers: Vec<usize>,
 
     /// 構造化エラー情報
     pub structured_errors: Option<Vec<ParseError>>,
+
+    /// 検証結果の集計（CSV/Excel読み込み時のみ）
+    #[serde(default)]
+    pub validation_summary: Option<ValidationSummary>,
+
+    /// 検出したヘッダー行の位置（元ファイルでの行インデックス、0始まり）
+    ///
+    /// ヘッダー行がない場合やCAD形式の場合はNone
+    #[serde(default)]
+    pub header_row_index: Option<usize>,
+
+    /// データ開始行の位置（元ファイルでの行インデックス、0始まり）
+    ///
+    /// CAD形式の場合は0
+    #[serde(default)]
+    pub data_start_index: usize,
+
+    /// セルの書式（`rows` と同じ行・列の並び）
+    ///
+    /// Excel読み込み時のみ設定される。CSV・CAD形式やスタイル情報を
+    /// 読み込めなかった場合はNone
+    #[serde(default)]
+    pub cell_styles: Option<Vec<Vec<CellStyle>>>,
+
+    /// 読み込んだPADS-ECOファイルの部品データ以外の行（ECO出力時に再現する）
+    ///
+    /// PADS-ECO形式の読み込み時のみ設定される
+    #[serde(default)]
+    pub eco_template: Option<EcoTemplate>,
+
+    /// 読み込み時のセルの値（`rows` と同じ行・列の並び、セル単位で元に戻す場合に使用）
+    ///
+    /// `ParseOptions::keep_original_rows` を指定して読み込んだ場合のみ設定される。
+    /// クレンジングなど行を増減しない処理では引き継ぎ、Reference展開など
+    /// 行の並びが変わる処理ではクリアする
+    #[serde(default)]
+    pub original_rows: Option<Vec<Vec<String>>>,
+}
+
+/// PADS-ECO出力のテンプレート
+///
+/// 部品データ（`Ref Part_No` の行）の前後に出力する行。
+/// `*PADS-ECO*` のバージョン指定や `*NET*` セクションなどをそのまま保持する。
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct EcoTemplate {
+    /// 部品データの前の行（`*PART*` 行まで）
+    pub header: Vec<String>,
+
+    /// 部品データの後の行（`*END*` 行まで）
+    pub footer: Vec<String>,
+}
+
+/// セルの書式情報
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct CellStyle {
+    /// 塗りつぶし色（"#rrggbb"、塗りつぶしなしの場合はNone）
+    pub fill_color: Option<String>,
+
+    /// 取り消し線の有無
+    pub strikethrough: bool,
 }
 
 impl ParseResult {
@@ -115,7 +180,7 @@ impl ParseResult {
     /// その役割を持つ全ての列の値（空文字は除外、トリム済み）
     ///
     /// # 例
-    /// ```rust
+    /// ```ignore
     /// let refs = parse_result.get_values(0, "ref");
     /// // → ["C1", "C2", "C3"] （3つのReference列がある場合）
     /// ```
@@ -154,7 +219,7 @@ impl ParseResult {
     /// Reference値の文字列（複数ある場合は ", " で結合）
     ///
     /// # 例
-    /// ```rust
+    /// ```ignore
     /// let ref_value = parse_result.get_ref(0);
     /// // → "C1, C2, C3" または "C1" （単一の場合）
     /// ```
@@ -192,6 +257,43 @@ impl ParseResult {
             .unwrap_or_default()
     }
 
+    /// 行を対応付けるためのキーを取得
+    ///
+    /// 役割ごとの値（複数列の場合は ", " で結合）を指定順に連結する。
+    /// すべての役割の値が空の場合は空文字を返す
+    ///
+    /// # 引数
+    /// * `row_index` - 行インデックス
+    /// * `roles` - キーに使用する役割名（順序どおりに連結）
+    pub fn get_match_key(&self, row_index: usize, roles: &[String]) -> String {
+        let parts: Vec<String> = roles
+            .iter()
+            .map(|role| self.get_values(row_index, role).join(", "))
+            .collect();
+        if parts.iter().all(|part| part.trim().is_empty()) {
+            return String::new();
+        }
+        parts.join(MATCH_KEY_SEPARATOR)
+    }
+
+    /// コメント値を取得（複数列の場合は空でない値を " / " で結合）
+    pub fn get_comment(&self, row_index: usize) -> String {
+        self.get_values(row_index, "comment")
+            .into_iter()
+            .filter(|value| !value.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// 未実装（DNP）の行か（役割 "dnp" の列のいずれかが "DNP"・"未実装"・"x" などの場合）
+    ///
+    /// 役割 "dnp" の列がない場合は常に false
+    pub fn is_dnp(&self, row_index: usize) -> bool {
+        self.get_values(row_index, "dnp")
+            .iter()
+            .any(|value| is_dnp_marker(value))
+    }
+
     // ========================================================================
     // 列情報ヘルパー
     // ========================================================================
@@ -226,6 +328,325 @@ impl ParseResult {
             .map(|ids| ids.contains(&col_id.to_string()))
             .unwrap_or(false)
     }
+
+    /// 行の元ファイルでの行番号（行番号がない場合は1始まりの行位置）
+    ///
+    /// 展開・分割・マージで作られた行は元の行の番号を引き継ぐため、
+    /// 複数の行が同じ番号を持つことがある
+    pub fn source_row_number(&self, row_idx: usize) -> usize {
+        self.row_numbers
+            .get(row_idx)
+            .copied()
+            .unwrap_or(row_idx + 1)
+    }
+
+    /// 列数（ヘッダー・列メタデータ・各行のうち最大のもの）
+    pub fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.len())
+            .chain([self.headers.len(), self.columns.len()])
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// ヘッダーから列メタデータを再作成し、列数を揃える
+    ///
+    /// 列の追加などで `headers`・`columns`・行の長さがずれた場合に呼び出す。
+    /// ヘッダー名が空の列は既存の列メタデータの名前、なければ "Column N" とする
+    pub fn rebuild_columns_from_headers(&mut self) {
+        let column_count = self.column_count();
+
+        let headers: Vec<String> = (0..column_count)
+            .map(|idx| {
+                self.headers
+                    .get(idx)
+                    .filter(|name| !name.trim().is_empty())
+                    .or_else(|| {
+                        self.columns
+                            .get(idx)
+                            .map(|column| &column.name)
+                            .filter(|name| !name.trim().is_empty())
+                    })
+                    .cloned()
+                    .unwrap_or_else(|| format!("Column {}", idx + 1))
+            })
+            .collect();
+
+        self.columns = headers
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| ColumnMeta {
+                id: format!("col-{}", idx),
+                name: name.clone(),
+            })
+            .collect();
+        self.headers = headers;
+
+        debug_assert!(self.columns_consistent());
+    }
+
+    // ========================================================================
+    // 作成ヘルパー
+    // ========================================================================
+
+    /// `template` の列構造を引き継ぎ、行だけを置き換えたParseResultを作成
+    ///
+    /// 行の追加・削除をしない処理（クレンジングなど）向け。
+    /// 行番号・書式・読み込み時の値は `template` のものを引き継ぎ、エラー・検証結果はクリアする
+    pub fn with_rows(rows: Vec<Vec<String>>, template: &ParseResult) -> ParseResult {
+        ParseResult::builder()
+            .schema_from(template)
+            .rows(rows)
+            .row_numbers(template.row_numbers.clone())
+            .cell_styles(template.cell_styles.clone())
+            .original_rows(template.original_rows.clone())
+            .build()
+    }
+
+    /// ParseResultのビルダーを作成
+    pub fn builder() -> ParseResultBuilder {
+        ParseResultBuilder::default()
+    }
+
+    /// `headers` と `columns` の長さ・列IDが一致し、各行が列数を超えないか
+    pub fn columns_consistent(&self) -> bool {
+        self.headers.len() == self.columns.len()
+            && self
+                .columns
+                .iter()
+                .enumerate()
+                .all(|(idx, column)| column.id == format!("col-{}", idx))
+            && self.rows.iter().all(|row| row.len() <= self.headers.len())
+    }
+
+    /// 指定した列（"col-2" など）に関する構造化エラー
+    ///
+    /// 列IDが不正な場合や `structured_errors` がない場合は空
+    pub fn errors_for_column(&self, col_id: &str) -> Vec<&ParseError> {
+        let Some(col_idx) = col_id
+            .strip_prefix("col-")
+            .and_then(|idx| idx.parse::<usize>().ok())
+        else {
+            return Vec::new();
+        };
+        self.structured_errors
+            .iter()
+            .flatten()
+            .filter(|error| error.column == Some(col_idx))
+            .collect()
+    }
+
+    /// 構造化エラーの重要度ごとの件数
+    ///
+    /// # 戻り値
+    /// (error, warning, info) の件数（`structured_errors` がない場合は全て0）
+    pub fn counts_by_severity(&self) -> (usize, usize, usize) {
+        self.structured_errors.iter().flatten().fold(
+            (0, 0, 0),
+            |(errors, warnings, infos), error| match error.severity.as_str() {
+                "error" => (errors + 1, warnings, infos),
+                "warning" => (errors, warnings + 1, infos),
+                "info" => (errors, warnings, infos + 1),
+                _ => (errors, warnings, infos),
+            },
+        )
+    }
+
+    /// BOMデータの内容を表すハッシュ値（SHA-256の16進文字列）
+    ///
+    /// ヘッダー・行データ（前後の空白と末尾の空欄を除く）・列の役割から計算し、
+    /// エラー・行番号・書式などの付随情報は含めない。役割は名前順に並べるため、
+    /// `column_roles` の挿入順や実行ごとに変わらない。
+    /// 保存済みのBOMと内容が同じかどうかの確認に使う
+    pub fn fingerprint(&self) -> String {
+        #[derive(Serialize)]
+        struct Canonical<'a> {
+            headers: Vec<&'a str>,
+            rows: Vec<Vec<&'a str>>,
+            roles: BTreeMap<&'a str, &'a [String]>,
+        }
+
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut cells: Vec<&str> = row.iter().map(|cell| cell.trim()).collect();
+                while cells.last().is_some_and(|cell| cell.is_empty()) {
+                    cells.pop();
+                }
+                cells
+            })
+            .collect();
+        let canonical = Canonical {
+            headers: self.headers.iter().map(|header| header.trim()).collect(),
+            rows,
+            roles: self
+                .column_roles
+                .iter()
+                .filter(|(_, col_ids)| !col_ids.is_empty())
+                .map(|(role, col_ids)| (role.as_str(), col_ids.as_slice()))
+                .collect(),
+        };
+
+        // 文字列・配列・BTreeMap のみのため直列化は失敗しない
+        let bytes = serde_json::to_vec(&canonical).unwrap_or_default();
+        format!("{:x}", Sha256::digest(bytes))
+    }
+}
+
+/// `ParseResult` のビルダー
+///
+/// 指定しなかった項目は空（エラー・検証結果・書式なし）になる。
+/// 非推奨の `guessed_columns`・`guessed_roles` は常に空で作成する
+pub struct ParseResultBuilder {
+    result: ParseResult,
+}
+
+impl Default for ParseResultBuilder {
+    fn default() -> Self {
+        Self {
+            result: ParseResult {
+                rows: vec![],
+                column_roles: HashMap::new(),
+                column_order: vec![],
+                #[allow(deprecated)]
+                guessed_columns: HashMap::new(),
+                #[allow(deprecated)]
+                guessed_roles: HashMap::new(),
+                errors: vec![],
+                headers: vec![],
+                columns: vec![],
+                row_numbers: vec![],
+                structured_errors: None,
+                validation_summary: None,
+                header_row_index: None,
+                data_start_index: 0,
+                cell_styles: None,
+                eco_template: None,
+                original_rows: None,
+            },
+        }
+    }
+}
+
+impl ParseResultBuilder {
+    /// 列構造（役割・表示順序・ヘッダー・列メタデータ）と元ファイルの情報
+    /// （ヘッダー行・データ開始行の位置、ECOテンプレート）を `template` から引き継ぐ
+    pub fn schema_from(mut self, template: &ParseResult) -> Self {
+        self.result.column_roles = template.column_roles.clone();
+        self.result.column_order = template.column_order.clone();
+        self.result.headers = template.headers.clone();
+        self.result.columns = template.columns.clone();
+        self.result.header_row_index = template.header_row_index;
+        self.result.data_start_index = template.data_start_index;
+        self.result.eco_template = template.eco_template.clone();
+        self
+    }
+
+    /// ヘッダーを設定し、列メタデータ（`col-N`）と表示順序をヘッダーの並びで作成する
+    pub fn headers(mut self, headers: Vec<String>) -> Self {
+        self.result.columns = headers
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| ColumnMeta {
+                id: format!("col-{}", idx),
+                name: name.clone(),
+            })
+            .collect();
+        self.result.column_order = (0..headers.len())
+            .map(|idx| format!("col-{}", idx))
+            .collect();
+        self.result.headers = headers;
+        self
+    }
+
+    pub fn column_roles(mut self, column_roles: HashMap<String, Vec<String>>) -> Self {
+        self.result.column_roles = column_roles;
+        self
+    }
+
+    /// 役割に列（列インデックス）を割り当てる
+    pub fn role(mut self, role: &str, col_indices: &[usize]) -> Self {
+        self.result.column_roles.insert(
+            role.to_string(),
+            col_indices
+                .iter()
+                .map(|idx| format!("col-{}", idx))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn rows(mut self, rows: Vec<Vec<String>>) -> Self {
+        self.result.rows = rows;
+        self
+    }
+
+    pub fn errors(mut self, errors: Vec<String>) -> Self {
+        self.result.errors = errors;
+        self
+    }
+
+    pub fn structured_errors(mut self, structured_errors: Option<Vec<ParseError>>) -> Self {
+        self.result.structured_errors = structured_errors;
+        self
+    }
+
+    pub fn row_numbers(mut self, row_numbers: Vec<usize>) -> Self {
+        self.result.row_numbers = row_numbers;
+        self
+    }
+
+    pub fn header_row_index(mut self, header_row_index: Option<usize>) -> Self {
+        self.result.header_row_index = header_row_index;
+        self
+    }
+
+    pub fn data_start_index(mut self, data_start_index: usize) -> Self {
+        self.result.data_start_index = data_start_index;
+        self
+    }
+
+    pub fn cell_styles(mut self, cell_styles: Option<Vec<Vec<CellStyle>>>) -> Self {
+        self.result.cell_styles = cell_styles;
+        self
+    }
+
+    pub fn original_rows(mut self, original_rows: Option<Vec<Vec<String>>>) -> Self {
+        self.result.original_rows = original_rows;
+        self
+    }
+
+    pub fn build(self) -> ParseResult {
+        self.result
+    }
+}
+
+// ============================================================================
+// 正規化ビュー
+// ============================================================================
+
+/// `ParseResult` の1行を役割ごとに平坦化した読み取り専用のビュー
+///
+/// `ParseResult` 自体は保持せず、プラグインやエクスポート処理で
+/// 型付きの値が必要な場合に `normalize_bom` で変換して使用する
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BomRow {
+    /// Reference（複数列の場合は ", " で結合）
+    #[serde(rename = "ref")]
+    pub reference: String,
+
+    /// 部品型番（複数列の場合は最初のもの）
+    pub part_no: String,
+
+    /// Reference・部品型番以外の列（列名 → 値）
+    ///
+    /// 列名が空または重複する場合は列ID（"col-3" など）をキーにする
+    pub attributes: HashMap<String, String>,
+
+    /// 元ファイルでの行番号（1始まり）
+    pub row_number: Option<usize>,
 }
 
 // ============================================================================
@@ -242,6 +663,10 @@ impl ParseResult {
 /// - "added": BOM Bにのみ存在
 /// - "removed": BOM Aにのみ存在
 /// - "modified": 両方に存在するが内容が異なる
+/// - "likely_same": 部品型番の梱包コードのみが異なる（あいまい比較が有効な場合）
+/// - "comment_changed": 両方に存在し、コメント列のみが異なる
+/// - "dnp_changed": 実装・未実装（DNP）が切り替わった
+/// - "rotated" / "moved_xy": 実装位置の回転角・座標のみが異なる（許容差の指定時）
 /// - "unchanged": 両方に存在し内容が同一
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct DiffRow {
@@ -262,6 +687,209 @@ pub struct DiffRow {
     /// status が "modified" の場合のみ使用
     /// 例: ["col-2", "col-3"] （Part_NoとManufacturerが変更された）
     pub changed_columns: Vec<String>,
+
+    /// 変更された列ごとの変更前・変更後の値（`changed_columns` と同じ順）
+    ///
+    /// 元のBOMを参照できない場合（別ウィンドウへの転送やレポート）でも差分を表示できるよう、
+    /// 値をそのまま持つ。追加・削除・一致の行では空
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+}
+
+/// 変更された列の値
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// データセットAの列ID（`DiffRow::changed_columns` の要素）
+    pub column_id: String,
+
+    /// 列の役割（"part_no" など、役割のない列は None）
+    pub role: Option<String>,
+
+    /// データセットAの値
+    pub a_value: String,
+
+    /// データセットBの値（対応する列がない場合は空）
+    pub b_value: String,
+}
+
+/// 列構成の確認付きの比較結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CompareResult {
+    /// 差分行のリスト
+    pub diffs: Vec<DiffRow>,
+
+    /// 列構成の警告（片方のBOMにしかない役割など）
+    pub warnings: Vec<String>,
+}
+
+// ============================================================================
+// IPC登録名の適用結果
+// ============================================================================
+
+/// IPC登録名の適用状況
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct IpcApplyReport {
+    /// ルールマスタで登録名を付けた行数
+    pub matched_by_rule: usize,
+
+    /// 例外マスタで登録名を付けた行数
+    pub matched_by_exception: usize,
+
+    /// どちらにも一致しなかった行数（部品型番が空の行を含む）
+    pub unmatched: usize,
+
+    /// 一致しなかった部品型番（重複なし、出現順）
+    pub unmatched_parts: Vec<String>,
+}
+
+/// IPC登録名の適用結果と適用状況
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct IpcApplyResult {
+    /// IPC登録名を適用したBOM
+    pub result: ParseResult,
+
+    /// 適用状況
+    pub report: IpcApplyReport,
+}
+
+// ============================================================================
+// 重複行の削除
+// ============================================================================
+
+/// 重複行の削除の結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DedupResult {
+    /// 重複を削除したBOM
+    pub result: ParseResult,
+
+    /// 削除した行数
+    pub removed_count: usize,
+}
+
+// ============================================================================
+// 3方向マージ
+// ============================================================================
+
+/// 3方向マージの結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MergeResult {
+    /// マージ後のBOM（Aの列構造を維持、競合箇所はAの値）
+    pub merged: ParseResult,
+
+    /// 自動でマージできなかった箇所
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// 3方向マージの競合
+///
+/// Reference と列IDで競合箇所を特定する。
+///
+/// # 種類
+/// - "modified": A・Bの両方が同じ列を異なる値に変更
+/// - "added_both": A・Bの両方で追加された行の列の値が異なる
+/// - "deleted_in_a": Aで削除された行がBで変更されている（マージ結果はBの行を保持）
+/// - "deleted_in_b": Bで削除された行がAで変更されている（マージ結果はAの行を保持）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MergeConflict {
+    /// 競合の種類
+    pub kind: String,
+
+    /// Reference値
+    pub ref_value: String,
+
+    /// マージ結果での行インデックス
+    pub merged_index: usize,
+
+    /// 列ID（Aの列構造での "col-N"、行単位の競合の場合はNone）
+    pub column_id: Option<String>,
+
+    /// 列名
+    pub column_name: Option<String>,
+
+    /// 共通の祖先（base）の値
+    pub base_value: Option<String>,
+
+    /// Aの値
+    pub a_value: Option<String>,
+
+    /// Bの値
+    pub b_value: Option<String>,
+}
+
+// ============================================================================
+// 変更の記録（元に戻す用）
+// ============================================================================
+
+/// 処理結果と、その処理による変更の記録
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransformResult {
+    /// 処理後のBOM
+    pub result: ParseResult,
+
+    /// 処理前の状態に戻すための変更記録
+    pub transform: Transform,
+}
+
+/// 処理による変更の記録
+///
+/// BOM全体のスナップショットの代わりに保持し、`undo_transform` で処理前の状態を復元する。
+/// 行インデックスはすべて処理後のBOMでの位置。
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct Transform {
+    /// 処理名（"fill_blank_cells", "expand_reference" など）
+    pub operation: String,
+
+    /// セル単位の変更
+    pub cell_edits: Vec<CellEdit>,
+
+    /// 行の置き換え（処理後の位置の昇順）
+    pub row_splices: Vec<RowSplice>,
+
+    /// 処理前の行番号・検証結果などの付随情報（処理で変更された場合のみ）
+    pub previous_metadata: Option<ParseMetadata>,
+}
+
+/// セルの値の変更
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct CellEdit {
+    /// 行インデックス
+    pub row: usize,
+
+    /// 列インデックス
+    pub column: usize,
+
+    /// 変更前の値
+    pub before: String,
+
+    /// 変更後の値
+    pub after: String,
+}
+
+/// 行の置き換え（処理前の `removed_rows` が `index` から `inserted_count` 行に置き換わった）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct RowSplice {
+    /// 置き換え後の先頭の行インデックス
+    pub index: usize,
+
+    /// 置き換え後の行数
+    pub inserted_count: usize,
+
+    /// 置き換え前の行
+    pub removed_rows: Vec<Vec<String>>,
+}
+
+/// 行データ以外のParseResultの付随情報
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ParseMetadata {
+    pub errors: Vec<String>,
+    pub row_numbers: Vec<usize>,
+    pub structured_errors: Option<Vec<ParseError>>,
+    pub validation_summary: Option<ValidationSummary>,
+    pub header_row_index: Option<usize>,
+    pub data_start_index: usize,
+    pub cell_styles: Option<Vec<Vec<CellStyle>>>,
+    #[serde(default)]
+    pub original_rows: Option<Vec<Vec<String>>>,
 }
 
 // ============================================================================
@@ -278,6 +906,140 @@ pub struct ParseError {
     pub severity: String,
 }
 
+/// 読み込み時の検証結果の集計
+///
+/// 個々の警告は `structured_errors` に含まれる。こちらは一覧表示前のバナー用
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct ValidationSummary {
+    /// 検証したデータ行数
+    pub total_rows: usize,
+
+    /// Referenceが不足している行数
+    pub missing_reference_rows: usize,
+
+    /// Referenceが重複している行数
+    pub duplicate_reference_rows: usize,
+
+    /// 部品型番が空の行数
+    pub missing_part_no_rows: usize,
+
+    /// 無効な文字を含む行数
+    pub invalid_char_rows: usize,
+
+    /// 未実装（DNP）の行数
+    #[serde(default)]
+    pub dnp_rows: usize,
+}
+
+/// Reference番号の欠番（接頭辞ごと）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GapReport {
+    /// 接頭辞（"C", "R" など、大文字）
+    pub prefix: String,
+
+    /// 使用されている最小の番号
+    pub min: u32,
+
+    /// 使用されている最大の番号
+    pub max: u32,
+
+    /// 使用されている番号の数（重複を除く）
+    pub count: usize,
+
+    /// min..max の範囲で使用されていない番号（昇順）
+    pub missing: Vec<u32>,
+}
+
+/// 部品型番ごとの使用数
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PartSummary {
+    /// 部品型番（空の場合は "(未指定)"）
+    pub part_no: String,
+
+    /// 実装数（範囲指定は展開して数える、未実装の行は含めない）
+    pub ref_count: usize,
+
+    /// Referenceのリスト（出現順）
+    pub refs: Vec<String>,
+
+    /// 未実装（DNP）のReferenceのリスト（出現順）
+    #[serde(default)]
+    pub dnp_refs: Vec<String>,
+}
+
+/// Referenceの付け直しの結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RenumberResult {
+    /// Referenceを付け直したBOMデータ
+    pub result: ParseResult,
+
+    /// 変更したReference（セル内の表記ごと、出現順）
+    pub mapping: Vec<ReferenceRename>,
+}
+
+/// Referenceの変更（"C3" → "C2" など）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ReferenceRename {
+    pub old: String,
+    pub new: String,
+}
+
+// ============================================================================
+// 一括変換
+// ============================================================================
+
+/// フォルダ内のBOMの一括変換結果
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct BatchReport {
+    /// ファイルごとの結果（入力ファイルのパス順）
+    pub files: Vec<BatchFileResult>,
+
+    /// 変換できたファイル数
+    pub succeeded: usize,
+
+    /// 読み込み・出力に失敗したファイル数
+    pub failed: usize,
+
+    /// 対応していない拡張子のためスキップしたファイル数
+    pub skipped: usize,
+}
+
+/// 一括変換の1ファイルの結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BatchFileResult {
+    /// 入力ファイルのパス
+    pub input_path: String,
+
+    /// 出力ファイルのパス（変換できなかった場合はNone）
+    pub output_path: Option<String>,
+
+    /// "success" | "error" | "info"（スキップ）
+    pub severity: String,
+
+    /// エラー・スキップの理由
+    pub message: Option<String>,
+
+    /// 読み込み時の警告の件数
+    pub warning_count: usize,
+}
+
+// ============================================================================
+// 進捗
+// ============================================================================
+
+/// 時間のかかる処理の進捗（"bom-progress" イベントで通知）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// 処理の種類（"compare_boms", "apply_ipc_names" など）
+    pub stage: String,
+
+    /// 処理済みの件数
+    pub processed: usize,
+
+    /// 全体の件数
+    pub total: usize,
+}
+
 // ============================================================================
 // IPC Master関連
 // ============================================================================
@@ -288,6 +1050,9 @@ pub struct MasterCondition {
     pub field: String,
     pub match_type: String,
     pub value: String,
+    /// 一致しない場合にマッチとする（"contains" なら「含まない」）
+    #[serde(default)]
+    pub negate: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -295,7 +1060,11 @@ pub struct MasterCondition {
 pub struct IpcMasterRule {
     pub rule_name: String,
     pub conditions: Vec<MasterCondition>,
+    /// 登録名（"登録名" 列に書き込む値）
     pub output_name: String,
+    /// 登録名以外に書き込む値（役割名またはヘッダー名 → 値、"category" など）
+    #[serde(default)]
+    pub outputs: HashMap<String, String>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -305,28 +1074,448 @@ pub struct ExceptionMasterEntry {
     pub output_name: String,
 }
 
+/// 列エイリアス（ヘッダー名 → 列の役割）
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnAliasEntry {
+    pub alias: String,
+    pub role: String,
+}
+
+/// メーカー名の表記ゆれ（"Murata Mfg."、"村田製作所" など → 正式名）
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManufacturerAliasEntry {
+    pub alias: String,
+    pub canonical: String,
+}
+
+// ============================================================================
+// エクスポートオプション
 // ============================================================================
-// エクスポートオプション
-// ============================================================================
 
 /// ファイルエクスポート時のオプション
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportOptions {
-    /// 出力フォーマット（"csv", "eco", "ccf", "msf"）
+    /// 出力フォーマット（"csv", "eco", "ccf", "msf", "xlsx" など）
+    ///
+    /// `export_bom_to_path` では引数の形式が優先されるため省略できる
+    #[serde(default)]
     pub format: String,
 
     /// 差分コメントを含めるか
+    #[serde(default)]
     pub include_diff_comments: bool,
 
     /// フィルタ（"all", "added", "removed", "modified"）
     pub filter: Option<String>,
 
-    /// 出力する列名のリスト
+    /// 出力する列名または列IDのリスト（空の場合は全列）
+    #[serde(default)]
     pub headers: Vec<String>,
 
-    /// 差分情報（差分コメント用）
+    /// 差分情報（差分コメント・絞り込み用）
+    ///
+    /// `diff_side` を指定した場合はその側の行インデックスで対応付ける。
+    /// 指定しない場合（または行インデックスのない差分）はReferenceの出現順に対応付ける
     pub diffs: Option<Vec<DiffRow>>,
+
+    /// 出力するBOMが差分情報のどちら側か（A: 比較元 / B: 比較先）
+    #[serde(default)]
+    pub diff_side: Option<DiffSide>,
+
+    /// CSV出力時にUTF-8 BOMを付与するか（既定: true）
+    #[serde(default = "default_true")]
+    pub write_bom: bool,
+
+    /// CSV出力時の改行コード（既定: LF）
+    #[serde(default)]
+    pub line_ending: LineEnding,
+
+    /// CSV出力時の区切り文字（既定: ','、TSVの場合は '\t'）
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+
+    /// CCF/MSF/PWS出力時にグループ化する役割（既定: 部品型番）
+    #[serde(default)]
+    pub group_by: GroupBy,
+
+    /// ECO出力時のテンプレート（未指定の場合は読み込んだファイルのものを使用）
+    #[serde(default)]
+    pub eco_template: Option<EcoTemplate>,
+
+    /// 追加で出力する役割（"manufacturer", "value", "comment" など）
+    ///
+    /// ECOでは各行の末尾に空白区切りで、CSVでは `headers` に含まれない場合に列として追加する
+    #[serde(default)]
+    pub extra_roles: Vec<String>,
+
+    /// 未実装（DNP）の行を出力しないか
+    #[serde(default)]
+    pub exclude_dnp: bool,
+
+    /// CCF/MSF出力時の字下げ
+    #[serde(default)]
+    pub cad_indent: CadIndent,
+
+    /// 出力する行のインデックス（指定した順に出力、Noneの場合は全行）
+    ///
+    /// `filter`・`exclude_dnp` はこの行に対して適用する。範囲外のインデックスはエラー
+    #[serde(default)]
+    pub row_indices: Option<Vec<usize>>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: "csv".to_string(),
+            include_diff_comments: false,
+            filter: None,
+            headers: Vec::new(),
+            diffs: None,
+            diff_side: None,
+            write_bom: true,
+            line_ending: LineEnding::default(),
+            delimiter: default_delimiter(),
+            group_by: GroupBy::default(),
+            eco_template: None,
+            extra_roles: Vec::new(),
+            exclude_dnp: false,
+            cad_indent: CadIndent::default(),
+            row_indices: None,
+        }
+    }
+}
+
+/// 差分情報のデータセット
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffSide {
+    /// データセットA（`DiffRow::a_index`）
+    A,
+    /// データセットB（`DiffRow::b_index`）
+    B,
+}
+
+/// 改行コード
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// CAD形式出力時のグループ化の基準
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    #[default]
+    PartNo,
+    Value,
+    Manufacturer,
+}
+
+impl GroupBy {
+    /// 対応する役割名を取得
+    pub fn role_key(&self) -> &'static str {
+        match self {
+            GroupBy::PartNo => "part_no",
+            GroupBy::Value => "value",
+            GroupBy::Manufacturer => "manufacturer",
+        }
+    }
+}
+
+/// グループ化の値が空の場合のキー
+pub const UNSPECIFIED_GROUP_KEY: &str = "(未指定)";
+
+/// CCF/MSF出力時の字下げ（空白の数）
+///
+/// 既定値は従来の出力と同じ（"                GRM155:C1,\n                         C2;"）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CadIndent {
+    /// 各エントリ（"型番:Ref"）の行の字下げ（既定: 16）
+    pub entry: usize,
+
+    /// 2つ目以降のReferenceの継続行の字下げ（既定: 25）
+    pub continuation: usize,
+}
+
+impl Default for CadIndent {
+    fn default() -> Self {
+        Self {
+            entry: 16,
+            continuation: 25,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+// ============================================================================
+// 読み込みオプション
+// ============================================================================
+
+/// BOMファイル読み込み時のオプション
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ParseOptions {
+    /// 無効文字の警告対象外とする非ASCII文字（"±" のような1文字、または "ぁ-ん" のような範囲）
+    ///
+    /// 通常は辞書 "allowed_chars" の内容を渡す
+    pub allowed_chars: Vec<String>,
+
+    /// 読み込み時のセルの値を `ParseResult::original_rows` に保持するか
+    pub keep_original_rows: bool,
+
+    /// 読み込み前に読み飛ばす先頭の行数（CSV・Excel、開始行の自動判定より優先）
+    ///
+    /// 読み飛ばした行も `row_numbers` の行番号に数える
+    pub skip_lines: usize,
+
+    /// CSVの引用符（既定は '"'、"'" で囲む出力にも対応）
+    pub quote: char,
+
+    /// CSVのエスケープ文字（`\` など、省略時はエスケープなし）
+    pub escape: Option<char>,
+
+    /// CSVの引用符内で `""` を引用符1文字として扱うか（既定は true）
+    pub double_quote: bool,
+
+    /// ヘッダー行・データ開始行の決め方（既定は自動判定）
+    pub header_mode: HeaderMode,
+}
+
+/// ヘッダー行の扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderMode {
+    /// ヘッダー行・データ開始行を自動判定
+    #[default]
+    Auto,
+    /// 先頭行をヘッダー、2行目以降をデータとする
+    FirstRow,
+    /// ヘッダー行なし（全ての行をデータとする）
+    None,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            allowed_chars: Vec::new(),
+            keep_original_rows: false,
+            skip_lines: 0,
+            quote: '"',
+            escape: None,
+            double_quote: true,
+            header_mode: HeaderMode::Auto,
+        }
+    }
+}
+
+// ============================================================================
+// 比較オプション
+// ============================================================================
+
+/// 複合キーの区切り文字（セルの値に現れない制御文字）
+pub const MATCH_KEY_SEPARATOR: &str = "\u{1F}";
+
+/// 行を対応付けるキーに使う役割（役割名1つ、または役割名の配列）
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MatchKey {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Default for MatchKey {
+    fn default() -> Self {
+        MatchKey::Single("ref".to_string())
+    }
+}
+
+impl MatchKey {
+    /// キーに使う役割名の配列（空の場合は "ref"）
+    pub fn roles(&self) -> Vec<String> {
+        let roles: Vec<String> = match self {
+            MatchKey::Single(role) => vec![role.clone()],
+            MatchKey::Multiple(roles) => roles.clone(),
+        }
+        .into_iter()
+        .map(|role| role.trim().to_string())
+        .filter(|role| !role.is_empty())
+        .collect();
+
+        if roles.is_empty() {
+            vec!["ref".to_string()]
+        } else {
+            roles
+        }
+    }
+}
+
+/// BOM比較時のオプション
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompareOptions {
+    /// Value列を工学表記の値として比較するか（"0.1uF" と "100nF" を同一とみなす）
+    pub normalize_eng_values: bool,
+
+    /// 全角英数字を半角とみなして比較するか（"Ｃ１" と "C1" を同一とみなす）
+    ///
+    /// 比較キーにのみ適用し、返却する差分の値は元の表記のまま
+    pub normalize_width: bool,
+
+    /// 部品型番のあいまい比較のしきい値（0.0～1.0、Noneで無効）
+    ///
+    /// 末尾の梱包コード（"CT"、"TR" など）を除くと一致し、かつ正規化した
+    /// 編集距離による類似度がしきい値以上の型番変更を "likely_same" とする
+    pub fuzzy_part_threshold: Option<f64>,
+
+    /// 行を対応付けるキーの役割（既定: "ref"）
+    ///
+    /// "part_no" を含む場合は、部品型番ごとの数量を比較できるよう数量列を数値として比較する
+    pub match_key: MatchKey,
+
+    /// 対応付けキー・列の値を比較する際の正規化
+    pub normalize: NormalizeOptions,
+
+    /// 全列の比較から除外する列（列ID "col-3" またはヘッダー名、日時・管理番号など）
+    ///
+    /// 部品型番・メーカーなどの役割の比較には影響しない
+    pub ignore_columns: Vec<String>,
+
+    /// 実装位置（"pos_x"・"pos_y"・"rotation"）の許容差（mm・度、Noneで無効）
+    ///
+    /// 両方のBOMに座標・回転の役割がある場合のみ、許容差を超える違いを変更とし、
+    /// 回転角のみの変更を "rotated"、座標のみの変更を "moved_xy" とする
+    pub position_tolerance: Option<f64>,
+
+    /// 内容が同一（"unchanged"）の行も結果に含めるか（既定: true）
+    ///
+    /// false の場合は変更・追加・削除の行のみを返し、大きなBOMの結果を小さくする
+    pub include_unchanged: bool,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            normalize_eng_values: false,
+            normalize_width: false,
+            fuzzy_part_threshold: None,
+            match_key: MatchKey::default(),
+            normalize: NormalizeOptions::default(),
+            ignore_columns: Vec::new(),
+            position_tolerance: None,
+            include_unchanged: true,
+        }
+    }
+}
+
+/// 対応付けキー・比較値の正規化オプション（比較・マージで共通）
+///
+/// 正規化した値は比較にのみ使用し、結果には元の表記を返す
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NormalizeOptions {
+    /// 大文字・小文字を区別しないか（"c1" と "C1" を同一とみなす）
+    pub case_insensitive: bool,
+
+    /// 全角英数字・記号・スペースを半角とみなすか（"Ｃ１" と "C1" を同一とみなす）
+    pub width_fold: bool,
+
+    /// 前後の空白を無視するか（既定: true）
+    pub trim: bool,
+
+    /// 連続する空白を1つとみなすか（"GRM 155" と "GRM  155" を同一とみなす）
+    pub collapse_space: bool,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            case_insensitive: false,
+            width_fold: false,
+            trim: true,
+            collapse_space: false,
+        }
+    }
+}
+
+// ============================================================================
+// テキストクレンジングオプション
+// ============================================================================
+
+/// テキストクレンジングのオプション
+///
+/// 既定では括弧を削除しない（"74VHC08FT(BJ)" の "(BJ)" のような梱包指定を残す）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CleanseOptions {
+    /// 括弧 "()" "（）" を削除するか（既定: false）
+    pub remove_parentheses: bool,
+
+    /// 全角英数字・記号・スペースを半角に変換するか（既定: true）
+    pub normalize_width: bool,
+
+    /// 前後の空白を削除し、連続する空白（全角スペースを含む）を1つにまとめるか（既定: true）
+    pub normalize_whitespace: bool,
+}
+
+impl Default for CleanseOptions {
+    fn default() -> Self {
+        Self {
+            remove_parentheses: false,
+            normalize_width: true,
+            normalize_whitespace: true,
+        }
+    }
+}
+
+impl CleanseOptions {
+    /// 従来の動作（括弧も削除する）
+    pub fn legacy() -> Self {
+        Self {
+            remove_parentheses: true,
+            ..Self::default()
+        }
+    }
+}
+
+// ============================================================================
+// Reference展開オプション
+// ============================================================================
+
+/// Reference展開で1つのセルから作成するReferenceの既定の上限
+pub const DEFAULT_MAX_REFERENCE_EXPANSION: usize = 10_000;
+
+/// Reference展開のオプション
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExpandOptions {
+    /// 降順の範囲指定（"C5-C1"）を昇順に展開するか（既定: false、エラーにする）
+    pub allow_descending: bool,
+
+    /// 1つのセルから展開するReferenceの上限（既定: 10000、超えた場合はエラー）
+    pub max_expansion: usize,
+}
+
+impl Default for ExpandOptions {
+    fn default() -> Self {
+        Self {
+            allow_descending: false,
+            max_expansion: DEFAULT_MAX_REFERENCE_EXPANSION,
+        }
+    }
 }
 
 // ============================================================================
@@ -339,22 +1528,268 @@ pub struct FormatOptions {
     pub use_cell_color: bool,
 }
 
+// ============================================================================
+// 一括処理
+// ============================================================================
+
+/// 一括処理（`run_pipeline`）の処理ステップ
+///
+/// JSONでは `{"type": "cleanse", "removeParentheses": true}` のように、
+/// 処理名を "type" に指定し、オプションを同じオブジェクトに並べる
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PipelineStep {
+    /// Reference列の範囲指定を展開（"C1-C5" → 5行）
+    Expand(ExpandOptions),
+    /// Reference列の列挙を分割（"C1,C2" → 2行）
+    SplitRefs,
+    /// 空欄セルを上の行の値で埋める
+    FillBlanks,
+    /// テキストクレンジング（オプションを省略した項目は `CleanseOptions` の既定値）
+    Cleanse(CleanseOptions),
+    /// IPC登録名を適用
+    ApplyIpc {
+        rules: Vec<IpcMasterRule>,
+        #[serde(default)]
+        exceptions: Vec<ExceptionMasterEntry>,
+    },
+    /// 書式整形ルールを適用
+    Format(FormatOptions),
+}
+
+impl PipelineStep {
+    /// エラーメッセージに使う処理名
+    pub fn label(&self) -> &'static str {
+        match self {
+            PipelineStep::Expand(_) => "Reference展開",
+            PipelineStep::SplitRefs => "Reference分割",
+            PipelineStep::FillBlanks => "空欄補完",
+            PipelineStep::Cleanse(_) => "テキストクレンジング",
+            PipelineStep::ApplyIpc { .. } => "IPC登録名の適用",
+            PipelineStep::Format(_) => "書式整形",
+        }
+    }
+}
+
+// ============================================================================
+// 辞書
+// ============================================================================
+
+/// 辞書読み込み結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DictionaryLoadResult {
+    /// 辞書の内容（JSON文字列）
+    pub content: String,
+
+    /// 警告メッセージ（バックアップから復元した場合など）
+    pub warning: Option<String>,
+}
+
+/// 辞書取り込みモード
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DictionaryImportMode {
+    /// 既存の内容を取り込み内容で置き換える
+    Replace,
+    /// 既存にないエントリのみ追加する（既存エントリは保持）
+    Append,
+    /// 同じキーのエントリは取り込み内容で更新し、新規エントリは追加する
+    Merge,
+}
+
+/// 辞書取り込み結果
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct DictionaryImportReport {
+    /// 追加されたエントリ数
+    pub added: usize,
+
+    /// 更新されたエントリ数
+    pub updated: usize,
+
+    /// スキップされたエントリ数（既存と同一、または追加モードで既存を優先）
+    pub skipped: usize,
+
+    /// 同じキーで内容が異なったエントリのキー
+    pub conflicts: Vec<String>,
+}
+
+// ============================================================================
+// 自動保存
+// ============================================================================
+
+/// 自動保存ファイルの情報
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AutosaveEntry {
+    /// 自動保存ID（ファイル名から拡張子を除いたもの）
+    pub id: String,
+
+    /// 保存日時（UNIXエポックからのミリ秒）
+    pub timestamp: u64,
+
+    /// ファイルサイズ（バイト）
+    pub size: u64,
+}
+
+// ============================================================================
+// ウィンドウ
+// ============================================================================
+
+/// プロジェクトのウィンドウを開いた結果
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProjectWindowInfo {
+    /// ウィンドウのラベル
+    pub label: String,
+
+    /// 新しく開いた場合は true（既存のウィンドウを前面に出した場合は false）
+    pub created: bool,
+}
+
+/// プロジェクト転送データの形式のバージョン（形式を変更したら上げる）
+pub const PROJECT_TRANSFER_VERSION: u32 = 1;
+
+/// 別ウィンドウに転送するプロジェクト（`merge-project` イベントの内容）
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProjectTransfer {
+    /// 転送ID（受信確認の照合に使用）
+    pub transfer_id: u64,
+
+    /// プロジェクトID
+    pub project_id: String,
+
+    /// 形式のバージョン（`PROJECT_TRANSFER_VERSION`）
+    pub data_version: u32,
+
+    /// プロジェクトデータ（フロントエンドの `ProjectRecord`）
+    pub payload: serde_json::Value,
+}
+
+impl ProjectTransfer {
+    /// JSON文字列のプロジェクトデータから転送データを作成
+    ///
+    /// JSONとして解析できない場合や、`id` を持つオブジェクトでない場合はエラー
+    pub fn from_json(transfer_id: u64, project_data: &str) -> Result<Self, AppError> {
+        let payload: serde_json::Value = serde_json::from_str(project_data).map_err(|err| {
+            AppError::parse(format!(
+                "転送するプロジェクトデータの解析に失敗しました: {err}"
+            ))
+        })?;
+        let project_id = payload
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| AppError::validation("転送するプロジェクトデータにIDがありません。"))?
+            .to_string();
+        Ok(Self {
+            transfer_id,
+            project_id,
+            data_version: PROJECT_TRANSFER_VERSION,
+            payload,
+        })
+    }
+}
+
+/// 転送先ウィンドウからの受信確認（`merge-project-ack` イベントの内容）
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferAck {
+    /// 確認する転送のID（`ProjectTransfer::transfer_id`）
+    pub transfer_id: u64,
+
+    /// 転送先で取り込めなかった場合の理由
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl TransferAck {
+    /// イベントの内容（JSON文字列）が指定した転送の受信確認であれば返す
+    ///
+    /// 解析できない内容や、別の転送の確認は None
+    pub fn matching(payload: &str, transfer_id: u64) -> Option<Self> {
+        serde_json::from_str::<Self>(payload)
+            .ok()
+            .filter(|ack| ack.transfer_id == transfer_id)
+    }
+}
+
 // ============================================================================
 // エラー型
 // ============================================================================
 
+/// エラーの種類（UIでの案内の出し分けに使用）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// ファイル・列・ウィンドウなどが見つからない
+    NotFound,
+    /// 対応していないファイル形式・出力形式
+    UnsupportedFormat,
+    /// ファイル内容の解析に失敗した
+    Parse,
+    /// ファイルの読み書きに失敗した
+    Io,
+    /// 入力値・辞書の内容が不正
+    Validation,
+    /// 上記以外（内部エラー）
+    #[default]
+    Internal,
+    /// 処理が中断された
+    Cancelled,
+}
+
 /// アプリケーションエラー
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppError {
+    /// 表示用のメッセージ
     pub message: String,
+
+    /// エラーの種類
+    #[serde(default)]
+    pub kind: ErrorKind,
 }
 
 impl AppError {
+    /// 種類を特定しないエラー（`ErrorKind::Internal`）
     pub fn new(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::Internal, message)
+    }
+
+    pub fn with_kind(kind: ErrorKind, message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            kind,
         }
     }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::NotFound, message)
+    }
+
+    pub fn unsupported_format(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::UnsupportedFormat, message)
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::Parse, message)
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::Io, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::with_kind(ErrorKind::Validation, message)
+    }
+
+    /// 処理の中断を表すエラー
+    pub fn cancelled() -> Self {
+        Self::with_kind(ErrorKind::Cancelled, "処理が中断されました。")
+    }
+
+    /// 処理の中断によるエラーか
+    pub fn is_cancelled(&self) -> bool {
+        self.kind == ErrorKind::Cancelled
+    }
 }
 
 impl fmt::Display for AppError {
@@ -364,3 +1799,206 @@ impl fmt::Display for AppError {
 }
 
 impl std::error::Error for AppError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_for_column_and_counts_by_severity() {
+        let error = |column: Option<usize>, severity: &str| ParseError {
+            message: format!("{severity} {column:?}"),
+            row: Some(2),
+            column,
+            severity: severity.to_string(),
+        };
+        let mut parse = ParseResult::builder().build();
+        assert!(parse.errors_for_column("col-0").is_empty());
+        assert_eq!(parse.counts_by_severity(), (0, 0, 0));
+
+        parse.structured_errors = Some(vec![
+            error(Some(1), "error"),
+            error(Some(0), "warning"),
+            error(Some(1), "warning"),
+            error(None, "info"),
+            error(Some(1), "unknown"),
+        ]);
+        let column_errors: Vec<&str> = parse
+            .errors_for_column("col-1")
+            .iter()
+            .map(|error| error.severity.as_str())
+            .collect();
+        assert_eq!(column_errors, vec!["error", "warning", "unknown"]);
+        assert!(parse.errors_for_column("col-9").is_empty());
+        assert!(parse.errors_for_column("Ref").is_empty());
+        assert_eq!(parse.counts_by_severity(), (1, 2, 1));
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_role_order_and_metadata() {
+        let build = |roles: &[(&str, &str)]| {
+            let mut parse = ParseResult::builder()
+                .rows(vec![
+                    vec!["C1".to_string(), "GRM155".to_string(), "Murata".to_string()],
+                    vec!["R1".to_string(), "RC0402".to_string(), "Yageo".to_string()],
+                ])
+                .build();
+            parse.headers = vec!["Ref".to_string(), "Part".to_string(), "Maker".to_string()];
+            parse.rebuild_columns_from_headers();
+            for (role, col_id) in roles {
+                parse
+                    .column_roles
+                    .insert(role.to_string(), vec![col_id.to_string()]);
+            }
+            parse
+        };
+        let roles = [
+            ("ref", "col-0"),
+            ("part_no", "col-1"),
+            ("manufacturer", "col-2"),
+        ];
+        let parse = build(&roles);
+        let fingerprint = parse.fingerprint();
+        assert_eq!(fingerprint.len(), 64);
+
+        let mut reversed = roles;
+        reversed.reverse();
+        assert_eq!(build(&reversed).fingerprint(), fingerprint);
+
+        // エラー・行番号・前後の空白・末尾の空欄は含めない
+        let mut same = parse.clone();
+        same.errors.push("warning".to_string());
+        same.row_numbers = vec![5, 6];
+        same.rows[0][1] = " GRM155 ".to_string();
+        same.rows[1].push(String::new());
+        assert_eq!(same.fingerprint(), fingerprint);
+
+        let mut changed = parse.clone();
+        changed.rows[1][1] = "RC0603".to_string();
+        assert_ne!(changed.fingerprint(), fingerprint);
+
+        let mut changed = parse;
+        changed
+            .column_roles
+            .insert("value".to_string(), vec!["col-1".to_string()]);
+        assert_ne!(changed.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_rebuild_columns_from_headers_repairs_mismatch() {
+        // ヘッダー・列メタデータ・行の列数がずれたデータ
+        let mut parse = ParseResult::builder()
+            .headers(vec!["Ref".to_string(), "Part".to_string()])
+            .rows(vec![vec![
+                "C1".to_string(),
+                "GRM155".to_string(),
+                "Murata".to_string(),
+                "DNP".to_string(),
+            ]])
+            .row_numbers(vec![1])
+            .build();
+        parse.headers = vec!["Ref".to_string(), String::new(), "Maker".to_string()];
+        assert!(!parse.columns_consistent());
+
+        parse.rebuild_columns_from_headers();
+
+        assert!(parse.columns_consistent());
+        assert_eq!(parse.headers, vec!["Ref", "Part", "Maker", "Column 4"]);
+        let ids: Vec<&str> = parse.columns.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["col-0", "col-1", "col-2", "col-3"]);
+        assert_eq!(parse.columns[3].name, "Column 4");
+    }
+
+    #[test]
+    fn test_with_rows_keeps_schema_and_clears_errors() {
+        let mut template = ParseResult::builder()
+            .rows(vec![vec!["C1".to_string()], vec!["C2".to_string()]])
+            .row_numbers(vec![3, 4])
+            .header_row_index(Some(1))
+            .data_start_index(2)
+            .build();
+        template.headers = vec!["Ref".to_string()];
+        template.rebuild_columns_from_headers();
+        template
+            .column_roles
+            .insert("ref".to_string(), vec!["col-0".to_string()]);
+        template.errors = vec!["3行目: エラー".to_string()];
+
+        let result = ParseResult::with_rows(
+            vec![vec!["R1".to_string()], vec!["R2".to_string()]],
+            &template,
+        );
+
+        assert_eq!(result.get_ref(1), "R2");
+        assert_eq!(result.headers, template.headers);
+        assert_eq!(result.row_numbers, vec![3, 4]);
+        assert_eq!(result.header_row_index, Some(1));
+        assert_eq!(result.data_start_index, 2);
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn test_app_error_serializes_kind() {
+        let value = serde_json::to_value(AppError::not_found("見つかりません")).unwrap();
+        assert_eq!(value["kind"], "not_found");
+        assert_eq!(value["message"], "見つかりません");
+
+        let error: AppError = serde_json::from_str(r#"{"message":"旧形式"}"#).unwrap();
+        assert_eq!(error.kind, ErrorKind::Internal);
+        assert!(AppError::cancelled().is_cancelled());
+    }
+
+    #[test]
+    fn test_project_transfer_from_json() {
+        let transfer =
+            ProjectTransfer::from_json(3, r#"{"id": "p-1", "name": null, "data": {}}"#).unwrap();
+        assert_eq!(transfer.transfer_id, 3);
+        assert_eq!(transfer.project_id, "p-1");
+        assert_eq!(transfer.data_version, PROJECT_TRANSFER_VERSION);
+        assert_eq!(transfer.payload["data"], serde_json::json!({}));
+
+        let error = ProjectTransfer::from_json(1, "{broken").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Parse);
+        let error = ProjectTransfer::from_json(1, r#"{"name": "x"}"#).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Validation);
+        assert!(ProjectTransfer::from_json(1, r#"["p-1"]"#).is_err());
+    }
+
+    #[test]
+    fn test_transfer_ack_matching() {
+        let ack = TransferAck::matching(r#"{"transferId": 7, "error": null}"#, 7).unwrap();
+        assert_eq!(
+            ack,
+            TransferAck {
+                transfer_id: 7,
+                error: None
+            }
+        );
+        let ack = TransferAck::matching(r#"{"transferId": 7, "error": "未対応"}"#, 7).unwrap();
+        assert_eq!(ack.error.as_deref(), Some("未対応"));
+        assert!(TransferAck::matching(r#"{"transferId": 7}"#, 7).is_some());
+
+        // 別の転送の確認や解析できない内容は無視する
+        assert_eq!(TransferAck::matching(r#"{"transferId": 8}"#, 7), None);
+        assert_eq!(TransferAck::matching(r#"{"transfer_id": 7}"#, 7), None);
+        assert_eq!(TransferAck::matching("{broken", 7), None);
+    }
+
+    #[test]
+    fn test_builder_headers_and_roles() {
+        let parse = ParseResult::builder()
+            .headers(vec![
+                "Ref".to_string(),
+                "Maker".to_string(),
+                "Alt".to_string(),
+            ])
+            .role("ref", &[0])
+            .role("manufacturer", &[1, 2])
+            .build();
+
+        assert!(parse.columns_consistent());
+        assert_eq!(parse.column_order, vec!["col-0", "col-1", "col-2"]);
+        assert_eq!(parse.columns[2].name, "Alt");
+        assert_eq!(parse.column_roles["manufacturer"], vec!["col-1", "col-2"]);
+    }
+}
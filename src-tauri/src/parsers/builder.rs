@@ -1,18 +1,55 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::models::{AppError, ColumnMeta, ParseError, ParseResult};
-use crate::utils::text::find_invalid_char;
+use crate::models::{
+    AppError, ColumnMeta, HeaderMode, ParseError, ParseOptions, ParseResult, ValidationSummary,
+};
+use crate::utils::header::{matches_comment_header, matches_dnp_header, normalize_header};
+use crate::utils::text::{find_invalid_char_with, is_dnp_marker, CharAllowlist};
+
+use super::cpl::detect_cpl_roles;
 
 const MAX_SAMPLE_ROWS: usize = 50;
+/// データ開始とみなすために必要な連続データ行数
+const MIN_DATA_RUN: usize = 3;
+/// ヘッダー行を探すときにデータ開始行から遡る最大行数（空行を除く区切りを想定）
+const MAX_HEADER_LOOKBACK: usize = 3;
+/// 表示順序で優先する役割
+const ROLE_DISPLAY_ORDER: &[&str] = &["ref", "part_no", "manufacturer", "value"];
+/// 列の役割の自動判定に失敗した場合の警告（先頭に役割名が付く）
+const ROLE_NOT_DETECTED_SUFFIX: &str =
+    "列を自動判定できませんでした。編集モードで指定してください。";
+const ROLE_AMBIGUOUS_MARKER: &str = "列の候補が複数見つかりました";
+
+pub fn build_bom_rows(
+    rows: Vec<Vec<String>>,
+    options: &ParseOptions,
+) -> Result<ParseResult, AppError> {
+    build_bom_rows_at(rows.into_iter().enumerate().collect(), options)
+}
+
+/// 元ファイルでの行インデックス（0始まり）付きの行からBOMデータを作成
+///
+/// 読み込み時に一部の行を除いた場合（列数の異なるCSVレコードなど）でも、
+/// `row_numbers` や `header_row_index` が元ファイルの行を指すようにする
+///
+/// # 引数
+/// * `rows` - (元ファイルでの行インデックス, 行) のリスト（ファイル順）
+/// * `options` - 読み込みオプション
+pub fn build_bom_rows_at(
+    rows: Vec<(usize, Vec<String>)>,
+    options: &ParseOptions,
+) -> Result<ParseResult, AppError> {
+    let allowlist = CharAllowlist::from_entries(&options.allowed_chars)?;
 
-pub fn build_bom_rows(rows: Vec<Vec<String>>) -> Result<ParseResult, AppError> {
     if rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "BOMデータ内に有効な行が見つかりませんでした。",
         ));
     }
 
-    let mut indexed_rows: Vec<(usize, Vec<String>)> = rows.into_iter().enumerate().collect();
+    // 指定された先頭の行は読み飛ばす（行インデックスは元ファイルでの位置のまま）
+    let mut indexed_rows: Vec<(usize, Vec<String>)> =
+        rows.into_iter().skip(options.skip_lines).collect();
 
     // 先頭の空行を除去
     while let Some((_, row)) = indexed_rows.first() {
@@ -24,7 +61,7 @@ pub fn build_bom_rows(rows: Vec<Vec<String>>) -> Result<ParseResult, AppError> {
     }
 
     if indexed_rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "BOMデータ内に有効な行が見つかりませんでした。",
         ));
     }
@@ -32,34 +69,37 @@ pub fn build_bom_rows(rows: Vec<Vec<String>>) -> Result<ParseResult, AppError> {
     let mut errors: Vec<String> = Vec::new();
     let mut structured_errors: Vec<ParseError> = Vec::new();
 
-    let detected_start = detect_data_start(&indexed_rows);
-    let data_start = match detected_start {
-        Some(idx) => idx,
-        None => {
-            let message =
-                "開始行が判定できませんでした。編集モードで指定してください。".to_string();
-            push_warning(&mut errors, &mut structured_errors, message, None, None);
-            0
-        }
-    };
-
-    // 候補ヘッダー行を抽出（データ開始直前行で、明らかにデータ行でない場合のみ）
-    let header_row = if data_start > 0 {
-        let candidate = indexed_rows[data_start - 1].clone();
-        if is_potential_header(&candidate.1) {
-            Some(candidate)
-        } else {
-            None
+    let (data_start, header_row) = match options.header_mode {
+        HeaderMode::Auto => {
+            let data_start = match detect_data_start(&indexed_rows) {
+                Some(idx) => idx,
+                None => {
+                    let message =
+                        "開始行が判定できませんでした。編集モードで指定してください。".to_string();
+                    push_warning(&mut errors, &mut structured_errors, message, None, None);
+                    0
+                }
+            };
+            // 候補ヘッダー行を抽出（データ開始行の前の空行以外の行で、明らかにデータ行でない場合のみ）
+            let header_row =
+                find_header_row(&indexed_rows, data_start).map(|idx| indexed_rows[idx].clone());
+            (data_start, header_row)
         }
-    } else {
-        None
+        // 先頭行（読み飛ばし・空行除去後）をそのままヘッダーとする
+        HeaderMode::FirstRow => (1, Some(indexed_rows[0].clone())),
+        // 全ての行をデータとし、ヘッダー名は自動で付ける
+        HeaderMode::None => (0, None),
     };
 
     let data_rows = &indexed_rows[data_start..];
     if data_rows.is_empty() {
-        return Err(AppError::new("データ行が見つかりませんでした。"));
+        return Err(AppError::parse("データ行が見つかりませんでした。"));
     }
 
+    // 元ファイルでの位置（先頭の空行除去前の行インデックス）
+    let header_row_index = header_row.as_ref().map(|(line, _)| *line);
+    let data_start_index = data_rows[0].0;
+
     let max_columns = data_rows
         .iter()
         .map(|(_, row)| row.len())
@@ -96,60 +136,59 @@ pub fn build_bom_rows(rows: Vec<Vec<String>>) -> Result<ParseResult, AppError> {
         row_numbers.push(line_number + 1);
     }
 
-    let analysis = analyze_columns(data_rows, max_columns);
-    let mut column_roles: HashMap<String, Vec<String>> = HashMap::new();
-    let mut priority_order: Vec<usize> = Vec::new();
-
-    let assigned_refs = assign_role(
-        "Reference",
-        "ref",
-        &analysis.reference_candidates,
-        &mut column_roles,
-        &mut errors,
-        &mut structured_errors,
-        &mut priority_order,
-    );
-    let assigned_parts = assign_role(
-        "部品型番",
-        "part_no",
-        &analysis.part_candidates,
-        &mut column_roles,
-        &mut errors,
-        &mut structured_errors,
-        &mut priority_order,
-    );
-    assign_role(
-        "メーカー",
-        "manufacturer",
-        &analysis.manufacturer_candidates,
-        &mut column_roles,
-        &mut errors,
-        &mut structured_errors,
-        &mut priority_order,
-    );
-
-    let mut column_order: Vec<String> = Vec::with_capacity(max_columns);
-    let mut used = HashSet::new();
+    // 未実装（DNP）の列は "x" などの記号のため、コメント列と同じくヘッダー名のみで判定する
+    let dnp_columns = if header_row.is_some() {
+        detect_header_columns(&headers, matches_dnp_header)
+    } else {
+        Vec::new()
+    };
 
-    for idx in &priority_order {
-        if used.insert(*idx) {
-            column_order.push(format!("col-{}", idx));
+    // 実装位置（CPL）ファイルは座標の列を値の形で判定すると誤るため、ヘッダー名で役割を決める
+    let cpl_roles = header_row.as_ref().and_then(|_| detect_cpl_roles(&headers));
+    let (mut column_roles, assigned_refs, assigned_parts) = match cpl_roles {
+        Some(column_roles) => {
+            let message =
+                "実装位置（CPL）ファイルとして読み込みました（部品型番の列はありません）。"
+                    .to_string();
+            push_info(&mut errors, &mut structured_errors, message, None, None);
+            let assigned_refs = role_indices(&column_roles, "ref");
+            (column_roles, assigned_refs, Vec::new())
         }
+        None => detect_roles(
+            data_rows,
+            &headers,
+            header_row.is_some(),
+            &dnp_columns,
+            max_columns,
+            &mut errors,
+            &mut structured_errors,
+        ),
+    };
+    if !dnp_columns.is_empty() {
+        column_roles
+            .entry("dnp".to_string())
+            .or_insert_with(|| dnp_columns.iter().map(|idx| format!("col-{idx}")).collect());
     }
 
-    for idx in 0..max_columns {
-        if used.insert(idx) {
-            column_order.push(format!("col-{}", idx));
-        }
-    }
+    let column_order = build_column_order(&column_roles, max_columns);
 
-    validate_rows(
+    let mut validation_summary = validate_rows(
         data_rows,
         &assigned_refs,
         &assigned_parts,
+        &allowlist,
         &mut errors,
         &mut structured_errors,
     );
+    let dnp_indices = role_indices(&column_roles, "dnp");
+    validation_summary.dnp_rows = data_rows
+        .iter()
+        .filter(|(_, row)| {
+            dnp_indices
+                .iter()
+                .any(|&idx| row.get(idx).is_some_and(|value| is_dnp_marker(value)))
+        })
+        .count();
 
     Ok(ParseResult {
         rows: raw_rows,
@@ -164,18 +203,84 @@ pub fn build_bom_rows(rows: Vec<Vec<String>>) -> Result<ParseResult, AppError> {
         columns,
         row_numbers,
         structured_errors: Some(structured_errors),
+        validation_summary: Some(validation_summary),
+        header_row_index,
+        data_start_index,
+        cell_styles: None,
+        eco_template: None,
+        original_rows: None,
     })
 }
 
+/// データ開始行を判定
+///
+/// 先頭のタイトル・メタデータ（プロジェクト名、日付など）が偶然
+/// Referenceらしく見える場合に備え、`MIN_DATA_RUN` 行以上連続する
+/// データ行の先頭を開始行とする。ヘッダーらしい行の直後から始まる連続を優先し、
+/// 連続が見つからない小さなファイルでは最初のデータ行にフォールバックする。
 fn detect_data_start(rows: &[(usize, Vec<String>)]) -> Option<usize> {
-    for (idx, (_, row)) in rows.iter().enumerate() {
-        if is_data_row(row) {
-            return Some(idx);
+    let data_flags: Vec<bool> = rows.iter().map(|(_, row)| is_data_row(row)).collect();
+    let follows_header = |start: usize| find_header_row(rows, start).is_some();
+
+    // 連続するデータ行の先頭を収集
+    let mut run_starts: Vec<usize> = Vec::new();
+    let mut idx = 0;
+    while idx < data_flags.len() {
+        if !data_flags[idx] {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        while idx < data_flags.len() && data_flags[idx] {
+            idx += 1;
+        }
+        if idx - start >= MIN_DATA_RUN {
+            run_starts.push(start);
         }
     }
+
+    if let Some(&start) = run_starts.iter().find(|&&start| follows_header(start)) {
+        return Some(start);
+    }
+    if let Some(&start) = run_starts.first() {
+        return Some(start);
+    }
+
+    // 小さなファイル: 従来通り最初のデータ行
+    data_flags.iter().position(|&is_data| is_data)
+}
+
+/// データ開始行の前にあるヘッダー行の位置を探す
+///
+/// 空行を読み飛ばしながら `MAX_HEADER_LOOKBACK` 行まで遡り、最初に見つかった
+/// 空行以外の行がヘッダーらしい場合に採用する。空行を挟む場合は、タイトル行などを
+/// 誤って採用しないよう、空でない列数がデータ行と揃っている場合のみとする。
+fn find_header_row(rows: &[(usize, Vec<String>)], data_start: usize) -> Option<usize> {
+    let mut idx = data_start.checked_sub(1)?;
+    for lookback in 0..MAX_HEADER_LOOKBACK {
+        let row = &rows[idx].1;
+        if !is_blank_row(row) {
+            let aligned = lookback == 0 || header_aligns_with_data(row, &rows[data_start..]);
+            return (aligned && is_potential_header(row)).then_some(idx);
+        }
+        idx = idx.checked_sub(1)?;
+    }
     None
 }
 
+/// ヘッダー候補の空でない列数が、先頭のデータ行の空でない列数と揃っているか
+fn header_aligns_with_data(header: &[String], data_rows: &[(usize, Vec<String>)]) -> bool {
+    let non_empty = |row: &[String]| row.iter().filter(|cell| !cell.trim().is_empty()).count();
+    let data_columns = data_rows
+        .iter()
+        .take(MIN_DATA_RUN)
+        .map(|(_, row)| non_empty(row))
+        .max()
+        .unwrap_or(0);
+    let header_columns = non_empty(header);
+    header_columns >= 2 && header_columns.abs_diff(data_columns) <= 1
+}
+
 fn is_data_row(row: &[String]) -> bool {
     let mut reference_like = 0;
     let mut non_empty = 0;
@@ -213,52 +318,95 @@ struct ColumnAnalysis {
     manufacturer_candidates: Vec<usize>,
 }
 
-fn analyze_columns(rows: &[(usize, Vec<String>)], max_columns: usize) -> ColumnAnalysis {
+/// 候補を1つに絞り込む際、最高スコアに対してこの比率以上の候補は同点とみなす
+const ROLE_SCORE_TIE_RATIO: f64 = 0.8;
+/// メーカー略称（"TDK"、"3M" など）とみなす最大文字数
+const MAX_MANUFACTURER_CODE_LEN: usize = 4;
+/// 部品型番らしい平均文字数の下限
+const MIN_PART_NUMBER_AVG_LEN: f64 = 6.0;
+
+/// 列ごとの統計から ref → part_no → manufacturer の順に役割の候補を決める
+///
+/// 値単体の判定（`looks_like_*`）は英数字のトークンで重なるため、
+/// 列全体の統計で重み付けする。
+/// - Reference: 部品記号らしい形（"C1"、"R10A" など）の割合が高く、値の重複が少ない
+/// - 部品型番: 値の種類が多く、文字数が長い
+/// - メーカー: 値の繰り返しが多い（種類が少ない）
+///
+/// 先に決まった役割の列（`excluded_columns` を含む）は以降の候補から除外し、
+/// スコアが拮抗する候補が複数残った場合のみ複数候補として扱う。
+fn analyze_columns(
+    rows: &[(usize, Vec<String>)],
+    max_columns: usize,
+    excluded_columns: &[usize],
+) -> ColumnAnalysis {
     let mut stats = vec![ColumnStats::default(); max_columns];
 
     for (_, row) in rows.iter().take(MAX_SAMPLE_ROWS) {
-        for col_idx in 0..max_columns {
+        for (col_idx, stat) in stats.iter_mut().enumerate() {
             let value = row.get(col_idx).map(|s| s.trim()).unwrap_or("");
             if value.is_empty() {
                 continue;
             }
-            let stat = &mut stats[col_idx];
             stat.non_empty += 1;
+            stat.total_len += value.chars().count();
+            stat.distinct.insert(value.to_lowercase());
 
             if looks_like_reference(value) {
                 stat.reference_like += 1;
             }
-            if looks_like_part_number(value) {
+            if looks_like_designator(value) {
+                stat.designator_like += 1;
+            }
+            let part_like = looks_like_part_number(value);
+            if part_like {
                 stat.part_like += 1;
             }
-            if looks_like_manufacturer(value) {
+            if looks_like_manufacturer(value) || (part_like && looks_like_manufacturer_code(value))
+            {
                 stat.manufacturer_like += 1;
             }
         }
     }
 
-    let reference_candidates = stats
-        .iter()
-        .enumerate()
-        .filter(|(_, stat)| stat.reference_like > 0 && stat.reference_like * 2 >= stat.non_empty)
-        .map(|(idx, _)| idx)
-        .collect::<Vec<_>>();
+    let mut excluded: HashSet<usize> = excluded_columns.iter().copied().collect();
 
-    let part_candidates = stats
-        .iter()
-        .enumerate()
-        .filter(|(_, stat)| stat.part_like > 0 && stat.part_like * 2 >= stat.non_empty)
-        .map(|(idx, _)| idx)
-        .collect::<Vec<_>>();
+    let reference_candidates = pick_candidates(
+        &stats,
+        &excluded,
+        |stat| stat.reference_like,
+        |stat| {
+            (stat.ratio(stat.reference_like) + stat.ratio(stat.designator_like))
+                * (0.5 + 0.5 * stat.unique_ratio())
+        },
+    );
+    if let [idx] = reference_candidates[..] {
+        excluded.insert(idx);
+    }
 
-    let manufacturer_candidates = stats
-        .iter()
-        .enumerate()
-        .filter(|(_, stat)| {
-            stat.manufacturer_like > 0 && stat.manufacturer_like * 2 >= stat.non_empty
-        })
-        .map(|(idx, _)| idx)
-        .collect::<Vec<_>>();
+    let part_candidates = pick_candidates(
+        &stats,
+        &excluded,
+        |stat| stat.part_like,
+        |stat| {
+            let length_bonus = if stat.average_len() >= MIN_PART_NUMBER_AVG_LEN {
+                0.25
+            } else {
+                0.0
+            };
+            stat.ratio(stat.part_like) * (0.5 + 0.5 * stat.unique_ratio()) + length_bonus
+        },
+    );
+    if let [idx] = part_candidates[..] {
+        excluded.insert(idx);
+    }
+
+    let manufacturer_candidates = pick_candidates(
+        &stats,
+        &excluded,
+        |stat| stat.manufacturer_like,
+        |stat| stat.ratio(stat.manufacturer_like) * (1.5 - stat.unique_ratio()),
+    );
 
     ColumnAnalysis {
         reference_candidates,
@@ -267,14 +415,146 @@ fn analyze_columns(rows: &[(usize, Vec<String>)], max_columns: usize) -> ColumnA
     }
 }
 
+/// 半数以上の値が条件を満たす列を候補とし、スコアが拮抗する候補に絞り込む
+fn pick_candidates<C, S>(
+    stats: &[ColumnStats],
+    excluded: &HashSet<usize>,
+    count: C,
+    score: S,
+) -> Vec<usize>
+where
+    C: Fn(&ColumnStats) -> usize,
+    S: Fn(&ColumnStats) -> f64,
+{
+    let scored: Vec<(usize, f64)> = stats
+        .iter()
+        .enumerate()
+        .filter(|(idx, stat)| {
+            let matched = count(stat);
+            !excluded.contains(idx) && matched > 0 && matched * 2 >= stat.non_empty
+        })
+        .map(|(idx, stat)| (idx, score(stat)))
+        .collect();
+
+    let best = scored
+        .iter()
+        .map(|(_, score)| *score)
+        .fold(f64::MIN, f64::max);
+
+    scored
+        .into_iter()
+        .filter(|(_, score)| *score >= best * ROLE_SCORE_TIE_RATIO)
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
 #[derive(Default, Clone)]
 struct ColumnStats {
     non_empty: usize,
+    total_len: usize,
+    distinct: HashSet<String>,
     reference_like: usize,
+    designator_like: usize,
     part_like: usize,
     manufacturer_like: usize,
 }
 
+impl ColumnStats {
+    fn ratio(&self, count: usize) -> f64 {
+        if self.non_empty == 0 {
+            0.0
+        } else {
+            count as f64 / self.non_empty as f64
+        }
+    }
+
+    /// 値の種類の割合（すべて異なれば1.0）
+    fn unique_ratio(&self) -> f64 {
+        self.ratio(self.distinct.len())
+    }
+
+    fn average_len(&self) -> f64 {
+        self.ratio(self.total_len)
+    }
+}
+
+/// 値の形とヘッダー名（コメント列のみ）から ref・part_no・manufacturer・comment の列を決める
+///
+/// `dnp_columns`（未実装の指定の列）はほかの役割の候補から除外する
+///
+/// # 戻り値
+/// （役割名 → 列ID, Referenceの列, 部品型番の列）
+fn detect_roles(
+    data_rows: &[(usize, Vec<String>)],
+    headers: &[String],
+    has_header: bool,
+    dnp_columns: &[usize],
+    max_columns: usize,
+    errors: &mut Vec<String>,
+    structured_errors: &mut Vec<ParseError>,
+) -> (HashMap<String, Vec<String>>, Vec<usize>, Vec<usize>) {
+    // コメント列はヘッダー名で先に決め、ほかの役割の候補から除外する
+    let comment_columns: Vec<usize> = if has_header {
+        detect_header_columns(headers, matches_comment_header)
+            .into_iter()
+            .filter(|idx| !dnp_columns.contains(idx))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let excluded_columns: Vec<usize> = comment_columns.iter().chain(dnp_columns).copied().collect();
+
+    let analysis = analyze_columns(data_rows, max_columns, &excluded_columns);
+    let mut column_roles: HashMap<String, Vec<String>> = HashMap::new();
+
+    let assigned_refs = assign_role(
+        "Reference",
+        "ref",
+        &analysis.reference_candidates,
+        &mut column_roles,
+        errors,
+        structured_errors,
+    );
+    let assigned_parts = assign_role(
+        "部品型番",
+        "part_no",
+        &analysis.part_candidates,
+        &mut column_roles,
+        errors,
+        structured_errors,
+    );
+    assign_role(
+        "メーカー",
+        "manufacturer",
+        &analysis.manufacturer_candidates,
+        &mut column_roles,
+        errors,
+        structured_errors,
+    );
+
+    if !comment_columns.is_empty() {
+        column_roles.insert(
+            "comment".to_string(),
+            comment_columns
+                .iter()
+                .map(|idx| format!("col-{idx}"))
+                .collect(),
+        );
+    }
+
+    (column_roles, assigned_refs, assigned_parts)
+}
+
+/// 役割に割り当てた列のインデックス
+fn role_indices(column_roles: &HashMap<String, Vec<String>>, role: &str) -> Vec<usize> {
+    column_roles
+        .get(role)
+        .into_iter()
+        .flatten()
+        .filter_map(|col_id| col_id.strip_prefix("col-")?.parse().ok())
+        .collect()
+}
+
 fn assign_role(
     label: &str,
     role_key: &str,
@@ -282,19 +562,16 @@ fn assign_role(
     column_roles: &mut HashMap<String, Vec<String>>,
     errors: &mut Vec<String>,
     structured_errors: &mut Vec<ParseError>,
-    priority_order: &mut Vec<usize>,
 ) -> Vec<usize> {
     match candidates.len() {
         0 => {
-            let message =
-                format!("{label}列を自動判定できませんでした。編集モードで指定してください。");
+            let message = format!("{label}{ROLE_NOT_DETECTED_SUFFIX}");
             push_warning(errors, structured_errors, message, None, None);
             Vec::new()
         }
         1 => {
             let idx = candidates[0];
             column_roles.insert(role_key.to_string(), vec![format!("col-{idx}")]);
-            priority_order.push(idx);
             vec![idx]
         }
         _ => {
@@ -304,7 +581,7 @@ fn assign_role(
                 .collect::<Vec<_>>()
                 .join(", ");
             let message = format!(
-                "{label}列の候補が複数見つかりました（{human_candidates}）。編集モードで指定してください。"
+                "{label}{ROLE_AMBIGUOUS_MARKER}（{human_candidates}）。編集モードで指定してください。"
             );
             push_warning(errors, structured_errors, message, None, None);
             Vec::new()
@@ -312,20 +589,79 @@ fn assign_role(
     }
 }
 
+/// ヘッダー名で判定する列（コメント・未実装の指定）の列インデックスを取得
+///
+/// コメントは自由記述、未実装の指定は "x" などの記号で値の形から判定できないため、
+/// ヘッダー名のみで判定する。該当する列がなくても警告しない。
+fn detect_header_columns(headers: &[String], matches: fn(&str) -> bool) -> Vec<usize> {
+    headers
+        .iter()
+        .enumerate()
+        .filter(|(_, header)| matches(&normalize_header(header)))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// 列の表示順序を作成（ref → part_no → manufacturer → value → その他の列）
+///
+/// # 引数
+/// * `column_roles` - 列の役割マッピング
+/// * `column_count` - 列数
+///
+/// # 戻り値
+/// 列IDの配列（役割を持つ列は役割内の指定順、その他の列は元の順序）
+pub fn build_column_order(
+    column_roles: &HashMap<String, Vec<String>>,
+    column_count: usize,
+) -> Vec<String> {
+    let mut column_order: Vec<String> = Vec::with_capacity(column_count);
+    let mut used = HashSet::new();
+
+    let priority_indices = ROLE_DISPLAY_ORDER
+        .iter()
+        .filter_map(|role| column_roles.get(*role))
+        .flatten()
+        .filter_map(|col_id| col_id.strip_prefix("col-")?.parse::<usize>().ok());
+
+    for idx in priority_indices.chain(0..column_count) {
+        if idx < column_count && used.insert(idx) {
+            column_order.push(format!("col-{}", idx));
+        }
+    }
+
+    column_order
+}
+
+/// 列の役割の自動判定に関する警告かどうか
+///
+/// 役割を手動で指定した後は不要になるため、削除対象の判定に使用する
+pub fn is_role_detection_warning(message: &str) -> bool {
+    message.ends_with(ROLE_NOT_DETECTED_SUFFIX) || message.contains(ROLE_AMBIGUOUS_MARKER)
+}
+
 fn validate_rows(
     rows: &[(usize, Vec<String>)],
     ref_indices: &[usize],
     part_indices: &[usize],
+    allowlist: &CharAllowlist,
     errors: &mut Vec<String>,
     structured_errors: &mut Vec<ParseError>,
-) {
-    let mut seen_refs: HashSet<String> = HashSet::new();
+) -> ValidationSummary {
+    // Reference → 最初に出現した行の行番号と部品型番
+    let mut seen_refs: HashMap<String, (usize, String)> = HashMap::new();
+    let mut summary = ValidationSummary {
+        total_rows: rows.len(),
+        ..ValidationSummary::default()
+    };
 
     for (line_number_zero, row) in rows {
         let line_number = line_number_zero + 1;
+        let mut has_invalid_char = false;
+        let mut missing_reference = false;
 
         for (col_idx, cell) in row.iter().enumerate() {
-            if let Some(invalid_char) = find_invalid_char(cell) {
+            if let Some(invalid_char) = find_invalid_char_with(cell, allowlist) {
+                has_invalid_char = true;
                 let message = format!(
                     "{line_number}行目(列{}): 無効な文字 '{}' を検出しました。",
                     col_idx + 1,
@@ -347,6 +683,7 @@ fn validate_rows(
                 if let Some(value) = row.get(idx) {
                     let trimmed = value.trim();
                     if trimmed.is_empty() {
+                        missing_reference = true;
                         let message =
                             format!("{line_number}行目: Reference列のデータが不足しています。");
                         push_error(
@@ -360,6 +697,7 @@ fn validate_rows(
                     }
                     reference_values.push(trimmed.to_string());
                 } else {
+                    missing_reference = true;
                     let message =
                         format!("{line_number}行目: Reference列のデータが不足しています。");
                     push_error(
@@ -383,16 +721,57 @@ fn validate_rows(
                         Some(line_number),
                         ref_indices.first().copied(),
                     );
-                } else if !seen_refs.insert(reference.clone()) {
-                    let message =
-                        format!("{line_number}行目: Reference '{reference}' が重複しています。");
-                    push_warning(
-                        errors,
-                        structured_errors,
-                        message,
-                        Some(line_number),
-                        ref_indices.first().copied(),
-                    );
+                } else {
+                    let part_no = part_indices
+                        .iter()
+                        .filter_map(|&idx| row.get(idx))
+                        .map(|value| value.trim())
+                        .filter(|value| !value.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    match seen_refs.get(&reference) {
+                        None => {
+                            seen_refs.insert(reference, (line_number, part_no));
+                        }
+                        Some((first_line, first_part_no)) => {
+                            summary.duplicate_reference_rows += 1;
+                            let column = ref_indices.first().copied();
+                            if part_no.is_empty() || first_part_no.is_empty() {
+                                let message = format!(
+                                    "{line_number}行目: Reference '{reference}' が重複しています。"
+                                );
+                                push_warning(
+                                    errors,
+                                    structured_errors,
+                                    message,
+                                    Some(line_number),
+                                    column,
+                                );
+                            } else if part_no == *first_part_no {
+                                let message = format!(
+                                    "{line_number}行目: Reference '{reference}' が重複しています（部品型番 '{part_no}' は{first_line}行目と同一）。"
+                                );
+                                push_info(
+                                    errors,
+                                    structured_errors,
+                                    message,
+                                    Some(line_number),
+                                    column,
+                                );
+                            } else {
+                                let message = format!(
+                                    "{line_number}行目: Reference '{reference}' が重複し、部品型番が異なります（{first_line}行目: '{first_part_no}'、{line_number}行目: '{part_no}'）。"
+                                );
+                                push_error(
+                                    errors,
+                                    structured_errors,
+                                    message,
+                                    Some(line_number),
+                                    column,
+                                );
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -402,6 +781,7 @@ fn validate_rows(
                 .iter()
                 .all(|&idx| row.get(idx).map(|v| v.trim().is_empty()).unwrap_or(true));
             if part_is_empty {
+                summary.missing_part_no_rows += 1;
                 let message =
                     format!("{line_number}行目: 部品型番が空です。編集モードで指定してください。");
                 push_warning(
@@ -413,11 +793,20 @@ fn validate_rows(
                 );
             }
         }
+
+        if has_invalid_char {
+            summary.invalid_char_rows += 1;
+        }
+        if missing_reference {
+            summary.missing_reference_rows += 1;
+        }
     }
+
+    summary
 }
 
 fn looks_like_reference(value: &str) -> bool {
-    let tokens: Vec<&str> = value.split(|c| c == ',' || c == ';').collect();
+    let tokens: Vec<&str> = value.split([',', ';']).collect();
     let mut matched_any = false;
 
     for token in tokens {
@@ -453,6 +842,48 @@ fn looks_like_reference(value: &str) -> bool {
     matched_any
 }
 
+/// 部品記号らしい形か（英字1～3文字 + 数字 + 任意の英字1文字、例: "C1", "R10A"）
+///
+/// カンマ区切りの場合はすべてのトークンが該当すること
+fn looks_like_designator(value: &str) -> bool {
+    let mut matched_any = false;
+
+    for token in value.split([',', ';']).map(str::trim) {
+        if token.is_empty() {
+            continue;
+        }
+        let prefix = token
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+        let rest = &token[prefix..];
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let suffix = &rest[digits..];
+
+        let valid = (1..=3).contains(&prefix)
+            && digits > 0
+            && suffix.len() <= 1
+            && suffix.chars().all(|c| c.is_ascii_alphabetic());
+        if !valid {
+            return false;
+        }
+        matched_any = true;
+    }
+
+    matched_any
+}
+
+/// メーカー略称らしいか（"3M"、"NXP" など、英字が数字以上の短いコード）
+fn looks_like_manufacturer_code(value: &str) -> bool {
+    let letters = value.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    let digits = value.chars().filter(|c| c.is_ascii_digit()).count();
+
+    value.chars().count() <= MAX_MANUFACTURER_CODE_LEN
+        && value.chars().all(|c| c.is_ascii_alphanumeric())
+        && letters > 0
+        && letters >= digits
+}
+
 fn looks_like_part_number(value: &str) -> bool {
     if value.is_empty() || value.contains(char::is_whitespace) {
         return false;
@@ -535,6 +966,428 @@ fn push_error(
     });
 }
 
+fn push_info(
+    errors: &mut Vec<String>,
+    structured: &mut Vec<ParseError>,
+    message: String,
+    row: Option<usize>,
+    column: Option<usize>,
+) {
+    errors.push(message.clone());
+    structured.push(ParseError {
+        message,
+        row,
+        column,
+        severity: "info".to_string(),
+    });
+}
+
 fn is_blank_row(row: &[String]) -> bool {
     row.iter().all(|cell| cell.trim().is_empty())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_rows() -> Vec<Vec<String>> {
+        [
+            ["Ref", "Part", "Note"],
+            ["C1", "GRM155", "±10%"],
+            ["R1", "RC0402", "1µH"],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect()
+    }
+
+    fn invalid_char_warnings(result: &ParseResult) -> usize {
+        result
+            .errors
+            .iter()
+            .filter(|message| message.contains("無効な文字"))
+            .count()
+    }
+
+    fn to_rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    /// 5行のメタデータ（一部がReferenceに見える）の後にヘッダーとデータが続くBOM
+    const METADATA_PREAMBLE: &[&[&str]] = &[
+        &["Project", "PRJ2024"],
+        &["Date", "2024/05/01"],
+        &["Drawing", "DWG-1234"],
+        &["Revision", "R2"],
+        &["Author", "Tanaka"],
+    ];
+
+    #[test]
+    fn test_detect_data_start_skips_metadata_preamble() {
+        let mut rows: Vec<&[&str]> = METADATA_PREAMBLE.to_vec();
+        rows.extend_from_slice(&[
+            &["Ref", "Part", "Maker"],
+            &["C1", "GRM155R71C104", "Murata"],
+            &["C2", "GRM155R71C104", "Murata"],
+            &["R1", "RC0402FR-0710KL", "Yageo"],
+            &["U1", "74VHC08FT", "Toshiba"],
+        ]);
+
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part", "Maker"]);
+        assert_eq!(result.rows.len(), 4);
+        assert_eq!(result.row_numbers, vec![7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn test_build_bom_rows_skip_lines() {
+        let rows: Vec<&[&str]> = vec![
+            &["C100", "Exported by CAD"],
+            &["R200", "2024-01-01"],
+            &["Ref", "Part"],
+            &["C1", "GRM155R71C104"],
+            &["C2", "GRM155R71C104"],
+            &["R1", "RC0402FR-0710KL"],
+        ];
+        let options = ParseOptions {
+            skip_lines: 2,
+            ..ParseOptions::default()
+        };
+        let result = build_bom_rows(to_rows(&rows), &options).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part"]);
+        assert_eq!(result.header_row_index, Some(2));
+        assert_eq!(result.row_numbers, vec![4, 5, 6]);
+
+        let options = ParseOptions {
+            skip_lines: rows.len(),
+            ..ParseOptions::default()
+        };
+        assert!(build_bom_rows(to_rows(&rows), &options).is_err());
+    }
+
+    #[test]
+    fn test_build_bom_rows_header_before_blank_line() {
+        let rows: Vec<&[&str]> = vec![
+            &["Ref", "Part", "Maker"],
+            &["", "", ""],
+            &["C1", "GRM155R71C104", "Murata"],
+            &["C2", "GRM155R71C104", "Murata"],
+            &["R1", "RC0402FR-0710KL", "Yageo"],
+        ];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part", "Maker"]);
+        assert_eq!(result.header_row_index, Some(0));
+        assert_eq!(result.data_start_index, 2);
+
+        // 列数が揃わないタイトル行はヘッダーとして採用しない
+        let rows: Vec<&[&str]> = vec![
+            &["Sample Project", "", ""],
+            &["", "", ""],
+            &["C1", "GRM155R71C104", "Murata"],
+            &["C2", "GRM155R71C104", "Murata"],
+            &["R1", "RC0402FR-0710KL", "Yageo"],
+        ];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.header_row_index, None);
+    }
+
+    #[test]
+    fn test_build_bom_rows_header_modes() {
+        let rows: Vec<&[&str]> = vec![
+            &["Sample Project", ""],
+            &["Ref", "Part"],
+            &["C1", "GRM155R71C104"],
+            &["C2", "GRM155R71C104"],
+            &["R1", "RC0402FR-0710KL"],
+        ];
+        let build = |header_mode| {
+            let options = ParseOptions {
+                header_mode,
+                ..ParseOptions::default()
+            };
+            build_bom_rows(to_rows(&rows), &options).unwrap()
+        };
+
+        let result = build(HeaderMode::Auto);
+        assert_eq!(result.headers, vec!["Ref", "Part"]);
+        assert_eq!(result.header_row_index, Some(1));
+        assert_eq!(result.row_numbers, vec![3, 4, 5]);
+
+        let result = build(HeaderMode::FirstRow);
+        assert_eq!(result.headers, vec!["Sample Project", "Column 2"]);
+        assert_eq!(result.header_row_index, Some(0));
+        assert_eq!(result.row_numbers, vec![2, 3, 4, 5]);
+        // 役割の判定はデータ行に対して行う
+        assert_eq!(result.column_roles["ref"], vec!["col-0"]);
+
+        let result = build(HeaderMode::None);
+        assert_eq!(result.headers, vec!["Column 1", "Column 2"]);
+        assert_eq!(result.header_row_index, None);
+        assert_eq!(result.row_numbers, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_build_bom_rows_exposes_header_and_data_start() {
+        let rows: Vec<&[&str]> = vec![
+            &["", ""],
+            &["Title", "Sample"],
+            &["Ref", "Part"],
+            &["C1", "GRM155R71C104"],
+            &["C2", "GRM155R71C104"],
+            &["R1", "RC0402FR-0710KL"],
+        ];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.header_row_index, Some(2));
+        assert_eq!(result.data_start_index, 3);
+        assert_eq!(result.row_numbers[0], result.data_start_index + 1);
+
+        // ヘッダーなし
+        let rows: Vec<&[&str]> = vec![&["C1", "GRM155R71C104"], &["C2", "GRM155R71C104"]];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.header_row_index, None);
+        assert_eq!(result.data_start_index, 0);
+    }
+
+    #[test]
+    fn test_detect_data_start_prefers_run_after_header() {
+        // メタデータ自体が3行連続でReferenceに見える場合もヘッダー直後を優先
+        let rows: Vec<&[&str]> = vec![
+            &["PRJ2024"],
+            &["DWG-1234"],
+            &["R2"],
+            &["Issued by", "Tanaka"],
+            &["Ref", "Part"],
+            &["C1", "GRM155R71C104"],
+            &["C2", "GRM155R71C104"],
+            &["R1", "RC0402FR-0710KL"],
+        ];
+
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part"]);
+        assert_eq!(result.row_numbers, vec![6, 7, 8]);
+    }
+
+    #[test]
+    fn test_detect_data_start_single_row_fallback() {
+        let rows: Vec<&[&str]> = vec![&["Ref", "Part"], &["C1", "GRM155R71C104"]];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part"]);
+        assert_eq!(result.row_numbers, vec![2]);
+
+        let tiny = build_bom_rows(
+            to_rows(&[&["C1", "GRM155R71C104"]]),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(tiny.row_numbers, vec![1]);
+    }
+
+    fn detected_roles(rows: &[&[&str]]) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let result = build_bom_rows(to_rows(rows), &ParseOptions::default()).unwrap();
+        let role = |key: &str| result.column_roles.get(key).cloned().unwrap_or_default();
+        (role("ref"), role("part_no"), role("manufacturer"))
+    }
+
+    #[test]
+    fn test_analyze_columns_separates_ref_part_and_value() {
+        // 型番列も Reference らしく、値列も型番らしく見えるヘッダーなしのBOM
+        let (refs, parts, makers) = detected_roles(&[
+            &["C1", "C1608X7R1H104K", "TDK", "0.1uF"],
+            &["C2", "C1608X7R1H104K", "TDK", "0.1uF"],
+            &["C3", "GRM155R71C105KA", "Murata", "1uF"],
+            &["R1", "RC0402FR-0710KL", "Yageo", "10K"],
+            &["R2", "ERJ-2RKF1002X", "Panasonic", "10K"],
+        ]);
+        assert_eq!(refs, vec!["col-0"]);
+        assert_eq!(parts, vec!["col-1"]);
+        assert_eq!(makers, vec!["col-2"]);
+    }
+
+    #[test]
+    fn test_analyze_columns_detects_manufacturer_codes() {
+        // "3M" などのメーカー略称は単体では型番に見える
+        let (refs, parts, makers) = detected_roles(&[
+            &["Ref", "Maker", "Part"],
+            &["J1", "3M", "N2510-6002RB"],
+            &["J2", "3M", "N2510-6002RB"],
+            &["J3", "3M", "N2520-6002RB"],
+            &["U1", "NXP", "PCA9306DCUR"],
+        ]);
+        assert_eq!(refs, vec!["col-0"]);
+        assert_eq!(parts, vec!["col-2"]);
+        assert_eq!(makers, vec!["col-1"]);
+    }
+
+    #[test]
+    fn test_analyze_columns_keeps_real_ambiguity() {
+        // 同じ傾向の型番列が2つある場合は複数候補として警告する
+        let result = build_bom_rows(
+            to_rows(&[
+                &["Ref", "Part A", "Part B"],
+                &["C1", "GRM155R71C104KA", "CL05B104KO5NNNC"],
+                &["C2", "GRM155R71C105KA", "CL05A105KA5NQNC"],
+                &["R1", "RC0402FR-0710KL", "ERJ-2RKF1002X"],
+            ]),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(!result.column_roles.contains_key("part_no"));
+        assert!(result
+            .errors
+            .iter()
+            .any(|message| message.contains(ROLE_AMBIGUOUS_MARKER)));
+    }
+
+    #[test]
+    fn test_build_bom_rows_detects_notes_column() {
+        let rows: Vec<&[&str]> = vec![
+            &["Ref", "Part", "Notes"],
+            &["C1", "GRM155R71C104KA", "DNP"],
+            &["C2", "GRM155R71C104KA", ""],
+            &["R1", "RC0402FR-0710KL", "要確認, 代替可"],
+        ];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.column_roles["comment"], vec!["col-2"]);
+        assert_eq!(result.get_comment(0), "DNP");
+        assert_eq!(result.get_comment(1), "");
+        assert_eq!(result.get_comment(2), "要確認, 代替可");
+    }
+
+    #[test]
+    fn test_build_bom_rows_comment_requires_header() {
+        let rows: Vec<&[&str]> = vec![
+            &["C1", "GRM155R71C104KA", "Notes"],
+            &["C2", "GRM155R71C104KA", "note"],
+            &["R1", "RC0402FR-0710KL", "note"],
+        ];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert!(!result.column_roles.contains_key("comment"));
+    }
+
+    #[test]
+    fn test_build_bom_rows_detects_dnp_column() {
+        let rows: Vec<&[&str]> = vec![
+            &["Ref", "Part No", "DNP", "Notes"],
+            &["C1", "GRM155R71C104KA", "", ""],
+            &["C2", "GRM155R71C104KA", "x", "要確認"],
+            &["R1", "RC0402FR-0710KL", "DNP", ""],
+        ];
+        let result = build_bom_rows(to_rows(&rows), &ParseOptions::default()).unwrap();
+        assert_eq!(result.column_roles["dnp"], vec!["col-2"]);
+        assert_eq!(result.column_roles["comment"], vec!["col-3"]);
+        assert_eq!(result.column_roles["part_no"], vec!["col-1"]);
+        assert!(!result.is_dnp(0));
+        assert!(result.is_dnp(1));
+        assert!(result.is_dnp(2));
+        assert_eq!(result.validation_summary.unwrap().dnp_rows, 2);
+    }
+
+    #[test]
+    fn test_validate_rows_summary() {
+        let rows: Vec<(usize, Vec<String>)> = [
+            ["C1", "GRM155", ""],
+            ["C1", "", "±"],
+            ["", "", ""],
+            ["R2", "RC0402", "µ"],
+        ]
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| (idx + 1, row.iter().map(|cell| cell.to_string()).collect()))
+        .collect();
+
+        let mut errors = Vec::new();
+        let mut structured_errors = Vec::new();
+        let summary = validate_rows(
+            &rows,
+            &[0],
+            &[1],
+            &CharAllowlist::default(),
+            &mut errors,
+            &mut structured_errors,
+        );
+
+        assert_eq!(
+            summary,
+            ValidationSummary {
+                total_rows: 4,
+                missing_reference_rows: 1,
+                duplicate_reference_rows: 1,
+                missing_part_no_rows: 2,
+                invalid_char_rows: 2,
+                dnp_rows: 0,
+            }
+        );
+        // 個別の警告も従来通り残る
+        assert!(structured_errors
+            .iter()
+            .any(|error| error.row == Some(3) && error.message.contains("重複")));
+    }
+
+    fn duplicate_ref_errors(rows: &[[&str; 2]]) -> Vec<ParseError> {
+        let rows: Vec<(usize, Vec<String>)> = rows
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| (idx, row.iter().map(|cell| cell.to_string()).collect()))
+            .collect();
+        let mut errors = Vec::new();
+        let mut structured_errors = Vec::new();
+        let summary = validate_rows(
+            &rows,
+            &[0],
+            &[1],
+            &CharAllowlist::default(),
+            &mut errors,
+            &mut structured_errors,
+        );
+        assert_eq!(summary.duplicate_reference_rows, 1);
+        structured_errors
+            .into_iter()
+            .filter(|error| error.message.contains("重複"))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_rows_duplicate_ref_with_different_part_is_error() {
+        let errors = duplicate_ref_errors(&[["C1", "GRM155"], ["R1", "RC0402"], ["C1", "GRM188"]]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, "error");
+        assert_eq!(errors[0].row, Some(3));
+        assert!(errors[0].message.contains("'GRM155'"));
+        assert!(errors[0].message.contains("'GRM188'"));
+    }
+
+    #[test]
+    fn test_validate_rows_duplicate_ref_with_same_part_is_info() {
+        let errors = duplicate_ref_errors(&[["C1", "GRM155"], ["C1", " GRM155 "]]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, "info");
+        assert_eq!(errors[0].row, Some(2));
+    }
+
+    #[test]
+    fn test_build_bom_rows_attaches_validation_summary() {
+        let result = build_bom_rows(sample_rows(), &ParseOptions::default()).unwrap();
+        let summary = result.validation_summary.unwrap();
+        assert_eq!(summary.total_rows, 2);
+        assert_eq!(summary.invalid_char_rows, 2);
+    }
+
+    #[test]
+    fn test_build_bom_rows_warns_on_non_ascii_by_default() {
+        let result = build_bom_rows(sample_rows(), &ParseOptions::default()).unwrap();
+        assert_eq!(invalid_char_warnings(&result), 2);
+    }
+
+    #[test]
+    fn test_build_bom_rows_allowlisted_chars_do_not_warn() {
+        let options = ParseOptions {
+            allowed_chars: vec!["±".to_string(), "µ".to_string()],
+            ..ParseOptions::default()
+        };
+        let result = build_bom_rows(sample_rows(), &options).unwrap();
+        assert_eq!(invalid_char_warnings(&result), 0);
+        assert_eq!(result.rows.len(), 2);
+    }
+}
@@ -2,27 +2,90 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-use crate::models::{AppError, ColumnMeta, ParseError, ParseResult};
+use crate::models::{AppError, ColumnMeta, EcoTemplate, ParseError, ParseResult};
 
 /// CADネットリスト（PADS-ECO/MSF/CCF/PWS/BD/PADSレポート形式）をパース
 pub fn parse_cad_file(path: &Path) -> Result<ParseResult, AppError> {
     let content = fs::read_to_string(path)
-        .map_err(|e| AppError::new(format!("ファイルの読み込みに失敗しました: {}", e)))?;
+        .map_err(|e| AppError::io(format!("ファイルの読み込みに失敗しました: {}", e)))?;
 
-    // フォーマットを自動判定
+    // 改行コード・BOMを揃えてからフォーマットを自動判定
+    let (content, notes) = normalize_line_endings(&content);
     let format = detect_cad_format(&content)?;
 
-    match format {
+    let mut result = match format {
         CadFormat::PADSECO => parse_pads_eco_format(&content),
         CadFormat::MSF => parse_msf_shape_format(&content),
         CadFormat::CCF => parse_ccf_definition_format(&content),
         CadFormat::PWS => parse_pws_format(&content),
         CadFormat::BD => parse_bd_format(&content),
         CadFormat::PADSReport => parse_pads_report_format(&content),
+    }?;
+
+    for message in notes {
+        result.errors.push(message.clone());
+        result
+            .structured_errors
+            .get_or_insert_with(Vec::new)
+            .push(ParseError {
+                message,
+                row: None,
+                column: None,
+                severity: "info".to_string(),
+            });
+    }
+    Ok(result)
+}
+
+/// 改行コードを "\n" に揃え、BOM（U+FEFF）を取り除く
+///
+/// 先頭のBOMは通常のものとして黙って取り除く。途中のBOM、CRのみの改行、
+/// 改行コードの混在を修正した場合は、その内容を説明するメッセージを返す
+fn normalize_line_endings(content: &str) -> (String, Vec<String>) {
+    let content = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+
+    let mut normalized = String::with_capacity(content.len());
+    let mut stray_boms = 0;
+    let (mut crlf, mut cr, mut lf) = (0, 0, 0);
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{FEFF}' => stray_boms += 1,
+            '\r' => {
+                if chars.next_if_eq(&'\n').is_some() {
+                    crlf += 1;
+                } else {
+                    cr += 1;
+                }
+                normalized.push('\n');
+            }
+            '\n' => {
+                lf += 1;
+                normalized.push('\n');
+            }
+            other => normalized.push(other),
+        }
     }
+
+    let mut notes = Vec::new();
+    if stray_boms > 0 {
+        notes.push(format!(
+            "ファイルの途中にあるBOM（{stray_boms}箇所）を取り除きました。"
+        ));
+    }
+    let kinds = [crlf, cr, lf].iter().filter(|&&count| count > 0).count();
+    if kinds > 1 {
+        notes.push(format!(
+            "改行コードが混在していたため統一しました（CRLF: {crlf}、CR: {cr}、LF: {lf}）。"
+        ));
+    } else if cr > 0 {
+        notes.push("改行コードがCRのみのファイルを読み込みました。".to_string());
+    }
+    (normalized, notes)
 }
 
 #[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
 enum CadFormat {
     PADSECO,    // *PADS-ECO*
     MSF,        // $MSF { SHAPE { ... } }
@@ -32,6 +95,28 @@ enum CadFormat {
     PADSReport, // PADSレポート形式
 }
 
+impl CadFormat {
+    /// 形式名（`detect_format` の戻り値）
+    fn name(&self) -> &'static str {
+        match self {
+            CadFormat::PADSECO => "eco",
+            CadFormat::MSF => "msf",
+            CadFormat::CCF => "ccf",
+            CadFormat::PWS => "pws",
+            CadFormat::BD => "bd",
+            CadFormat::PADSReport => "rpt",
+        }
+    }
+}
+
+/// 内容から判定したCAD形式の名前（"eco", "msf", "ccf", "pws", "bd", "rpt"）
+///
+/// `parse_cad_file` と同じ判定を行う（判定できない場合は "bd"）
+pub fn cad_format_name(content: &str) -> &'static str {
+    let (content, _) = normalize_line_endings(content);
+    detect_cad_format(&content).map_or("bd", |format| format.name())
+}
+
 fn detect_cad_format(content: &str) -> Result<CadFormat, AppError> {
     let trimmed = content.trim();
 
@@ -75,7 +160,7 @@ fn detect_cad_format(content: &str) -> Result<CadFormat, AppError> {
 /// PADS-ECO形式をパース
 ///
 /// # フォーマット例
-/// ```
+/// ```text
 /// *PADS-ECO*
 /// *PART*
 /// C10 0603B104K500CT
@@ -83,41 +168,68 @@ fn detect_cad_format(content: &str) -> Result<CadFormat, AppError> {
 /// IC8 74VHC08FT(BJ)
 /// *END*
 /// ```
+///
+/// 部品データ以外の行（ヘッダーの指示行や `*NET*` セクションなど）は
+/// `eco_template` に保持する
 fn parse_pads_eco_format(content: &str) -> Result<ParseResult, AppError> {
     let mut errors = Vec::new();
     let mut raw_rows = Vec::new();
     let mut in_part_section = false;
     let mut row_num = 0;
+    // 部品データ以外の行（ECO出力時に再現する）
+    let mut template = EcoTemplate::default();
+    let mut after_part_section = false;
 
     for line in content.lines() {
         row_num += 1;
         let trimmed = line.trim();
 
+        // *PART* セクション終了後の行（*NET* セクションなど）はフッターとして保持
+        if after_part_section {
+            template.footer.push(line.trim_end().to_string());
+            if trimmed == "*END*" {
+                break;
+            }
+            continue;
+        }
+
         if trimmed.is_empty() {
+            if !in_part_section {
+                template.header.push(String::new());
+            }
             continue;
         }
 
         // *PART* セクション開始
         if trimmed == "*PART*" {
             in_part_section = true;
+            template.header.push(line.trim_end().to_string());
             continue;
         }
 
-        // *END* セクション終了
-        if trimmed == "*END*" {
-            break;
+        // *END* または他のセクションの開始で *PART* セクション終了
+        if trimmed == "*END*" || (in_part_section && is_eco_section_directive(trimmed)) {
+            after_part_section = true;
+            template.footer.push(line.trim_end().to_string());
+            if trimmed == "*END*" {
+                break;
+            }
+            continue;
         }
 
-        // *PADS-ECO* ヘッダーをスキップ
+        // *PADS-ECO* ヘッダーなどの指示行
         if trimmed.starts_with('*') {
+            if !in_part_section {
+                template.header.push(line.trim_end().to_string());
+            }
             continue;
         }
 
         // *PART* セクション内のみパース
         if !in_part_section {
+            template.header.push(line.trim_end().to_string());
             continue;
         }
-
         // スペース区切りでRef Part_Noを抽出
         let parts: Vec<&str> = trimmed.split_whitespace().collect();
 
@@ -138,7 +250,7 @@ fn parse_pads_eco_format(content: &str) -> Result<ParseResult, AppError> {
     }
 
     if raw_rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "有効な*PART*セクションが見つかりませんでした。".to_string(),
         ));
     }
@@ -181,9 +293,27 @@ fn parse_pads_eco_format(content: &str) -> Result<ParseResult, AppError> {
         ],
         row_numbers: (1..=row_count).collect(),
         structured_errors: Some(errors),
+        validation_summary: None,
+        header_row_index: None,
+        data_start_index: 0,
+        cell_styles: None,
+        eco_template: Some(template),
+        original_rows: None,
     })
 }
 
+/// *PART* セクションを終了させるセクション指示行か（"*NET*", "*SIGNAL* GND" など）
+///
+/// "*REMARK*" は部品データ中のコメントのため含めない
+fn is_eco_section_directive(trimmed: &str) -> bool {
+    let keyword = trimmed.split_whitespace().next().unwrap_or("");
+    keyword.len() > 2
+        && keyword.starts_with('*')
+        && keyword.ends_with('*')
+        && keyword != "*PART*"
+        && keyword != "*REMARK*"
+}
+
 /// MSF SHAPE形式をパース（逆引き構造）
 /// フォーマット例:
 /// $MSF {
@@ -227,70 +357,47 @@ fn parse_reverse_structure(content: &str, section_name: &str) -> Result<ParseRes
     let mut raw_rows = Vec::new();
     let errors = Vec::new();
 
-    // セクションを抽出
-    let section_start = format!("{} {{", section_name);
-    if let Some(start_idx) = content.find(&section_start) {
-        let after_start = &content[start_idx + section_start.len()..];
-
-        // 対応する閉じ括弧を見つける
-        let mut brace_count = 1;
-        let mut end_idx = 0;
-        for (i, c) in after_start.char_indices() {
-            if c == '{' {
-                brace_count += 1;
-            } else if c == '}' {
-                brace_count -= 1;
-                if brace_count == 0 {
-                    end_idx = i;
-                    break;
-                }
-            }
-        }
+    // コメント内の ":" ";" や括弧で誤って分割しないよう、先にコメントを除去する
+    let content = strip_block_comments(content);
 
-        if end_idx == 0 {
-            return Err(AppError::new(format!(
-                "{}セクションの閉じ括弧が見つかりません。",
-                section_name
-            )));
-        }
-
-        let section_content = &after_start[..end_idx];
-
-        // Part_No:Ref1,Ref2; のパターンをパース
-        for group in section_content.split(';') {
-            let group = group.trim();
-            if group.is_empty() {
-                continue;
-            }
+    // セクションを抽出（"SHAPE {" / "DEFINITION{" のように括弧前の空白は任意）
+    let Some(body_start) = find_section_body(&content, section_name) else {
+        return Err(AppError::parse(format!(
+            "{}セクションが見つかりませんでした。",
+            section_name
+        )));
+    };
+    let Some(groups) = split_section_groups(&content[body_start..]) else {
+        return Err(AppError::parse(format!(
+            "{}セクションの閉じ括弧が見つかりません。",
+            section_name
+        )));
+    };
 
-            // Part_No:Refs に分割
-            if let Some(colon_idx) = group.find(':') {
-                let part_no = group[..colon_idx].trim().to_string();
-                let refs_str = group[colon_idx + 1..].trim();
+    // Part_No:Ref1,Ref2; のパターンをパース
+    for group in groups {
+        // Part_No:Refs に分割
+        if let Some(colon_idx) = group.find(':') {
+            let part_no = group[..colon_idx].trim().to_string();
+            let refs_str = group[colon_idx + 1..].trim();
 
-                // Refをカンマ区切りで分割
-                for ref_item in refs_str.split(',') {
-                    let ref_item = ref_item.trim();
-                    if ref_item.is_empty() {
-                        continue;
-                    }
+            // Refをカンマ区切りで分割
+            for ref_item in refs_str.split(',') {
+                let ref_item = ref_item.trim();
+                if ref_item.is_empty() {
+                    continue;
+                }
 
-                    let ref_value = ref_item.to_string();
-                    let part_value = part_no.clone();
+                let ref_value = ref_item.to_string();
+                let part_value = part_no.clone();
 
-                    raw_rows.push(vec![ref_value, part_value]);
-                }
+                raw_rows.push(vec![ref_value, part_value]);
             }
         }
-    } else {
-        return Err(AppError::new(format!(
-            "{}セクションが見つかりませんでした。",
-            section_name
-        )));
     }
 
     if raw_rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "有効なデータが見つかりませんでした。".to_string(),
         ));
     }
@@ -333,12 +440,98 @@ fn parse_reverse_structure(content: &str, section_name: &str) -> Result<ParseRes
         ],
         row_numbers: (1..=row_count).collect(),
         structured_errors: Some(errors),
+        validation_summary: None,
+        header_row_index: None,
+        data_start_index: 0,
+        cell_styles: None,
+        eco_template: None,
+        original_rows: None,
     })
 }
 
+/// `/* ... */` コメントを空白に置き換える（閉じていないコメントは末尾まで）
+fn strip_block_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        result.push(' ');
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => return result,
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// セクション本体（開き括弧の直後から）を ";" 区切りのエントリに分割する
+///
+/// セクション内の入れ子のブロック（`NAME { ... }`）は部品データではないため、
+/// ブロック名を含めて読み飛ばす
+///
+/// # 戻り値
+/// 空でないエントリのリスト（セクションの閉じ括弧がない場合はNone）
+fn split_section_groups(body: &str) -> Option<Vec<String>> {
+    let mut groups = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+
+    for c in body.chars() {
+        match c {
+            '{' => {
+                if depth == 0 {
+                    // 直前のテキストは入れ子ブロックの名前
+                    current.clear();
+                }
+                depth += 1;
+            }
+            '}' if depth == 0 => {
+                // セクションの終わり
+                let group = current.trim();
+                if !group.is_empty() {
+                    groups.push(group.to_string());
+                }
+                return Some(groups);
+            }
+            '}' => depth -= 1,
+            _ if depth > 0 => {}
+            ';' => {
+                let group = current.trim();
+                if !group.is_empty() {
+                    groups.push(group.to_string());
+                }
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+
+    None
+}
+
+/// セクション名に続く開き括弧の直後の位置を返す
+///
+/// セクション名と `{` の間の空白（なし・スペース・改行）は許容する
+fn find_section_body(content: &str, section_name: &str) -> Option<usize> {
+    let mut search_from = 0;
+    while let Some(found) = content[search_from..].find(section_name) {
+        let name_end = search_from + found + section_name.len();
+        let rest = &content[name_end..];
+        let after_space = rest.trim_start();
+        if after_space.starts_with('{') {
+            return Some(name_end + (rest.len() - after_space.len()) + 1);
+        }
+        search_from = name_end;
+    }
+    None
+}
+
 /// PWS形式をパース
 /// フォーマット例:
-/// ```
+/// ```text
 /// /* コメント行 */
 /// 0603B104K500CT:C10,C12;
 /// 74VHC08FT(BJ):IC8,IC9,IC10;
@@ -383,7 +576,7 @@ fn parse_pws_format(content: &str) -> Result<ParseResult, AppError> {
     }
 
     if raw_rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "有効なPWSデータが見つかりませんでした。".to_string(),
         ));
     }
@@ -421,12 +614,18 @@ fn parse_pws_format(content: &str) -> Result<ParseResult, AppError> {
         ],
         row_numbers: (1..=row_count).collect(),
         structured_errors: Some(errors),
+        validation_summary: None,
+        header_row_index: None,
+        data_start_index: 0,
+        cell_styles: None,
+        eco_template: None,
+        original_rows: None,
     })
 }
 
 /// BD形式をパース
 /// フォーマット例:
-/// ```
+/// ```text
 /// C10 0603B104K500CT
 /// C12 0603B104K500CT
 /// IC8 74VHC08FT(BJ)
@@ -464,7 +663,7 @@ fn parse_bd_format(content: &str) -> Result<ParseResult, AppError> {
     }
 
     if raw_rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "有効なBDデータが見つかりませんでした。".to_string(),
         ));
     }
@@ -501,22 +700,32 @@ fn parse_bd_format(content: &str) -> Result<ParseResult, AppError> {
         ],
         row_numbers: (1..=row_count).collect(),
         structured_errors: Some(errors),
+        validation_summary: None,
+        header_row_index: None,
+        data_start_index: 0,
+        cell_styles: None,
+        eco_template: None,
+        original_rows: None,
     })
 }
 
 /// PADSレポート形式をパース
 /// フォーマット例:
-/// ```
+/// ```text
 /// 部品表１レポート
 /// 参照名       型番           登録名
 /// -----------------------------------------
 /// C10          XXX            0603B104K500CT
 /// C12          YYY            0603B104K500CT
 /// ```
+///
+/// 列は罫線（`---`）とヘッダー行から求めた桁位置で切り出す。
+/// 罫線がない場合のみ空白区切りで分割する。
 fn parse_pads_report_format(content: &str) -> Result<ParseResult, AppError> {
     let mut errors = Vec::new();
     let mut raw_rows = Vec::new();
     let mut row_num = 0;
+    let columns = detect_report_columns(content);
 
     for line in content.lines() {
         row_num += 1;
@@ -529,14 +738,18 @@ fn parse_pads_report_format(content: &str) -> Result<ParseResult, AppError> {
         // ヘッダー行をスキップ
         if trimmed.contains("部品表１レポート")
             || trimmed.contains("参照名")
-            || trimmed.starts_with("---")
-            || trimmed.starts_with("===")
+            || is_report_ruler(trimmed)
         {
             continue;
         }
 
-        // スペース区切りで分割
-        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+        let parts: Vec<String> = match &columns {
+            // 桁位置で切り出し（型番などに空白を含む場合も1列として扱う）
+            // 空の列も位置を保つため、参照名は先頭・登録名は最後の列から取る
+            Some(starts) => slice_report_columns(line, starts),
+            // スペース区切りで分割
+            None => trimmed.split_whitespace().map(str::to_string).collect(),
+        };
 
         if parts.len() < 2 {
             errors.push(ParseError {
@@ -548,14 +761,24 @@ fn parse_pads_report_format(content: &str) -> Result<ParseResult, AppError> {
             continue;
         }
 
-        let ref_value = parts[0].to_string();
-        let part_value = parts.last().unwrap().to_string(); // 最終列をPart_Noとする
+        if parts[0].is_empty() {
+            errors.push(ParseError {
+                message: format!("参照名がありません: {}", trimmed),
+                row: Some(row_num),
+                column: None,
+                severity: "warning".to_string(),
+            });
+            continue;
+        }
+
+        let ref_value = parts[0].clone();
+        let part_value = parts[parts.len() - 1].clone(); // 最終列をPart_Noとする
 
         raw_rows.push(vec![ref_value, part_value]);
     }
 
     if raw_rows.is_empty() {
-        return Err(AppError::new(
+        return Err(AppError::parse(
             "有効なPADSレポートデータが見つかりませんでした。".to_string(),
         ));
     }
@@ -592,5 +815,211 @@ fn parse_pads_report_format(content: &str) -> Result<ParseResult, AppError> {
         ],
         row_numbers: (1..=row_count).collect(),
         structured_errors: Some(errors),
+        validation_summary: None,
+        header_row_index: None,
+        data_start_index: 0,
+        cell_styles: None,
+        eco_template: None,
+        original_rows: None,
     })
 }
+
+fn is_report_ruler(trimmed: &str) -> bool {
+    trimmed.starts_with("---") || trimmed.starts_with("===")
+}
+
+/// PADSレポートの列の開始桁（表示幅）を求める
+///
+/// 罫線が空白で区切られている場合は罫線の各区間、
+/// 1本の罫線の場合は直前のヘッダー行の各列名の位置を列の開始とする。
+/// 罫線がない・2列以上を検出できない場合はNone
+fn detect_report_columns(content: &str) -> Option<Vec<usize>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let ruler_idx = lines.iter().position(|line| is_report_ruler(line.trim()))?;
+
+    let ruler_starts = token_starts(lines[ruler_idx]);
+    if ruler_starts.len() >= 2 {
+        return Some(ruler_starts);
+    }
+
+    let header = lines[..ruler_idx]
+        .iter()
+        .rev()
+        .find(|line| !line.trim().is_empty())?;
+    let header_starts = token_starts(header);
+    (header_starts.len() >= 2).then_some(header_starts)
+}
+
+/// 空白で区切られた各トークンの開始桁（表示幅）
+fn token_starts(line: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut position = 0;
+    let mut in_token = false;
+
+    for c in line.chars() {
+        if c.is_whitespace() {
+            in_token = false;
+        } else if !in_token {
+            starts.push(position);
+            in_token = true;
+        }
+        position += display_width(c);
+    }
+
+    starts
+}
+
+/// 行を列の開始桁で切り出す（最後の列は行末まで）
+fn slice_report_columns(line: &str, starts: &[usize]) -> Vec<String> {
+    let mut cells = vec![String::new(); starts.len()];
+    let mut position = 0;
+
+    for c in line.chars() {
+        let column = starts
+            .iter()
+            .rposition(|&start| start <= position)
+            .unwrap_or(0);
+        cells[column].push(c);
+        position += display_width(c);
+    }
+
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+/// 固定幅レポートでの文字の表示幅（全角は2桁）
+fn display_width(c: char) -> usize {
+    if c.is_ascii() || ('\u{FF61}'..='\u{FF9F}').contains(&c) {
+        1
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cad_file_normalizes_line_endings() {
+        let path = std::env::temp_dir().join(format!("bomsync_cad_cr_{}.eco", std::process::id()));
+        fs::write(
+            &path,
+            "\u{FEFF}*PADS-ECO*\r*PART*\rC1 GRM155\r\u{FEFF}R1 RC0402\r*END*\r",
+        )
+        .unwrap();
+        let result = parse_cad_file(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            pairs(&result),
+            vec![
+                ("C1".to_string(), "GRM155".to_string()),
+                ("R1".to_string(), "RC0402".to_string()),
+            ]
+        );
+        let notes: Vec<&ParseError> = result
+            .structured_errors
+            .iter()
+            .flatten()
+            .filter(|error| error.severity == "info")
+            .collect();
+        assert_eq!(notes.len(), 2);
+        assert!(notes[0].message.contains("BOM"));
+        assert!(notes[1].message.contains("CRのみ"));
+
+        let (normalized, notes) = normalize_line_endings("A\r\nB\nC\rD");
+        assert_eq!(normalized, "A\nB\nC\nD");
+        assert!(notes[0].contains("混在"));
+        assert!(normalize_line_endings("A\r\nB\r\n").1.is_empty());
+    }
+
+    fn pairs(result: &ParseResult) -> Vec<(String, String)> {
+        (0..result.rows.len())
+            .map(|idx| (result.get_ref(idx), result.get_part_no(idx)))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_msf_with_comments_and_nested_blocks() {
+        let content = "\
+$MSF {
+  /* 部品定義: 自動生成; 編集しないこと */
+  SHAPE {
+    0603B104K500CT : C10,
+        C12;   /* 1608サイズ {旧} */
+    ATTR { SIZE: 1608; }
+    74VHC08FT(BJ):IC8,
+      /* IC9 は実装しない; */ IC10;
+  }
+}
+";
+        let result = parse_msf_shape_format(content).unwrap();
+        assert_eq!(
+            pairs(&result),
+            vec![
+                ("C10".to_string(), "0603B104K500CT".to_string()),
+                ("C12".to_string(), "0603B104K500CT".to_string()),
+                ("IC8".to_string(), "74VHC08FT(BJ)".to_string()),
+                ("IC10".to_string(), "74VHC08FT(BJ)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ccf_ignores_section_name_in_comment() {
+        let content = "$CCF{ /* DEFINITION{ X:Y; } */ DEFINITION{ GRM155:C1; } NET{ } }";
+        let result = parse_ccf_definition_format(content).unwrap();
+        assert_eq!(
+            pairs(&result),
+            vec![("C1".to_string(), "GRM155".to_string())]
+        );
+
+        assert!(parse_ccf_definition_format("$CCF{ DEFINITION{ GRM155:C1; ").is_err());
+    }
+
+    #[test]
+    fn test_parse_pads_report_fixed_width_columns() {
+        let content = "\
+部品表１レポート
+参照名       型番           登録名
+-----------------------------------------
+C10          GRM 155 X      0603B104K500CT
+IC8          TC 74          74VHC08FT (BJ)
+R1           RC 0402
+             GRM 033        GRM033R61A104KE15
+";
+        let result = parse_pads_report_format(content).unwrap();
+        // 登録名が空の行は型番の列を登録名として扱わない
+        assert_eq!(
+            pairs(&result),
+            vec![
+                ("C10".to_string(), "0603B104K500CT".to_string()),
+                ("IC8".to_string(), "74VHC08FT (BJ)".to_string()),
+                ("R1".to_string(), String::new()),
+            ]
+        );
+        // 参照名が空の行は取り込まずに報告する
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].contains("参照名がありません"));
+    }
+
+    #[test]
+    fn test_parse_pads_report_column_ruler() {
+        let content = "\
+参照名 型番 登録名
+------ -------- --------------
+C10    GRM 155  0603B104K500CT
+";
+        assert_eq!(detect_report_columns(content), Some(vec![0, 7, 16]));
+        let result = parse_pads_report_format(content).unwrap();
+        assert_eq!(result.rows, vec![vec!["C10", "0603B104K500CT"]]);
+    }
+
+    #[test]
+    fn test_parse_pads_report_without_ruler_splits_whitespace() {
+        let content = "参照名 型番 登録名\nC10 XXX 0603B104K500CT\n";
+        assert_eq!(detect_report_columns(content), None);
+        let result = parse_pads_report_format(content).unwrap();
+        assert_eq!(result.rows, vec![vec!["C10", "0603B104K500CT"]]);
+    }
+}
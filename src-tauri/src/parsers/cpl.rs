@@ -0,0 +1,158 @@
+//! ピック＆プレース（CPL・部品実装位置）ファイルの列の判定
+//!
+//! KiCad の `*-top-pos.csv`（Ref, Val, Package, PosX, PosY, Rot, Side）や
+//! Altium・JLCPCB のCPL（Designator, Mid X, Mid Y, Layer, Rotation, Comment）は
+//! 座標の列が値の形から部品記号・型番と誤判定されやすいため、ヘッダー名で役割を決める。
+//! 部品の外形・座標・回転・実装面の列には "package"・"pos_x"・"pos_y"・"rotation"・"layer" の役割を割り当てる。
+
+use std::collections::HashMap;
+
+use crate::utils::header::{matches_ref_header, normalize_header};
+
+/// 座標の列名の接頭辞（"Mid X"、"PosX"、"Center-X(mm)" など）
+const COORDINATE_PREFIXES: &[&str] = &["mid", "pos", "center", "location", "ref", "pad", ""];
+
+/// 座標の列名の末尾の単位
+const UNIT_SUFFIXES: &[&str] = &["mm", "mil"];
+
+/// ヘッダーがCPLの形式であれば、ヘッダー名から列の役割を決める
+///
+/// Referenceの列と、X・Y座標の列がそろっている場合のみCPLとみなす。
+/// 値の列は "Val"・"Value" を優先し、ない場合は "Comment"（Altium）を使う。
+///
+/// # 戻り値
+/// 役割名 → 列ID（CPLでない場合は None）
+pub fn detect_cpl_roles(headers: &[String]) -> Option<HashMap<String, Vec<String>>> {
+    let keys: Vec<String> = headers.iter().map(|header| cpl_key(header)).collect();
+
+    let mut roles: HashMap<String, Vec<String>> = HashMap::new();
+    let mut assign = |role: &str, idx: usize| {
+        roles
+            .entry(role.to_string())
+            .or_insert_with(|| vec![format!("col-{idx}")]);
+    };
+
+    for (idx, key) in keys.iter().enumerate() {
+        if let Some(role) = attribute_role(key) {
+            assign(role, idx);
+        }
+    }
+    let value_idx = keys
+        .iter()
+        .position(|key| key == "val" || key == "value")
+        .or_else(|| keys.iter().position(|key| key == "comment"));
+    if let Some(idx) = value_idx {
+        assign("value", idx);
+    }
+    // "Ref X" などの座標の列はReferenceとみなさない
+    let ref_idx = headers.iter().zip(&keys).position(|(header, key)| {
+        coordinate_axis(key).is_none() && matches_ref_header(&normalize_header(header))
+    })?;
+    assign("ref", ref_idx);
+
+    (roles.contains_key("pos_x") && roles.contains_key("pos_y")).then_some(roles)
+}
+
+/// 英数字以外と末尾の単位を除いた小文字の列名（"Mid X(mm)" → "midx"）
+fn cpl_key(header: &str) -> String {
+    let key: String = header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    UNIT_SUFFIXES
+        .iter()
+        .find_map(|unit| key.strip_suffix(unit).filter(|rest| !rest.is_empty()))
+        .map(str::to_string)
+        .unwrap_or(key)
+}
+
+/// 座標の列であれば軸（'x' または 'y'）
+fn coordinate_axis(key: &str) -> Option<char> {
+    let axis = key.chars().last().filter(|c| matches!(c, 'x' | 'y'))?;
+    let prefix = &key[..key.len() - 1];
+    COORDINATE_PREFIXES.contains(&prefix).then_some(axis)
+}
+
+fn attribute_role(key: &str) -> Option<&'static str> {
+    // "Ref X"・"Pad X" は基準点・パッドの座標のため、部品中心の座標がある場合はそちらが優先される
+    // （同じ役割は最初に見つかった列のみ割り当てる）
+    if !key.starts_with("ref") && !key.starts_with("pad") {
+        match coordinate_axis(key) {
+            Some('x') => return Some("pos_x"),
+            Some('y') => return Some("pos_y"),
+            _ => {}
+        }
+    }
+    match key {
+        "rotation" | "rot" | "angle" => Some("rotation"),
+        "layer" | "side" | "tb" => Some("layer"),
+        "package" | "footprint" | "pattern" => Some("package"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_cpl_roles_kicad() {
+        let roles = detect_cpl_roles(&headers(&[
+            "Ref", "Val", "Package", "PosX", "PosY", "Rot", "Side",
+        ]))
+        .unwrap();
+        let role = |name: &str| roles[name][0].as_str();
+        assert_eq!(role("ref"), "col-0");
+        assert_eq!(role("value"), "col-1");
+        assert_eq!(role("package"), "col-2");
+        assert_eq!(role("pos_x"), "col-3");
+        assert_eq!(role("pos_y"), "col-4");
+        assert_eq!(role("rotation"), "col-5");
+        assert_eq!(role("layer"), "col-6");
+    }
+
+    #[test]
+    fn test_detect_cpl_roles_altium() {
+        let roles = detect_cpl_roles(&headers(&[
+            "Designator",
+            "Footprint",
+            "Mid X",
+            "Mid Y",
+            "Ref X",
+            "Ref Y",
+            "Pad X",
+            "Pad Y",
+            "Layer",
+            "Rotation",
+            "Comment",
+        ]))
+        .unwrap();
+        assert_eq!(roles["ref"], vec!["col-0"]);
+        assert_eq!(roles["pos_x"], vec!["col-2"]);
+        assert_eq!(roles["pos_y"], vec!["col-3"]);
+        assert_eq!(roles["value"], vec!["col-10"]);
+        assert!(!roles.contains_key("part_no"));
+
+        let roles = detect_cpl_roles(&headers(&[
+            "Ref X",
+            "Ref Y",
+            "Designator",
+            "Center-X(mm)",
+            "Center-Y(mm)",
+        ]))
+        .unwrap();
+        assert_eq!(roles["ref"], vec!["col-2"]);
+        assert_eq!(roles["pos_x"], vec!["col-3"]);
+    }
+
+    #[test]
+    fn test_detect_cpl_roles_rejects_plain_bom() {
+        assert!(detect_cpl_roles(&headers(&["Ref", "Part No", "Maker", "Qty"])).is_none());
+        assert!(detect_cpl_roles(&headers(&["Mid X", "Mid Y", "Layer"])).is_none());
+    }
+}
@@ -1,26 +1,245 @@
 use std::path::Path;
 
-use csv::ReaderBuilder;
+use csv::{ErrorKind, ReaderBuilder, StringRecord};
 
-use crate::models::{AppError, ParseResult};
+use crate::models::{AppError, ParseError, ParseOptions, ParseResult};
 
-use super::build_bom_rows;
+use super::build_bom_rows_at;
 
-pub fn parse_csv_file(path: &Path) -> Result<ParseResult, AppError> {
+pub fn parse_csv_file(path: &Path, options: &ParseOptions) -> Result<ParseResult, AppError> {
+    let quote = csv_byte(options.quote, "引用符")?;
+    let escape = options
+        .escape
+        .map(|escape| csv_byte(escape, "エスケープ文字"))
+        .transpose()?;
     let mut reader = ReaderBuilder::new()
         .has_headers(false)
+        .quote(quote)
+        .escape(escape)
+        .double_quote(options.double_quote)
+        .flexible(true)
         .from_path(path)
-        .map_err(|err| AppError::new(format!("CSVの読み込みに失敗しました: {err}")))?;
+        .map_err(|err| AppError::io(format!("CSVの読み込みに失敗しました: {err}")))?;
 
-    let mut rows = Vec::new();
-    for record in reader
-        .records()
-        .map(|result| result.map(|rec| rec.iter().map(|cell| cell.to_string()).collect()))
+    // (元ファイルでの行インデックス, 行)。除いたレコードがあっても行番号がずれないようにする
+    let mut rows: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut record_errors: Vec<ParseError> = Vec::new();
+    let mut expected_len: Option<usize> = None;
+    let mut record = StringRecord::new();
+    loop {
+        match reader.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => {
+                return Err(match err.kind() {
+                    ErrorKind::Utf8 { pos, err: utf8_err } => {
+                        let line = pos.as_ref().map(|pos| pos.line() as usize);
+                        AppError::parse(format!(
+                            "CSVの解析に失敗しました（{}）: 文字コードがUTF-8ではありません。",
+                            location(line, utf8_err.field() + 1)
+                        ))
+                    }
+                    _ => {
+                        let prefix = err
+                            .position()
+                            .map(|pos| format!("（{}行目）", pos.line()))
+                            .unwrap_or_default();
+                        AppError::parse(format!("CSVの解析に失敗しました{prefix}: {err}"))
+                    }
+                });
+            }
+        }
+
+        // 読み飛ばす先頭の行は列数の確認から除く
+        if rows.len() >= options.skip_lines {
+            let expected = *expected_len.get_or_insert(record.len());
+            // 列数が異なる行は読み飛ばしてエラーとして記録し、続きを読み込む
+            if record.len() != expected {
+                record_errors.push(unequal_length_error(&record, expected, options.quote));
+                continue;
+            }
+        }
+        let line_index = record
+            .position()
+            .map(|pos| pos.line() as usize - 1)
+            .unwrap_or(rows.len());
+        rows.push((
+            line_index,
+            record.iter().map(|cell| cell.to_string()).collect(),
+        ));
+    }
+
+    let mut result = build_bom_rows_at(rows, options)?;
+    if !record_errors.is_empty() {
+        let messages = record_errors.iter().map(|error| error.message.clone());
+        result.errors.splice(0..0, messages);
+        result
+            .structured_errors
+            .get_or_insert_with(Vec::new)
+            .splice(0..0, record_errors);
+    }
+    Ok(result)
+}
+
+/// 列数が先頭の行と異なるレコードのエラー
+fn unequal_length_error(record: &StringRecord, expected: usize, quote: char) -> ParseError {
+    let line = record.position().map(|pos| pos.line() as usize);
+    let len = record.len();
+    let column = expected.min(len);
+    let mut message = format!(
+        "CSVの解析に失敗しました（{}）: 列数が{len}列です（先頭の行は{expected}列）。",
+        location(line, column + 1)
+    );
+    if record
+        .iter()
+        .any(|cell| cell.contains('\n') || cell.contains(quote))
     {
-        let row: Vec<String> =
-            record.map_err(|err| AppError::new(format!("CSVの解析に失敗しました: {err}")))?;
-        rows.push(row);
+        message.push_str("引用符が閉じられていない可能性があります。");
     }
+    ParseError {
+        message,
+        row: line,
+        column: Some(column),
+        severity: "error".to_string(),
+    }
+}
+
+/// CSVの区切り設定に使う文字をバイトに変換（ASCII以外はエラー）
+fn csv_byte(value: char, label: &str) -> Result<u8, AppError> {
+    if value.is_ascii() {
+        Ok(value as u8)
+    } else {
+        Err(AppError::validation(format!(
+            "CSVの{label}にはASCII文字を指定してください: '{value}'"
+        )))
+    }
+}
+
+/// エラー位置の表記（"3行目・2列目"、行・列は1始まり）
+fn location(line: Option<usize>, column: usize) -> String {
+    match line {
+        Some(line) => format!("{line}行目・{column}列目"),
+        None => format!("{column}列目"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
 
-    build_bom_rows(rows)
+    fn write_temp_csv(name: &str, content: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("bomsync_csv_{}_{}.csv", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_csv_reports_malformed_record_line() {
+        let path = write_temp_csv(
+            "malformed",
+            "Ref,Part\nC1,GRM155\nR1,RC0402,extra\nU1,74HC08\n",
+        );
+        let result = parse_csv_file(&path, &ParseOptions::default()).unwrap();
+
+        let refs: Vec<&str> = result.rows.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(refs, vec!["C1", "U1"]);
+        // 除いたレコードの後の行も元ファイルの行番号のまま
+        assert_eq!(result.row_numbers, vec![2, 4]);
+        let structured = result.structured_errors.as_ref().unwrap();
+        assert_eq!(structured[0].row, Some(3));
+        assert_eq!(structured[0].column, Some(2));
+        assert_eq!(structured[0].severity, "error");
+        assert!(result.errors[0].contains("3行目・3列目"));
+
+        let path = write_temp_csv("unclosed", "Ref,Part\nC1,GRM155\n\"R1,RC0402\nU1,74HC08\n");
+        let result = parse_csv_file(&path, &ParseOptions::default()).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.row_numbers, vec![2]);
+        let structured = result.structured_errors.as_ref().unwrap();
+        assert_eq!(structured[0].row, Some(3));
+        assert!(structured[0].message.contains("引用符"));
+    }
+
+    #[test]
+    fn test_parse_csv_with_custom_quote_and_escape() {
+        let path = write_temp_csv(
+            "single_quote",
+            "Ref,Part,Value\n'C1,C2',GRM155,'0.1uF, 16V'\nR1,'RC\\'0402',10k\n",
+        );
+        let options = ParseOptions {
+            quote: '\'',
+            escape: Some('\\'),
+            double_quote: false,
+            ..ParseOptions::default()
+        };
+        let result = parse_csv_file(&path, &options).unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                vec!["C1,C2", "GRM155", "0.1uF, 16V"],
+                vec!["R1", "RC'0402", "10k"],
+            ]
+        );
+
+        let result = parse_csv_file(&path, &ParseOptions::default()).unwrap();
+        // 既定の設定ではカンマで分割され、列数が合わない行になる
+        assert_eq!(result.rows.len(), 1);
+        assert!(result.errors[0].contains("2行目"));
+
+        let options = ParseOptions {
+            quote: '”',
+            ..ParseOptions::default()
+        };
+        assert!(parse_csv_file(&path, &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_skip_lines() {
+        let path = write_temp_csv(
+            "banner",
+            "BOM export\nC100,2024-01-01,rev3\nRef,Part\nC1,GRM155\nR1,RC0402\nU1,74HC08\n",
+        );
+        let options = ParseOptions {
+            skip_lines: 2,
+            ..ParseOptions::default()
+        };
+        let result = parse_csv_file(&path, &options).unwrap();
+        assert_eq!(result.headers, vec!["Ref", "Part"]);
+        assert_eq!(result.row_numbers, vec![4, 5, 6]);
+        assert!(!result.errors.iter().any(|error| error.contains("列数")));
+    }
+
+    #[test]
+    fn test_parse_kicad_position_file() {
+        // KiCad の「部品配置ファイル」（CSV、mm単位、両面）の出力
+        let path = write_temp_csv(
+            "kicad_pos",
+            "Ref,Val,Package,PosX,PosY,Rot,Side\n\
+             \"C1\",\"100nF\",\"C_0402_1005Metric\",101.6000,-52.0700,90.0000,top\n\
+             \"C2\",\"10uF\",\"C_0805_2012Metric\",108.2040,-48.2600,180.0000,top\n\
+             \"R1\",\"10k\",\"R_0402_1005Metric\",95.2500,-60.9600,0.0000,top\n\
+             \"U1\",\"STM32F103C8Tx\",\"LQFP-48_7x7mm_P0.5mm\",120.0000,-55.0000,45.0000,top\n\
+             \"D1\",\"LED\",\"LED_0603_1608Metric\",90.1700,-45.7200,270.0000,bottom\n",
+        );
+        let result = parse_csv_file(&path, &ParseOptions::default()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let role = |name: &str| result.column_roles.get(name).cloned().unwrap_or_default();
+        assert_eq!(role("ref"), vec!["col-0"]);
+        assert_eq!(role("value"), vec!["col-1"]);
+        assert_eq!(role("package"), vec!["col-2"]);
+        assert_eq!(role("pos_x"), vec!["col-3"]);
+        assert_eq!(role("pos_y"), vec!["col-4"]);
+        assert_eq!(role("rotation"), vec!["col-5"]);
+        assert_eq!(role("layer"), vec!["col-6"]);
+        assert_eq!(result.rows.len(), 5);
+        assert_eq!(result.get_ref(0), "C1");
+        assert!(!result
+            .structured_errors
+            .iter()
+            .flatten()
+            .any(|error| error.severity == "warning"));
+    }
 }
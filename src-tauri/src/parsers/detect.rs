@@ -0,0 +1,189 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::models::AppError;
+
+use super::cad::cad_format_name;
+use super::excel::{EXCEL_EXTENSIONS, OLE_SIGNATURE};
+
+/// 内容から形式を判定するときに読み込む先頭のバイト数
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// 内容から形式を判定するときに見る先頭の行数（空行を除く）
+const SNIFF_LINES: usize = 20;
+
+/// ZIP（.xlsx/.xlsm/.xlsb）の先頭のシグネチャ
+const ZIP_SIGNATURE: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+
+/// 内容で形式を判定するCADネットリストの拡張子
+const CAD_EXTENSIONS: &[&str] = &["eco", "ccf", "msf", "net", "pws", "bd", "rpt"];
+
+/// ファイルの形式を判定する
+///
+/// CSV・Excel・JSONの拡張子はそのまま使い、CADネットリストの拡張子は
+/// `parse_cad_file` と同じく内容から形式を判定する。
+/// 拡張子がない・未知・".txt" の場合は先頭の内容から判定する。
+///
+/// # 戻り値
+/// 形式名（"csv", "xlsx", "xls", "json", "eco", "msf", "ccf", "pws", "bd", "rpt" など）。
+/// ファイルがない場合や、内容から判定できない場合はエラー
+pub fn detect_format(path: &Path) -> Result<String, AppError> {
+    if !path.exists() {
+        return Err(AppError::not_found(format!(
+            "ファイルが見つかりません: {}",
+            path.display()
+        )));
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    match ext.as_deref() {
+        Some("csv") => Ok("csv".to_string()),
+        Some("json") => Ok("json".to_string()),
+        Some(ext) if EXCEL_EXTENSIONS.contains(&ext) => Ok(ext.to_string()),
+        Some(ext) if CAD_EXTENSIONS.contains(&ext) => {
+            let head = read_head(path)?;
+            Ok(cad_format_name(&String::from_utf8_lossy(&head)).to_string())
+        }
+        _ => {
+            let head = read_head(path)?;
+            sniff_format(&head).map(str::to_string).ok_or_else(|| {
+                AppError::unsupported_format(format!(
+                    "ファイルの形式を内容から判定できませんでした: {}",
+                    path.display()
+                ))
+            })
+        }
+    }
+}
+
+fn read_head(path: &Path) -> Result<Vec<u8>, AppError> {
+    let mut head = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(SNIFF_BYTES).read_to_end(&mut head))
+        .map_err(|err| AppError::io(format!("ファイルの読み込みに失敗しました: {err}")))?;
+    Ok(head)
+}
+
+/// ファイルの先頭の内容から形式を判定する（判定できない場合は None）
+///
+/// CADネットリストの目印（`*PADS-ECO*`・`$MSF`・`$CCF` など）を優先し、
+/// 目印がない場合はカンマ区切りの列数がそろっていればCSV、
+/// 空白区切りで2項目以上並んでいればBDとみなす
+pub fn sniff_format(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(&ZIP_SIGNATURE) {
+        return Some("xlsx");
+    }
+    if head.starts_with(&OLE_SIGNATURE) {
+        return Some("xls");
+    }
+    if head.contains(&0) {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(head);
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(&text);
+    if text.trim_start().starts_with('{') {
+        return Some("json");
+    }
+
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    match cad_format_name(text) {
+        "pws" if looks_like_pws(&lines) => Some("pws"),
+        "pws" | "bd" if looks_like_csv(&lines) => Some("csv"),
+        "pws" => Some("pws"),
+        "bd" if looks_like_bd(&lines) => Some("bd"),
+        "bd" => None,
+        other => Some(other),
+    }
+}
+
+/// 全ての行が "型番:Ref,...;" の形
+fn looks_like_pws(lines: &[&str]) -> bool {
+    !lines.is_empty()
+        && lines
+            .iter()
+            .all(|line| line.contains(':') && line.ends_with(';'))
+}
+
+/// 全ての行にカンマがあり、先頭行と同じ列数の行が半数以上
+fn looks_like_csv(lines: &[&str]) -> bool {
+    let counts: Vec<usize> = lines.iter().map(|line| line.matches(',').count()).collect();
+    let Some(&first) = counts.first() else {
+        return false;
+    };
+    if counts.contains(&0) {
+        return false;
+    }
+    let same = counts[1..].iter().filter(|&&count| count == first).count();
+    counts.len() == 1 || same * 2 >= counts.len() - 1
+}
+
+/// 全ての行が空白区切りで2項目以上
+fn looks_like_bd(lines: &[&str]) -> bool {
+    !lines.is_empty()
+        && lines
+            .iter()
+            .all(|line| line.split_whitespace().count() >= 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_format() {
+        assert_eq!(
+            sniff_format(b"*PADS-ECO*\n*PART*\nC1 GRM155\n*END*\n"),
+            Some("eco")
+        );
+        assert_eq!(sniff_format(b"$MSF\n{ SHAPE { } }\n"), Some("msf"));
+        assert_eq!(sniff_format(b"$CCF{\nDEFINITION{ }\n}\n"), Some("ccf"));
+        assert_eq!(sniff_format(b"74HC08:U1,U2;\nGRM155:C1;\n"), Some("pws"));
+        assert_eq!(
+            sniff_format("\u{FEFF}Ref,Part,Note\nC1,GRM155,a:b;\nR1,RC0402,\n".as_bytes()),
+            Some("csv")
+        );
+        assert_eq!(sniff_format(b"C1 GRM155\nR1 RC0402\n"), Some("bd"));
+        assert_eq!(sniff_format(br#"{"headers": []}"#), Some("json"));
+        assert_eq!(sniff_format(&[0x50, 0x4B, 0x03, 0x04, 0x14]), Some("xlsx"));
+        assert_eq!(sniff_format(&OLE_SIGNATURE), Some("xls"));
+        assert_eq!(sniff_format(b"dummy"), None);
+        assert_eq!(sniff_format(b"%PDF-1.4\n\x00\x01"), None);
+        assert_eq!(sniff_format(b""), None);
+    }
+
+    #[test]
+    fn test_detect_format_prefers_known_extension() {
+        let dir = std::env::temp_dir().join(format!("bomsync_detect_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+
+        // 既知の拡張子は内容より優先
+        let path = write("parts.csv", "C1 GRM155\n");
+        assert_eq!(detect_format(&path).unwrap(), "csv");
+        // CADの拡張子は内容から形式を判定
+        let path = write("board.net", "*PADS-ECO*\n*PART*\nC1 GRM155\n*END*\n");
+        assert_eq!(detect_format(&path).unwrap(), "eco");
+        // 拡張子なし・.txt は内容から判定
+        let path = write("dump", "Ref,Part\nC1,GRM155\n");
+        assert_eq!(detect_format(&path).unwrap(), "csv");
+        let path = write("netlist.txt", "$MSF\n{ SHAPE { } }\n");
+        assert_eq!(detect_format(&path).unwrap(), "msf");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
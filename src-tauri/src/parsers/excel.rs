@@ -1,34 +1,172 @@
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
-use calamine::{open_workbook_auto, DataType, Reader};
+use calamine::{open_workbook_auto, DataType, Reader, XlsError, XlsxError};
 
-use crate::models::{AppError, ParseResult};
+use crate::models::{AppError, CellStyle, ParseError, ParseOptions, ParseResult};
 
 use super::build_bom_rows;
+use super::excel_styles::{read_first_sheet_styles, CellStyleMap};
 
-pub fn parse_excel_file(path: &Path) -> Result<ParseResult, AppError> {
-    let mut workbook = open_workbook_auto(path)
-        .map_err(|err| AppError::new(format!("Excelファイルの読み込みに失敗しました: {err}")))?;
+/// Excel形式として読み込む拡張子（小文字）
+pub const EXCEL_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xlsb", "xls"];
+
+/// OLE複合ドキュメント（.xls、暗号化されたExcelファイル）の先頭のシグネチャ
+pub const OLE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Excelファイル（.xlsx/.xlsm/.xlsb/.xls）の最初のワークシートを読み込む
+pub fn parse_excel_file(path: &Path, options: &ParseOptions) -> Result<ParseResult, AppError> {
+    let mut workbook = open_workbook_auto(path).map_err(|err| open_error(path, err))?;
 
     let range = workbook
         .worksheet_range_at(0)
-        .ok_or_else(|| AppError::new("ワークシートが見つかりませんでした。"))?
-        .map_err(|err| AppError::new(format!("ワークシートの解析に失敗しました: {err}")))?;
+        .ok_or_else(|| AppError::parse("ワークシートが見つかりませんでした。"))?
+        .map_err(|err| AppError::parse(format!("ワークシートの解析に失敗しました: {err}")))?;
 
-    let rows = range
+    let rows: Vec<Vec<String>> = range
         .rows()
         .map(|row| row.iter().map(data_type_to_string).collect())
         .collect();
+    let numeric_cells: Vec<Vec<bool>> = range
+        .rows()
+        .map(|row| row.iter().map(is_numeric_cell).collect())
+        .collect();
+
+    // セル書式はスタイル情報を読み込めた場合のみ付与（値の読み込みには影響させない）
+    let styles = read_first_sheet_styles(path);
+    let origin = range.start().unwrap_or((0, 0));
+    let style_grid = styles.map(|styles| build_style_grid(&rows, &styles, origin));
+
+    let mut result = build_bom_rows(rows, options)?;
+
+    // データ行（row_numbers は渡した行の1始まりの位置）に合わせて書式を抽出
+    result.cell_styles = style_grid.map(|grid| {
+        result
+            .row_numbers
+            .iter()
+            .map(|&row_number| grid.get(row_number - 1).cloned().unwrap_or_default())
+            .collect()
+    });
+    append_numeric_part_notes(&mut result, &numeric_cells);
+
+    Ok(result)
+}
+
+/// 部品型番列に数値として保存されたセルがある場合に注意を追加
+///
+/// "0603" のような型番は数値として保存されると先頭の0が失われ、読み込み時には復元できない。
+///
+/// # 引数
+/// * `numeric_cells` - ワークシートの行データと同じ並びの、数値セルかどうかのグリッド
+fn append_numeric_part_notes(result: &mut ParseResult, numeric_cells: &[Vec<bool>]) {
+    for col_idx in result.get_column_indices("part_no") {
+        let numeric_rows: Vec<usize> = result
+            .row_numbers
+            .iter()
+            .copied()
+            .filter(|&row_number| {
+                numeric_cells
+                    .get(row_number - 1)
+                    .and_then(|row| row.get(col_idx))
+                    .copied()
+                    .unwrap_or(false)
+            })
+            .collect();
+        let Some(&first_row) = numeric_rows.first() else {
+            continue;
+        };
+
+        let header = result.headers.get(col_idx).cloned().unwrap_or_default();
+        let message = format!(
+            "部品型番列「{header}」に数値として保存されたセルが{}件あります（{first_row}行目など）。先頭の0などが失われている可能性があるため、Excelで列の書式を文字列にして保存し直してください。",
+            numeric_rows.len()
+        );
+        result.errors.push(message.clone());
+        result
+            .structured_errors
+            .get_or_insert_with(Vec::new)
+            .push(ParseError {
+                message,
+                row: Some(first_row),
+                column: Some(col_idx),
+                severity: "info".to_string(),
+            });
+    }
+}
+
+/// ワークブックを開けなかった理由をエラーに変換
+fn open_error(path: &Path, err: calamine::Error) -> AppError {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    let password_protected = match &err {
+        calamine::Error::Xls(XlsError::Password) | calamine::Error::Xlsx(XlsxError::Password) => {
+            true
+        }
+        // .xlsx/.xlsm/.xlsb は本来ZIP形式で、暗号化されている場合のみOLE形式になる
+        _ => ext != "xls" && is_ole_file(path),
+    };
+    if password_protected {
+        return AppError::unsupported_format(format!(
+            "Excelファイル（.{ext}）はパスワードで保護されているため読み込めません。パスワードを解除して保存し直してください。"
+        ));
+    }
+
+    AppError::parse(format!(
+        "Excelファイル（.{ext}）の読み込みに失敗しました: {err}"
+    ))
+}
+
+/// ファイルがOLE複合ドキュメント形式か
+fn is_ole_file(path: &Path) -> bool {
+    let mut signature = [0u8; 8];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut signature))
+        .is_ok_and(|_| signature == OLE_SIGNATURE)
+}
 
-    build_bom_rows(rows)
+/// 行データと同じ並びの書式グリッドを作成
+///
+/// # 引数
+/// * `rows` - ワークシートの行データ
+/// * `styles` - シート上の位置 → 書式
+/// * `origin` - 行データの先頭セルのシート上の位置（行, 列）
+fn build_style_grid(
+    rows: &[Vec<String>],
+    styles: &CellStyleMap,
+    origin: (u32, u32),
+) -> Vec<Vec<CellStyle>> {
+    rows.iter()
+        .enumerate()
+        .map(|(row_idx, row)| {
+            (0..row.len())
+                .map(|col_idx| {
+                    let position = (origin.0 + row_idx as u32, origin.1 + col_idx as u32);
+                    styles.get(&position).cloned().unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect()
 }
 
+fn is_numeric_cell(cell: &DataType) -> bool {
+    matches!(cell, DataType::Int(_) | DataType::Float(_))
+}
+
+/// セルの値を文字列に変換
+///
+/// 文字列として保存されたセルはそのまま使う。数値は指数表記（"1.00E+05"）にせず、
+/// 整数値は小数点なしの整数として出力する。
 fn data_type_to_string(cell: &DataType) -> String {
     match cell {
         DataType::Empty => String::new(),
         DataType::String(s) => s.trim().to_string(),
         DataType::Float(f) => {
-            if f.fract().abs() < f64::EPSILON {
+            if f.fract() == 0.0 {
                 format!("{:.0}", f)
             } else {
                 f.to_string()
@@ -41,3 +179,316 @@ fn data_type_to_string(cell: &DataType) -> String {
         _ => cell.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ErrorKind;
+    use crate::parsers::parse_bom_file;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use zip::write::FileOptions;
+
+    const ROWS: [[&str; 2]; 3] = [
+        ["Ref", "Part No"],
+        ["C1", "GRM155R71C104KA88D"],
+        ["R1", "RC0402FR-0710KL"],
+    ];
+
+    fn fixture_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bomsync_excel_{}_{}", std::process::id(), name))
+    }
+
+    fn write_fixture(name: &str, content: &[u8]) -> String {
+        let path = fixture_path(name);
+        std::fs::write(&path, content).unwrap();
+        path.display().to_string()
+    }
+
+    /// BIFF8のレコード（種類・長さ・データ）を追加
+    fn biff_record(out: &mut Vec<u8>, typ: u16, data: &[u8]) {
+        out.extend_from_slice(&typ.to_le_bytes());
+        out.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        out.extend_from_slice(data);
+    }
+
+    fn biff_bof(out: &mut Vec<u8>, sheet_type: u16) {
+        let mut data = vec![0u8; 16];
+        data[..2].copy_from_slice(&0x0600u16.to_le_bytes());
+        data[2..4].copy_from_slice(&sheet_type.to_le_bytes());
+        biff_record(out, 0x0809, &data);
+    }
+
+    /// 1シートの .xls の Workbook ストリーム（`password` の場合はFilePassレコードを含む）
+    fn xls_workbook_stream(password: bool) -> Vec<u8> {
+        let mut stream = Vec::new();
+        biff_bof(&mut stream, 0x0005);
+        if password {
+            biff_record(&mut stream, 0x002F, &[1, 0, 1, 0, 1, 0]);
+        }
+        let bound_sheet_pos = stream.len() + 4;
+        let mut bound_sheet = vec![0u8; 6];
+        bound_sheet.extend_from_slice(&[6, 0]);
+        bound_sheet.extend_from_slice(b"Sheet1");
+        biff_record(&mut stream, 0x0085, &bound_sheet);
+        biff_record(&mut stream, 0x000A, &[]);
+
+        let sheet_pos = stream.len() as u32;
+        stream[bound_sheet_pos..bound_sheet_pos + 4].copy_from_slice(&sheet_pos.to_le_bytes());
+        biff_bof(&mut stream, 0x0010);
+        for (row_idx, row) in ROWS.iter().enumerate() {
+            for (col_idx, value) in row.iter().enumerate() {
+                let mut label = Vec::new();
+                label.extend_from_slice(&(row_idx as u16).to_le_bytes());
+                label.extend_from_slice(&(col_idx as u16).to_le_bytes());
+                label.extend_from_slice(&[0, 0]);
+                label.extend_from_slice(&(value.len() as u16).to_le_bytes());
+                label.push(0);
+                label.extend_from_slice(value.as_bytes());
+                biff_record(&mut stream, 0x0204, &label);
+            }
+        }
+        biff_record(&mut stream, 0x000A, &[]);
+        stream
+    }
+
+    /// ストリームを1つだけ持つOLE複合ドキュメント（512バイトセクタ、ミニストリームなし）
+    fn ole_file(stream_name: &str, stream: &[u8]) -> Vec<u8> {
+        const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+        const FREE_SECTOR: u32 = 0xFFFF_FFFF;
+
+        // 4096バイト未満のストリームはミニストリームに置かれるため、それ以上に揃える
+        let mut data = stream.to_vec();
+        data.resize(data.len().max(4096).div_ceil(512) * 512, 0);
+        let stream_sectors = data.len() / 512;
+
+        let mut header = vec![0u8; 512];
+        header[..8].copy_from_slice(&OLE_SIGNATURE);
+        header[24..26].copy_from_slice(&0x003Eu16.to_le_bytes());
+        header[26..28].copy_from_slice(&3u16.to_le_bytes());
+        header[28..30].copy_from_slice(&0xFFFEu16.to_le_bytes());
+        header[30..32].copy_from_slice(&9u16.to_le_bytes());
+        header[32..34].copy_from_slice(&6u16.to_le_bytes());
+        header[44..48].copy_from_slice(&1u32.to_le_bytes());
+        header[48..52].copy_from_slice(&1u32.to_le_bytes());
+        header[56..60].copy_from_slice(&4096u32.to_le_bytes());
+        header[60..64].copy_from_slice(&END_OF_CHAIN.to_le_bytes());
+        header[68..72].copy_from_slice(&END_OF_CHAIN.to_le_bytes());
+        for (idx, chunk) in header[76..].chunks_mut(4).enumerate() {
+            let sector: u32 = if idx == 0 { 0 } else { FREE_SECTOR };
+            chunk.copy_from_slice(&sector.to_le_bytes());
+        }
+
+        // セクタ0: FAT、セクタ1: ディレクトリ、セクタ2以降: ストリーム
+        let mut fat = vec![0xFFFF_FFFDu32, END_OF_CHAIN];
+        fat.extend((0..stream_sectors as u32).map(|idx| {
+            if idx + 1 == stream_sectors as u32 {
+                END_OF_CHAIN
+            } else {
+                idx + 3
+            }
+        }));
+        fat.resize(128, FREE_SECTOR);
+
+        let directory_entry = |name: &str, entry_type: u8, child: u32, start: u32, len: u32| {
+            let mut entry = vec![0u8; 128];
+            let name: Vec<u16> = name.encode_utf16().collect();
+            for (idx, unit) in name.iter().enumerate() {
+                entry[idx * 2..idx * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+            }
+            entry[64..66].copy_from_slice(&((name.len() as u16 + 1) * 2).to_le_bytes());
+            entry[66] = entry_type;
+            entry[67] = 1;
+            entry[68..72].copy_from_slice(&FREE_SECTOR.to_le_bytes());
+            entry[72..76].copy_from_slice(&FREE_SECTOR.to_le_bytes());
+            entry[76..80].copy_from_slice(&child.to_le_bytes());
+            entry[116..120].copy_from_slice(&start.to_le_bytes());
+            entry[120..124].copy_from_slice(&len.to_le_bytes());
+            entry
+        };
+        let mut directory = directory_entry("Root Entry", 5, 1, END_OF_CHAIN, 0);
+        directory.extend(directory_entry(
+            stream_name,
+            2,
+            FREE_SECTOR,
+            2,
+            data.len() as u32,
+        ));
+        directory.resize(512, 0);
+
+        let mut file = header;
+        file.extend(fat.iter().flat_map(|entry| entry.to_le_bytes()));
+        file.extend(directory);
+        file.extend(data);
+        file
+    }
+
+    /// XLSBのレコード（可変長の種類・長さとデータ）を追加
+    fn xlsb_record(out: &mut Vec<u8>, typ: u16, data: &[u8]) {
+        if typ < 0x80 {
+            out.push(typ as u8);
+        } else {
+            out.extend_from_slice(&[0x80 | (typ & 0x7F) as u8, (typ >> 7) as u8]);
+        }
+        let mut len = data.len();
+        loop {
+            let byte = (len & 0x7F) as u8;
+            len >>= 7;
+            if len == 0 {
+                out.push(byte);
+                break;
+            }
+            out.push(byte | 0x80);
+        }
+        out.extend_from_slice(data);
+    }
+
+    fn xlsb_wide_str(value: &str) -> Vec<u8> {
+        let units: Vec<u16> = value.encode_utf16().collect();
+        let mut data = (units.len() as u32).to_le_bytes().to_vec();
+        data.extend(units.iter().flat_map(|unit| unit.to_le_bytes()));
+        data
+    }
+
+    /// 1シートの .xlsb（ZIP内のバイナリレコード）
+    fn xlsb_file() -> Vec<u8> {
+        let mut workbook = Vec::new();
+        let mut bundle_sheet = vec![0u8; 8];
+        bundle_sheet.extend(xlsb_wide_str("rId1"));
+        bundle_sheet.extend(xlsb_wide_str("Sheet1"));
+        xlsb_record(&mut workbook, 0x009C, &bundle_sheet);
+        xlsb_record(&mut workbook, 0x0090, &[]);
+        xlsb_record(&mut workbook, 0x009D, &[]);
+
+        let mut sheet = Vec::new();
+        let mut dimensions = Vec::new();
+        for value in [0u32, ROWS.len() as u32 - 1, 0, 1] {
+            dimensions.extend_from_slice(&value.to_le_bytes());
+        }
+        xlsb_record(&mut sheet, 0x0094, &dimensions);
+        xlsb_record(&mut sheet, 0x0091, &[]);
+        for (row_idx, row) in ROWS.iter().enumerate() {
+            xlsb_record(&mut sheet, 0x0000, &(row_idx as u32).to_le_bytes());
+            for (col_idx, value) in row.iter().enumerate() {
+                let mut cell = (col_idx as u32).to_le_bytes().to_vec();
+                cell.extend_from_slice(&[0, 0, 0, 0]);
+                cell.extend(xlsb_wide_str(value));
+                xlsb_record(&mut sheet, 0x0006, &cell);
+            }
+        }
+        xlsb_record(&mut sheet, 0x0092, &[]);
+
+        let relationships = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Target="worksheets/sheet1.bin"/>
+</Relationships>"#;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in [
+            ("xl/workbook.bin", workbook.as_slice()),
+            ("xl/_rels/workbook.bin.rels", relationships.as_bytes()),
+            ("xl/worksheets/sheet1.bin", sheet.as_slice()),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_parse_xls_and_xlsb() {
+        let fixtures = [
+            ("bom.xls", ole_file("Workbook", &xls_workbook_stream(false))),
+            ("bom.xlsb", xlsb_file()),
+        ];
+
+        for (name, content) in fixtures {
+            let path = write_fixture(name, &content);
+            let result = parse_bom_file(path).unwrap();
+            assert_eq!(result.headers, vec!["Ref", "Part No"], "{name}");
+            assert_eq!(
+                result.rows,
+                vec![
+                    vec!["C1".to_string(), "GRM155R71C104KA88D".to_string()],
+                    vec!["R1".to_string(), "RC0402FR-0710KL".to_string()],
+                ],
+                "{name}"
+            );
+            assert_eq!(result.get_part_no(0), "GRM155R71C104KA88D", "{name}");
+        }
+    }
+
+    #[test]
+    fn test_password_protected_workbooks() {
+        let fixtures = [
+            (
+                "locked.xls",
+                ole_file("Workbook", &xls_workbook_stream(true)),
+            ),
+            ("locked.xlsx", ole_file("EncryptedPackage", &[0u8; 64])),
+            ("locked.xlsb", ole_file("EncryptedPackage", &[0u8; 64])),
+        ];
+
+        for (name, content) in fixtures {
+            let error = parse_bom_file(write_fixture(name, &content)).unwrap_err();
+            assert_eq!(error.kind, ErrorKind::UnsupportedFormat, "{name}");
+            assert!(
+                error.message.contains("パスワード"),
+                "{name}: {}",
+                error.message
+            );
+        }
+
+        // パスワード保護ではない壊れたファイルは解析エラー
+        let error = parse_bom_file(write_fixture("broken.xls", b"not an excel file")).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Parse);
+        assert!(error.message.contains(".xls"));
+    }
+
+    #[test]
+    fn test_data_type_to_string_keeps_part_numbers() {
+        // 文字列として保存された "0603" はそのまま
+        assert_eq!(
+            data_type_to_string(&DataType::String("0603".to_string())),
+            "0603"
+        );
+        // 数値として保存された場合は先頭の0が失われるが、整数として出力する
+        assert_eq!(data_type_to_string(&DataType::Float(603.0)), "603");
+        assert_eq!(data_type_to_string(&DataType::Int(603)), "603");
+        // 指数表記（1.00E+05）で表示されるセルも整数として出力する
+        assert_eq!(data_type_to_string(&DataType::Float(1.00E+05)), "100000");
+        assert_eq!(data_type_to_string(&DataType::Float(1.5)), "1.5");
+    }
+
+    #[test]
+    fn test_append_numeric_part_notes() {
+        let mut result = ParseResult::builder()
+            .rows(vec![
+                vec!["C1".to_string(), "603".to_string()],
+                vec!["C2".to_string(), "GRM155".to_string()],
+                vec!["C3".to_string(), "100000".to_string()],
+            ])
+            .row_numbers(vec![2, 3, 4])
+            .build();
+        result.headers = vec!["Ref".to_string(), "Part".to_string()];
+        result.rebuild_columns_from_headers();
+        result
+            .column_roles
+            .insert("part_no".to_string(), vec!["col-1".to_string()]);
+        let numeric_cells = vec![
+            vec![false, false],
+            vec![false, true],
+            vec![false, false],
+            vec![false, true],
+        ];
+
+        append_numeric_part_notes(&mut result, &numeric_cells);
+        let notes = result.structured_errors.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].severity, "info");
+        assert_eq!(notes[0].row, Some(2));
+        assert_eq!(notes[0].column, Some(1));
+        assert!(notes[0].message.contains("2件"));
+    }
+}
@@ -0,0 +1,313 @@
+//! xlsx のスタイル情報（塗りつぶし色・取り消し線）の読み込み
+//!
+//! calamine はセルの値のみを返すため、xlsx（zip）内の
+//! `xl/styles.xml` と先頭シートのXMLを直接読み込んでセルごとの書式を取得する。
+//! 読み込めない場合は None を返し、値の読み込みには影響させない。
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+use crate::models::CellStyle;
+
+/// セル位置（0始まりの行, 列） → 書式
+pub type CellStyleMap = HashMap<(u32, u32), CellStyle>;
+
+/// 先頭シートのセル書式を読み込む
+///
+/// # 戻り値
+/// 既定以外の書式を持つセルのマップ（スタイル情報がない・読み込めない場合はNone）
+pub fn read_first_sheet_styles(path: &Path) -> Option<CellStyleMap> {
+    let file = File::open(path).ok()?;
+    let mut archive = ZipArchive::new(file).ok()?;
+
+    let styles = parse_styles(&read_entry(&mut archive, "xl/styles.xml")?);
+    let sheet_path =
+        first_sheet_path(&mut archive).unwrap_or_else(|| "xl/worksheets/sheet1.xml".to_string());
+    let sheet = read_entry(&mut archive, &sheet_path)?;
+
+    Some(parse_sheet_styles(&sheet, &styles))
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content).ok()?;
+    Some(content)
+}
+
+/// workbook.xml とそのリレーションから先頭シートのパスを求める
+fn first_sheet_path(archive: &mut ZipArchive<File>) -> Option<String> {
+    let workbook = read_entry(archive, "xl/workbook.xml")?;
+    let rels = read_entry(archive, "xl/_rels/workbook.xml.rels")?;
+
+    let mut sheet_rel_id = None;
+    for_each_element(&workbook, |element, _| {
+        if sheet_rel_id.is_none() && element.local_name().as_ref() == b"sheet" {
+            sheet_rel_id = attribute(element, b"r:id");
+        }
+    });
+    let sheet_rel_id = sheet_rel_id?;
+
+    let mut target = None;
+    for_each_element(&rels, |element, _| {
+        if element.local_name().as_ref() == b"Relationship"
+            && attribute(element, b"Id").as_deref() == Some(sheet_rel_id.as_str())
+        {
+            target = attribute(element, b"Target");
+        }
+    });
+
+    target.map(|target| match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", target),
+    })
+}
+
+/// styles.xml から解析した書式テーブル
+#[derive(Default)]
+struct StyleTable {
+    /// フォントごとの取り消し線
+    font_strike: Vec<bool>,
+    /// 塗りつぶしごとの色（"#RRGGBB"）
+    fill_colors: Vec<Option<String>>,
+    /// セル書式（fontId, fillId）
+    cell_xfs: Vec<(usize, usize)>,
+}
+
+impl StyleTable {
+    fn style_for(&self, xf_index: usize) -> CellStyle {
+        let Some(&(font_id, fill_id)) = self.cell_xfs.get(xf_index) else {
+            return CellStyle::default();
+        };
+        CellStyle {
+            fill_color: self.fill_colors.get(fill_id).cloned().flatten(),
+            strikethrough: self.font_strike.get(font_id).copied().unwrap_or(false),
+        }
+    }
+}
+
+fn parse_styles(xml: &str) -> StyleTable {
+    let mut table = StyleTable::default();
+    let mut pattern_is_none = false;
+
+    for_each_element(xml, |element, parents| {
+        let name = element.local_name();
+        let parent = parents.last().map(Vec::as_slice);
+        let grandparent = parents
+            .len()
+            .checked_sub(2)
+            .map(|idx| parents[idx].as_slice());
+
+        match (name.as_ref(), parent, grandparent) {
+            (b"font", Some(b"fonts"), _) => table.font_strike.push(false),
+            (b"strike", Some(b"font"), Some(b"fonts")) => {
+                let enabled = !matches!(attribute(element, b"val").as_deref(), Some("0" | "false"));
+                if let Some(strike) = table.font_strike.last_mut() {
+                    *strike = enabled;
+                }
+            }
+            (b"fill", Some(b"fills"), _) => {
+                table.fill_colors.push(None);
+                pattern_is_none = false;
+            }
+            (b"patternFill", Some(b"fill"), Some(b"fills")) => {
+                pattern_is_none = attribute(element, b"patternType").as_deref() == Some("none");
+            }
+            (b"fgColor", Some(b"patternFill"), _) if !pattern_is_none => {
+                if let (Some(color), Some(last)) =
+                    (color_attribute(element), table.fill_colors.last_mut())
+                {
+                    *last = Some(color);
+                }
+            }
+            (b"xf", Some(b"cellXfs"), _) => {
+                let id = |key: &[u8]| {
+                    attribute(element, key)
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .unwrap_or(0)
+                };
+                table.cell_xfs.push((id(b"fontId"), id(b"fillId")));
+            }
+            _ => {}
+        }
+    });
+
+    table
+}
+
+/// シートXMLからセルごとの書式を取得（既定の書式のセルは含めない）
+fn parse_sheet_styles(xml: &str, styles: &StyleTable) -> CellStyleMap {
+    let mut map = CellStyleMap::new();
+    let mut current_row: u32 = 0;
+    let mut next_col: u32 = 0;
+
+    for_each_element(xml, |element, _| match element.local_name().as_ref() {
+        b"row" => {
+            if let Some(row) = attribute(element, b"r").and_then(|r| r.parse::<u32>().ok()) {
+                current_row = row.saturating_sub(1);
+            } else {
+                current_row += 1;
+            }
+            next_col = 0;
+        }
+        b"c" => {
+            let (row, col) = attribute(element, b"r")
+                .and_then(|reference| parse_cell_reference(&reference))
+                .unwrap_or((current_row, next_col));
+            next_col = col + 1;
+
+            if let Some(xf_index) = attribute(element, b"s").and_then(|s| s.parse::<usize>().ok()) {
+                let style = styles.style_for(xf_index);
+                if style != CellStyle::default() {
+                    map.insert((row, col), style);
+                }
+            }
+        }
+        _ => {}
+    });
+
+    map
+}
+
+/// "B3" → (2, 1)
+fn parse_cell_reference(reference: &str) -> Option<(u32, u32)> {
+    let letters: String = reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    let digits = &reference[letters.len()..];
+    if letters.is_empty() || digits.is_empty() {
+        return None;
+    }
+
+    let col = letters.chars().try_fold(0u32, |acc, c| {
+        acc.checked_mul(26)?
+            .checked_add(c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+    })?;
+    let row = digits.parse::<u32>().ok()?;
+
+    Some((row.checked_sub(1)?, col.checked_sub(1)?))
+}
+
+/// 色属性を "#RRGGBB" に変換（rgb属性と基本のインデックス色に対応）
+fn color_attribute(element: &BytesStart) -> Option<String> {
+    if let Some(rgb) = attribute(element, b"rgb") {
+        // "FFRRGGBB"（ARGB）または "RRGGBB"
+        if rgb.len() >= 6 && rgb.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Some(format!("#{}", rgb[rgb.len() - 6..].to_ascii_lowercase()));
+        }
+        return None;
+    }
+
+    let indexed = attribute(element, b"indexed")?.parse::<usize>().ok()?;
+    const BASIC_PALETTE: [&str; 8] = [
+        "#000000", "#ffffff", "#ff0000", "#00ff00", "#0000ff", "#ffff00", "#ff00ff", "#00ffff",
+    ];
+    match indexed {
+        0..=7 => Some(BASIC_PALETTE[indexed].to_string()),
+        8..=15 => Some(BASIC_PALETTE[indexed - 8].to_string()),
+        _ => None,
+    }
+}
+
+fn attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key)
+        .and_then(|attr| {
+            let raw = String::from_utf8_lossy(&attr.value).into_owned();
+            unescape(&raw).ok().map(|value| value.into_owned())
+        })
+}
+
+/// XMLの各要素（開始タグ・空要素タグ）を、親要素名のスタックとともに走査する
+fn for_each_element<F>(xml: &str, mut visit: F)
+where
+    F: FnMut(&BytesStart, &[Vec<u8>]),
+{
+    let mut reader = Reader::from_str(xml);
+    let mut parents: Vec<Vec<u8>> = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => {
+                visit(&element, &parents);
+                parents.push(element.local_name().as_ref().to_vec());
+            }
+            Ok(Event::Empty(element)) => visit(&element, &parents),
+            Ok(Event::End(_)) => {
+                parents.pop();
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <fonts count="2"><font><sz val="11"/></font><font><strike/><sz val="11"/></font></fonts>
+  <fills count="3">
+    <fill><patternFill patternType="none"/></fill>
+    <fill><patternFill patternType="gray125"/></fill>
+    <fill><patternFill patternType="solid"><fgColor rgb="FF00FF00"/></patternFill></fill>
+  </fills>
+  <cellXfs count="3"><xf fontId="0" fillId="0"/><xf fontId="1" fillId="0"/><xf fontId="0" fillId="2"/></cellXfs>
+</styleSheet>"#;
+
+    const SHEET_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <sheetData>
+    <row r="1"><c r="A1" t="inlineStr"><is><t>Ref</t></is></c></row>
+    <row r="2"><c r="A2" s="1" t="inlineStr"><is><t>C1</t></is></c><c r="B2" s="2"/></row>
+  </sheetData>
+</worksheet>"#;
+
+    #[test]
+    fn test_read_first_sheet_styles() {
+        use std::io::Write;
+        use zip::write::FileOptions;
+
+        let path = std::env::temp_dir().join(format!("bomsync_styles_{}.xlsx", std::process::id()));
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+            let options = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+            for (name, content) in [
+                ("xl/styles.xml", STYLES_XML),
+                ("xl/worksheets/sheet1.xml", SHEET_XML),
+            ] {
+                zip.start_file(name, options).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+
+        let styles = read_first_sheet_styles(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(styles.len(), 2);
+        assert!(styles[&(1, 0)].strikethrough);
+        assert_eq!(styles[&(1, 1)].fill_color.as_deref(), Some("#00ff00"));
+        assert!(!styles.contains_key(&(0, 0)));
+    }
+
+    #[test]
+    fn test_parse_cell_reference() {
+        assert_eq!(parse_cell_reference("A1"), Some((0, 0)));
+        assert_eq!(parse_cell_reference("B3"), Some((2, 1)));
+        assert_eq!(parse_cell_reference("AA10"), Some((9, 26)));
+        assert_eq!(parse_cell_reference("10"), None);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::models::{AppError, ParseResult};
+
+use super::build_column_order;
+
+/// 簡易形式のJSON BOM（`{ "headers": [...], "rows": [[...]], "roles": {...} }`）
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SimpleJsonBom {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// 役割名 → 列（列ID "col-0" またはヘッダー名）
+    #[serde(default)]
+    roles: HashMap<String, Vec<String>>,
+}
+
+/// JSON形式のBOMを読み込む
+///
+/// `export_bom_file` の "JSON" 形式で出力した `ParseResult` をそのまま読み込むほか、
+/// `headers`・`rows`・`roles` だけの簡易形式にも対応する（列の自動判定は行わない）。
+///
+/// # 戻り値
+/// パース結果（形式が不正な場合や列の参照が合わない場合はエラー）
+pub fn parse_json_file(path: &Path) -> Result<ParseResult, AppError> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| AppError::io(format!("JSONの読み込みに失敗しました: {err}")))?;
+    let value: Value = serde_json::from_str(&content)
+        .map_err(|err| AppError::parse(format!("JSONの解析に失敗しました: {err}")))?;
+
+    let is_parse_result = value
+        .as_object()
+        .map(|object| object.contains_key("column_roles"))
+        .ok_or_else(|| AppError::parse("JSONの最上位はオブジェクトである必要があります。"))?;
+
+    if is_parse_result {
+        let result: ParseResult = serde_json::from_value(value).map_err(|err| {
+            AppError::parse(format!("JSONがBOMデータの形式と一致しません: {err}"))
+        })?;
+        validate_parse_result(&result)?;
+        Ok(result)
+    } else {
+        let bom: SimpleJsonBom = serde_json::from_value(value).map_err(|err| {
+            AppError::parse(format!(
+                "JSONがBOMデータの形式（headers・rows・roles）と一致しません: {err}"
+            ))
+        })?;
+        from_simple(bom)
+    }
+}
+
+/// 簡易形式から `ParseResult` を作成
+fn from_simple(bom: SimpleJsonBom) -> Result<ParseResult, AppError> {
+    if let Some(idx) = bom
+        .rows
+        .iter()
+        .position(|row| row.len() > bom.headers.len())
+    {
+        return Err(AppError::parse(format!(
+            "JSONのrowsの{}行目の列数がheadersより多くなっています。",
+            idx + 1
+        )));
+    }
+
+    let mut column_roles: HashMap<String, Vec<String>> = HashMap::new();
+    for (role, columns) in &bom.roles {
+        let mut ids = Vec::with_capacity(columns.len());
+        for column in columns {
+            let idx = bom
+                .headers
+                .iter()
+                .enumerate()
+                .position(|(idx, header)| format!("col-{idx}") == *column || header == column)
+                .ok_or_else(|| {
+                    AppError::parse(format!(
+                        "JSONのroles '{role}' の列 '{column}' が見つかりません。"
+                    ))
+                })?;
+            ids.push(format!("col-{idx}"));
+        }
+        column_roles.insert(role.clone(), ids);
+    }
+
+    let row_count = bom.rows.len();
+    let mut result = ParseResult::builder()
+        .rows(bom.rows)
+        .row_numbers((1..=row_count).collect())
+        .build();
+    result.headers = bom.headers;
+    result.rebuild_columns_from_headers();
+    result.column_order = build_column_order(&column_roles, result.headers.len());
+    result.column_roles = column_roles;
+    Ok(result)
+}
+
+/// `ParseResult` 形式のJSONの列・行の整合性を確認
+fn validate_parse_result(result: &ParseResult) -> Result<(), AppError> {
+    if !result.columns_consistent() {
+        return Err(AppError::parse(
+            "JSONのheaders・columns・rowsの列数が一致しません。",
+        ));
+    }
+    if result.row_numbers.len() != result.rows.len() {
+        return Err(AppError::parse(format!(
+            "JSONのrow_numbersの件数（{}）がrowsの件数（{}）と一致しません。",
+            result.row_numbers.len(),
+            result.rows.len()
+        )));
+    }
+
+    let column_ids: HashSet<&str> = result
+        .columns
+        .iter()
+        .map(|column| column.id.as_str())
+        .collect();
+    let unknown = result
+        .column_roles
+        .values()
+        .flatten()
+        .chain(&result.column_order)
+        .find(|id| !column_ids.contains(id.as_str()));
+    if let Some(id) = unknown {
+        return Err(AppError::parse(format!(
+            "JSONのcolumn_roles・column_orderに存在しない列 '{id}' が含まれています。"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporters;
+    use crate::models::{ErrorKind, ExportOptions};
+
+    fn write_temp_json(name: &str, content: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("bomsync_json_{}_{}.json", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut parse = ParseResult::builder()
+            .rows(vec![
+                vec!["C1".to_string(), "GRM155".to_string(), "Murata".to_string()],
+                vec!["R1".to_string(), "RC0402".to_string()],
+            ])
+            .row_numbers(vec![3, 5])
+            .header_row_index(Some(1))
+            .data_start_index(2)
+            .build();
+        parse.headers = vec!["Maker".to_string(), "Part".to_string(), "Ref".to_string()];
+        parse.rebuild_columns_from_headers();
+        parse.column_roles = HashMap::from([
+            ("ref".to_string(), vec!["col-0".to_string()]),
+            ("part_no".to_string(), vec!["col-1".to_string()]),
+        ]);
+        parse.column_order = vec![
+            "col-2".to_string(),
+            "col-0".to_string(),
+            "col-1".to_string(),
+        ];
+
+        let options = ExportOptions {
+            format: "JSON".to_string(),
+            ..ExportOptions::default()
+        };
+        let content = exporters::export_bom_file(&parse, &options).unwrap();
+        let path = write_temp_json("round_trip", &content);
+        let loaded = parse_json_file(&path).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&parse).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_simple_json() {
+        let path = write_temp_json(
+            "simple",
+            r#"{"headers": ["Ref", "Part"], "rows": [["C1", "GRM155"], ["R1"]], "roles": {"ref": ["Ref"], "part_no": ["col-1"]}}"#,
+        );
+        let result = parse_json_file(&path).unwrap();
+        assert_eq!(result.get_ref(0), "C1");
+        assert_eq!(result.get_part_no(0), "GRM155");
+        assert_eq!(result.row_numbers, vec![1, 2]);
+        assert_eq!(result.column_order, vec!["col-0", "col-1"]);
+
+        let path = write_temp_json(
+            "unknown_column",
+            r#"{"headers": ["Ref"], "rows": [], "roles": {"ref": ["Reference"]}}"#,
+        );
+        let error = parse_json_file(&path).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Parse);
+        assert!(error.message.contains("'Reference'"));
+
+        let path = write_temp_json("wrong_shape", r#"{"headers": "Ref", "rows": []}"#);
+        assert_eq!(parse_json_file(&path).unwrap_err().kind, ErrorKind::Parse);
+
+        let path = write_temp_json(
+            "bad_roles",
+            r#"{"headers": ["Ref"], "rows": [["C1"]], "column_roles": {"ref": ["col-5"]},
+                "column_order": ["col-0"], "guessed_columns": {}, "guessed_roles": {},
+                "errors": [], "columns": [{"id": "col-0", "name": "Ref"}], "row_numbers": [1],
+                "structured_errors": null}"#,
+        );
+        assert!(parse_json_file(&path)
+            .unwrap_err()
+            .message
+            .contains("'col-5'"));
+    }
+}
@@ -1,19 +1,46 @@
 mod builder;
 mod cad;
+mod cpl;
 mod csv;
+mod detect;
 mod excel;
+mod excel_styles;
+mod json;
 
 use std::path::PathBuf;
 
-use crate::models::{AppError, ParseResult};
+use crate::models::{AppError, ErrorKind, ParseOptions, ParseResult};
 
-pub use builder::build_bom_rows;
+pub use builder::{
+    build_bom_rows, build_bom_rows_at, build_column_order, is_role_detection_warning,
+};
+pub use detect::{detect_format, sniff_format};
+
+/// 読み込みに対応しているファイルの拡張子（小文字）
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "csv", "xlsx", "xlsm", "xlsb", "xls", "eco", "ccf", "msf", "net", "pws", "bd", "rpt", "txt",
+    "json",
+];
 
 pub fn parse_bom_file(path: String) -> Result<ParseResult, AppError> {
+    parse_bom_file_with_options(path, &ParseOptions::default())
+}
+
+/// オプションを指定してBOMファイルを読み込む
+///
+/// 拡張子がない・未知・".txt" の場合は、先頭の内容から形式を判定する（`detect_format`）
+///
+/// # 引数
+/// * `path` - ファイルパス
+/// * `options` - 読み込みオプション（CSV/Excelのみに適用）
+pub fn parse_bom_file_with_options(
+    path: String,
+    options: &ParseOptions,
+) -> Result<ParseResult, AppError> {
     let path = PathBuf::from(path);
 
     if !path.exists() {
-        return Err(AppError::new(format!(
+        return Err(AppError::not_found(format!(
             "ファイルが見つかりません: {}",
             path.display()
         )));
@@ -23,18 +50,64 @@ pub fn parse_bom_file(path: String) -> Result<ParseResult, AppError> {
         .extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| ext.to_lowercase())
-        .ok_or_else(|| AppError::new("ファイル拡張子を判定できませんでした。"))?;
-
-    match ext.as_str() {
-        "csv" => csv::parse_csv_file(&path),
-        "xlsx" => excel::parse_excel_file(&path),
-        // CADネットリスト形式（ECO/CCF/MSF/PWS/BD/PADSレポート）
-        "eco" | "ccf" | "msf" | "net" | "pws" | "bd" | "rpt" => cad::parse_cad_file(&path),
-        // .txt は内容から形式を自動判定
-        "txt" => cad::parse_cad_file(&path),
-        other => Err(AppError::new(format!(
-            "サポートされていないファイル形式です: {}",
-            other
-        ))),
+        .filter(|ext| ext != "txt" && SUPPORTED_EXTENSIONS.contains(&ext.as_str()));
+
+    // 既知の拡張子はそのまま使い、それ以外は内容から判定する
+    let format = match ext {
+        Some(ext) => ext,
+        None => detect_format(&path).map_err(|err| {
+            if err.kind == ErrorKind::UnsupportedFormat {
+                AppError::unsupported_format(format!(
+                    "サポートされていないファイル形式です: {}（対応している拡張子: {}）",
+                    path.display(),
+                    SUPPORTED_EXTENSIONS.join(", ")
+                ))
+            } else {
+                err
+            }
+        })?,
+    };
+
+    let mut result = match format.as_str() {
+        "csv" => csv::parse_csv_file(&path, options),
+        ext if excel::EXCEL_EXTENSIONS.contains(&ext) => excel::parse_excel_file(&path, options),
+        // 自ツールの交換形式（列の役割・表示順序を含む）
+        "json" => json::parse_json_file(&path),
+        // CADネットリスト形式（ECO/CCF/MSF/PWS/BD/PADSレポート、形式は内容から判定）
+        _ => cad::parse_cad_file(&path),
+    }?;
+
+    if options.keep_original_rows {
+        result.original_rows = Some(result.rows.clone());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bom_file_error_kinds() {
+        let dir = std::env::temp_dir();
+        let missing = dir.join(format!("bomsync_missing_{}.csv", std::process::id()));
+        let error = parse_bom_file(missing.display().to_string()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::NotFound);
+
+        let unsupported = dir.join(format!("bomsync_kind_{}.pdf", std::process::id()));
+        std::fs::write(&unsupported, "dummy").unwrap();
+        let error = parse_bom_file(unsupported.display().to_string()).unwrap_err();
+        let _ = std::fs::remove_file(&unsupported);
+        assert_eq!(error.kind, ErrorKind::UnsupportedFormat);
+    }
+
+    #[test]
+    fn test_parse_bom_file_sniffs_unknown_extension() {
+        let path = std::env::temp_dir().join(format!("bomsync_sniff_{}.dat", std::process::id()));
+        std::fs::write(&path, "Ref,Part\nC1,GRM155\nR1,RC0402\n").unwrap();
+        let result = parse_bom_file(path.display().to_string()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(result.headers, vec!["Ref", "Part"]);
+        assert_eq!(result.rows.len(), 2);
     }
 }
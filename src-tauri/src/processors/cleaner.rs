@@ -1,20 +1,34 @@
-use std::collections::HashMap;
+use crate::models::{AppError, CellEdit, CleanseOptions, ParseResult, Transform, TransformResult};
+use crate::utils::text::cleanse_string_with;
 
-use crate::models::{AppError, ParseResult};
-use crate::utils::text::cleanse_string;
+use super::transform::capture_metadata;
 
 /// 空白セルを前の行の値で埋める
 pub fn fill_blank_cells(parse: &ParseResult) -> Result<ParseResult, AppError> {
+    fill_blank_cells_with_transform(parse).map(|output| output.result)
+}
+
+/// 空白セルを前の行の値で埋め、埋めたセルの変更記録を返す
+pub fn fill_blank_cells_with_transform(parse: &ParseResult) -> Result<TransformResult, AppError> {
     let mut filled_rows = Vec::new();
+    let mut cell_edits = Vec::new();
     let mut prev_row: Option<Vec<String>> = None;
 
-    for row in &parse.rows {
+    for (row_idx, row) in parse.rows.iter().enumerate() {
         let mut new_row = row.clone();
 
         if let Some(prev) = &prev_row {
             for (col_idx, cell) in new_row.iter_mut().enumerate() {
                 if cell.trim().is_empty() {
                     if let Some(prev_cell) = prev.get(col_idx) {
+                        if cell != prev_cell {
+                            cell_edits.push(CellEdit {
+                                row: row_idx,
+                                column: col_idx,
+                                before: cell.clone(),
+                                after: prev_cell.clone(),
+                            });
+                        }
                         *cell = prev_cell.clone();
                     }
                 }
@@ -25,42 +39,173 @@ pub fn fill_blank_cells(parse: &ParseResult) -> Result<ParseResult, AppError> {
         filled_rows.push(new_row);
     }
 
-    Ok(ParseResult {
-        rows: filled_rows,
-        column_roles: parse.column_roles.clone(),
-        column_order: parse.column_order.clone(),
-        #[allow(deprecated)]
-        guessed_columns: HashMap::new(),
-        #[allow(deprecated)]
-        guessed_roles: HashMap::new(),
-        errors: vec![],
-        headers: parse.headers.clone(),
-        columns: parse.columns.clone(),
-        row_numbers: parse.row_numbers.clone(),
-        structured_errors: None,
+    let result = ParseResult::with_rows(filled_rows, parse);
+
+    // エラー・検証結果はクリアされるため、残っていた場合のみ記録する
+    let previous_metadata = (!parse.errors.is_empty()
+        || parse.structured_errors.is_some()
+        || parse.validation_summary.is_some())
+    .then(|| capture_metadata(parse));
+
+    Ok(TransformResult {
+        result,
+        transform: Transform {
+            operation: "fill_blank_cells".to_string(),
+            cell_edits,
+            row_splices: vec![],
+            previous_metadata,
+        },
     })
 }
 
 /// テキストデータをクレンジング（不要な空白・特殊文字を削除）
 pub fn cleanse_text_data(parse: &ParseResult) -> ParseResult {
+    cleanse_text_data_with_options(parse, &CleanseOptions::legacy())
+}
+
+/// オプションに従ってテキストデータをクレンジング
+pub fn cleanse_text_data_with_options(
+    parse: &ParseResult,
+    options: &CleanseOptions,
+) -> ParseResult {
     let cleansed_rows: Vec<Vec<String>> = parse
         .rows
         .iter()
-        .map(|row| row.iter().map(|cell| cleanse_string(cell)).collect())
+        .map(|row| {
+            row.iter()
+                .map(|cell| cleanse_string_with(cell, options))
+                .collect()
+        })
         .collect();
 
-    ParseResult {
-        rows: cleansed_rows,
-        column_roles: parse.column_roles.clone(),
-        column_order: parse.column_order.clone(),
-        #[allow(deprecated)]
-        guessed_columns: HashMap::new(),
-        #[allow(deprecated)]
-        guessed_roles: HashMap::new(),
-        errors: vec![],
-        headers: parse.headers.clone(),
-        columns: parse.columns.clone(),
-        row_numbers: parse.row_numbers.clone(),
-        structured_errors: None,
+    ParseResult::with_rows(cleansed_rows, parse)
+}
+
+/// 1つのセルを読み込み時の値（`original_rows`）に戻す
+///
+/// # 戻り値
+/// セルを戻したBOM（読み込み時の値を保持していない場合、セルが範囲外の場合はエラー）
+pub fn revert_cell(
+    parse: &ParseResult,
+    row: usize,
+    column: usize,
+) -> Result<ParseResult, AppError> {
+    let original_rows = parse.original_rows.as_ref().ok_or_else(|| {
+        AppError::validation("読み込み時の値を保持していないため元に戻せません。")
+    })?;
+    let original = original_rows
+        .get(row)
+        .and_then(|original_row| original_row.get(column))
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "{}行目・{}列目の読み込み時の値がありません。",
+                row + 1,
+                column + 1
+            ))
+        })?;
+
+    let mut result = parse.clone();
+    let cell = result
+        .rows
+        .get_mut(row)
+        .and_then(|current_row| current_row.get_mut(column))
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "{}行目・{}列目のセルがありません。",
+                row + 1,
+                column + 1
+            ))
+        })?;
+    *cell = original.clone();
+    Ok(result)
+}
+
+/// クレンジングで値が変わるセルの一覧（データは変更しない）
+///
+/// `cleanse_text_data_with_options` と同じ `cleanse_string_with` を使うため、
+/// 同じオプションでの適用結果と一致する
+pub fn preview_cleanse(parse: &ParseResult, options: &CleanseOptions) -> Vec<CellEdit> {
+    parse
+        .rows
+        .iter()
+        .enumerate()
+        .flat_map(|(row_idx, row)| {
+            row.iter().enumerate().filter_map(move |(col_idx, cell)| {
+                let cleansed = cleanse_string_with(cell, options);
+                (cleansed != *cell).then(|| CellEdit {
+                    row: row_idx,
+                    column: col_idx,
+                    before: cell.clone(),
+                    after: cleansed,
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_cleanse_matches_apply() {
+        let headers = vec!["Ref".to_string(), "Part".to_string()];
+        let parse = ParseResult::builder()
+            .headers(headers)
+            .rows(vec![
+                vec!["C1".to_string(), "74VHC08FT(BJ)".to_string()],
+                vec!["R1".to_string(), "RC0402".to_string()],
+            ])
+            .row_numbers(vec![2, 3])
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build();
+
+        // 既定では括弧を残す
+        assert!(preview_cleanse(&parse, &CleanseOptions::default()).is_empty());
+
+        let changes = preview_cleanse(&parse, &CleanseOptions::legacy());
+        assert_eq!(
+            changes,
+            vec![CellEdit {
+                row: 0,
+                column: 1,
+                before: "74VHC08FT(BJ)".to_string(),
+                after: "74VHC08FTBJ".to_string(),
+            }]
+        );
+
+        // プレビューは元データを変更せず、適用結果と一致する
+        assert_eq!(parse.rows[0][1], "74VHC08FT(BJ)");
+        let applied = cleanse_text_data(&parse);
+        for change in &changes {
+            assert_eq!(applied.rows[change.row][change.column], change.after);
+        }
+        assert_eq!(applied.rows[1], parse.rows[1]);
+    }
+
+    #[test]
+    fn test_revert_cell_after_cleanse() {
+        let rows = vec![
+            vec!["C1".to_string(), "74VHC08FT(BJ)".to_string()],
+            vec!["R1".to_string(), "ＲＣ0402".to_string()],
+        ];
+        let parse = ParseResult::builder()
+            .rows(rows.clone())
+            .original_rows(Some(rows))
+            .build();
+
+        let cleansed = cleanse_text_data(&parse);
+        assert_eq!(cleansed.original_rows, parse.original_rows);
+        assert_eq!(cleansed.rows[1][1], "RC0402");
+
+        let reverted = revert_cell(&cleansed, 0, 1).unwrap();
+        assert_eq!(reverted.rows[0][1], "74VHC08FT(BJ)");
+        assert_eq!(reverted.rows[1][1], "RC0402");
+
+        assert!(revert_cell(&cleansed, 5, 0).is_err());
+        let without_original =
+            ParseResult::with_rows(cleansed.rows.clone(), &ParseResult::builder().build());
+        assert!(revert_cell(&without_original, 0, 1).is_err());
     }
 }
@@ -0,0 +1,183 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use crate::diff::KeyNormalizer;
+use crate::models::{DedupResult, ParseResult, MATCH_KEY_SEPARATOR};
+
+/// キーが重複する行を削除
+///
+/// キーの値は前後の空白を除いて比較する。キーがすべて空の行は重複とみなさず残す。
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `key_roles` - キーに使う役割（"ref", "part_no" など、空の場合は行全体の値で判定）
+/// * `prefer_complete` - true の場合、重複する行のうち値のあるセルが最も多い行を残す
+///   （同数の場合は先の行）。false の場合は最初の行を残す
+///
+/// # 戻り値
+/// 重複を削除したBOM（残した行は元の行番号・書式を引き継ぐ）と削除した行数
+pub fn deduplicate_rows(
+    parse: &ParseResult,
+    key_roles: &[String],
+    prefer_complete: bool,
+) -> DedupResult {
+    let normalizer = KeyNormalizer::default();
+    let mut kept_by_key: HashMap<String, usize> = HashMap::new();
+    let mut keep = vec![true; parse.rows.len()];
+
+    for (row_idx, row) in parse.rows.iter().enumerate() {
+        let Some(key) = row_key(parse, row_idx, key_roles, &normalizer) else {
+            continue;
+        };
+
+        match kept_by_key.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(row_idx);
+            }
+            Entry::Occupied(mut entry) => {
+                let kept_idx = *entry.get();
+                if prefer_complete
+                    && filled_cell_count(row) > filled_cell_count(&parse.rows[kept_idx])
+                {
+                    keep[kept_idx] = false;
+                    entry.insert(row_idx);
+                } else {
+                    keep[row_idx] = false;
+                }
+            }
+        }
+    }
+
+    let kept: Vec<usize> = (0..parse.rows.len()).filter(|&idx| keep[idx]).collect();
+    let select = |rows: &Vec<Vec<String>>| -> Vec<Vec<String>> {
+        kept.iter().map(|&idx| rows[idx].clone()).collect()
+    };
+
+    let cell_styles = parse
+        .cell_styles
+        .as_ref()
+        .filter(|styles| styles.len() == parse.rows.len())
+        .map(|styles| kept.iter().map(|&idx| styles[idx].clone()).collect());
+    let original_rows = parse
+        .original_rows
+        .as_ref()
+        .filter(|rows| rows.len() == parse.rows.len())
+        .map(select);
+
+    let result = ParseResult::builder()
+        .schema_from(parse)
+        .rows(select(&parse.rows))
+        .row_numbers(
+            kept.iter()
+                .map(|&idx| parse.source_row_number(idx))
+                .collect(),
+        )
+        .cell_styles(cell_styles)
+        .original_rows(original_rows)
+        .build();
+
+    DedupResult {
+        result,
+        removed_count: parse.rows.len() - kept.len(),
+    }
+}
+
+/// 行のキー（キーがすべて空の場合はNone）
+fn row_key(
+    parse: &ParseResult,
+    row_idx: usize,
+    key_roles: &[String],
+    normalizer: &KeyNormalizer,
+) -> Option<String> {
+    let mut values: Vec<String> = if key_roles.is_empty() {
+        parse.rows[row_idx]
+            .iter()
+            .map(|cell| normalizer.normalize(cell))
+            .collect()
+    } else {
+        key_roles
+            .iter()
+            .flat_map(|role| parse.get_values(row_idx, role))
+            .map(|value| normalizer.normalize(&value))
+            .collect()
+    };
+
+    // 行の長さの違い（末尾の空セル）は区別しない
+    while values.last().is_some_and(|value| value.is_empty()) {
+        values.pop();
+    }
+    (!values.is_empty()).then(|| values.join(MATCH_KEY_SEPARATOR))
+}
+
+/// 値のあるセルの数
+fn filled_cell_count(row: &[String]) -> usize {
+    row.iter().filter(|cell| !cell.trim().is_empty()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(rows: &[[&str; 3]]) -> ParseResult {
+        let mut parse = ParseResult::builder()
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .row_numbers((2..rows.len() + 2).collect())
+            .build();
+        parse.headers = vec!["Ref".into(), "Part".into(), "Maker".into()];
+        parse.rebuild_columns_from_headers();
+        parse
+            .column_roles
+            .insert("ref".into(), vec!["col-0".into()]);
+        parse
+            .column_roles
+            .insert("part_no".into(), vec!["col-1".into()]);
+        parse
+    }
+
+    #[test]
+    fn test_deduplicate_exact_rows() {
+        let parse = build(&[
+            ["C1", "GRM155", "Murata"],
+            ["R1", "RC0402", "Yageo"],
+            ["C1 ", "GRM155", "Murata"],
+            ["C1", "GRM155", "TDK"],
+            ["", "", ""],
+            ["", "", ""],
+        ]);
+
+        let output = deduplicate_rows(&parse, &[], false);
+        assert_eq!(output.removed_count, 1);
+        assert_eq!(output.result.row_numbers, vec![2, 3, 5, 6, 7]);
+
+        // Referenceをキーにすると値の異なる行も重複とみなす
+        let output = deduplicate_rows(&parse, &["ref".to_string()], false);
+        assert_eq!(output.removed_count, 2);
+        assert_eq!(output.result.row_numbers, vec![2, 3, 6, 7]);
+        assert_eq!(output.result.rows[0][2], "Murata");
+    }
+
+    #[test]
+    fn test_deduplicate_prefers_complete_rows() {
+        let parse = build(&[
+            ["C1", "GRM155", ""],
+            ["R1", "", ""],
+            ["C1", "GRM155", "Murata"],
+            ["R1", "RC0402", ""],
+            ["R1", "", "Yageo"],
+        ]);
+        let key = vec!["ref".to_string()];
+
+        let output = deduplicate_rows(&parse, &key, true);
+        assert_eq!(output.removed_count, 3);
+        assert_eq!(output.result.row_numbers, vec![4, 5]);
+        assert_eq!(output.result.rows[0], vec!["C1", "GRM155", "Murata"]);
+        assert_eq!(output.result.rows[1], vec!["R1", "RC0402", ""]);
+
+        let output = deduplicate_rows(&parse, &key, false);
+        assert_eq!(output.result.row_numbers, vec![2, 3]);
+    }
+}
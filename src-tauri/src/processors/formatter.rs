@@ -1,12 +1,282 @@
-use crate::models::{FormatOptions, ParseResult};
+use crate::models::{CellStyle, FormatOptions, ParseResult};
+use crate::utils::header::normalize_header;
+use crate::utils::text::{color_to_status, is_truthy};
 
-/// フォーマットルールを適用（現在は元データをそのまま返す）
+/// 取り消し線フラグを持つ列の役割
+pub const STRIKETHROUGH_ROLE: &str = "strikethrough";
+/// セル色を持つ列の役割
+pub const COLOR_ROLE: &str = "color";
+/// セル色から求めたステータスを格納する列の役割
+pub const STATUS_ROLE: &str = "status";
+/// ステータス列の列名
+pub const STATUS_HEADER: &str = "ステータス";
+
+/// 役割が未指定の場合に取り消し線列とみなす列名（正規化済み）
+const STRIKETHROUGH_HEADERS: &[&str] = &["strikethrough", "取り消し線", "取消線"];
+/// 役割が未指定の場合にセル色列とみなす列名（正規化済み）
+const COLOR_HEADERS: &[&str] = &["color", "cellcolor", "色", "セル色"];
+
+/// フォーマットルールを適用
+///
+/// # 動作
+/// - `use_strikethrough`: 取り消し線フラグが真の行を除外
+/// - `use_cell_color`: セル色からステータス（"added" など）を求め、ステータス列に格納
 ///
-/// 将来的に以下のような処理を追加可能:
-/// - 取り消し線フラグによる行フィルタ
-/// - セル色によるステータス設定
-pub fn apply_format_rules(parse: &ParseResult, _options: &FormatOptions) -> ParseResult {
-    // 現在はオプションを無視して元データを返す
-    // 将来的にフォーマット処理を追加可能
-    parse.clone()
+/// 取り消し線・セル色の列は役割（"strikethrough" / "color"）で指定するか、
+/// 規定の列名（"取り消し線" / "セル色" など）で判定する。
+/// 該当する列がない場合は Excel から読み込んだセル書式（`cell_styles`）を使い、
+/// それもなければそのオプションは何もしない。
+pub fn apply_format_rules(parse: &ParseResult, options: &FormatOptions) -> ParseResult {
+    let mut result = parse.clone();
+
+    if options.use_strikethrough {
+        match find_column(parse, STRIKETHROUGH_ROLE, STRIKETHROUGH_HEADERS) {
+            Some(struck_col) => remove_rows(&mut result, |row, _| {
+                row.get(struck_col).is_some_and(|value| is_truthy(value))
+            }),
+            None if parse.cell_styles.is_some() => remove_rows(&mut result, is_row_struck),
+            None => {}
+        }
+    }
+
+    if options.use_cell_color {
+        match find_column(parse, COLOR_ROLE, COLOR_HEADERS) {
+            Some(color_col) => apply_status(&mut result, |row, _| {
+                row.get(color_col).and_then(|value| color_to_status(value))
+            }),
+            None if result.cell_styles.is_some() => apply_status(&mut result, |_, styles| {
+                styles.iter().find_map(|style| {
+                    style
+                        .fill_color
+                        .as_deref()
+                        .and_then(|color| color_to_status(color))
+                })
+            }),
+            None => {}
+        }
+    }
+
+    result
+}
+
+/// 役割または列名から列インデックスを取得
+fn find_column(parse: &ParseResult, role: &str, header_names: &[&str]) -> Option<usize> {
+    parse.get_column_indices(role).first().copied().or_else(|| {
+        parse
+            .headers
+            .iter()
+            .position(|header| header_names.contains(&normalize_header(header).as_str()))
+    })
+}
+
+/// 値のあるセルがすべて取り消し線付きの行か
+fn is_row_struck(row: &[String], styles: &[CellStyle]) -> bool {
+    let mut filled = row
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .peekable();
+    filled.peek().is_some()
+        && filled.all(|(col_idx, _)| styles.get(col_idx).is_some_and(|style| style.strikethrough))
+}
+
+/// 条件に一致する行を除外（行番号・セル書式も合わせて除外）
+fn remove_rows<F>(result: &mut ParseResult, should_remove: F)
+where
+    F: Fn(&[String], &[CellStyle]) -> bool,
+{
+    let styles = result.cell_styles.as_deref().unwrap_or_default();
+    let keep: Vec<bool> = result
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            let row_styles = styles.get(idx).map(Vec::as_slice).unwrap_or_default();
+            !should_remove(row, row_styles)
+        })
+        .collect();
+
+    if result.row_numbers.len() == keep.len() {
+        retain_by(&mut result.row_numbers, &keep);
+    }
+    if let Some(cell_styles) = result.cell_styles.as_mut() {
+        if cell_styles.len() == keep.len() {
+            retain_by(cell_styles, &keep);
+        }
+    }
+    if let Some(original_rows) = result.original_rows.as_mut() {
+        if original_rows.len() == keep.len() {
+            retain_by(original_rows, &keep);
+        }
+    }
+    retain_by(&mut result.rows, &keep);
+}
+
+fn retain_by<T>(items: &mut Vec<T>, keep: &[bool]) {
+    let mut flags = keep.iter();
+    items.retain(|_| flags.next().copied().unwrap_or(true));
+}
+
+/// 行ごとにステータスを求めてステータス列に格納
+fn apply_status<F>(result: &mut ParseResult, status_of: F)
+where
+    F: Fn(&[String], &[CellStyle]) -> Option<&'static str>,
+{
+    let status_col = match result.get_column_indices(STATUS_ROLE).first() {
+        Some(&idx) => idx,
+        None => add_status_column(result),
+    };
+    let styles = result.cell_styles.take();
+
+    for (idx, row) in result.rows.iter_mut().enumerate() {
+        let row_styles = styles
+            .as_ref()
+            .and_then(|styles| styles.get(idx))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let status = status_of(row, row_styles).unwrap_or_default().to_string();
+
+        if row.len() <= status_col {
+            row.resize(status_col + 1, String::new());
+        }
+        row[status_col] = status;
+    }
+
+    result.cell_styles = styles;
+}
+
+/// ステータス列を末尾に追加して列インデックスを返す
+fn add_status_column(result: &mut ParseResult) -> usize {
+    let column_count = result.column_count();
+    let col_id = format!("col-{}", column_count);
+
+    result.headers.resize(column_count, String::new());
+    result.headers.push(STATUS_HEADER.to_string());
+    result.rebuild_columns_from_headers();
+    result.column_order.push(col_id.clone());
+    result
+        .column_roles
+        .insert(STATUS_ROLE.to_string(), vec![col_id]);
+
+    column_count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_parse() -> ParseResult {
+        let headers: Vec<String> = ["Ref", "Part", "取り消し線", "セル色"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let rows = [
+            ["C1", "GRM155", "", "#FF0000"],
+            ["C2", "GRM155", "TRUE", "green"],
+            ["R1", "RC0402", "0", "ffa500"],
+        ];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers(vec![2, 3, 4])
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build()
+    }
+
+    fn options(use_strikethrough: bool, use_cell_color: bool) -> FormatOptions {
+        FormatOptions {
+            use_strikethrough,
+            use_cell_color,
+        }
+    }
+
+    #[test]
+    fn test_apply_format_rules_without_options_is_identity() {
+        let parse = sample_parse();
+        let result = apply_format_rules(&parse, &options(false, false));
+        assert_eq!(result.rows, parse.rows);
+        assert_eq!(result.headers, parse.headers);
+    }
+
+    #[test]
+    fn test_apply_format_rules_strikethrough_drops_rows() {
+        let result = apply_format_rules(&sample_parse(), &options(true, false));
+        let refs: Vec<String> = (0..result.rows.len())
+            .map(|idx| result.get_ref(idx))
+            .collect();
+        assert_eq!(refs, vec!["C1", "R1"]);
+        assert_eq!(result.row_numbers, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_apply_format_rules_cell_color_sets_status() {
+        let result = apply_format_rules(&sample_parse(), &options(false, true));
+        let statuses: Vec<String> = (0..result.rows.len())
+            .map(|idx| result.get_values(idx, STATUS_ROLE).join(""))
+            .collect();
+        assert_eq!(statuses, vec!["removed", "added", "modified"]);
+        assert_eq!(
+            result.headers.last().map(String::as_str),
+            Some(STATUS_HEADER)
+        );
+        assert_eq!(result.columns.len(), result.headers.len());
+        assert_eq!(
+            result.column_order.last().map(String::as_str),
+            Some("col-4")
+        );
+
+        // 再適用しても列は増えない
+        let again = apply_format_rules(&result, &options(false, true));
+        assert_eq!(again.headers.len(), result.headers.len());
+    }
+
+    #[test]
+    fn test_apply_format_rules_falls_back_to_cell_styles() {
+        let mut parse = sample_parse();
+        parse.headers.truncate(2);
+        parse.columns.truncate(2);
+        parse.column_order.truncate(2);
+        for row in parse.rows.iter_mut() {
+            row.truncate(2);
+        }
+        let struck = CellStyle {
+            fill_color: None,
+            strikethrough: true,
+        };
+        let green = CellStyle {
+            fill_color: Some("#00ff00".to_string()),
+            strikethrough: false,
+        };
+        parse.cell_styles = Some(vec![
+            vec![CellStyle::default(), green.clone()],
+            vec![struck.clone(), struck],
+            vec![green, CellStyle::default()],
+        ]);
+
+        let result = apply_format_rules(&parse, &options(true, true));
+        assert_eq!(result.row_numbers, vec![2, 4]);
+        assert_eq!(result.cell_styles.as_ref().map(Vec::len), Some(2));
+        assert_eq!(result.get_values(0, STATUS_ROLE), vec!["added"]);
+        assert_eq!(result.get_values(1, STATUS_ROLE), vec!["added"]);
+    }
+
+    #[test]
+    fn test_apply_format_rules_uses_role_over_header() {
+        let mut parse = sample_parse();
+        parse.headers[2] = "Flag".to_string();
+        parse
+            .column_roles
+            .insert(STRIKETHROUGH_ROLE.to_string(), vec!["col-2".to_string()]);
+
+        let result = apply_format_rules(&parse, &options(true, true));
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.get_values(1, STATUS_ROLE), vec!["modified"]);
+    }
 }
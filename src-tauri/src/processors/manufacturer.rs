@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::models::{ManufacturerAliasEntry, ParseResult};
+
+/// メーカー列の表記ゆれを正式名に置き換える
+///
+/// 前後の空白を除いた値で完全一致を優先し、見つからない場合は大文字小文字を区別せずに照合する。
+/// 辞書にない値はそのまま残す。
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `aliases` - メーカー名エイリアス（辞書 "manufacturer_alias" の内容）
+///
+/// # 戻り値
+/// メーカー列を置き換えたBOMデータ（メーカー列がない場合は元のまま）
+pub fn apply_manufacturer_aliases(
+    parse: &ParseResult,
+    aliases: &[ManufacturerAliasEntry],
+) -> ParseResult {
+    let mut exact: HashMap<&str, &str> = HashMap::new();
+    let mut lowercase: HashMap<String, &str> = HashMap::new();
+    for entry in aliases {
+        let alias = entry.alias.trim();
+        let canonical = entry.canonical.trim();
+        if alias.is_empty() || canonical.is_empty() {
+            continue;
+        }
+        exact.entry(alias).or_insert(canonical);
+        lowercase.entry(alias.to_lowercase()).or_insert(canonical);
+    }
+
+    let manufacturer_indices = parse.get_column_indices("manufacturer");
+    let mut result = parse.clone();
+    for row in &mut result.rows {
+        for &col_idx in &manufacturer_indices {
+            let Some(cell) = row.get_mut(col_idx) else {
+                continue;
+            };
+            let value = cell.trim();
+            let canonical = exact
+                .get(value)
+                .copied()
+                .or_else(|| lowercase.get(&value.to_lowercase()).copied());
+            if let Some(canonical) = canonical {
+                *cell = canonical.to_string();
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_manufacturer_aliases() {
+        let rows = [
+            ["C1", "Murata"],
+            ["C2", "Murata Mfg."],
+            ["C3", " MURATA MFG. "],
+            ["C4", "村田製作所"],
+            ["R1", "Yageo"],
+            ["R2", ""],
+        ];
+        let mut parse = ParseResult::builder()
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .build();
+        parse.headers = vec!["Ref".to_string(), "Maker".to_string()];
+        parse.rebuild_columns_from_headers();
+        parse
+            .column_roles
+            .insert("manufacturer".to_string(), vec!["col-1".to_string()]);
+
+        let aliases = vec![
+            ManufacturerAliasEntry {
+                alias: "Murata Mfg.".to_string(),
+                canonical: "Murata".to_string(),
+            },
+            ManufacturerAliasEntry {
+                alias: "村田製作所".to_string(),
+                canonical: "Murata".to_string(),
+            },
+        ];
+
+        let result = apply_manufacturer_aliases(&parse, &aliases);
+        let makers: Vec<&str> = result.rows.iter().map(|row| row[1].as_str()).collect();
+        assert_eq!(
+            makers,
+            vec!["Murata", "Murata", "Murata", "Murata", "Yageo", ""]
+        );
+        assert_eq!(result.rows[0][0], "C1");
+    }
+}
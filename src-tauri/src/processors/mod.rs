@@ -1,4 +1,11 @@
 pub mod cleaner;
+pub mod dedup;
 pub mod formatter;
+pub mod manufacturer;
+pub mod normalizer;
+pub mod pipeline;
 pub mod reference;
+pub mod roles;
+pub mod sort;
+pub mod transform;
 pub mod validator;
@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::BomRow;
+
+/// BOMデータを役割ごとに平坦化した行に変換
+///
+/// # 引数
+/// * `rows` - 元データ（`ParseResult::rows`）
+/// * `headers` - 列名（`ParseResult::headers`）
+/// * `column_roles` - 役割名 → 列IDの配列（`ParseResult::column_roles`）
+/// * `row_numbers` - 元ファイルでの行番号（`ParseResult::row_numbers`）
+///
+/// # 戻り値
+/// 元データと同じ件数・順序の行（Referenceが空の行も含む）
+pub fn normalize_bom_data(
+    rows: &[Vec<String>],
+    headers: &[String],
+    column_roles: &HashMap<String, Vec<String>>,
+    row_numbers: &[usize],
+) -> Vec<BomRow> {
+    let ref_indices = role_indices(column_roles, "ref");
+    let part_indices = role_indices(column_roles, "part_no");
+
+    // Reference・部品型番以外の列の属性キーを決定
+    let column_count = rows
+        .iter()
+        .map(|row| row.len())
+        .chain(std::iter::once(headers.len()))
+        .max()
+        .unwrap_or(0);
+    let mut used_keys = HashSet::new();
+    let attribute_columns: Vec<(usize, String)> = (0..column_count)
+        .filter(|idx| !ref_indices.contains(idx) && !part_indices.contains(idx))
+        .map(|idx| {
+            let name = headers.get(idx).map(|h| h.trim()).unwrap_or("");
+            let key = if name.is_empty() || used_keys.contains(name) {
+                format!("col-{}", idx)
+            } else {
+                name.to_string()
+            };
+            used_keys.insert(key.clone());
+            (idx, key)
+        })
+        .collect();
+
+    rows.iter()
+        .enumerate()
+        .map(|(row_idx, row)| {
+            let values = |indices: &[usize]| -> Vec<String> {
+                indices
+                    .iter()
+                    .filter_map(|&idx| row.get(idx))
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect()
+            };
+
+            let attributes = attribute_columns
+                .iter()
+                .map(|(idx, key)| (key.clone(), row.get(*idx).cloned().unwrap_or_default()))
+                .collect();
+
+            BomRow {
+                reference: values(&ref_indices).join(", "),
+                part_no: values(&part_indices).into_iter().next().unwrap_or_default(),
+                attributes,
+                row_number: row_numbers.get(row_idx).copied(),
+            }
+        })
+        .collect()
+}
+
+/// 役割を持つ列のインデックスを取得（"col-0" → 0）
+fn role_indices(column_roles: &HashMap<String, Vec<String>>, role: &str) -> Vec<usize> {
+    column_roles
+        .get(role)
+        .map(|col_ids| {
+            col_ids
+                .iter()
+                .filter_map(|col_id| col_id.strip_prefix("col-")?.parse::<usize>().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_bom_data_multi_ref_columns() {
+        let headers = to_strings(&["Ref1", "Part", "Ref2", "Maker", "", "Maker"]);
+        let rows = vec![
+            to_strings(&["C1", "GRM155", "C2", "Murata", "x", "TDK"]),
+            to_strings(&["", "RC0402", "", "Yageo", "", ""]),
+        ];
+        let column_roles = HashMap::from([
+            ("ref".to_string(), to_strings(&["col-0", "col-2"])),
+            ("part_no".to_string(), to_strings(&["col-1"])),
+            ("manufacturer".to_string(), to_strings(&["col-3", "col-5"])),
+        ]);
+
+        let normalized = normalize_bom_data(&rows, &headers, &column_roles, &[3, 4]);
+
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(normalized[0].reference, "C1, C2");
+        assert_eq!(normalized[0].part_no, "GRM155");
+        assert_eq!(normalized[0].row_number, Some(3));
+        assert_eq!(
+            normalized[0].attributes,
+            HashMap::from([
+                ("Maker".to_string(), "Murata".to_string()),
+                ("col-4".to_string(), "x".to_string()),
+                ("col-5".to_string(), "TDK".to_string()),
+            ])
+        );
+        assert_eq!(normalized[1].reference, "");
+        assert_eq!(normalized[1].part_no, "RC0402");
+
+        let json = serde_json::to_value(&normalized[0]).unwrap();
+        assert_eq!(json["ref"], "C1, C2");
+    }
+}
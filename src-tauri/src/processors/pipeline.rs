@@ -0,0 +1,127 @@
+use crate::matchers::ipc::apply_ipc_names_with_progress;
+use crate::models::{AppError, ParseResult, PipelineStep};
+use crate::utils::progress::OperationContext;
+
+use super::{cleaner, formatter, reference};
+
+/// 複数の処理を順に適用する
+///
+/// 処理ごとにフロントエンドとBOMデータをやり取りせずに済むよう、まとめて実行する。
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `steps` - 適用する処理（先頭から順に実行）
+/// * `context` - 進捗の通知先と中断指示（IPC登録名の適用で使用）
+///
+/// # 戻り値
+/// 全ての処理を適用したBOMデータ。途中で失敗した場合は、何番目のどの処理で失敗したかを
+/// メッセージに含むエラー（種類は元のエラーのまま、中断の場合は中断のエラー）
+pub fn run_pipeline(
+    parse: ParseResult,
+    steps: Vec<PipelineStep>,
+    context: &OperationContext,
+) -> Result<ParseResult, AppError> {
+    let mut current = parse;
+    for (idx, step) in steps.into_iter().enumerate() {
+        let label = step.label();
+        current = run_step(&current, step, context).map_err(|err| {
+            if err.is_cancelled() {
+                return err;
+            }
+            AppError::with_kind(
+                err.kind,
+                format!(
+                    "{}番目の処理（{label}）に失敗しました: {}",
+                    idx + 1,
+                    err.message
+                ),
+            )
+        })?;
+    }
+    Ok(current)
+}
+
+fn run_step(
+    parse: &ParseResult,
+    step: PipelineStep,
+    context: &OperationContext,
+) -> Result<ParseResult, AppError> {
+    match step {
+        PipelineStep::Expand(options) => reference::expand_reference_with_options(parse, &options),
+        PipelineStep::SplitRefs => reference::split_reference_rows(parse),
+        PipelineStep::FillBlanks => cleaner::fill_blank_cells(parse),
+        PipelineStep::Cleanse(options) => {
+            Ok(cleaner::cleanse_text_data_with_options(parse, &options))
+        }
+        PipelineStep::ApplyIpc { rules, exceptions } => {
+            apply_ipc_names_with_progress(parse, rules, exceptions, context)
+        }
+        PipelineStep::Format(options) => Ok(formatter::apply_format_rules(parse, &options)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ErrorKind, ExpandOptions};
+
+    fn sample_parse() -> ParseResult {
+        let rows = [["C1-C3", "GRM155"], ["R1,R2", ""], ["R3", "RC0402"]];
+        let mut parse = ParseResult::builder()
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .row_numbers(vec![2, 3, 4])
+            .build();
+        parse.headers = vec!["Ref".to_string(), "Part".to_string()];
+        parse.rebuild_columns_from_headers();
+        parse
+            .column_roles
+            .insert("ref".to_string(), vec!["col-0".to_string()]);
+        parse
+            .column_roles
+            .insert("part_no".to_string(), vec!["col-1".to_string()]);
+        parse
+    }
+
+    #[test]
+    fn test_run_pipeline_three_steps() {
+        let steps: Vec<PipelineStep> = serde_json::from_str(
+            r#"[{"type": "expand"}, {"type": "split_refs"}, {"type": "fill_blanks"}]"#,
+        )
+        .unwrap();
+        let result = run_pipeline(sample_parse(), steps, &OperationContext::default()).unwrap();
+
+        let refs: Vec<String> = (0..result.rows.len())
+            .map(|idx| result.get_ref(idx))
+            .collect();
+        assert_eq!(refs, vec!["C1", "C2", "C3", "R1", "R2", "R3"]);
+        let parts: Vec<String> = (0..result.rows.len())
+            .map(|idx| result.get_part_no(idx))
+            .collect();
+        assert_eq!(
+            parts,
+            vec!["GRM155", "GRM155", "GRM155", "GRM155", "GRM155", "RC0402"]
+        );
+    }
+
+    #[test]
+    fn test_run_pipeline_names_failing_step() {
+        let steps = vec![
+            PipelineStep::FillBlanks,
+            PipelineStep::Expand(ExpandOptions {
+                max_expansion: 2,
+                ..ExpandOptions::default()
+            }),
+        ];
+        let error = run_pipeline(sample_parse(), steps, &OperationContext::default()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Validation);
+        assert!(
+            error.message.starts_with("2番目の処理（Reference展開）"),
+            "{}",
+            error.message
+        );
+    }
+}
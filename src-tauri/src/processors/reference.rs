@@ -1,63 +1,132 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
-use crate::models::{AppError, ParseResult};
+use crate::models::{
+    AppError, ExpandOptions, ParseResult, PartSummary, ReferenceRename, RenumberResult, RowSplice,
+    Transform, TransformResult, DEFAULT_MAX_REFERENCE_EXPANSION, UNSPECIFIED_GROUP_KEY,
+};
+
+use super::transform::capture_metadata;
+
+/// セル内のReferenceの区切り文字
+const REFERENCE_SEPARATORS: [char; 2] = [',', ';'];
 
 /// Reference展開（C1-C5 → C1, C2, C3, C4, C5）
 ///
-/// ParseResultの元データを操作し、範囲指定を展開します
+/// ParseResultの元データを操作し、範囲指定を展開します。
+/// カンマ・セミコロン区切りで複数の範囲がある場合（"C1-C3,C7-C9"）は
+/// それぞれを展開し、範囲でないReferenceもそのまま1行にします。
+/// 範囲指定を含まないセル（"R1, R2"）は変更しません（`split_reference_rows` で分割）
 pub fn expand_reference(parse: &ParseResult) -> Result<ParseResult, AppError> {
+    expand_reference_with_options(parse, &ExpandOptions::default())
+}
+
+/// オプションに従ってReference展開
+pub fn expand_reference_with_options(
+    parse: &ParseResult,
+    options: &ExpandOptions,
+) -> Result<ParseResult, AppError> {
+    expand_reference_transform_with_options(parse, options).map(|output| output.result)
+}
+
+/// Reference展開を行い、展開した行の変更記録を返す
+pub fn expand_reference_with_transform(parse: &ParseResult) -> Result<TransformResult, AppError> {
+    expand_reference_transform_with_options(parse, &ExpandOptions::default())
+}
+
+/// オプションに従ってReference展開を行い、展開した行の変更記録を返す
+///
+/// # エラー
+/// - 降順の範囲指定（`allow_descending` が false の場合）
+/// - 1つのセルの展開数が `max_expansion` を超える場合
+pub fn expand_reference_transform_with_options(
+    parse: &ParseResult,
+    options: &ExpandOptions,
+) -> Result<TransformResult, AppError> {
     let mut expanded_rows = Vec::new();
+    let mut row_numbers = Vec::new();
+    let mut row_splices = Vec::new();
 
     for (idx, row) in parse.rows.iter().enumerate() {
         let ref_value = parse.get_ref(idx);
-        let normalized = ref_value.replace(' ', "");
-
-        if let Some((prefix, start, end)) = parse_reference_range(&normalized) {
-            if end < start {
-                return Err(AppError::new(format!(
-                    "Refの範囲指定が不正です: {}",
-                    ref_value
-                )));
+        let mut references = Vec::new();
+        let mut has_range = false;
+
+        for token in split_reference_tokens(&ref_value) {
+            match parse_reference_range(&token) {
+                Some((prefix, start, end)) => {
+                    let (start, end) = if end >= start {
+                        (start, end)
+                    } else if options.allow_descending {
+                        (end, start)
+                    } else {
+                        return Err(AppError::validation(format!(
+                            "Refの範囲指定が不正です: {}",
+                            token
+                        )));
+                    };
+                    let count = (end - start) as usize + 1;
+                    if references.len() + count > options.max_expansion {
+                        return Err(AppError::validation(format!(
+                            "Refの範囲指定 {} は展開できる上限（1セルあたり{}件）を超えています: {}",
+                            token, options.max_expansion, ref_value
+                        )));
+                    }
+                    has_range = true;
+                    references.extend((start..=end).map(|index| format!("{}{}", prefix, index)));
+                }
+                None => references.push(token),
             }
+        }
+
+        if has_range {
+            row_splices.push(RowSplice {
+                index: expanded_rows.len(),
+                inserted_count: references.len(),
+                removed_rows: vec![row.clone()],
+            });
 
             // 範囲を展開
-            for index in start..=end {
+            let ref_indices = parse.get_column_indices("ref");
+            for reference in references {
                 let mut new_row = row.clone();
                 // Reference列を更新
-                let ref_indices = parse.get_column_indices("ref");
                 for &col_idx in &ref_indices {
                     if col_idx < new_row.len() {
-                        new_row[col_idx] = format!("{}{}", prefix, index);
+                        new_row[col_idx] = reference.clone();
                     }
                 }
                 expanded_rows.push(new_row);
+                row_numbers.push(parse.source_row_number(idx));
             }
         } else {
             expanded_rows.push(row.clone());
+            row_numbers.push(parse.source_row_number(idx));
         }
     }
 
-    let row_count = expanded_rows.len();
-
-    Ok(ParseResult {
-        rows: expanded_rows,
-        column_roles: parse.column_roles.clone(),
-        column_order: parse.column_order.clone(),
-        #[allow(deprecated)]
-        guessed_columns: HashMap::new(),
-        #[allow(deprecated)]
-        guessed_roles: HashMap::new(),
-        errors: vec![],
-        headers: parse.headers.clone(),
-        columns: parse.columns.clone(),
-        row_numbers: (1..=row_count).collect(),
-        structured_errors: None,
+    // 展開した行は元の行の行番号を引き継ぐ
+    let result = ParseResult::builder()
+        .schema_from(parse)
+        .rows(expanded_rows)
+        .row_numbers(row_numbers)
+        .build();
+
+    // エラー・検証結果・書式はクリアされるため、元の値を記録する
+    Ok(TransformResult {
+        result,
+        transform: Transform {
+            operation: "expand_reference".to_string(),
+            cell_edits: vec![],
+            row_splices,
+            previous_metadata: Some(capture_metadata(parse)),
+        },
     })
 }
 
 /// Reference分割（"C1, C2, C3" → 3行に分割）
 pub fn split_reference_rows(parse: &ParseResult) -> Result<ParseResult, AppError> {
     let mut result_rows = Vec::new();
+    let mut row_numbers = Vec::new();
 
     for (idx, row) in parse.rows.iter().enumerate() {
         let ref_value = parse.get_ref(idx);
@@ -69,6 +138,7 @@ pub fn split_reference_rows(parse: &ParseResult) -> Result<ParseResult, AppError
 
         if references.len() <= 1 {
             result_rows.push(row.clone());
+            row_numbers.push(parse.source_row_number(idx));
             continue;
         }
 
@@ -82,28 +152,289 @@ pub fn split_reference_rows(parse: &ParseResult) -> Result<ParseResult, AppError
                 }
             }
             result_rows.push(new_row);
+            row_numbers.push(parse.source_row_number(idx));
+        }
+    }
+
+    // 分割した行は元の行の行番号を引き継ぐ
+    Ok(ParseResult::builder()
+        .schema_from(parse)
+        .rows(result_rows)
+        .row_numbers(row_numbers)
+        .build())
+}
+
+/// 部品型番ごとに実装数を集計
+///
+/// 範囲指定（"C1-C5"）は展開し、カンマ・セミコロン区切りのReferenceはそれぞれ1つとして数える。
+/// CAD形式の出力と同様に、Referenceが空の行は除き、型番が空の場合は "(未指定)" にまとめる。
+/// 未実装（DNP）の行は実装数に含めず、`dnp_refs` に分けて返す。
+///
+/// # 戻り値
+/// 部品型番ごとの集計（型番順）。範囲指定の展開数が上限を超える場合はエラー
+pub fn summarize_by_part(parse: &ParseResult) -> Result<Vec<PartSummary>, AppError> {
+    // 部品型番 → （実装するReference, 未実装のReference）
+    let mut refs_by_part: BTreeMap<String, (Vec<String>, Vec<String>)> = BTreeMap::new();
+
+    for idx in 0..parse.rows.len() {
+        let refs = individual_references(&parse.get_ref(idx))?;
+        if refs.is_empty() {
+            continue;
+        }
+
+        let part_no = parse
+            .get_values(idx, "part_no")
+            .into_iter()
+            .map(|value| value.trim().to_string())
+            .find(|value| !value.is_empty())
+            .unwrap_or_else(|| UNSPECIFIED_GROUP_KEY.to_string());
+        let (placed, dnp) = refs_by_part.entry(part_no).or_default();
+        if parse.is_dnp(idx) {
+            dnp.extend(refs);
+        } else {
+            placed.extend(refs);
+        }
+    }
+
+    Ok(refs_by_part
+        .into_iter()
+        .map(|(part_no, (refs, dnp_refs))| PartSummary {
+            part_no,
+            ref_count: refs.len(),
+            refs,
+            dnp_refs,
+        })
+        .collect())
+}
+
+/// 指定した接頭辞のReferenceを連番で付け直す（欠番を詰める）
+///
+/// 使用されている番号を昇順に並べ、`start` から順に割り当てる（"C1, C3, C4" → "C1, C2, C3"）。
+/// 範囲指定（"C5-C7"）は範囲内の番号をすべて使用済みとして数え、両端を付け直す。
+/// 接頭辞は大文字・小文字を区別せずに完全一致で判定し（"C" を指定しても "CN1" は対象外）、
+/// 番号の後の文字（"U1A" の "A"）と、先頭の0による桁数（"C01" → "C02"）はそのまま残す。
+/// Reference列以外のセルやほかの接頭辞のReferenceは変更しない。
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `prefix` - 付け直す接頭辞（"C" など、英字のみ）
+/// * `start` - 最初に割り当てる番号
+///
+/// # 戻り値
+/// 付け直したBOMデータと、変更したReferenceの一覧。接頭辞が不正な場合や
+/// 番号が `u32` の範囲を超える場合はエラー
+pub fn renumber_references(
+    parse: &ParseResult,
+    prefix: &str,
+    start: u32,
+) -> Result<RenumberResult, AppError> {
+    let prefix = prefix.trim().to_uppercase();
+    if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(AppError::validation(format!(
+            "接頭辞 '{prefix}' は英字のみで指定してください。"
+        )));
+    }
+    let ref_indices = parse.get_column_indices("ref");
+
+    // 使用されている番号を収集
+    let mut used: BTreeSet<u32> = BTreeSet::new();
+    for row in &parse.rows {
+        for &col_idx in &ref_indices {
+            let Some(cell) = row.get(col_idx) else {
+                continue;
+            };
+            for token in cell.split(is_renumber_separator) {
+                let Some((first, last)) = parse_numbered_token(token, &prefix) else {
+                    continue;
+                };
+                let (low, high) = (first.number.min(last.number), first.number.max(last.number));
+                if (high - low) as usize >= DEFAULT_MAX_REFERENCE_EXPANSION {
+                    return Err(AppError::validation(format!(
+                        "範囲指定 '{token}' のReferenceが多すぎます（上限: {DEFAULT_MAX_REFERENCE_EXPANSION}）。"
+                    )));
+                }
+                used.extend(low..=high);
+            }
+        }
+    }
+
+    let mut numbers: HashMap<u32, u32> = HashMap::with_capacity(used.len());
+    for (rank, &old) in used.iter().enumerate() {
+        let new = u32::try_from(rank)
+            .ok()
+            .and_then(|rank| start.checked_add(rank))
+            .ok_or_else(|| AppError::validation("付け直した番号が大きすぎます。"))?;
+        numbers.insert(old, new);
+    }
+
+    let mut result = parse.clone();
+    let mut mapping = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for row in &mut result.rows {
+        for &col_idx in &ref_indices {
+            let Some(cell) = row.get_mut(col_idx) else {
+                continue;
+            };
+            let mut renamed = String::with_capacity(cell.len());
+            let mut token_start = None;
+            for (idx, c) in cell.char_indices().chain([(cell.len(), ',')]) {
+                if !is_renumber_separator(c) {
+                    token_start.get_or_insert(idx);
+                    continue;
+                }
+                if let Some(begin) = token_start.take() {
+                    let token = &cell[begin..idx];
+                    let new_token = renumber_token(token, &prefix, &numbers);
+                    if new_token != token && seen.insert(token.to_string()) {
+                        mapping.push(ReferenceRename {
+                            old: token.to_string(),
+                            new: new_token.clone(),
+                        });
+                    }
+                    renamed.push_str(&new_token);
+                }
+                if idx < cell.len() {
+                    renamed.push(c);
+                }
+            }
+            *cell = renamed;
         }
     }
 
-    let row_count = result_rows.len();
-
-    Ok(ParseResult {
-        rows: result_rows,
-        column_roles: parse.column_roles.clone(),
-        column_order: parse.column_order.clone(),
-        #[allow(deprecated)]
-        guessed_columns: HashMap::new(),
-        #[allow(deprecated)]
-        guessed_roles: HashMap::new(),
-        errors: vec![],
-        headers: parse.headers.clone(),
-        columns: parse.columns.clone(),
-        row_numbers: (1..=row_count).collect(),
-        structured_errors: None,
+    Ok(RenumberResult { result, mapping })
+}
+
+/// 番号を付け直すときのReferenceの区切り文字
+fn is_renumber_separator(c: char) -> bool {
+    REFERENCE_SEPARATORS.contains(&c) || c.is_whitespace()
+}
+
+/// 接頭辞・番号・番号の後の文字に分解したReference
+struct NumberedRef<'a> {
+    /// 元の表記の接頭辞（範囲の終端で省略された場合は空）
+    head: &'a str,
+    digits: &'a str,
+    number: u32,
+    tail: &'a str,
+}
+
+impl NumberedRef<'_> {
+    fn render(&self, number: u32) -> String {
+        // 先頭の0で桁数をそろえている場合は同じ桁数にする
+        let digits = if self.digits.len() > 1 && self.digits.starts_with('0') {
+            format!("{number:0width$}", width = self.digits.len())
+        } else {
+            number.to_string()
+        };
+        format!("{}{digits}{}", self.head, self.tail)
+    }
+}
+
+/// Referenceを分解する（接頭辞が `prefix` と一致しない場合は None）
+fn parse_numbered_ref<'a>(
+    reference: &'a str,
+    prefix: &str,
+    allow_bare: bool,
+) -> Option<NumberedRef<'a>> {
+    let head_len = reference
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(reference.len());
+    let head = &reference[..head_len];
+    if !(head.eq_ignore_ascii_case(prefix) || allow_bare && head.is_empty()) {
+        return None;
+    }
+    let rest = &reference[head_len..];
+    let digits_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let digits = &rest[..digits_len];
+    Some(NumberedRef {
+        head,
+        digits,
+        number: digits.parse().ok()?,
+        tail: &rest[digits_len..],
     })
 }
 
-fn parse_reference_range(reference: &str) -> Option<(String, u32, u32)> {
+/// セル内の1つの要素を分解（範囲指定の場合は両端、単独の場合は同じものを2つ）
+fn parse_numbered_token<'a>(
+    token: &'a str,
+    prefix: &str,
+) -> Option<(NumberedRef<'a>, NumberedRef<'a>)> {
+    match token.split_once('-') {
+        Some((first, last)) => Some((
+            parse_numbered_ref(first, prefix, false)?,
+            parse_numbered_ref(last, prefix, true)?,
+        )),
+        None => Some((
+            parse_numbered_ref(token, prefix, false)?,
+            parse_numbered_ref(token, prefix, false)?,
+        )),
+    }
+}
+
+/// セル内の1つの要素の番号を付け直す（対象外の要素はそのまま）
+fn renumber_token(token: &str, prefix: &str, numbers: &HashMap<u32, u32>) -> String {
+    let Some((first, last)) = parse_numbered_token(token, prefix) else {
+        return token.to_string();
+    };
+    let new_number = |reference: &NumberedRef| {
+        numbers
+            .get(&reference.number)
+            .copied()
+            .unwrap_or(reference.number)
+    };
+    if token.contains('-') {
+        format!(
+            "{}-{}",
+            first.render(new_number(&first)),
+            last.render(new_number(&last))
+        )
+    } else {
+        first.render(new_number(&first))
+    }
+}
+
+/// セル内のReferenceを1つずつに分ける（"C1,C2;C5-C7" → C1, C2, C5, C6, C7）
+///
+/// カンマ・セミコロンで分割し、範囲指定は展開する。空白は除去し、空の要素は除く。
+/// 展開数が `DEFAULT_MAX_REFERENCE_EXPANSION` を超える場合はエラー（Reference展開と同じ上限）
+pub fn individual_references(value: &str) -> Result<Vec<String>, AppError> {
+    let mut refs = Vec::new();
+    for normalized in split_reference_tokens(value) {
+        match parse_reference_range(&normalized) {
+            Some((prefix, start, end)) if start <= end => {
+                let count = (end - start) as usize + 1;
+                if refs.len() + count > DEFAULT_MAX_REFERENCE_EXPANSION {
+                    return Err(AppError::validation(format!(
+                        "Refの範囲指定 {} は展開できる上限（1セルあたり{}件）を超えています: {}",
+                        normalized, DEFAULT_MAX_REFERENCE_EXPANSION, value
+                    )));
+                }
+                refs.extend((start..=end).map(|index| format!("{}{}", prefix, index)));
+            }
+            _ => refs.push(normalized),
+        }
+    }
+    Ok(refs)
+}
+
+/// セル内のReferenceを区切り文字で分割（空白は除去し、空の要素は除く）
+fn split_reference_tokens(value: &str) -> Vec<String> {
+    value
+        .split(REFERENCE_SEPARATORS)
+        .map(|token| {
+            token
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+        })
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// 範囲指定のReferenceを (接頭辞, 開始番号, 終了番号) に分解（"C1-C5" → ("C", 1, 5)）
+pub(crate) fn parse_reference_range(reference: &str) -> Option<(String, u32, u32)> {
     let parts: Vec<&str> = reference.split('-').collect();
     if parts.len() != 2 {
         return None;
@@ -152,3 +483,216 @@ fn split_prefix_digits(value: &str) -> (String, String) {
     let digits = value.get(prefix.len()..).unwrap_or("").to_string();
     (prefix, digits)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_parse() -> ParseResult {
+        let headers = vec!["Ref".to_string(), "Part".to_string()];
+        let rows = [["C1-C3", "GRM155"], ["R1, R2", "RC0402"], ["U1", "74HC08"]];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .row_numbers(vec![4, 5, 7])
+            .header_row_index(Some(2))
+            .data_start_index(3)
+            .build()
+    }
+
+    #[test]
+    fn test_expand_and_split_keep_source_row_numbers() {
+        let expanded = expand_reference(&sample_parse()).unwrap();
+        assert_eq!(expanded.row_numbers, vec![4, 4, 4, 5, 7]);
+
+        let split = split_reference_rows(&expanded).unwrap();
+        let refs: Vec<String> = (0..split.rows.len())
+            .map(|idx| split.get_ref(idx))
+            .collect();
+        assert_eq!(refs, vec!["C1", "C2", "C3", "R1", "R2", "U1"]);
+        assert_eq!(split.row_numbers, vec![4, 4, 4, 5, 5, 7]);
+        assert_eq!(split.header_row_index, Some(2));
+        assert_eq!(split.data_start_index, 3);
+    }
+
+    #[test]
+    fn test_expand_multi_range_tokens() {
+        let mut parse = sample_parse();
+        parse.rows = vec![
+            vec!["C1-C3,C7".to_string(), "GRM155".to_string()],
+            vec!["R1,R2-R4;R8".to_string(), "RC0402".to_string()],
+            vec!["U1, U2".to_string(), "74HC08".to_string()],
+        ];
+
+        let output = expand_reference_with_transform(&parse).unwrap();
+        let refs: Vec<String> = (0..output.result.rows.len())
+            .map(|idx| output.result.get_ref(idx))
+            .collect();
+        assert_eq!(
+            refs,
+            vec!["C1", "C2", "C3", "C7", "R1", "R2", "R3", "R4", "R8", "U1, U2"]
+        );
+        assert_eq!(
+            output.result.row_numbers,
+            vec![4, 4, 4, 4, 5, 5, 5, 5, 5, 7]
+        );
+        assert_eq!(output.result.get_part_no(8), "RC0402");
+        let splices: Vec<(usize, usize)> = output
+            .transform
+            .row_splices
+            .iter()
+            .map(|splice| (splice.index, splice.inserted_count))
+            .collect();
+        assert_eq!(splices, vec![(0, 4), (4, 5)]);
+
+        parse.rows[0][0] = "C1-C3,C9-C7".to_string();
+        assert!(expand_reference(&parse).is_err());
+    }
+
+    #[test]
+    fn test_expand_descending_ranges_and_cap() {
+        let mut parse = sample_parse();
+        parse.rows[0][0] = "C5-C3".to_string();
+        assert!(expand_reference(&parse).is_err());
+
+        let options = ExpandOptions {
+            allow_descending: true,
+            ..ExpandOptions::default()
+        };
+        let expanded = expand_reference_with_options(&parse, &options).unwrap();
+        let refs: Vec<String> = (0..3).map(|idx| expanded.get_ref(idx)).collect();
+        assert_eq!(refs, vec!["C3", "C4", "C5"]);
+
+        parse.rows[0][0] = "C1-C100000".to_string();
+        let error = expand_reference(&parse).unwrap_err();
+        assert!(error.message.contains("上限"));
+
+        // 上限は1セル内の範囲の合計で判定する
+        parse.rows[0][0] = "C1-C3,C10-C12".to_string();
+        let options = ExpandOptions {
+            max_expansion: 5,
+            ..ExpandOptions::default()
+        };
+        assert!(expand_reference_with_options(&parse, &options).is_err());
+        let options = ExpandOptions {
+            max_expansion: 6,
+            ..ExpandOptions::default()
+        };
+        assert_eq!(
+            expand_reference_with_options(&parse, &options)
+                .unwrap()
+                .rows
+                .len(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_summarize_by_part() {
+        let mut parse = sample_parse();
+        parse.rows.extend([
+            vec!["C4, C6-C7".to_string(), "GRM155".to_string()],
+            vec!["TP1".to_string(), String::new()],
+            vec![String::new(), "GRM155".to_string()],
+        ]);
+
+        let summary = summarize_by_part(&parse).unwrap();
+        let counts: Vec<(&str, usize)> = summary
+            .iter()
+            .map(|part| (part.part_no.as_str(), part.ref_count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("(未指定)", 1), ("74HC08", 1), ("GRM155", 6), ("RC0402", 2)]
+        );
+        assert_eq!(summary[2].refs, vec!["C1", "C2", "C3", "C4", "C6", "C7"]);
+        assert!(summary.iter().all(|part| part.dnp_refs.is_empty()));
+
+        // 大きすぎる範囲指定は展開せずにエラー
+        parse
+            .rows
+            .push(vec!["C1-C999999999".to_string(), "GRM155".to_string()]);
+        let error = summarize_by_part(&parse).unwrap_err();
+        assert_eq!(error.kind, crate::models::ErrorKind::Validation);
+    }
+
+    #[test]
+    fn test_summarize_by_part_excludes_dnp() {
+        let mut parse = sample_parse();
+        for row in parse.rows.iter_mut() {
+            row.push(String::new());
+        }
+        parse.rows.push(vec![
+            "C4-C5".to_string(),
+            "GRM155".to_string(),
+            "DNP".to_string(),
+        ]);
+        parse
+            .column_roles
+            .insert("dnp".to_string(), vec!["col-2".to_string()]);
+
+        let summary = summarize_by_part(&parse).unwrap();
+        let grm = summary
+            .iter()
+            .find(|part| part.part_no == "GRM155")
+            .unwrap();
+        assert_eq!(grm.refs, vec!["C1", "C2", "C3"]);
+        assert_eq!(grm.ref_count, 3);
+        assert_eq!(grm.dnp_refs, vec!["C4", "C5"]);
+    }
+
+    fn renumber_parse(refs: &[&str]) -> ParseResult {
+        let mut parse = sample_parse();
+        parse.rows = refs
+            .iter()
+            .map(|reference| vec![reference.to_string(), "GRM155".to_string()])
+            .collect();
+        parse.row_numbers = (1..=refs.len()).collect();
+        parse
+    }
+
+    fn refs_of(parse: &ParseResult) -> Vec<&str> {
+        parse.rows.iter().map(|row| row[0].as_str()).collect()
+    }
+
+    #[test]
+    fn test_renumber_references_closes_gaps() {
+        let parse = renumber_parse(&["C4", "c1, CN3", "R2", "C3", "C7-C8", "U1A"]);
+        let renumbered = renumber_references(&parse, "c", 1).unwrap();
+        assert_eq!(
+            refs_of(&renumbered.result),
+            vec!["C3", "c1, CN3", "R2", "C2", "C4-C5", "U1A"]
+        );
+        assert_eq!(renumbered.result.rows[0][1], "GRM155");
+        let mapping: Vec<(&str, &str)> = renumbered
+            .mapping
+            .iter()
+            .map(|rename| (rename.old.as_str(), rename.new.as_str()))
+            .collect();
+        assert_eq!(
+            mapping,
+            vec![("C4", "C3"), ("C3", "C2"), ("C7-C8", "C4-C5")]
+        );
+
+        // 開始番号を指定
+        let renumbered = renumber_references(&parse, "R", 101).unwrap();
+        assert_eq!(refs_of(&renumbered.result)[2], "R101");
+
+        assert!(renumber_references(&parse, "C1", 1).is_err());
+        assert!(renumber_references(&parse, " ", 1).is_err());
+    }
+
+    #[test]
+    fn test_renumber_references_keeps_zero_padding() {
+        let parse = renumber_parse(&["R01", "R05;R09", "R12A"]);
+        let renumbered = renumber_references(&parse, "R", 1).unwrap();
+        assert_eq!(refs_of(&renumbered.result), vec!["R01", "R02;R03", "R4A"]);
+        assert!(renumbered.mapping.iter().all(|rename| rename.old != "R01"));
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{AppError, ParseResult};
+use crate::parsers::{build_column_order, is_role_detection_warning};
+
+/// 列の役割を手動で設定する
+///
+/// # 動作
+/// 1. 指定された列IDが存在するか検証
+/// 2. 同じ列が異なる役割に割り当てられていないか検証
+/// 3. 読み込み時と同じ規則で列の表示順序を再作成
+/// 4. 役割の自動判定に関する警告を削除
+///
+/// # 引数
+/// * `parse` - 元のBOMデータ
+/// * `roles` - 役割名 → 列IDの配列
+///
+/// # 戻り値
+/// 役割と表示順序を更新したBOMデータ
+pub fn set_column_roles(
+    parse: &ParseResult,
+    roles: HashMap<String, Vec<String>>,
+) -> Result<ParseResult, AppError> {
+    let column_count = parse.column_count();
+
+    let mut column_roles: HashMap<String, Vec<String>> = HashMap::new();
+    let mut assigned: HashMap<String, String> = HashMap::new();
+
+    // 役割名の順序に依存しないよう、並べ替えてから検証する
+    let mut role_names: Vec<&String> = roles.keys().collect();
+    role_names.sort();
+
+    for role in role_names {
+        let role_key = role.trim();
+        if role_key.is_empty() {
+            return Err(AppError::validation("役割名が空です。"));
+        }
+
+        let mut seen = HashSet::new();
+        let mut col_ids = Vec::new();
+        for col_id in &roles[role] {
+            let col_id = col_id.trim();
+            let exists = col_id
+                .strip_prefix("col-")
+                .and_then(|num| num.parse::<usize>().ok())
+                .is_some_and(|idx| idx < column_count);
+            if !exists {
+                return Err(AppError::validation(format!(
+                    "列が見つかりません: {col_id}"
+                )));
+            }
+
+            if let Some(other_role) = assigned.get(col_id) {
+                if other_role != role_key {
+                    return Err(AppError::validation(format!(
+                        "列 {col_id} が複数の役割（{other_role}, {role_key}）に割り当てられています。"
+                    )));
+                }
+            }
+
+            if seen.insert(col_id.to_string()) {
+                assigned.insert(col_id.to_string(), role_key.to_string());
+                col_ids.push(col_id.to_string());
+            }
+        }
+
+        if !col_ids.is_empty() {
+            column_roles.insert(role_key.to_string(), col_ids);
+        }
+    }
+
+    let column_order = build_column_order(&column_roles, column_count);
+
+    let errors = parse
+        .errors
+        .iter()
+        .filter(|message| !is_role_detection_warning(message))
+        .cloned()
+        .collect();
+    let structured_errors = parse.structured_errors.as_ref().map(|errors| {
+        errors
+            .iter()
+            .filter(|error| !is_role_detection_warning(&error.message))
+            .cloned()
+            .collect()
+    });
+
+    // 行は変わらないため、行番号・書式・読み込み時の値・検証結果はそのまま引き継ぐ
+    let mut result = ParseResult::with_rows(parse.rows.clone(), parse);
+    result.column_roles = column_roles;
+    result.column_order = column_order;
+    result.errors = errors;
+    result.structured_errors = structured_errors;
+    result.validation_summary = parse.validation_summary.clone();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ParseError;
+
+    fn sample_parse() -> ParseResult {
+        let headers: Vec<String> = ["Note", "Maker", "Part", "Ref", "Value"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let warning = "部品型番列の候補が複数見つかりました（Column 3, Column 4）。編集モードで指定してください。";
+        ParseResult::builder()
+            .headers(headers)
+            .rows(vec![["", "Murata", "GRM155", "C1", "0.1uF"]
+                .iter()
+                .map(|cell| cell.to_string())
+                .collect()])
+            .errors(vec![
+                warning.to_string(),
+                "2行目: 部品型番が空です。".to_string(),
+            ])
+            .row_numbers(vec![2])
+            .structured_errors(Some(vec![ParseError {
+                message: warning.to_string(),
+                row: None,
+                column: None,
+                severity: "warning".to_string(),
+            }]))
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build()
+    }
+
+    fn roles(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(role, ids)| {
+                (
+                    role.to_string(),
+                    ids.iter().map(|id| id.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_set_column_roles_rebuilds_order_and_clears_warnings() {
+        let result = set_column_roles(
+            &sample_parse(),
+            roles(&[
+                ("value", &["col-4"]),
+                ("manufacturer", &["col-1"]),
+                ("part_no", &["col-2"]),
+                ("ref", &["col-3"]),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.column_order,
+            vec!["col-3", "col-2", "col-1", "col-4", "col-0"]
+        );
+        assert_eq!(result.get_ref(0), "C1");
+        assert_eq!(result.get_part_no(0), "GRM155");
+        assert_eq!(result.errors, vec!["2行目: 部品型番が空です。".to_string()]);
+        assert!(result.structured_errors.unwrap().is_empty());
+        assert_eq!(result.rows, sample_parse().rows);
+    }
+
+    #[test]
+    fn test_set_column_roles_rejects_unknown_and_conflicting_columns() {
+        let err = set_column_roles(&sample_parse(), roles(&[("ref", &["col-9"])])).unwrap_err();
+        assert!(err.message.contains("col-9"));
+
+        let err = set_column_roles(
+            &sample_parse(),
+            roles(&[("ref", &["col-3"]), ("part_no", &["col-3"])]),
+        )
+        .unwrap_err();
+        assert!(err.message.contains("複数の役割"));
+
+        // 同じ役割内での重複は1つにまとめる
+        let result =
+            set_column_roles(&sample_parse(), roles(&[("ref", &["col-3", "col-3"])])).unwrap();
+        assert_eq!(result.column_roles["ref"], vec!["col-3"]);
+    }
+}
@@ -0,0 +1,127 @@
+use std::cmp::Ordering;
+
+use crate::models::ParseResult;
+use crate::utils::text::natural_ref_cmp;
+
+/// 指定した役割の列の値で行を並べ替える
+///
+/// 列構造（列・ヘッダー・役割）は変更せず、行と、行に対応する行番号・書式・
+/// 読み込み時の値を同じ順に並べ替える。値が空の行は元の順序のまま末尾に置く。
+/// 同じ値の行も元の順序を保つ（安定ソート）
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `by_role` - 並べ替えに使う役割（"ref", "part_no" など、複数列の場合は最初の値のある列）
+/// * `natural` - true かつ役割が "ref" の場合は自然順（"C2" < "C10"）、それ以外は文字列順
+///
+/// # 戻り値
+/// 並べ替えたBOM（役割の列がない場合は元の順序のまま）
+pub fn sort_rows(parse: &ParseResult, by_role: &str, natural: bool) -> ParseResult {
+    let use_natural = natural && by_role == "ref";
+    let keys: Vec<String> = (0..parse.rows.len())
+        .map(|idx| {
+            parse
+                .get_values(idx, by_role)
+                .into_iter()
+                .map(|value| value.trim().to_string())
+                .find(|value| !value.is_empty())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..parse.rows.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a_key, b_key) = (&keys[a], &keys[b]);
+        match (a_key.is_empty(), b_key.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) if use_natural => natural_ref_cmp(a_key, b_key),
+            (false, false) => a_key.cmp(b_key),
+        }
+    });
+
+    let row_count = parse.rows.len();
+
+    let cell_styles = parse
+        .cell_styles
+        .as_ref()
+        .filter(|styles| styles.len() == row_count)
+        .map(|styles| reorder(styles, &order));
+    let original_rows = parse
+        .original_rows
+        .as_ref()
+        .filter(|rows| rows.len() == row_count)
+        .map(|rows| reorder(rows, &order));
+
+    ParseResult::builder()
+        .schema_from(parse)
+        .rows(reorder(&parse.rows, &order))
+        .row_numbers(
+            order
+                .iter()
+                .map(|&idx| parse.source_row_number(idx))
+                .collect(),
+        )
+        .cell_styles(cell_styles)
+        .original_rows(original_rows)
+        .build()
+}
+
+/// `order` の順（元のインデックスの並び）に要素を並べ替える
+fn reorder<T: Clone>(items: &[T], order: &[usize]) -> Vec<T> {
+    order.iter().map(|&idx| items[idx].clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(rows: &[[&str; 2]]) -> ParseResult {
+        let mut parse = ParseResult::builder()
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .row_numbers((2..rows.len() + 2).collect())
+            .build();
+        parse.headers = vec!["Ref".into(), "Part".into()];
+        parse.rebuild_columns_from_headers();
+        parse
+            .column_roles
+            .insert("ref".into(), vec!["col-0".into()]);
+        parse
+            .column_roles
+            .insert("part_no".into(), vec!["col-1".into()]);
+        parse
+    }
+
+    #[test]
+    fn test_sort_rows_by_reference() {
+        let parse = build(&[
+            ["C10", "GRM155"],
+            ["", "TP"],
+            ["R1", "RC0402"],
+            ["C2", "GRM188"],
+            ["", "NC"],
+            ["C1", "GRM155"],
+        ]);
+
+        let sorted = sort_rows(&parse, "ref", true);
+        let refs: Vec<String> = (0..sorted.rows.len())
+            .map(|idx| sorted.get_ref(idx))
+            .collect();
+        assert_eq!(refs, vec!["C1", "C2", "C10", "R1", "", ""]);
+        assert_eq!(sorted.row_numbers, vec![7, 5, 2, 4, 3, 6]);
+        assert_eq!(sorted.headers, parse.headers);
+        assert_eq!(sorted.column_roles, parse.column_roles);
+
+        // 文字列順では "C10" が "C2" より前
+        let sorted = sort_rows(&parse, "ref", false);
+        assert_eq!(sorted.row_numbers, vec![7, 2, 5, 4, 3, 6]);
+
+        let sorted = sort_rows(&parse, "part_no", true);
+        assert_eq!(sorted.row_numbers, vec![2, 7, 5, 6, 4, 3]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::models::{AppError, ParseMetadata, ParseResult, Transform};
+
+/// 処理前の付随情報を記録
+pub(crate) fn capture_metadata(parse: &ParseResult) -> ParseMetadata {
+    ParseMetadata {
+        errors: parse.errors.clone(),
+        row_numbers: parse.row_numbers.clone(),
+        structured_errors: parse.structured_errors.clone(),
+        validation_summary: parse.validation_summary.clone(),
+        header_row_index: parse.header_row_index,
+        data_start_index: parse.data_start_index,
+        cell_styles: parse.cell_styles.clone(),
+        original_rows: parse.original_rows.clone(),
+    }
+}
+
+/// 変更の記録を使って処理前の状態に戻す
+///
+/// # 引数
+/// * `parse` - 処理後のBOM（記録を作成した処理の結果）
+/// * `transform` - 処理が返した変更の記録
+///
+/// # 戻り値
+/// 処理前のBOM（記録と現在のデータが一致しない場合はエラー）
+pub fn undo_transform(parse: &ParseResult, transform: &Transform) -> Result<ParseResult, AppError> {
+    let mismatch = || {
+        AppError::validation(format!(
+            "変更の記録（{}）が現在のデータと一致しないため元に戻せません",
+            transform.operation
+        ))
+    };
+    let mut result = parse.clone();
+
+    // 行インデックスは処理後の位置のため、セルの変更を先に戻してから行を戻す
+    for edit in transform.cell_edits.iter().rev() {
+        let cell = result
+            .rows
+            .get_mut(edit.row)
+            .and_then(|row| row.get_mut(edit.column))
+            .ok_or_else(mismatch)?;
+        if *cell != edit.after {
+            return Err(mismatch());
+        }
+        *cell = edit.before.clone();
+    }
+
+    // 後ろから戻すことで、前方の置き換え位置がずれないようにする
+    for splice in transform.row_splices.iter().rev() {
+        let end = splice.index + splice.inserted_count;
+        if end > result.rows.len() {
+            return Err(mismatch());
+        }
+        result
+            .rows
+            .splice(splice.index..end, splice.removed_rows.iter().cloned());
+    }
+
+    if let Some(metadata) = &transform.previous_metadata {
+        result.errors = metadata.errors.clone();
+        result.row_numbers = metadata.row_numbers.clone();
+        result.structured_errors = metadata.structured_errors.clone();
+        result.validation_summary = metadata.validation_summary.clone();
+        result.header_row_index = metadata.header_row_index;
+        result.data_start_index = metadata.data_start_index;
+        result.cell_styles = metadata.cell_styles.clone();
+        result.original_rows = metadata.original_rows.clone();
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::cleaner::fill_blank_cells_with_transform;
+    use crate::processors::reference::expand_reference_with_transform;
+
+    fn sample_parse(rows: &[[&str; 2]]) -> ParseResult {
+        let headers = vec!["Ref".to_string(), "Part".to_string()];
+        ParseResult::builder()
+            .headers(headers)
+            .rows(
+                rows.iter()
+                    .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                    .collect(),
+            )
+            .role("ref", &[0])
+            .role("part_no", &[1])
+            .errors(vec!["警告".to_string()])
+            .row_numbers((2..rows.len() + 2).collect())
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build()
+    }
+
+    #[test]
+    fn test_undo_fill_blank_cells() {
+        let parse = sample_parse(&[["C1", "GRM155"], ["C2", ""], ["", " "]]);
+        let output = fill_blank_cells_with_transform(&parse).unwrap();
+
+        assert_eq!(output.result.rows[2], vec!["C2", "GRM155"]);
+        assert_eq!(output.transform.cell_edits.len(), 3);
+        assert!(output.transform.row_splices.is_empty());
+        assert!(output.result.errors.is_empty());
+
+        let restored = undo_transform(&output.result, &output.transform).unwrap();
+        assert_eq!(restored.rows, parse.rows);
+        assert_eq!(restored.errors, parse.errors);
+    }
+
+    #[test]
+    fn test_undo_expand_reference() {
+        let parse = sample_parse(&[["C1-C3", "GRM155"], ["R1", "RC0402"], ["R2-R3", "RC0603"]]);
+        let output = expand_reference_with_transform(&parse).unwrap();
+
+        assert_eq!(output.result.rows.len(), 6);
+        assert_eq!(
+            output
+                .transform
+                .row_splices
+                .iter()
+                .map(|splice| (splice.index, splice.inserted_count))
+                .collect::<Vec<_>>(),
+            vec![(0, 3), (4, 2)]
+        );
+
+        let restored = undo_transform(&output.result, &output.transform).unwrap();
+        assert_eq!(restored.rows, parse.rows);
+        assert_eq!(restored.row_numbers, parse.row_numbers);
+        assert_eq!(restored.errors, parse.errors);
+        assert_eq!(restored.header_row_index, Some(0));
+        assert_eq!(restored.data_start_index, 1);
+    }
+
+    #[test]
+    fn test_undo_transform_rejects_mismatched_data() {
+        let parse = sample_parse(&[["C1", "GRM155"], ["C2", ""]]);
+        let output = fill_blank_cells_with_transform(&parse).unwrap();
+
+        let mut edited = output.result.clone();
+        edited.rows[1][1] = "GRM188".to_string();
+        assert!(undo_transform(&edited, &output.transform).is_err());
+        assert!(undo_transform(&sample_parse(&[]), &output.transform).is_err());
+    }
+}
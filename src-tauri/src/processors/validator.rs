@@ -1 +1,259 @@
 // TODO: バリデーション関連のロジックを段階的に移行する
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::models::{
+    AppError, GapReport, ParseError, ParseResult, DEFAULT_MAX_REFERENCE_EXPANSION,
+};
+use crate::processors::reference::parse_reference_range;
+
+/// 欠番を検出する接頭辞の最小の使用数（これより少ない接頭辞は報告しない）
+pub const MIN_GAP_GROUP_SIZE: usize = 3;
+
+/// Referenceの接頭辞（"C1" の "C"）が許可リストに含まれるか検証
+///
+/// 1つのセルに複数のReference（"C1, C2"、"C1-C5" など）がある場合は、それぞれを検証する。
+/// 接頭辞は大文字・小文字を区別しない。
+///
+/// # 引数
+/// * `parse` - BOMデータ
+/// * `allowed` - 許可する接頭辞（"C", "R", "IC" など、空の場合は検証しない）
+///
+/// # 戻り値
+/// 許可されていない接頭辞のReferenceごとの警告（行番号は元ファイルでの行番号）
+pub fn validate_ref_prefixes(parse: &ParseResult, allowed: &[String]) -> Vec<ParseError> {
+    let allowed: HashSet<String> = allowed
+        .iter()
+        .map(|prefix| prefix.trim().to_uppercase())
+        .filter(|prefix| !prefix.is_empty())
+        .collect();
+    if allowed.is_empty() {
+        return Vec::new();
+    }
+
+    let ref_indices = parse.get_column_indices("ref");
+    let mut warnings = Vec::new();
+
+    for (row_idx, row) in parse.rows.iter().enumerate() {
+        let line_number = parse.source_row_number(row_idx);
+
+        for &col_idx in &ref_indices {
+            let Some(cell) = row.get(col_idx) else {
+                continue;
+            };
+
+            for reference in split_references(cell) {
+                let prefix = reference_prefix(reference);
+                if allowed.contains(&prefix) {
+                    continue;
+                }
+
+                let message = if prefix.is_empty() {
+                    format!("{line_number}行目: Reference '{reference}' に接頭辞がありません。")
+                } else {
+                    format!(
+                        "{line_number}行目: Reference '{reference}' の接頭辞 '{prefix}' は許可されていません。"
+                    )
+                };
+                warnings.push(ParseError {
+                    message,
+                    row: Some(line_number),
+                    column: Some(col_idx),
+                    severity: "warning".to_string(),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Referenceの番号の欠番を接頭辞ごとに検出
+///
+/// "C1".."C20" のうち "C7" がない場合などを報告する（未実装の部品の確認用）。
+/// 接頭辞は大文字・小文字を区別せず、番号の後の文字（"U1A" の "A"）は無視する。
+///
+/// # 戻り値
+/// 欠番がある接頭辞ごとの報告（接頭辞順）。使用数が `MIN_GAP_GROUP_SIZE` 未満の接頭辞と、
+/// 最小と最大の番号の差が `DEFAULT_MAX_REFERENCE_EXPANSION` 以上の接頭辞（欠番を列挙できない）は除く。
+/// 1つの範囲指定の番号が `DEFAULT_MAX_REFERENCE_EXPANSION` 件以上の場合はエラー
+pub fn find_reference_gaps(parse: &ParseResult) -> Result<Vec<GapReport>, AppError> {
+    let ref_indices = parse.get_column_indices("ref");
+    let mut numbers_by_prefix: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+
+    for row in &parse.rows {
+        for &col_idx in &ref_indices {
+            let Some(cell) = row.get(col_idx) else {
+                continue;
+            };
+            let items = cell
+                .split(|c: char| c == ',' || c == ';' || c == '、' || c.is_whitespace())
+                .filter(|item| !item.is_empty());
+            for item in items {
+                // 範囲指定（"C3-C6"）は範囲内の番号をすべて使用済みとみなす
+                if let Some((prefix, start, end)) = parse_reference_range(item) {
+                    if end >= start {
+                        if (end - start) as usize >= DEFAULT_MAX_REFERENCE_EXPANSION {
+                            return Err(AppError::validation(format!(
+                                "範囲指定 '{item}' のReferenceが多すぎます（上限: {DEFAULT_MAX_REFERENCE_EXPANSION}）。"
+                            )));
+                        }
+                        numbers_by_prefix
+                            .entry(prefix.to_uppercase())
+                            .or_default()
+                            .extend(start..=end);
+                        continue;
+                    }
+                }
+                let start = item.split_once('-').map_or(item, |(start, _)| start);
+                let Some((prefix, number)) = parse_reference_number(start) else {
+                    continue;
+                };
+                numbers_by_prefix.entry(prefix).or_default().insert(number);
+            }
+        }
+    }
+
+    Ok(numbers_by_prefix
+        .into_iter()
+        .filter(|(_, numbers)| numbers.len() >= MIN_GAP_GROUP_SIZE)
+        .filter_map(|(prefix, numbers)| {
+            let min = *numbers.first()?;
+            let max = *numbers.last()?;
+            if (max - min) as usize >= DEFAULT_MAX_REFERENCE_EXPANSION {
+                return None;
+            }
+            let missing: Vec<u32> = (min..=max)
+                .filter(|number| !numbers.contains(number))
+                .collect();
+            (!missing.is_empty()).then_some(GapReport {
+                prefix,
+                min,
+                max,
+                count: numbers.len(),
+                missing,
+            })
+        })
+        .collect())
+}
+
+/// Referenceを接頭辞（大文字）と番号に分解（"C12" → ("C", 12)）
+fn parse_reference_number(reference: &str) -> Option<(String, u32)> {
+    let prefix = reference_prefix(reference);
+    if prefix.is_empty() {
+        return None;
+    }
+    let digits: String = reference[prefix.len()..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let number = digits.parse::<u32>().ok()?;
+    Some((prefix, number))
+}
+
+/// セル内のReferenceを分割（区切り文字と範囲指定の "-"）
+fn split_references(cell: &str) -> impl Iterator<Item = &str> {
+    cell.split(|c: char| c == ',' || c == ';' || c == '、' || c == '-' || c.is_whitespace())
+        .filter(|reference| !reference.is_empty())
+}
+
+/// 先頭の英字部分を大文字で返す
+fn reference_prefix(reference: &str) -> String {
+    reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ref_parse(refs: &[&str]) -> ParseResult {
+        ParseResult::builder()
+            .headers(vec!["Ref".to_string()])
+            .rows(refs.iter().map(|r| vec![r.to_string()]).collect())
+            .role("ref", &[0])
+            .row_numbers((2..refs.len() + 2).collect())
+            .header_row_index(Some(0))
+            .data_start_index(1)
+            .build()
+    }
+
+    #[test]
+    fn test_validate_ref_prefixes() {
+        let parse = ref_parse(&["C1", "CC1", "R1, X2-X4", "ic3", "ＴＰ1", "5"]);
+        let allowed: Vec<String> = ["C", "R", "IC", "TP"]
+            .iter()
+            .map(|prefix| prefix.to_string())
+            .collect();
+
+        let warnings = validate_ref_prefixes(&parse, &allowed);
+        let flagged: Vec<(Option<usize>, &str)> = warnings
+            .iter()
+            .map(|warning| {
+                let reference = warning.message.split('\'').nth(1).unwrap_or("");
+                (warning.row, reference)
+            })
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (Some(3), "CC1"),
+                (Some(4), "X2"),
+                (Some(4), "X4"),
+                (Some(6), "ＴＰ1"),
+                (Some(7), "5"),
+            ]
+        );
+        assert!(warnings.iter().all(|warning| warning.severity == "warning"));
+        assert!(warnings[4].message.contains("接頭辞がありません"));
+
+        assert!(validate_ref_prefixes(&parse, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_find_reference_gaps() {
+        let parse = ref_parse(&[
+            "C1, C2", "C3-C5", "c8", "C10", "C10", "R1", "R3", "U1A", "U2", "U4B", "TP1",
+        ]);
+
+        assert_eq!(
+            find_reference_gaps(&parse).unwrap(),
+            vec![
+                GapReport {
+                    prefix: "C".to_string(),
+                    min: 1,
+                    max: 10,
+                    count: 7,
+                    missing: vec![6, 7, 9],
+                },
+                GapReport {
+                    prefix: "U".to_string(),
+                    min: 1,
+                    max: 4,
+                    count: 3,
+                    missing: vec![3],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_reference_gaps_rejects_huge_ranges() {
+        let parse = ref_parse(&["C1-C999999999", "C2"]);
+        let error = find_reference_gaps(&parse).unwrap_err();
+        assert_eq!(error.kind, crate::models::ErrorKind::Validation);
+
+        // 離れた番号だけの接頭辞は欠番を列挙せずに除く
+        let parse = ref_parse(&["R1", "R2", "R999999999", "C1", "C3", "C4"]);
+        let gaps = find_reference_gaps(&parse).unwrap();
+        assert_eq!(
+            gaps.iter()
+                .map(|gap| gap.prefix.as_str())
+                .collect::<Vec<_>>(),
+            vec!["C"]
+        );
+    }
+}
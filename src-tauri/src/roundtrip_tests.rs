@@ -0,0 +1,271 @@
+//! パース → エクスポート → 再パースの往復テスト
+//!
+//! 各フォーマットについて、フィクスチャを読み込んでエクスポートし、
+//! 出力を再度読み込んだ結果で Reference ↔ 部品型番 の対応が保たれていることを確認する。
+//! CCF/MSF/PWS のように部品型番でグルーピングされる形式は行順が変わるため、
+//! (Reference, 部品型番) の組の集合で比較する。
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::exporters;
+use crate::models::{EcoTemplate, ExportOptions, ParseResult};
+use crate::parsers;
+
+const CSV_FIXTURE: &str = "\
+Ref,Part No,Maker,Note
+C1,GRM155R71C104KA88D,Murata,
+C2,GRM155R71C104KA88D,Murata,bypass
+R1,RC0402FR-0710KL,Yageo,pull-up
+IC1,74VHC08FT(BJ),Toshiba,
+";
+
+const ECO_FIXTURE: &str = "\
+*PADS-ECO*
+*PART*
+C10 0603B104K500CT
+C12 0603B104K500CT
+IC8 74VHC08FT(BJ)
+*END*
+";
+
+const MSF_FIXTURE: &str = "\
+$MSF {
+     SHAPE {
+                0603B104K500CT:C10,
+                         C12;
+                74VHC08FT(BJ):IC8,
+                         IC9,
+                         IC10;
+           }
+      }
+";
+
+const CCF_FIXTURE: &str = "\
+$CCF{
+     DEFINITION{
+                0603B104K500CT:C10,
+                         C12;
+                74VHC08FT(BJ):IC8,
+                         IC9;
+               }
+     NET{
+        }
+    }
+";
+
+const PWS_FIXTURE: &str = "\
+/* PWS */
+0603B104K500CT:C10,C12;
+74VHC08FT(BJ):IC8,IC9,IC10;
+";
+
+const BD_FIXTURE: &str = "\
+C10 0603B104K500CT
+C12 0603B104K500CT
+IC8 74VHC08FT(BJ)
+";
+
+const PADS_REPORT_FIXTURE: &str = "\
+部品表１レポート
+参照名       型番           登録名
+-----------------------------------------
+C10          XXX            0603B104K500CT
+C12          YYY            0603B104K500CT
+IC8          ZZZ            74VHC08FT(BJ)
+";
+
+/// 一時ファイルに書き出してパースする
+fn parse_content(name: &str, extension: &str, content: &str) -> ParseResult {
+    let path = temp_path(name, extension);
+    fs::write(&path, content).expect("fixture write failed");
+    let result = parsers::parse_bom_file(path.to_string_lossy().into_owned());
+    let _ = fs::remove_file(&path);
+    result.unwrap_or_else(|err| panic!("{name}.{extension} のパースに失敗: {err}"))
+}
+
+fn temp_path(name: &str, extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "bomsync_roundtrip_{}_{}.{}",
+        std::process::id(),
+        name,
+        extension
+    ))
+}
+
+fn export(parse: &ParseResult, format: &str) -> String {
+    let options = ExportOptions {
+        format: format.to_string(),
+        ..ExportOptions::default()
+    };
+    exporters::export_bom_file(parse, &options)
+        .unwrap_or_else(|err| panic!("{format} のエクスポートに失敗: {err}"))
+}
+
+fn ref_part_pairs(parse: &ParseResult) -> BTreeSet<(String, String)> {
+    (0..parse.rows.len())
+        .map(|idx| (parse.get_ref(idx), parse.get_part_no(idx)))
+        .collect()
+}
+
+fn ref_part_sequence(parse: &ParseResult) -> Vec<(String, String)> {
+    (0..parse.rows.len())
+        .map(|idx| (parse.get_ref(idx), parse.get_part_no(idx)))
+        .collect()
+}
+
+/// CAD形式の往復（出力 → 同じ形式として再パース）
+fn assert_cad_roundtrip(name: &str, extension: &str, fixture: &str, format: &str, ordered: bool) {
+    let original = parse_content(name, extension, fixture);
+    assert!(!original.rows.is_empty());
+
+    let exported = export(&original, format);
+    let reparsed = parse_content(&format!("{name}_out"), extension, &exported);
+
+    assert_eq!(
+        ref_part_pairs(&original),
+        ref_part_pairs(&reparsed),
+        "{format} の往復で Reference/部品型番 の対応が変化しました:\n{exported}"
+    );
+    assert_eq!(original.rows.len(), reparsed.rows.len());
+    if ordered {
+        assert_eq!(ref_part_sequence(&original), ref_part_sequence(&reparsed));
+    }
+}
+
+#[test]
+fn test_roundtrip_csv() {
+    let mut original = parse_content("csv", "csv", CSV_FIXTURE);
+    // 編集モードで指定する場合と同様に役割を明示する
+    original.column_roles = HashMap::from([
+        ("ref".to_string(), vec!["col-0".to_string()]),
+        ("part_no".to_string(), vec!["col-1".to_string()]),
+        ("manufacturer".to_string(), vec!["col-2".to_string()]),
+    ]);
+
+    let exported = export(&original, "csv");
+    let mut reparsed = parse_content("csv_out", "csv", &exported);
+    reparsed.column_roles = original.column_roles.clone();
+
+    assert_eq!(original.headers, reparsed.headers);
+    assert_eq!(original.rows, reparsed.rows);
+    assert_eq!(ref_part_sequence(&original), ref_part_sequence(&reparsed));
+}
+
+#[test]
+fn test_roundtrip_eco() {
+    assert_cad_roundtrip("eco", "eco", ECO_FIXTURE, "eco", true);
+}
+
+#[test]
+fn test_roundtrip_msf() {
+    assert_cad_roundtrip("msf", "msf", MSF_FIXTURE, "msf", false);
+}
+
+#[test]
+fn test_roundtrip_ccf() {
+    assert_cad_roundtrip("ccf", "ccf", CCF_FIXTURE, "ccf", false);
+}
+
+#[test]
+fn test_roundtrip_pws() {
+    assert_cad_roundtrip("pws", "pws", PWS_FIXTURE, "pws", false);
+}
+
+#[test]
+fn test_roundtrip_bd() {
+    assert_cad_roundtrip("bd", "bd", BD_FIXTURE, "bd", true);
+}
+
+#[test]
+fn test_roundtrip_pads_report() {
+    assert_cad_roundtrip("rpt", "rpt", PADS_REPORT_FIXTURE, "rpt", true);
+}
+
+#[test]
+fn test_roundtrip_eco_preserves_directives() {
+    let fixture = "\
+*PADS-ECO* V9.5
+*REMARK* generated by CAD
+*PART*
+C10 0603B104K500CT
+IC8 74VHC08FT(BJ)
+*NET*
+*SIGNAL* GND
+C10.2 IC8.7
+*END*";
+    let original = parse_content("eco_directives", "eco", fixture);
+    assert_eq!(original.rows.len(), 2);
+
+    let mut edited = original.clone();
+    edited.rows[0][1] = "0603B105K250CT".to_string();
+    assert_eq!(
+        export(&edited, "eco"),
+        fixture.replace("C10 0603B104K500CT", "C10 0603B105K250CT")
+    );
+
+    // テンプレートを指定すると読み込んだファイルのものより優先される
+    let options = ExportOptions {
+        format: "eco".to_string(),
+        eco_template: Some(EcoTemplate {
+            header: vec!["*PADS-ECO* V2007.0".to_string()],
+            footer: vec![],
+        }),
+        ..ExportOptions::default()
+    };
+    assert_eq!(
+        exporters::export_bom_file(&original, &options).unwrap(),
+        "*PADS-ECO* V2007.0\n*PART*\nC10 0603B104K500CT\nIC8 74VHC08FT(BJ)\n*END*"
+    );
+}
+
+#[test]
+fn test_cross_format_csv_to_cad_preserves_pairs() {
+    let mut original = parse_content("cross", "csv", CSV_FIXTURE);
+    original.column_roles = HashMap::from([
+        ("ref".to_string(), vec!["col-0".to_string()]),
+        ("part_no".to_string(), vec!["col-1".to_string()]),
+    ]);
+    let expected = ref_part_pairs(&original);
+
+    for (format, extension) in [
+        ("eco", "eco"),
+        ("ccf", "ccf"),
+        ("msf", "msf"),
+        ("pws", "pws"),
+        ("bd", "bd"),
+        ("rpt", "rpt"),
+    ] {
+        let exported = export(&original, format);
+        let reparsed = parse_content(&format!("cross_{format}"), extension, &exported);
+        assert_eq!(
+            expected,
+            ref_part_pairs(&reparsed),
+            "CSV → {format} で Reference/部品型番 の対応が変化しました:\n{exported}"
+        );
+    }
+}
+
+#[test]
+fn test_grouped_export_skips_rows_without_reference() {
+    let mut original = parse_content("noref", "csv", "Ref,Part\nC1,GRM155\n,GRM188\nC2,GRM155\n");
+    original.column_roles = HashMap::from([
+        ("ref".to_string(), vec!["col-0".to_string()]),
+        ("part_no".to_string(), vec!["col-1".to_string()]),
+    ]);
+
+    for format in ["ccf", "msf", "pws"] {
+        let exported = export(&original, format);
+        let reparsed = parse_content(&format!("noref_{format}"), format, &exported);
+        assert_eq!(
+            ref_part_pairs(&reparsed),
+            BTreeSet::from([
+                ("C1".to_string(), "GRM155".to_string()),
+                ("C2".to_string(), "GRM155".to_string()),
+            ]),
+            "{format}:\n{exported}"
+        );
+        assert!(!exported.contains("GRM188"), "{format}:\n{exported}");
+    }
+}
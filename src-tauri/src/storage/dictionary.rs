@@ -1,27 +1,200 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
 
 use tauri::Manager;
 
-use crate::models::{AppError, IpcMasterRule};
+use crate::matchers::template::CompiledRule;
+use crate::models::{
+    AppError, ColumnAliasEntry, DictionaryImportMode, DictionaryImportReport, DictionaryLoadResult,
+    IpcMasterRule, ManufacturerAliasEntry,
+};
+use crate::utils::header::normalize_header;
+use crate::utils::text::CharAllowlist;
 
-fn ensure_dictionary_path(
-    app: &tauri::AppHandle,
-    dictionary_name: &str,
-) -> Result<(PathBuf, PathBuf), AppError> {
-    let file_name = dictionary_file_name(dictionary_name)
-        .ok_or_else(|| AppError::new(format!("未知の辞書名です: {dictionary_name}")))?;
+use super::atomic::{backup_path_for, write_atomic};
+
+/// 列エイリアスで割り当て可能な役割
+const ALIAS_ROLES: &[&str] = &["ref", "part_no", "manufacturer", "value", "comment"];
+
+/// 辞書ディレクトリを指定する環境変数（設定より優先）
+pub const DICTIONARY_DIR_ENV: &str = "BOMSYNC_DICTIONARY_DIR";
+
+/// 辞書ディレクトリの設定ファイル（app_config_dir() 直下）
+const DICTIONARY_DIR_SETTING_FILE: &str = "dictionary_dir.json";
+
+/// 書き込み確認に使う一時ファイル名
+const WRITE_PROBE_FILE: &str = ".bomsync_write_test";
 
-    let base_dir = app
-        .path()
+/// 辞書ディレクトリの設定
+#[derive(Serialize, Deserialize)]
+struct DictionaryDirSetting {
+    path: PathBuf,
+}
+
+/// 例外マスタ辞書のエントリ（フロントエンドが保存する形式）
+///
+/// 適用時の `ExceptionMasterEntry` の表記（`partNo` / `outputName`）も受け付ける
+#[derive(Deserialize)]
+struct ExceptionDictionaryEntry {
+    #[serde(default, rename = "ref")]
+    reference: Option<String>,
+    #[serde(alias = "partNo")]
+    part_no: String,
+    #[serde(default, alias = "outputName")]
+    registration_name: Option<String>,
+}
+
+impl ExceptionDictionaryEntry {
+    /// 同一性のキー（Referenceがあれば "Reference / 部品型番"、大文字小文字を区別しない）
+    fn key(&self) -> Option<String> {
+        let part_no = self.part_no.trim();
+        if part_no.is_empty() {
+            return None;
+        }
+        let reference = self.reference.as_deref().unwrap_or_default().trim();
+        Some(if reference.is_empty() {
+            part_no.to_lowercase()
+        } else {
+            format!("{} / {}", reference, part_no).to_lowercase()
+        })
+    }
+}
+
+fn app_config_dir(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
         .app_config_dir()
-        .map_err(|err| AppError::new(format!("設定ディレクトリの取得に失敗しました: {err}")))?;
+        .map_err(|err| AppError::io(format!("設定ディレクトリの取得に失敗しました: {err}")))
+}
+
+/// 辞書ディレクトリ（環境変数 → 設定 → app_config_dir()/dictionaries/ の順）
+fn dictionaries_dir(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let config_dir = app_config_dir(app)?;
+    let env_override = std::env::var_os(DICTIONARY_DIR_ENV);
+    let configured = read_dictionary_dir_setting_in(&config_dir);
+    resolve_dictionaries_dir(env_override.as_deref(), configured.as_deref(), &config_dir)
+}
+
+/// 辞書ディレクトリを決める
+///
+/// # 引数
+/// * `env_override` - 環境変数 `BOMSYNC_DICTIONARY_DIR` の値（空の場合は無視）
+/// * `configured` - `set_dictionary_dir` で設定したディレクトリ
+/// * `config_dir` - アプリの設定ディレクトリ（app_config_dir()）
+///
+/// # 戻り値
+/// 辞書ファイルを置くディレクトリ（環境変数のディレクトリが絶対パスでない・書き込めない場合はエラー）
+pub fn resolve_dictionaries_dir(
+    env_override: Option<&OsStr>,
+    configured: Option<&Path>,
+    config_dir: &Path,
+) -> Result<PathBuf, AppError> {
+    if let Some(path) = env_override.filter(|value| !value.is_empty()) {
+        let path = PathBuf::from(path);
+        validate_dictionary_dir(&path).map_err(|err| {
+            AppError::validation(format!(
+                "環境変数 {DICTIONARY_DIR_ENV} の辞書ディレクトリを使用できません: {}",
+                err.message
+            ))
+        })?;
+        return Ok(path);
+    }
+    Ok(configured
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| config_dir.join("dictionaries")))
+}
+
+/// 現在の辞書ディレクトリを取得
+pub fn get_dictionary_dir(app: tauri::AppHandle) -> Result<String, AppError> {
+    Ok(dictionaries_dir(&app)?.display().to_string())
+}
+
+/// 辞書ディレクトリを設定する（None・空文字の場合は既定の場所に戻す）
+///
+/// 書き込めないディレクトリは設定しない。環境変数が指定されている場合はそちらが優先される。
+///
+/// # 戻り値
+/// 設定後に使われる辞書ディレクトリ
+pub fn set_dictionary_dir(app: tauri::AppHandle, path: Option<String>) -> Result<String, AppError> {
+    let config_dir = app_config_dir(&app)?;
+    let path = path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+    write_dictionary_dir_setting_in(&config_dir, path.as_deref())?;
+    get_dictionary_dir(app)
+}
+
+/// 設定ディレクトリから辞書ディレクトリの設定を読み込む（未設定・破損時は None）
+fn read_dictionary_dir_setting_in(config_dir: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(config_dir.join(DICTIONARY_DIR_SETTING_FILE)).ok()?;
+    serde_json::from_str::<DictionaryDirSetting>(&content)
+        .ok()
+        .map(|setting| setting.path)
+}
 
-    let dictionaries_dir = base_dir.join("dictionaries");
-    let file_path = dictionaries_dir.join(file_name);
+/// 設定ディレクトリに辞書ディレクトリの設定を保存する（None の場合は設定を削除）
+fn write_dictionary_dir_setting_in(config_dir: &Path, path: Option<&Path>) -> Result<(), AppError> {
+    let setting_path = config_dir.join(DICTIONARY_DIR_SETTING_FILE);
+    let Some(path) = path else {
+        if setting_path.exists() {
+            fs::remove_file(&setting_path).map_err(|err| {
+                AppError::io(format!("辞書ディレクトリの設定の削除に失敗しました: {err}"))
+            })?;
+        }
+        return Ok(());
+    };
+
+    validate_dictionary_dir(path)?;
+
+    let content = serde_json::to_string_pretty(&DictionaryDirSetting {
+        path: path.to_path_buf(),
+    })
+    .map_err(|err| {
+        AppError::new(format!(
+            "辞書ディレクトリの設定のエンコードに失敗しました: {err}"
+        ))
+    })?;
+    fs::create_dir_all(config_dir)
+        .map_err(|err| AppError::io(format!("設定ディレクトリの作成に失敗しました: {err}")))?;
+    write_atomic(&setting_path, content.as_bytes())
+        .map_err(|err| AppError::io(format!("辞書ディレクトリの設定の保存に失敗しました: {err}")))
+}
+
+/// 辞書ディレクトリとして使えるか（絶対パスで、書き込めること）を確認する
+fn validate_dictionary_dir(path: &Path) -> Result<(), AppError> {
+    if !path.is_absolute() {
+        return Err(AppError::validation(format!(
+            "辞書ディレクトリは絶対パスで指定してください: {}",
+            path.display()
+        )));
+    }
+    ensure_writable_dir(path)
+}
 
-    Ok((dictionaries_dir, file_path))
+/// ディレクトリを作成し、ファイルを書き込めることを確認する
+fn ensure_writable_dir(dir: &Path) -> Result<(), AppError> {
+    let not_writable = |err: std::io::Error| {
+        AppError::validation(format!(
+            "辞書ディレクトリに書き込めません: {}（{err}）",
+            dir.display()
+        ))
+    };
+    fs::create_dir_all(dir).map_err(not_writable)?;
+    let probe = dir.join(WRITE_PROBE_FILE);
+    fs::write(&probe, b"").map_err(not_writable)?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// 辞書ディレクトリ内の辞書ファイルのパス
+fn dictionary_path_in(dir: &Path, dictionary_name: &str) -> Result<PathBuf, AppError> {
+    let file_name = dictionary_file_name(dictionary_name)
+        .ok_or_else(|| AppError::validation(format!("未知の辞書名です: {dictionary_name}")))?;
+    Ok(dir.join(file_name))
 }
 
 fn dictionary_file_name(name: &str) -> Option<&'static str> {
@@ -29,23 +202,22 @@ fn dictionary_file_name(name: &str) -> Option<&'static str> {
         "ipc_master" => Some("ipc_master.json"),
         "exception_master" => Some("exception_master.json"),
         "column_alias" => Some("column_alias.json"),
+        "allowed_chars" => Some("allowed_chars.json"),
+        "manufacturer_alias" => Some("manufacturer_alias.json"),
         _ => None,
     }
 }
 
 pub fn load_dictionary(app: tauri::AppHandle, dictionary_name: String) -> Result<String, AppError> {
-    let (dictionaries_dir, file_path) = ensure_dictionary_path(&app, &dictionary_name)?;
-
-    if !file_path.exists() {
-        fs::create_dir_all(&dictionaries_dir)
-            .map_err(|err| AppError::new(format!("辞書ディレクトリの作成に失敗しました: {err}")))?;
-        return Ok("[]".to_string());
-    }
-
-    let content = fs::read_to_string(&file_path)
-        .map_err(|err| AppError::new(format!("辞書ファイルの読み込みに失敗しました: {err}")))?;
+    load_dictionary_with_status(app, dictionary_name).map(|result| result.content)
+}
 
-    Ok(content)
+/// 辞書を読み込み、バックアップから復元した場合は警告を付けて返す
+pub fn load_dictionary_with_status(
+    app: tauri::AppHandle,
+    dictionary_name: String,
+) -> Result<DictionaryLoadResult, AppError> {
+    load_dictionary_in(&dictionaries_dir(&app)?, &dictionary_name)
 }
 
 pub fn save_dictionary(
@@ -53,31 +225,834 @@ pub fn save_dictionary(
     dictionary_name: String,
     content: String,
 ) -> Result<(), AppError> {
-    let (dictionaries_dir, file_path) = ensure_dictionary_path(&app, &dictionary_name)?;
-
-    if dictionary_name == "ipc_master" {
-        let rules: Vec<IpcMasterRule> = serde_json::from_str(&content)
-            .map_err(|err| AppError::new(format!("登録名マスタの解析に失敗しました: {err}")))?;
-        let mut seen = HashSet::new();
-        for rule in rules {
-            let name = rule.rule_name.trim();
-            if name.is_empty() {
-                continue;
+    save_dictionary_in(&dictionaries_dir(&app)?, &dictionary_name, &content)
+}
+
+/// 外部ファイルの辞書内容を取り込む
+///
+/// # 引数
+/// * `dictionary_name` - 辞書名
+/// * `incoming_content` - 取り込む辞書の内容（JSON文字列）
+/// * `mode` - 取り込み方法（置換 / 追加 / マージ）
+///
+/// # 戻り値
+/// 追加・更新・スキップ件数と競合したエントリのキー
+pub fn import_dictionary(
+    app: tauri::AppHandle,
+    dictionary_name: String,
+    incoming_content: String,
+    mode: DictionaryImportMode,
+) -> Result<DictionaryImportReport, AppError> {
+    import_dictionary_in(
+        &dictionaries_dir(&app)?,
+        &dictionary_name,
+        &incoming_content,
+        mode,
+    )
+}
+
+/// 指定ディレクトリから辞書を読み込む（ファイルがない場合は空の辞書）
+pub fn load_dictionary_in(
+    dir: &Path,
+    dictionary_name: &str,
+) -> Result<DictionaryLoadResult, AppError> {
+    let file_path = dictionary_path_in(dir, dictionary_name)?;
+
+    if !file_path.exists() && !backup_path_for(&file_path).exists() {
+        fs::create_dir_all(dir)
+            .map_err(|err| AppError::io(format!("辞書ディレクトリの作成に失敗しました: {err}")))?;
+        return Ok(DictionaryLoadResult {
+            content: "[]".to_string(),
+            warning: None,
+        });
+    }
+
+    read_dictionary_file(&file_path)
+}
+
+/// 指定ディレクトリに辞書を検証して保存する
+pub fn save_dictionary_in(
+    dir: &Path,
+    dictionary_name: &str,
+    content: &str,
+) -> Result<(), AppError> {
+    let file_path = dictionary_path_in(dir, dictionary_name)?;
+
+    validate_dictionary(dictionary_name, content)?;
+
+    fs::create_dir_all(dir)
+        .map_err(|err| AppError::io(format!("辞書ディレクトリの作成に失敗しました: {err}")))?;
+
+    write_dictionary_file(&file_path, content)
+}
+
+/// 指定ディレクトリの辞書に外部ファイルの辞書内容を取り込む
+pub fn import_dictionary_in(
+    dir: &Path,
+    dictionary_name: &str,
+    incoming_content: &str,
+    mode: DictionaryImportMode,
+) -> Result<DictionaryImportReport, AppError> {
+    let file_path = dictionary_path_in(dir, dictionary_name)?;
+
+    let existing_content = if file_path.exists() || backup_path_for(&file_path).exists() {
+        read_dictionary_file(&file_path)?.content
+    } else {
+        "[]".to_string()
+    };
+
+    let (merged_content, report) =
+        merge_dictionary_content(dictionary_name, &existing_content, incoming_content, mode)?;
+
+    fs::create_dir_all(dir)
+        .map_err(|err| AppError::io(format!("辞書ディレクトリの作成に失敗しました: {err}")))?;
+    write_dictionary_file(&file_path, &merged_content)?;
+
+    Ok(report)
+}
+
+/// 辞書ファイルを読み込む
+///
+/// 本体が存在しない・JSONとして不正な場合は `.bak` にフォールバックし、警告を返す
+pub fn read_dictionary_file(file_path: &Path) -> Result<DictionaryLoadResult, AppError> {
+    let primary_error = match fs::read_to_string(file_path) {
+        Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
+            Ok(_) => {
+                return Ok(DictionaryLoadResult {
+                    content,
+                    warning: None,
+                })
+            }
+            Err(err) => format!("JSONが不正です: {err}"),
+        },
+        Err(err) => format!("読み込みに失敗しました: {err}"),
+    };
+
+    let backup_path = backup_path_for(file_path);
+    let backup = fs::read_to_string(&backup_path)
+        .ok()
+        .filter(|content| serde_json::from_str::<serde_json::Value>(content).is_ok());
+
+    match backup {
+        Some(content) => Ok(DictionaryLoadResult {
+            content,
+            warning: Some(format!(
+                "辞書ファイルの{primary_error} バックアップ（{}）から復元しました。",
+                backup_path.display()
+            )),
+        }),
+        None => Err(AppError::io(format!(
+            "辞書ファイルの{primary_error}（有効なバックアップもありません）"
+        ))),
+    }
+}
+
+/// 既存の辞書内容に取り込み内容を反映する
+///
+/// エントリの同一性は辞書ごとのキーで判定する
+/// （ipc_master: ルール名、exception_master: Reference・部品型番、column_alias: エイリアス、
+/// manufacturer_alias: 表記ゆれのメーカー名）。
+/// 反映後の内容は保存時と同じ検証を通過したもののみ返す。
+///
+/// # 戻り値
+/// (反映後の辞書内容, 取り込み結果)
+pub fn merge_dictionary_content(
+    dictionary_name: &str,
+    existing_content: &str,
+    incoming_content: &str,
+    mode: DictionaryImportMode,
+) -> Result<(String, DictionaryImportReport), AppError> {
+    let key_of = dictionary_key_fn(dictionary_name)
+        .ok_or_else(|| AppError::validation(format!("未知の辞書名です: {dictionary_name}")))?;
+
+    // 取り込み内容が辞書として正しい形式かを先に確認
+    validate_dictionary(dictionary_name, incoming_content).map_err(|err| {
+        AppError::validation(format!("取り込むファイルが不正です: {}", err.message))
+    })?;
+
+    let existing: Vec<serde_json::Value> = serde_json::from_str(existing_content)
+        .map_err(|err| AppError::parse(format!("既存の辞書の解析に失敗しました: {err}")))?;
+    let incoming: Vec<serde_json::Value> = serde_json::from_str(incoming_content)
+        .map_err(|err| AppError::parse(format!("取り込むファイルの解析に失敗しました: {err}")))?;
+
+    let mut report = DictionaryImportReport::default();
+
+    let merged = match mode {
+        DictionaryImportMode::Replace => {
+            report.added = incoming.len();
+            incoming
+        }
+        DictionaryImportMode::Append | DictionaryImportMode::Merge => {
+            let mut merged = existing;
+            let mut positions: HashMap<String, usize> = HashMap::new();
+            for (idx, entry) in merged.iter().enumerate() {
+                if let Some(key) = key_of(entry) {
+                    positions.entry(key).or_insert(idx);
+                }
+            }
+
+            for entry in incoming {
+                let Some(key) = key_of(&entry) else {
+                    // キーのないエントリ（ルール名が空など）はそのまま追加
+                    merged.push(entry);
+                    report.added += 1;
+                    continue;
+                };
+
+                match positions.get(&key) {
+                    None => {
+                        positions.insert(key, merged.len());
+                        merged.push(entry);
+                        report.added += 1;
+                    }
+                    Some(&idx) if merged[idx] == entry => {
+                        report.skipped += 1;
+                    }
+                    Some(&idx) => {
+                        report.conflicts.push(key);
+                        if mode == DictionaryImportMode::Merge {
+                            merged[idx] = entry;
+                            report.updated += 1;
+                        } else {
+                            report.skipped += 1;
+                        }
+                    }
+                }
             }
-            if !seen.insert(name.to_string()) {
-                return Err(AppError::new(format!(
-                    "ルール名'{}'が重複しています。",
-                    name
+            merged
+        }
+    };
+
+    let content = serde_json::to_string_pretty(&merged)
+        .map_err(|err| AppError::new(format!("辞書のエンコードに失敗しました: {err}")))?;
+    validate_dictionary(dictionary_name, &content)?;
+
+    Ok((content, report))
+}
+
+/// 辞書ごとのエントリ同一性キーを取り出す関数
+fn dictionary_key_fn(dictionary_name: &str) -> Option<fn(&serde_json::Value) -> Option<String>> {
+    fn string_field(entry: &serde_json::Value, field: &str) -> Option<String> {
+        entry
+            .get(field)
+            .and_then(|value| value.as_str())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    match dictionary_name {
+        "ipc_master" => Some(|entry| string_field(entry, "ruleName")),
+        "exception_master" => Some(|entry| {
+            serde_json::from_value::<ExceptionDictionaryEntry>(entry.clone())
+                .ok()
+                .and_then(|entry| entry.key())
+        }),
+        "column_alias" => Some(|entry| string_field(entry, "alias").map(|v| normalize_header(&v))),
+        "manufacturer_alias" => {
+            Some(|entry| string_field(entry, "alias").map(|v| v.to_lowercase()))
+        }
+        "allowed_chars" => Some(|entry| {
+            entry
+                .as_str()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        }),
+        _ => None,
+    }
+}
+
+/// 辞書の内容を検証する
+///
+/// # 引数
+/// * `dictionary_name` - 辞書名（"ipc_master", "exception_master", "column_alias",
+///   "manufacturer_alias", "allowed_chars"）
+/// * `content` - 辞書の内容（JSON文字列）
+///
+/// # 戻り値
+/// 問題がなければ Ok(())、不正なエントリがあればその内容を示すエラー
+pub fn validate_dictionary(dictionary_name: &str, content: &str) -> Result<(), AppError> {
+    match dictionary_name {
+        "ipc_master" => {
+            let rules: Vec<IpcMasterRule> = serde_json::from_str(content).map_err(|err| {
+                AppError::parse(format!("登録名マスタの解析に失敗しました: {err}"))
+            })?;
+            validate_ipc_master(&rules)
+        }
+        "exception_master" => {
+            let entries: Vec<ExceptionDictionaryEntry> = serde_json::from_str(content)
+                .map_err(|err| AppError::parse(format!("例外マスタの解析に失敗しました: {err}")))?;
+            validate_exception_master(&entries)
+        }
+        "column_alias" => {
+            let entries: Vec<ColumnAliasEntry> = serde_json::from_str(content).map_err(|err| {
+                AppError::parse(format!("列エイリアスの解析に失敗しました: {err}"))
+            })?;
+            validate_column_alias(&entries)
+        }
+        "manufacturer_alias" => {
+            let entries: Vec<ManufacturerAliasEntry> =
+                serde_json::from_str(content).map_err(|err| {
+                    AppError::parse(format!("メーカー名エイリアスの解析に失敗しました: {err}"))
+                })?;
+            validate_manufacturer_alias(&entries)
+        }
+        "allowed_chars" => {
+            let entries: Vec<String> = serde_json::from_str(content).map_err(|err| {
+                AppError::parse(format!("許可文字リストの解析に失敗しました: {err}"))
+            })?;
+            CharAllowlist::from_entries(&entries).map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 登録名マスタの検証（ルール名の重複、出力先が空の追加出力、正規表現・登録名テンプレート）
+fn validate_ipc_master(rules: &[IpcMasterRule]) -> Result<(), AppError> {
+    let mut seen = HashSet::new();
+    for rule in rules {
+        let name = rule.rule_name.trim();
+        if rule.outputs.keys().any(|key| key.trim().is_empty()) {
+            return Err(AppError::validation(format!(
+                "ルール'{}'の追加出力に出力先の列が空のものがあります。",
+                name
+            )));
+        }
+        CompiledRule::compile(rule)?;
+        if name.is_empty() {
+            continue;
+        }
+        if !seen.insert(name.to_string()) {
+            return Err(AppError::validation(format!(
+                "ルール名'{}'が重複しています。",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 例外マスタの検証
+///
+/// - 部品型番が空のエントリは不可
+/// - 同じReference・部品型番（大文字小文字を区別しない）に異なる登録名を割り当てるのは不可
+///   （適用時に大文字小文字を区別せず照合するため）
+fn validate_exception_master(entries: &[ExceptionDictionaryEntry]) -> Result<(), AppError> {
+    let mut seen: HashMap<String, (usize, &str)> = HashMap::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let line = idx + 1;
+        let part_no = entry.part_no.trim();
+        let Some(key) = entry.key() else {
+            return Err(AppError::validation(format!(
+                "例外マスタの{line}件目: 部品型番が空です。"
+            )));
+        };
+
+        let output_name = entry
+            .registration_name
+            .as_deref()
+            .unwrap_or_default()
+            .trim();
+        match seen.get(&key) {
+            Some((first_line, existing)) if *existing != output_name => {
+                return Err(AppError::validation(format!(
+                    "例外マスタの{line}件目: 部品型番'{part_no}'が{first_line}件目と異なる登録名（'{existing}' / '{output_name}'）で重複しています。"
                 )));
             }
+            Some(_) => {}
+            None => {
+                seen.insert(key, (line, output_name));
+            }
         }
     }
+    Ok(())
+}
 
-    fs::create_dir_all(&dictionaries_dir)
-        .map_err(|err| AppError::new(format!("辞書ディレクトリの作成に失敗しました: {err}")))?;
+/// 列エイリアスの検証
+///
+/// - 割り当て先の役割が既知のものであること
+/// - 同じエイリアス（正規化後）を複数の役割に割り当てていないこと
+fn validate_column_alias(entries: &[ColumnAliasEntry]) -> Result<(), AppError> {
+    let mut seen: HashMap<String, (usize, &str)> = HashMap::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let line = idx + 1;
+        let alias = entry.alias.trim();
+        if alias.is_empty() {
+            return Err(AppError::validation(format!(
+                "列エイリアスの{line}件目: エイリアスが空です。"
+            )));
+        }
 
-    fs::write(&file_path, content)
-        .map_err(|err| AppError::new(format!("辞書ファイルの書き込みに失敗しました: {err}")))?;
+        let role = entry.role.trim();
+        if !ALIAS_ROLES.contains(&role) {
+            return Err(AppError::validation(format!(
+                "列エイリアスの{line}件目: '{alias}'の役割'{role}'は不正です（使用可能: {}）。",
+                ALIAS_ROLES.join(", ")
+            )));
+        }
 
+        let normalized = normalize_header(alias);
+        match seen.get(&normalized) {
+            Some((first_line, existing)) if *existing != role => {
+                return Err(AppError::validation(format!(
+                    "列エイリアスの{line}件目: '{alias}'が{first_line}件目で'{existing}'に割り当て済みのため、'{role}'には割り当てられません。"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(normalized, (line, role));
+            }
+        }
+    }
     Ok(())
 }
+
+/// メーカー名エイリアスの検証
+///
+/// - 表記ゆれ・正式名が空のエントリは不可
+/// - 同じ表記ゆれ（大文字小文字を区別しない）を異なる正式名に割り当てるのは不可
+fn validate_manufacturer_alias(entries: &[ManufacturerAliasEntry]) -> Result<(), AppError> {
+    let mut seen: HashMap<String, (usize, &str)> = HashMap::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let line = idx + 1;
+        let alias = entry.alias.trim();
+        let canonical = entry.canonical.trim();
+        if alias.is_empty() || canonical.is_empty() {
+            return Err(AppError::validation(format!(
+                "メーカー名エイリアスの{line}件目: 表記ゆれと正式名の両方を指定してください。"
+            )));
+        }
+
+        match seen.get(&alias.to_lowercase()) {
+            Some((first_line, existing)) if *existing != canonical => {
+                return Err(AppError::validation(format!(
+                    "メーカー名エイリアスの{line}件目: '{alias}'が{first_line}件目と異なる正式名（'{existing}' / '{canonical}'）で重複しています。"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(alias.to_lowercase(), (line, canonical));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 辞書ファイルを原子的に書き込む
+///
+/// 既存ファイルは `.bak` として退避してから、一時ファイル経由で置き換える。
+/// 既存ファイルが壊れている（JSONとして読めない）場合は、読み込み時の復元に使う
+/// 既存のバックアップを残すため退避しない
+pub fn write_dictionary_file(file_path: &Path, content: &str) -> Result<(), AppError> {
+    let previous = fs::read_to_string(file_path)
+        .ok()
+        .filter(|previous| serde_json::from_str::<serde_json::Value>(previous).is_ok());
+    if let Some(previous) = previous {
+        write_atomic(&backup_path_for(file_path), previous.as_bytes()).map_err(|err| {
+            AppError::io(format!("辞書ファイルのバックアップに失敗しました: {err}"))
+        })?;
+    }
+
+    write_atomic(file_path, content.as_bytes())
+        .map_err(|err| AppError::io(format!("辞書ファイルの書き込みに失敗しました: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dictionary(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("bomsync_dict_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("ipc_master.json")
+    }
+
+    #[test]
+    fn test_write_keeps_backup_of_previous_version() {
+        let path = temp_dictionary("backup");
+        write_dictionary_file(&path, "[1]").unwrap();
+        write_dictionary_file(&path, "[2]").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[2]");
+        assert_eq!(fs::read_to_string(backup_path_for(&path)).unwrap(), "[1]");
+        assert!(!path.with_file_name("ipc_master.json.tmp").exists());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_write_keeps_backup_when_primary_is_corrupted() {
+        let path = temp_dictionary("corrupt_save");
+        write_dictionary_file(&path, "[1]").unwrap();
+        write_dictionary_file(&path, "[2]").unwrap();
+        fs::write(&path, "[{\"ruleName\":").unwrap();
+
+        // 壊れたファイルでバックアップを上書きしない
+        write_dictionary_file(&path, "[3]").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[3]");
+        assert_eq!(fs::read_to_string(backup_path_for(&path)).unwrap(), "[1]");
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_read_falls_back_to_backup_when_corrupted() {
+        let path = temp_dictionary("corrupt");
+        write_dictionary_file(&path, "[{\"ruleName\":\"A\"}]").unwrap();
+        write_dictionary_file(&path, "[]").unwrap();
+        fs::write(&path, "[{\"ruleName\":").unwrap();
+
+        let loaded = read_dictionary_file(&path).unwrap();
+        assert_eq!(loaded.content, "[{\"ruleName\":\"A\"}]");
+        assert!(loaded.warning.is_some());
+
+        fs::write(backup_path_for(&path), "not json").unwrap();
+        assert!(read_dictionary_file(&path).is_err());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_validate_ipc_master_duplicate_rule_name() {
+        let content = r#"[
+            {"ruleName": "A", "conditions": [], "outputName": "X"},
+            {"ruleName": "A", "conditions": [], "outputName": "Y"}
+        ]"#;
+        let err = validate_dictionary("ipc_master", content).unwrap_err();
+        assert!(err.message.contains("'A'"));
+    }
+
+    #[test]
+    fn test_validate_ipc_master_empty_output_key() {
+        let content =
+            r#"[{"ruleName": "A", "conditions": [], "outputName": "X", "outputs": {" ": "Y"}}]"#;
+        assert!(validate_dictionary("ipc_master", content).is_err());
+
+        let ok = r#"[{"ruleName": "A", "conditions": [], "outputName": "X", "outputs": {"category": "Y"}}]"#;
+        assert!(validate_dictionary("ipc_master", ok).is_ok());
+    }
+
+    #[test]
+    fn test_validate_ipc_master_regex_template() {
+        let ok = r#"[{"ruleName": "A", "outputName": "CAP_${size}",
+            "conditions": [{"field": "part_no", "matchType": "regex", "value": "^GRM(?P<size>\\d{3})"}]}]"#;
+        assert!(validate_dictionary("ipc_master", ok).is_ok());
+
+        let unknown = r#"[{"ruleName": "A", "outputName": "CAP_${2}",
+            "conditions": [{"field": "part_no", "matchType": "regex", "value": "^GRM(\\d{3})"}]}]"#;
+        let err = validate_dictionary("ipc_master", unknown).unwrap_err();
+        assert!(err.message.contains("${2}"));
+
+        let invalid = r#"[{"ruleName": "A", "outputName": "X",
+            "conditions": [{"field": "part_no", "matchType": "regex", "value": "GRM("}]}]"#;
+        assert!(validate_dictionary("ipc_master", invalid).is_err());
+    }
+
+    #[test]
+    fn test_validate_exception_master_empty_part_no() {
+        let content = r#"[
+            {"partNo": "GRM155", "outputName": "C0402"},
+            {"partNo": "  ", "outputName": "C0603"}
+        ]"#;
+        let err = validate_dictionary("exception_master", content).unwrap_err();
+        assert!(err.message.contains("2件目"));
+    }
+
+    #[test]
+    fn test_validate_exception_master_conflicting_duplicate() {
+        let content = r#"[
+            {"partNo": "GRM155", "outputName": "C0402"},
+            {"partNo": "grm155", "outputName": "C0603"}
+        ]"#;
+        let err = validate_dictionary("exception_master", content).unwrap_err();
+        assert!(err.message.contains("grm155"));
+        assert!(err.message.contains("C0603"));
+    }
+
+    #[test]
+    fn test_validate_exception_master_identical_duplicate_allowed() {
+        let content = r#"[
+            {"partNo": "GRM155", "outputName": "C0402"},
+            {"partNo": "GRM155", "outputName": "C0402"}
+        ]"#;
+        assert!(validate_dictionary("exception_master", content).is_ok());
+    }
+
+    #[test]
+    fn test_validate_exception_master_ui_payload() {
+        // フロントエンド（exceptionsToJson）が保存する形式
+        let content = r#"[
+          {
+            "ref": "C1",
+            "part_no": "GRM155",
+            "registration_name": "C0402"
+          },
+          {
+            "ref": "C2",
+            "part_no": "GRM155",
+            "registration_name": "C0402_ALT"
+          },
+          {
+            "ref": "",
+            "part_no": "RC0402",
+            "registration_name": "R0402"
+          }
+        ]"#;
+        assert!(validate_dictionary("exception_master", content).is_ok());
+
+        let path = temp_dictionary("exception_ui").with_file_name("exception_master.json");
+        save_dictionary_in(path.parent().unwrap(), "exception_master", content).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        let content = r#"[
+          {"ref": "C1", "part_no": "GRM155", "registration_name": "C0402"},
+          {"ref": "c1", "part_no": "grm155", "registration_name": "C0603"}
+        ]"#;
+        let err = validate_dictionary("exception_master", content).unwrap_err();
+        assert!(err.message.contains("2件目"));
+    }
+
+    #[test]
+    fn test_validate_column_alias_unknown_role() {
+        let content = r#"[{"alias": "Qty", "role": "quantity"}]"#;
+        let err = validate_dictionary("column_alias", content).unwrap_err();
+        assert!(err.message.contains("quantity"));
+    }
+
+    #[test]
+    fn test_validate_column_alias_empty_alias() {
+        let content = r#"[{"alias": "", "role": "ref"}]"#;
+        assert!(validate_dictionary("column_alias", content).is_err());
+    }
+
+    #[test]
+    fn test_validate_column_alias_conflicting_roles() {
+        let content = r#"[
+            {"alias": "Part No", "role": "part_no"},
+            {"alias": "partno", "role": "ref"}
+        ]"#;
+        let err = validate_dictionary("column_alias", content).unwrap_err();
+        assert!(err.message.contains("partno"));
+
+        let ok = r#"[
+            {"alias": "Part No", "role": "part_no"},
+            {"alias": "PartNo", "role": "part_no"}
+        ]"#;
+        assert!(validate_dictionary("column_alias", ok).is_ok());
+    }
+
+    #[test]
+    fn test_validate_manufacturer_alias() {
+        let ok = r#"[
+            {"alias": "Murata Mfg.", "canonical": "Murata"},
+            {"alias": "村田製作所", "canonical": "Murata"},
+            {"alias": "murata mfg.", "canonical": "Murata"}
+        ]"#;
+        assert!(validate_dictionary("manufacturer_alias", ok).is_ok());
+
+        let conflict = r#"[
+            {"alias": "TI", "canonical": "Texas Instruments"},
+            {"alias": "ti", "canonical": "Toshiba"}
+        ]"#;
+        let err = validate_dictionary("manufacturer_alias", conflict).unwrap_err();
+        assert!(err.message.contains("2件目"));
+
+        let empty = r#"[{"alias": "Murata", "canonical": " "}]"#;
+        assert!(validate_dictionary("manufacturer_alias", empty).is_err());
+    }
+
+    #[test]
+    fn test_validate_malformed_json() {
+        assert!(validate_dictionary("exception_master", "{").is_err());
+        assert!(validate_dictionary("column_alias", "[{\"alias\": 1}]").is_err());
+    }
+
+    #[test]
+    fn test_validate_allowed_chars() {
+        assert!(validate_dictionary("allowed_chars", r#"["±", "µ", "ぁ-ん"]"#).is_ok());
+        assert!(validate_dictionary("allowed_chars", r#"["±µ"]"#).is_err());
+    }
+
+    const EXISTING_RULES: &str = r#"[
+        {"ruleName": "R0402", "conditions": [], "outputName": "R_0402"},
+        {"ruleName": "C0402", "conditions": [], "outputName": "C_0402"}
+    ]"#;
+
+    const INCOMING_RULES: &str = r#"[
+        {"ruleName": "C0402", "conditions": [], "outputName": "C_0402_NEW"},
+        {"ruleName": "R0402", "conditions": [], "outputName": "R_0402"},
+        {"ruleName": "L0603", "conditions": [], "outputName": "L_0603"}
+    ]"#;
+
+    fn output_names(content: &str) -> Vec<String> {
+        let rules: Vec<IpcMasterRule> = serde_json::from_str(content).unwrap();
+        rules.into_iter().map(|rule| rule.output_name).collect()
+    }
+
+    #[test]
+    fn test_import_merge_updates_and_appends() {
+        let (content, report) = merge_dictionary_content(
+            "ipc_master",
+            EXISTING_RULES,
+            INCOMING_RULES,
+            DictionaryImportMode::Merge,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output_names(&content),
+            vec!["R_0402", "C_0402_NEW", "L_0603"]
+        );
+        assert_eq!(report.added, 1);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.conflicts, vec!["C0402".to_string()]);
+    }
+
+    #[test]
+    fn test_import_append_keeps_existing_on_conflict() {
+        let (content, report) = merge_dictionary_content(
+            "ipc_master",
+            EXISTING_RULES,
+            INCOMING_RULES,
+            DictionaryImportMode::Append,
+        )
+        .unwrap();
+
+        assert_eq!(output_names(&content), vec!["R_0402", "C_0402", "L_0603"]);
+        assert_eq!(report.added, 1);
+        assert_eq!(report.updated, 0);
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.conflicts, vec!["C0402".to_string()]);
+    }
+
+    #[test]
+    fn test_import_replace_and_invalid_incoming() {
+        let (content, report) = merge_dictionary_content(
+            "ipc_master",
+            EXISTING_RULES,
+            INCOMING_RULES,
+            DictionaryImportMode::Replace,
+        )
+        .unwrap();
+        assert_eq!(
+            output_names(&content),
+            vec!["C_0402_NEW", "R_0402", "L_0603"]
+        );
+        assert_eq!(report.added, 3);
+
+        let duplicated = r#"[
+            {"ruleName": "A", "conditions": [], "outputName": "X"},
+            {"ruleName": "A", "conditions": [], "outputName": "Y"}
+        ]"#;
+        assert!(merge_dictionary_content(
+            "ipc_master",
+            EXISTING_RULES,
+            duplicated,
+            DictionaryImportMode::Merge
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_dictionary_in_dir_without_app_handle() {
+        let dir = temp_dictionary("in_dir")
+            .parent()
+            .unwrap()
+            .join("dictionaries");
+
+        let empty = load_dictionary_in(&dir, "ipc_master").unwrap();
+        assert_eq!(empty.content, "[]");
+        assert!(dir.exists());
+
+        save_dictionary_in(&dir, "ipc_master", EXISTING_RULES).unwrap();
+        let report = import_dictionary_in(
+            &dir,
+            "ipc_master",
+            INCOMING_RULES,
+            DictionaryImportMode::Merge,
+        )
+        .unwrap();
+        assert_eq!(report.added, 1);
+
+        let loaded = load_dictionary_in(&dir, "ipc_master").unwrap();
+        assert_eq!(
+            output_names(&loaded.content),
+            vec!["R_0402", "C_0402_NEW", "L_0603"]
+        );
+        assert!(load_dictionary_in(&dir, "unknown").is_err());
+    }
+
+    #[test]
+    fn test_resolve_dictionaries_dir() {
+        let config_dir = Path::new("/config");
+        let shared = Path::new("/shared/dictionaries");
+        assert_eq!(
+            resolve_dictionaries_dir(None, None, config_dir).unwrap(),
+            Path::new("/config/dictionaries")
+        );
+        assert_eq!(
+            resolve_dictionaries_dir(None, Some(shared), config_dir).unwrap(),
+            shared
+        );
+        let env_dir = std::env::temp_dir().join(format!("bomsync_dict_env_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&env_dir);
+        assert_eq!(
+            resolve_dictionaries_dir(Some(env_dir.as_os_str()), Some(shared), config_dir).unwrap(),
+            env_dir
+        );
+        assert!(env_dir.is_dir());
+        assert_eq!(
+            resolve_dictionaries_dir(Some(OsStr::new("")), Some(shared), config_dir).unwrap(),
+            shared
+        );
+
+        // 環境変数のディレクトリも設定と同じく検証する
+        let error = resolve_dictionaries_dir(Some(OsStr::new("dict")), Some(shared), config_dir)
+            .unwrap_err();
+        assert_eq!(error.kind, crate::models::ErrorKind::Validation);
+        assert!(error.message.contains(DICTIONARY_DIR_ENV));
+        let not_dir = env_dir.join("file.json");
+        fs::write(&not_dir, "{}").unwrap();
+        let error = resolve_dictionaries_dir(Some(not_dir.as_os_str()), Some(shared), config_dir)
+            .unwrap_err();
+        assert!(error.message.contains("書き込めません"));
+        let _ = fs::remove_dir_all(&env_dir);
+    }
+
+    #[test]
+    fn test_dictionary_dir_setting() {
+        let config_dir =
+            std::env::temp_dir().join(format!("bomsync_dict_setting_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&config_dir);
+        let shared = config_dir.join("shared");
+        assert_eq!(read_dictionary_dir_setting_in(&config_dir), None);
+
+        write_dictionary_dir_setting_in(&config_dir, Some(&shared)).unwrap();
+        assert_eq!(
+            read_dictionary_dir_setting_in(&config_dir),
+            Some(shared.clone())
+        );
+        assert!(shared.is_dir());
+        assert!(!shared.join(WRITE_PROBE_FILE).exists());
+
+        let error =
+            write_dictionary_dir_setting_in(&config_dir, Some(Path::new("relative"))).unwrap_err();
+        assert_eq!(error.kind, crate::models::ErrorKind::Validation);
+
+        // ファイルの下にはディレクトリを作れない
+        let file = config_dir.join("file");
+        fs::write(&file, "").unwrap();
+        let error =
+            write_dictionary_dir_setting_in(&config_dir, Some(&file.join("dict"))).unwrap_err();
+        assert!(error.message.contains("書き込めません"));
+        assert_eq!(read_dictionary_dir_setting_in(&config_dir), Some(shared));
+
+        write_dictionary_dir_setting_in(&config_dir, None).unwrap();
+        assert_eq!(read_dictionary_dir_setting_in(&config_dir), None);
+
+        let _ = fs::remove_dir_all(&config_dir);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod atomic;
 pub mod dictionary;
 pub mod session;
+pub mod window_state;
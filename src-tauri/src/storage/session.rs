@@ -1,21 +1,30 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::models::AppError;
+use tauri::Manager;
+
+use crate::models::{AppError, AutosaveEntry};
+
+/// 保持する自動保存ファイルの最大数
+const MAX_AUTOSAVES: usize = 10;
+
+const AUTOSAVE_PREFIX: &str = "autosave-";
+const AUTOSAVE_EXTENSION: &str = "json";
 
 pub fn save_session_to_file(path: String, content: String) -> Result<(), AppError> {
     let path = PathBuf::from(path);
 
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|err| {
-            AppError::new(format!(
+            AppError::io(format!(
                 "セッション保存先ディレクトリの作成に失敗しました: {err}"
             ))
         })?;
     }
 
     fs::write(&path, content).map_err(|err| {
-        AppError::new(format!("セッションファイルの書き込みに失敗しました: {err}"))
+        AppError::io(format!("セッションファイルの書き込みに失敗しました: {err}"))
     })?;
 
     Ok(())
@@ -25,15 +34,192 @@ pub fn load_session_from_file(path: String) -> Result<String, AppError> {
     let path = PathBuf::from(path);
 
     if !path.exists() {
-        return Err(AppError::new(format!(
+        return Err(AppError::not_found(format!(
             "セッションファイルが見つかりません: {}",
             path.display()
         )));
     }
 
     let content = fs::read_to_string(&path).map_err(|err| {
-        AppError::new(format!("セッションファイルの読み込みに失敗しました: {err}"))
+        AppError::io(format!("セッションファイルの読み込みに失敗しました: {err}"))
     })?;
 
     Ok(content)
 }
+
+/// セッションを自動保存する（app_config_dir()/autosave/ 以下に保存）
+pub fn save_session_autosave(
+    app: tauri::AppHandle,
+    content: String,
+) -> Result<AutosaveEntry, AppError> {
+    let dir = autosave_dir(&app)?;
+    save_autosave_in(&dir, &content, MAX_AUTOSAVES)
+}
+
+/// 自動保存ファイルの一覧を取得（新しい順）
+pub fn list_autosaves(app: tauri::AppHandle) -> Result<Vec<AutosaveEntry>, AppError> {
+    let dir = autosave_dir(&app)?;
+    list_autosaves_in(&dir)
+}
+
+/// 自動保存ファイルを読み込む
+pub fn load_autosave(app: tauri::AppHandle, id: String) -> Result<String, AppError> {
+    let dir = autosave_dir(&app)?;
+    load_autosave_in(&dir, &id)
+}
+
+fn autosave_dir(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let base_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|err| AppError::io(format!("設定ディレクトリの取得に失敗しました: {err}")))?;
+    Ok(base_dir.join("autosave"))
+}
+
+/// 指定ディレクトリに自動保存ファイルを書き込み、古いものを削除する
+///
+/// # 引数
+/// * `dir` - 自動保存ディレクトリ
+/// * `content` - セッション内容
+/// * `max_entries` - 保持する最大数
+///
+/// # 戻り値
+/// 保存したファイルのエントリ
+pub fn save_autosave_in(
+    dir: &Path,
+    content: &str,
+    max_entries: usize,
+) -> Result<AutosaveEntry, AppError> {
+    fs::create_dir_all(dir)
+        .map_err(|err| AppError::io(format!("自動保存ディレクトリの作成に失敗しました: {err}")))?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    // 同一ミリ秒内の連続保存でもIDが衝突せず、新しい順に並ぶよう単調増加させる
+    let latest = list_autosaves_in(dir)?.first().map(|entry| entry.timestamp);
+    let timestamp = match latest {
+        Some(latest) if latest >= now => latest + 1,
+        _ => now,
+    };
+    let id = format!("{AUTOSAVE_PREFIX}{timestamp:013}");
+
+    let path = autosave_path(dir, &id);
+    // 書き込み途中で終了しても、壊れた自動保存が一覧に残らないようにする
+    super::atomic::write_atomic(&path, content.as_bytes())
+        .map_err(|err| AppError::io(format!("自動保存ファイルの書き込みに失敗しました: {err}")))?;
+
+    prune_autosaves(dir, max_entries)?;
+
+    Ok(AutosaveEntry {
+        id,
+        timestamp,
+        size: content.len() as u64,
+    })
+}
+
+/// 指定ディレクトリの自動保存ファイル一覧を取得（新しい順）
+pub fn list_autosaves_in(dir: &Path) -> Result<Vec<AutosaveEntry>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|err| {
+        AppError::io(format!(
+            "自動保存ディレクトリの読み込みに失敗しました: {err}"
+        ))
+    })?;
+
+    let mut autosaves: Vec<AutosaveEntry> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(AUTOSAVE_EXTENSION) {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            let timestamp = parse_autosave_timestamp(&id)?;
+            let size = entry.metadata().ok()?.len();
+            Some(AutosaveEntry {
+                id,
+                timestamp,
+                size,
+            })
+        })
+        .collect();
+
+    // 新しい順
+    autosaves.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    Ok(autosaves)
+}
+
+/// 指定ディレクトリの自動保存ファイルを読み込む
+pub fn load_autosave_in(dir: &Path, id: &str) -> Result<String, AppError> {
+    if parse_autosave_timestamp(id).is_none() {
+        return Err(AppError::validation(format!("不正な自動保存IDです: {id}")));
+    }
+
+    let path = autosave_path(dir, id);
+    if !path.exists() {
+        return Err(AppError::not_found(format!(
+            "自動保存ファイルが見つかりません: {id}"
+        )));
+    }
+
+    fs::read_to_string(&path)
+        .map_err(|err| AppError::io(format!("自動保存ファイルの読み込みに失敗しました: {err}")))
+}
+
+fn prune_autosaves(dir: &Path, max_entries: usize) -> Result<(), AppError> {
+    let autosaves = list_autosaves_in(dir)?;
+    for entry in autosaves.iter().skip(max_entries) {
+        fs::remove_file(autosave_path(dir, &entry.id)).map_err(|err| {
+            AppError::io(format!("古い自動保存ファイルの削除に失敗しました: {err}"))
+        })?;
+    }
+    Ok(())
+}
+
+fn autosave_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.{AUTOSAVE_EXTENSION}"))
+}
+
+/// "autosave-<ミリ秒>" 形式のIDからタイムスタンプを取り出す
+///
+/// パス区切り文字などを含む不正なIDは None を返す
+fn parse_autosave_timestamp(id: &str) -> Option<u64> {
+    let timestamp = id.strip_prefix(AUTOSAVE_PREFIX)?;
+    if timestamp.is_empty() || !timestamp.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    timestamp.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autosave_prunes_to_newest_entries() {
+        let dir = std::env::temp_dir().join(format!("bomsync_autosave_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut saved = Vec::new();
+        for i in 0..5 {
+            saved.push(save_autosave_in(&dir, &format!("{{\"n\":{i}}}"), 3).unwrap());
+        }
+
+        let listed = list_autosaves_in(&dir).unwrap();
+        assert_eq!(listed.len(), 3);
+        assert_eq!(listed[0].id, saved[4].id);
+        assert_eq!(listed[2].id, saved[2].id);
+        assert_eq!(listed[0].size, 7);
+        assert_eq!(load_autosave_in(&dir, &saved[4].id).unwrap(), "{\"n\":4}");
+        assert!(load_autosave_in(&dir, &saved[0].id).is_err());
+        assert!(load_autosave_in(&dir, "../secret").is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
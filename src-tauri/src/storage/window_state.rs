@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::models::AppError;
+
+use super::atomic::write_atomic;
+
+const WINDOW_STATE_FILE: &str = "window_state.json";
+
+/// ウィンドウの位置とサイズ（論理座標）
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WindowState {
+    /// ウィンドウの現在の位置（外枠）とサイズ（内側）を取得
+    pub fn from_window(window: &tauri::WebviewWindow) -> Result<Self, AppError> {
+        let to_error =
+            |err: tauri::Error| AppError::new(format!("ウィンドウ情報の取得に失敗しました: {err}"));
+        let scale = window.scale_factor().map_err(to_error)?;
+        let position = window
+            .outer_position()
+            .map_err(to_error)?
+            .to_logical::<f64>(scale);
+        let size = window
+            .inner_size()
+            .map_err(to_error)?
+            .to_logical::<f64>(scale);
+        Ok(Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        })
+    }
+}
+
+/// ウィンドウの現在の配置をプロジェクトIDに対応付けて保存する（app_config_dir()/window_state.json）
+pub fn save_window_state(
+    app: &tauri::AppHandle,
+    window: &tauri::WebviewWindow,
+    project_id: &str,
+) -> Result<(), AppError> {
+    let state = WindowState::from_window(window)?;
+    save_window_state_in(&window_state_path(app)?, project_id, state)
+}
+
+/// プロジェクトIDに対応付けて保存したウィンドウの配置を取得（未保存の場合は None）
+pub fn load_window_state(app: &tauri::AppHandle, project_id: &str) -> Option<WindowState> {
+    let path = window_state_path(app).ok()?;
+    load_window_state_in(&path, project_id)
+}
+
+fn window_state_path(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let base_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|err| AppError::io(format!("設定ディレクトリの取得に失敗しました: {err}")))?;
+    Ok(base_dir.join(WINDOW_STATE_FILE))
+}
+
+/// 保存済みの配置を全て読み込む（ファイルがない・壊れている場合は空）
+fn read_states(path: &Path) -> BTreeMap<String, WindowState> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 指定ファイルからプロジェクトのウィンドウ配置を読み込む
+pub fn load_window_state_in(path: &Path, project_id: &str) -> Option<WindowState> {
+    read_states(path).remove(project_id)
+}
+
+/// 指定ファイルのプロジェクトのウィンドウ配置を更新する（他のプロジェクトの配置は残す）
+///
+/// # 引数
+/// * `path` - 保存先ファイル
+/// * `project_id` - プロジェクトID（ウィンドウのラベルではなくIDで対応付ける）
+/// * `state` - ウィンドウの配置
+pub fn save_window_state_in(
+    path: &Path,
+    project_id: &str,
+    state: WindowState,
+) -> Result<(), AppError> {
+    if !state.width.is_finite()
+        || !state.height.is_finite()
+        || state.width <= 0.0
+        || state.height <= 0.0
+    {
+        return Err(AppError::validation(
+            "ウィンドウのサイズが不正なため保存できません。",
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| AppError::io(format!("設定ディレクトリの作成に失敗しました: {err}")))?;
+    }
+
+    let mut states = read_states(path);
+    states.insert(project_id.to_string(), state);
+    let content = serde_json::to_string_pretty(&states)
+        .map_err(|err| AppError::new(format!("ウィンドウ配置のエンコードに失敗しました: {err}")))?;
+    write_atomic(path, content.as_bytes())
+        .map_err(|err| AppError::io(format!("ウィンドウ配置の保存に失敗しました: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_state_round_trip() {
+        let dir = std::env::temp_dir().join(format!("bomsync_window_state_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join(WINDOW_STATE_FILE);
+        assert_eq!(load_window_state_in(&path, "a b"), None);
+
+        let first = WindowState {
+            x: 10.0,
+            y: 20.0,
+            width: 800.0,
+            height: 600.0,
+        };
+        let second = WindowState {
+            x: -5.0,
+            y: 0.0,
+            width: 1024.0,
+            height: 768.0,
+        };
+        // 同じラベルに正規化されるIDでも別々に保存される
+        save_window_state_in(&path, "a b", first).unwrap();
+        save_window_state_in(&path, "a-b", second).unwrap();
+        assert_eq!(load_window_state_in(&path, "a b"), Some(first));
+        assert_eq!(load_window_state_in(&path, "a-b"), Some(second));
+
+        let moved = WindowState { x: 300.0, ..first };
+        save_window_state_in(&path, "a b", moved).unwrap();
+        assert_eq!(load_window_state_in(&path, "a b"), Some(moved));
+        assert_eq!(load_window_state_in(&path, "a-b"), Some(second));
+
+        let invalid = WindowState {
+            width: 0.0,
+            ..first
+        };
+        assert!(save_window_state_in(&path, "a b", invalid).is_err());
+
+        fs::write(&path, "{broken").unwrap();
+        assert_eq!(load_window_state_in(&path, "a b"), None);
+        save_window_state_in(&path, "a b", first).unwrap();
+        assert_eq!(load_window_state_in(&path, "a b"), Some(first));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,185 @@
+/// 工学表記の値（"0.1uF", "4K7", "10kΩ" など）を解析した結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EngValue {
+    /// 接頭辞を適用した値（基本単位換算）
+    pub magnitude: f64,
+
+    /// 単位（'Ω', 'F', 'H'、省略時はNone）
+    pub unit: Option<char>,
+}
+
+/// 工学表記の値を解析
+///
+/// SI接頭辞（p/n/u/µ/m/k/M/G）と単位（Ω/F/H）に対応し、
+/// "4R7"（= 4.7）や "R47"（= 0.47）、"4K7"（= 4700）のような
+/// 小数点代わりの表記も扱う。
+///
+/// # 戻り値
+/// 解析できた場合は値と単位（解析できない場合はNone）
+pub fn parse_eng_value(input: &str) -> Option<EngValue> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return None;
+    }
+
+    let (body, unit) = strip_unit(&compact);
+    if body.is_empty() {
+        return None;
+    }
+
+    let chars: Vec<char> = body.chars().collect();
+    let letter_positions: Vec<usize> = chars
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !c.is_ascii_digit() && **c != '.')
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let magnitude = match letter_positions.as_slice() {
+        // 数値のみ
+        [] => parse_number(body)?,
+        // 接頭辞（または R）1文字を含む
+        [pos] => {
+            let letter = chars[*pos];
+            let multiplier = prefix_multiplier(letter)?;
+            let left: String = chars[..*pos].iter().collect();
+            let right: String = chars[pos + 1..].iter().collect();
+
+            if right.is_empty() {
+                // 末尾の接頭辞（"100n", "10k"）
+                parse_number(&left)? * multiplier
+            } else {
+                // 小数点代わりの表記（"4R7", "R47", "4K7"）
+                if left.contains('.') || right.contains('.') {
+                    return None;
+                }
+                let left = if left.is_empty() { "0" } else { left.as_str() };
+                parse_number(&format!("{}.{}", left, right))? * multiplier
+            }
+        }
+        _ => return None,
+    };
+
+    Some(EngValue { magnitude, unit })
+}
+
+/// 2つの値が電気的に同じ値かどうか判定
+///
+/// 文字列として一致する場合は常にtrue。
+/// 両方が工学表記として解析でき、単位が矛盾せず値が等しい場合もtrueを返す。
+/// 解析できない値は文字列比較の結果のみで判定する。
+pub fn values_equivalent(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+
+    let (Some(value_a), Some(value_b)) = (parse_eng_value(a), parse_eng_value(b)) else {
+        return false;
+    };
+
+    let units_compatible = match (value_a.unit, value_b.unit) {
+        (Some(unit_a), Some(unit_b)) => unit_a == unit_b,
+        _ => true,
+    };
+
+    units_compatible && approximately_equal(value_a.magnitude, value_b.magnitude)
+}
+
+/// 末尾の単位を取り除く
+fn strip_unit(value: &str) -> (&str, Option<char>) {
+    let lower = value.to_lowercase();
+    for suffix in ["ohms", "ohm"] {
+        if lower.ends_with(suffix) && value.is_char_boundary(value.len() - suffix.len()) {
+            return (&value[..value.len() - suffix.len()], Some('Ω'));
+        }
+    }
+
+    match value.chars().last() {
+        // U+03A9（ギリシャ文字）と U+2126（オーム記号）の両方を受け付ける
+        Some(c @ ('\u{03A9}' | '\u{2126}')) => (&value[..value.len() - c.len_utf8()], Some('Ω')),
+        Some(c @ ('F' | 'f')) => (&value[..value.len() - c.len_utf8()], Some('F')),
+        Some(c @ ('H' | 'h')) => (&value[..value.len() - c.len_utf8()], Some('H')),
+        _ => (value, None),
+    }
+}
+
+/// 接頭辞の倍率を取得（'R' は小数点代わりの表記で倍率1）
+fn prefix_multiplier(prefix: char) -> Option<f64> {
+    match prefix {
+        'p' => Some(1e-12),
+        'n' => Some(1e-9),
+        'u' | 'µ' | 'μ' => Some(1e-6),
+        'm' => Some(1e-3),
+        'R' | 'r' => Some(1.0),
+        'k' | 'K' => Some(1e3),
+        'M' => Some(1e6),
+        'G' => Some(1e9),
+        _ => None,
+    }
+}
+
+/// 数字と小数点のみからなる文字列を数値に変換
+fn parse_number(value: &str) -> Option<f64> {
+    if value.is_empty()
+        || value == "."
+        || !value.chars().all(|c| c.is_ascii_digit() || c == '.')
+        || value.matches('.').count() > 1
+    {
+        return None;
+    }
+    value.parse::<f64>().ok()
+}
+
+fn approximately_equal(a: f64, b: f64) -> bool {
+    let scale = a.abs().max(b.abs());
+    if scale == 0.0 {
+        return true;
+    }
+    (a - b).abs() <= scale * 1e-9
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_eng_value() {
+        let cases = [
+            ("10k", 1e4, None),
+            ("10kΩ", 1e4, Some('Ω')),
+            ("0.1uF", 1e-7, Some('F')),
+            ("100nF", 1e-7, Some('F')),
+            ("4R7", 4.7, None),
+            ("R47", 0.47, None),
+            ("4K7", 4700.0, None),
+            ("2u2H", 2.2e-6, Some('H')),
+            ("1 MOhm", 1e6, Some('Ω')),
+            ("47", 47.0, None),
+        ];
+
+        for (input, magnitude, unit) in cases {
+            let parsed = parse_eng_value(input).unwrap_or_else(|| panic!("{input}"));
+            assert!(approximately_equal(parsed.magnitude, magnitude), "{input}");
+            assert_eq!(parsed.unit, unit, "{input}");
+        }
+
+        for input in ["", "GRM155", "10k5%", "1.2.3", "4.7R1", "abc"] {
+            assert_eq!(parse_eng_value(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_values_equivalent() {
+        assert!(values_equivalent("10k", "10kΩ"));
+        assert!(values_equivalent("0.1uF", "100nF"));
+        assert!(values_equivalent("4R7", "4.7"));
+        assert!(values_equivalent("R47", "470m"));
+        assert!(values_equivalent("1µF", "1uF"));
+
+        assert!(!values_equivalent("10k", "1k"));
+        assert!(!values_equivalent("1uF", "1uH"));
+        // 解析できない値は文字列比較
+        assert!(values_equivalent("N/A", "N/A"));
+        assert!(!values_equivalent("DNP", "dnp"));
+    }
+}
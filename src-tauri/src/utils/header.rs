@@ -113,6 +113,32 @@ pub fn matches_comment_header(normalized: &str) -> bool {
     false
 }
 
+/// 未実装（DNP）の指定を示すヘッダーかどうか判定
+///
+/// "Mount"・"実装" のように真偽が逆になる列名は含めない
+pub fn matches_dnp_header(normalized: &str) -> bool {
+    // 完全一致パターン
+    if normalized == "dnp"
+        || normalized == "dni"
+        || normalized == "dnf"
+        || normalized == "donotplace"
+        || normalized == "donotpopulate"
+        || normalized == "donotfit"
+        || normalized == "nomount"
+        || normalized == "未実装"
+        || normalized == "実装しない"
+    {
+        return true;
+    }
+
+    // 部分一致パターン
+    if normalized.contains("dnp") || normalized.contains("未実装") {
+        return true;
+    }
+
+    false
+}
+
 /// メーカーを示すヘッダーかどうか判定
 pub fn matches_manufacturer_header(normalized: &str) -> bool {
     // 完全一致パターン
@@ -146,6 +172,34 @@ pub fn matches_manufacturer_header(normalized: &str) -> bool {
     false
 }
 
+/// 数量を示すヘッダーかどうか判定
+pub fn matches_quantity_header(normalized: &str) -> bool {
+    // 完全一致パターン
+    if normalized == "qty"
+        || normalized == "q'ty"
+        || normalized == "quantity"
+        || normalized == "count"
+        || normalized == "数量"
+        || normalized == "個数"
+        || normalized == "員数"
+        || normalized == "使用数"
+    {
+        return true;
+    }
+
+    // 部分一致パターン
+    if normalized.contains("qty") || normalized.contains("quantity") {
+        return true;
+    }
+
+    // 日本語パターン
+    if normalized.contains("数量") || normalized.contains("員数") {
+        return true;
+    }
+
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +216,21 @@ mod tests {
         assert!(matches_ref_header("reference"));
         assert!(!matches_ref_header("value"));
     }
+
+    #[test]
+    fn test_matches_quantity_header() {
+        assert!(matches_quantity_header(&normalize_header("Qty")));
+        assert!(matches_quantity_header(&normalize_header("Order Quantity")));
+        assert!(matches_quantity_header("員数"));
+        assert!(!matches_quantity_header("partno"));
+    }
+
+    #[test]
+    fn test_matches_dnp_header() {
+        assert!(matches_dnp_header(&normalize_header("DNP")));
+        assert!(matches_dnp_header(&normalize_header("Do Not Place")));
+        assert!(matches_dnp_header("未実装"));
+        assert!(!matches_dnp_header("mount"));
+        assert!(!matches_dnp_header("comment"));
+    }
 }
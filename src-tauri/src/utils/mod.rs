@@ -1,2 +1,5 @@
+pub mod eng;
 pub mod header;
+pub mod progress;
 pub mod text;
+pub mod window_label;
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::models::{AppError, Progress};
+
+/// 進捗の通知と中断の確認を行う間隔（件数）
+pub const PROGRESS_INTERVAL: usize = 1000;
+
+/// 処理の中断指示
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 時間のかかる処理に渡す、進捗の通知先と中断指示
+pub struct OperationContext<'a> {
+    pub on_progress: &'a dyn Fn(&Progress),
+    pub cancel: Option<&'a CancelToken>,
+}
+
+impl Default for OperationContext<'_> {
+    fn default() -> Self {
+        Self {
+            on_progress: &|_| {},
+            cancel: None,
+        }
+    }
+}
+
+/// 行ごとの処理の進捗を一定間隔で通知し、中断指示を確認する
+///
+/// 開始時（0件）・`PROGRESS_INTERVAL` 件ごと・完了時にのみ確認するため、
+/// 行ごとの処理にはほとんど負荷をかけない
+pub struct ProgressReporter<'a> {
+    stage: &'static str,
+    total: usize,
+    interval: usize,
+    context: &'a OperationContext<'a>,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(stage: &'static str, total: usize, context: &'a OperationContext<'a>) -> Self {
+        Self::with_interval(stage, total, PROGRESS_INTERVAL, context)
+    }
+
+    pub fn with_interval(
+        stage: &'static str,
+        total: usize,
+        interval: usize,
+        context: &'a OperationContext<'a>,
+    ) -> Self {
+        Self {
+            stage,
+            total,
+            interval: interval.max(1),
+            context,
+        }
+    }
+
+    /// 処理済みの件数を更新
+    ///
+    /// # 戻り値
+    /// 中断が指示されていた場合は `AppError::cancelled()`
+    pub fn tick(&self, processed: usize) -> Result<(), AppError> {
+        if !processed.is_multiple_of(self.interval) && processed != self.total {
+            return Ok(());
+        }
+        if self.context.cancel.is_some_and(CancelToken::is_cancelled) {
+            return Err(AppError::cancelled());
+        }
+        (self.context.on_progress)(&Progress {
+            stage: self.stage.to_string(),
+            processed,
+            total: self.total,
+        });
+        Ok(())
+    }
+}
+
+/// 実行中の処理の中断指示（処理IDごと）
+#[derive(Default)]
+pub struct OperationRegistry {
+    tokens: Mutex<HashMap<String, CancelToken>>,
+}
+
+impl OperationRegistry {
+    /// 処理IDを登録して処理を実行し、終了後に登録を解除する
+    ///
+    /// 処理IDがNoneの場合は中断指示なしで実行する
+    pub fn run<T>(
+        &self,
+        operation_id: Option<&str>,
+        operation: impl FnOnce(Option<&CancelToken>) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let Some(operation_id) = operation_id else {
+            return operation(None);
+        };
+
+        let token = CancelToken::default();
+        self.lock().insert(operation_id.to_string(), token.clone());
+        let result = operation(Some(&token));
+        self.lock().remove(operation_id);
+        result
+    }
+
+    /// 実行中の処理に中断を指示する
+    ///
+    /// # 戻り値
+    /// 処理IDが実行中だった場合は true
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self.lock().get(operation_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancelToken>> {
+        // 処理中のパニックで中断指示が使えなくならないよう、ポイズン状態は無視する
+        self.tokens
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_tick_reports_at_interval_and_completion() {
+        let reported = RefCell::new(Vec::new());
+        let on_progress = |progress: &Progress| reported.borrow_mut().push(progress.processed);
+        let context = OperationContext {
+            on_progress: &on_progress,
+            cancel: None,
+        };
+        let reporter = ProgressReporter::with_interval("test", 5, 2, &context);
+
+        for processed in 0..=5 {
+            reporter.tick(processed).unwrap();
+        }
+
+        assert_eq!(reported.into_inner(), vec![0, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_registry_cancels_running_operation() {
+        let registry = OperationRegistry::default();
+        assert!(!registry.cancel("op-1"));
+
+        let result: Result<(), AppError> = registry.run(Some("op-1"), |cancel| {
+            assert!(registry.cancel("op-1"));
+            let context = OperationContext {
+                cancel,
+                ..OperationContext::default()
+            };
+            let reporter = ProgressReporter::with_interval("test", 10, 2, &context);
+            // 中断は確認する間隔でのみ反映される
+            reporter.tick(1)?;
+            reporter.tick(2)
+        });
+
+        assert!(result.unwrap_err().is_cancelled());
+        // 終了した処理の登録は解除される
+        assert!(!registry.cancel("op-1"));
+    }
+}
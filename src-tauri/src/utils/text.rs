@@ -1,10 +1,37 @@
-/// 括弧を削除し、全角文字を半角に変換する
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::models::{AppError, CleanseOptions};
+
+/// 括弧を削除し、全角文字を半角に変換して空白を整える
 pub fn cleanse_string(input: &str) -> String {
-    let without_parentheses = input.replace(['(', ')', '（', '）'], "");
-    without_parentheses
+    cleanse_string_with(input, &CleanseOptions::legacy())
+}
+
+/// オプションに従って括弧の削除・全角文字の半角変換・空白の正規化を行う
+pub fn cleanse_string_with(input: &str, options: &CleanseOptions) -> String {
+    let converted: String = input
         .chars()
-        .map(fullwidth_to_halfwidth)
-        .collect()
+        .filter(|c| !(options.remove_parentheses && matches!(c, '(' | ')' | '（' | '）')))
+        .map(|c| {
+            if options.normalize_width {
+                fullwidth_to_halfwidth(c)
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    if options.normalize_whitespace {
+        converted.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        converted
+    }
+}
+
+/// 全角英数字・記号・スペースを半角に変換する（`cleanse_string` と異なり括弧は削除しない）
+pub fn normalize_width(input: &str) -> String {
+    input.chars().map(fullwidth_to_halfwidth).collect()
 }
 
 /// 全角文字を半角に変換
@@ -25,15 +52,93 @@ pub fn is_truthy(value: &str) -> bool {
     )
 }
 
+/// 未実装（DNP）列の値が「未実装」を示すかどうか判定
+///
+/// 真偽値の「真」に加え、"DNP"・"未実装"・"x" などの記号も未実装とみなす
+pub fn is_dnp_marker(value: &str) -> bool {
+    let normalized = value.trim().to_lowercase();
+    is_truthy(&normalized)
+        || matches!(
+            normalized.as_str(),
+            "dnp" | "dni" | "dnf" | "x" | "×" | "未実装" | "実装しない" | "no mount" | "nomount"
+        )
+}
+
 /// セルに無効な文字が含まれていないかチェック
 pub fn find_invalid_char(cell: &str) -> Option<char> {
+    find_invalid_char_with(cell, &CharAllowlist::default())
+}
+
+/// 許可リストを考慮してセルに無効な文字が含まれていないかチェック
+///
+/// 許可リストに含まれる非ASCII文字は無効とみなさない（制御文字は常に無効）
+pub fn find_invalid_char_with(cell: &str, allowlist: &CharAllowlist) -> Option<char> {
     cell.chars().find(|c| {
         let code = *c as u32;
         (code < 0x20 && *c != '\t')
-            || (code >= 0x7f && !(c.is_ascii_graphic() || c.is_ascii_whitespace()))
+            || (code >= 0x7f
+                && !(c.is_ascii_graphic() || c.is_ascii_whitespace())
+                && !allowlist.contains(*c))
     })
 }
 
+/// 無効文字チェックで許可する非ASCII文字の集合
+#[derive(Debug, Clone, Default)]
+pub struct CharAllowlist {
+    chars: HashSet<char>,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharAllowlist {
+    /// 許可文字の指定から作成
+    ///
+    /// # 引数
+    /// * `entries` - 1文字（"±"）または範囲（"ぁ-ん"）の指定
+    ///
+    /// # 戻り値
+    /// 許可リスト（指定が不正な場合はエラー）
+    pub fn from_entries(entries: &[String]) -> Result<Self, AppError> {
+        let mut allowlist = CharAllowlist::default();
+
+        for entry in entries {
+            let chars: Vec<char> = entry.trim().chars().collect();
+            match chars.as_slice() {
+                [c] => {
+                    allowlist.chars.insert(*c);
+                }
+                [start, '-', end] if start <= end => {
+                    allowlist.ranges.push((*start, *end));
+                }
+                _ => {
+                    return Err(AppError::validation(format!(
+                        "許可文字の指定が不正です: '{}'（1文字または「開始-終了」の形式で指定してください）",
+                        entry
+                    )))
+                }
+            }
+        }
+
+        Ok(allowlist)
+    }
+
+    /// 文字が許可されているか
+    pub fn contains(&self, c: char) -> bool {
+        self.chars.contains(&c)
+            || self
+                .ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&c))
+    }
+}
+
+/// ステータスと対応するセル色（先頭が表示用の色）
+const STATUS_PALETTE: &[(&str, &[&str])] = &[
+    ("removed", &["#ff4d4f", "#ff0000", "red"]),
+    ("added", &["#00b894", "#00ff00", "green"]),
+    ("modified", &["#ffb347", "#ffa500", "orange"]),
+    ("unchanged", &["#a9a9a9", "#808080", "gray", "grey"]),
+];
+
 /// セルの色情報をステータス文字列に変換
 pub fn color_to_status(value: &str) -> Option<&'static str> {
     let mut normalized = value.trim().to_lowercase();
@@ -41,25 +146,181 @@ pub fn color_to_status(value: &str) -> Option<&'static str> {
         normalized = format!("#{}", normalized);
     }
 
-    match normalized.as_str() {
-        "#ff0000" | "#ff4d4f" | "red" => Some("removed"),
-        "#00b894" | "#00ff00" | "green" => Some("added"),
-        "#ffa500" | "#ffb347" | "orange" => Some("modified"),
-        "#808080" | "#a9a9a9" | "gray" | "grey" => Some("unchanged"),
-        _ => None,
+    STATUS_PALETTE
+        .iter()
+        .find(|(_, colors)| colors.contains(&normalized.as_str()))
+        .map(|(status, _)| *status)
+}
+
+/// ステータス文字列を表示用の色（"#rrggbb"）に変換
+pub fn status_to_color(status: &str) -> Option<&'static str> {
+    let status = status.trim().to_lowercase();
+    STATUS_PALETTE
+        .iter()
+        .find(|(name, _)| *name == status)
+        .map(|(_, colors)| colors[0])
+}
+
+/// Referenceを自然順で比較（"C2" < "C10"、"U1A" < "U1B"）
+///
+/// 数字の部分は数値として、それ以外の部分は大文字・小文字を区別せずに比較する。
+/// 同じ順序になる場合は文字列として比較し、順序を一意にする
+pub fn natural_ref_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chunks = natural_chunks(a.trim());
+    let mut b_chunks = natural_chunks(b.trim());
+
+    loop {
+        let ordering = match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_chunk), Some(b_chunk)) => {
+                let a_digits = a_chunk.starts_with(|c: char| c.is_ascii_digit());
+                let b_digits = b_chunk.starts_with(|c: char| c.is_ascii_digit());
+                match (a_digits, b_digits) {
+                    (true, true) => {
+                        let a_number = a_chunk.trim_start_matches('0');
+                        let b_number = b_chunk.trim_start_matches('0');
+                        a_number
+                            .len()
+                            .cmp(&b_number.len())
+                            .then_with(|| a_number.cmp(b_number))
+                    }
+                    // 数字は英字より前
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => a_chunk.to_uppercase().cmp(&b_chunk.to_uppercase()),
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
     }
 }
 
+/// 数字の並びとそれ以外の並びに分割（"U12A" → "U", "12", "A"）
+fn natural_chunks(value: &str) -> impl Iterator<Item = &str> {
+    let mut rest = value;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let digits = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_natural_ref_cmp() {
+        let mut refs = vec!["C10", "c3", "R1", "C2", "U1B", "C02", "U1A", "IC1", "C1"];
+        refs.sort_by(|a, b| natural_ref_cmp(a, b));
+        assert_eq!(
+            refs,
+            vec!["C1", "C02", "C2", "c3", "C10", "IC1", "R1", "U1A", "U1B"]
+        );
+    }
+
     #[test]
     fn test_cleanse_string() {
         assert_eq!(cleanse_string("test(123)"), "test123");
         assert_eq!(cleanse_string("テスト（全角）"), "テスト全角");
     }
 
+    #[test]
+    fn test_cleanse_string_with_options() {
+        let cleanse = |remove_parentheses, normalize_width| {
+            let options = CleanseOptions {
+                remove_parentheses,
+                normalize_width,
+                normalize_whitespace: true,
+            };
+            (
+                cleanse_string_with("74VHC08FT(BJ)", &options),
+                cleanse_string_with("７４ＶＨＣ０８ＦＴ（ＢＪ）", &options),
+            )
+        };
+
+        assert_eq!(
+            cleanse(false, true),
+            ("74VHC08FT(BJ)".to_string(), "74VHC08FT(BJ)".to_string())
+        );
+        assert_eq!(
+            cleanse(true, true),
+            ("74VHC08FTBJ".to_string(), "74VHC08FTBJ".to_string())
+        );
+        assert_eq!(
+            cleanse(true, false),
+            (
+                "74VHC08FTBJ".to_string(),
+                "７４ＶＨＣ０８ＦＴＢＪ".to_string()
+            )
+        );
+        assert_eq!(
+            cleanse(false, false),
+            (
+                "74VHC08FT(BJ)".to_string(),
+                "７４ＶＨＣ０８ＦＴ（ＢＪ）".to_string()
+            )
+        );
+        assert_eq!(
+            cleanse_string_with("74VHC08FT(BJ)", &CleanseOptions::default()),
+            "74VHC08FT(BJ)"
+        );
+    }
+
+    #[test]
+    fn test_cleanse_string_normalizes_whitespace() {
+        let input = "  ＴＤＫ　　Corp  ";
+        assert_eq!(
+            cleanse_string_with(input, &CleanseOptions::default()),
+            "TDK Corp"
+        );
+        assert_eq!(
+            cleanse_string("Murata  Mfg.\t(Japan) "),
+            "Murata Mfg. Japan"
+        );
+
+        let keep_spaces = CleanseOptions {
+            normalize_whitespace: false,
+            ..CleanseOptions::default()
+        };
+        assert_eq!(cleanse_string_with(input, &keep_spaces), "  TDK  Corp  ");
+    }
+
+    #[test]
+    fn test_normalize_width() {
+        assert_eq!(normalize_width("Ｃ１"), "C1");
+        assert_eq!(normalize_width("ＧＲＭ１５５（Ｘ）"), "GRM155(X)");
+        assert_eq!(normalize_width("Ｒ１\u{3000}Ｒ２"), "R1 R2");
+        assert_eq!(normalize_width("抵抗"), "抵抗");
+    }
+
+    #[test]
+    fn test_find_invalid_char_with_allowlist() {
+        let allowlist =
+            CharAllowlist::from_entries(&["±".to_string(), "ぁ-ん".to_string()]).unwrap();
+
+        assert_eq!(find_invalid_char("10k±1%"), Some('±'));
+        assert_eq!(find_invalid_char_with("10k±1%", &allowlist), None);
+        assert_eq!(find_invalid_char_with("てすと", &allowlist), None);
+        assert_eq!(find_invalid_char_with("テスト", &allowlist), Some('テ'));
+        assert_eq!(
+            find_invalid_char_with("a\u{0007}", &allowlist),
+            Some('\u{0007}')
+        );
+
+        assert!(CharAllowlist::from_entries(&["ab".to_string()]).is_err());
+        assert!(CharAllowlist::from_entries(&["ん-ぁ".to_string()]).is_err());
+    }
+
     #[test]
     fn test_is_truthy() {
         assert!(is_truthy("true"));
@@ -68,4 +329,27 @@ mod tests {
         assert!(!is_truthy("false"));
         assert!(!is_truthy("0"));
     }
+
+    #[test]
+    fn test_is_dnp_marker() {
+        for value in ["DNP", " 未実装 ", "x", "X", "×", "true", "1"] {
+            assert!(is_dnp_marker(value), "{value}");
+        }
+        for value in ["", "0", "実装", "-", "GRM155"] {
+            assert!(!is_dnp_marker(value), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_status_color_round_trip() {
+        assert_eq!(color_to_status("FF0000"), Some("removed"));
+        assert_eq!(color_to_status("Grey"), Some("unchanged"));
+        assert_eq!(color_to_status("#123456"), None);
+
+        for status in ["removed", "added", "modified", "unchanged"] {
+            let color = status_to_color(status).unwrap();
+            assert_eq!(color_to_status(color), Some(status));
+        }
+        assert_eq!(status_to_color("likely_same"), None);
+    }
 }
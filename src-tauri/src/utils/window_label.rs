@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// プロジェクトIDが空・記号のみの場合に使うラベル
+const DEFAULT_LABEL: &str = "project";
+
+/// プロジェクトIDをウィンドウのラベルに使える文字列にする（英数字以外は "-"）
+pub fn sanitize_window_label(project_id: &str) -> String {
+    let sanitized: String = project_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let sanitized = sanitized.trim_matches('-');
+    if sanitized.is_empty() {
+        DEFAULT_LABEL.to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// プロジェクトID → ウィンドウのラベルの対応
+///
+/// 同じ文字列に正規化される別々のプロジェクト（"A/B" と "A-B" など）にも別のラベルを割り当て、
+/// 同じプロジェクトを開き直したときは既存のラベルを返す。
+#[derive(Default)]
+pub struct ProjectWindowRegistry {
+    labels: Mutex<HashMap<String, String>>,
+}
+
+impl ProjectWindowRegistry {
+    /// プロジェクトのラベルを取得し、未割り当ての場合は新しく割り当てる
+    ///
+    /// # 引数
+    /// * `project_id` - プロジェクトID
+    /// * `in_use` - 対応表にないウィンドウがラベルを使用しているか
+    ///
+    /// # 戻り値
+    /// （ラベル, 新しく割り当てたか）
+    pub fn claim(&self, project_id: &str, in_use: impl Fn(&str) -> bool) -> (String, bool) {
+        let mut labels = self.labels.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(label) = labels.get(project_id) {
+            return (label.clone(), false);
+        }
+
+        let base = sanitize_window_label(project_id);
+        let mut label = base.clone();
+        let mut counter = 0usize;
+        while labels.values().any(|used| *used == label) || in_use(&label) {
+            counter += 1;
+            label = format!("{base}-{counter}");
+        }
+        labels.insert(project_id.to_string(), label.clone());
+        (label, true)
+    }
+
+    /// ウィンドウが閉じられたラベルの割り当てを解除
+    pub fn release(&self, label: &str) {
+        let mut labels = self.labels.lock().unwrap_or_else(|err| err.into_inner());
+        labels.retain(|_, used| used != label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_window_label() {
+        assert_eq!(sanitize_window_label("A/B"), "A-B");
+        assert_eq!(sanitize_window_label("--x y--"), "x-y");
+        assert_eq!(sanitize_window_label("日本語"), "project");
+    }
+
+    #[test]
+    fn test_registry_claim() {
+        let registry = ProjectWindowRegistry::default();
+        let in_use = |label: &str| label == "main";
+
+        assert_eq!(registry.claim("A/B", in_use), ("A-B".to_string(), true));
+        assert_eq!(registry.claim("A-B", in_use), ("A-B-1".to_string(), true));
+        // 同じプロジェクトは同じラベル
+        assert_eq!(registry.claim("A/B", in_use), ("A-B".to_string(), false));
+        // 対応表にないウィンドウのラベルは避ける
+        assert_eq!(registry.claim("main", in_use), ("main-1".to_string(), true));
+
+        registry.release("A-B");
+        assert_eq!(registry.claim("A-B", in_use), ("A-B-1".to_string(), false));
+        assert_eq!(registry.claim("A/B", in_use), ("A-B".to_string(), true));
+    }
+}
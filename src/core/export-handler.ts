@@ -514,3 +514,42 @@ export async function exportToPADSReport(source: ExportSource, context: ExportCo
     setProcessing(false);
   }
 }
+
+/**
+ * JSON形式でエクスポート（列の役割・表示順序を含めて読み込み直せる形式）
+ */
+export async function exportToJSON(source: ExportSource, context: ExportContext): Promise<void> {
+  const data = getParseResultForSource(source, context, 'netlist');
+  if (!data || data.rows.length === 0) {
+    alert(`${SOURCE_LABEL[source]}からエクスポートできるデータがありません。`);
+    return;
+  }
+
+  const filePath = await save({
+    filters: [{ name: 'JSON Files', extensions: ['json'] }],
+    defaultPath: `${getExportFilename(source)}.json`
+  });
+
+  if (!filePath) return;
+
+  try {
+    setProcessing(true, `${SOURCE_LABEL[source]}をJSON出力中...`);
+
+    const content = await invoke<string>('export_bom_file', {
+      parse: data,
+      format: 'JSON',
+      diffs: null,
+      includeComments: false
+    });
+
+    await saveSessionToFile(filePath, content);
+
+    logActivity(`${SOURCE_LABEL[source]}をJSONに出力しました。`);
+    alert(`JSON出力が完了しました。\n${data.rows.length}行のデータを出力しました。`);
+  } catch (error: unknown) {
+    console.error('JSON export failed', error);
+    alert(`JSON出力に失敗しました: ${error}`);
+  } finally {
+    setProcessing(false);
+  }
+}
@@ -110,7 +110,12 @@ export function useComparison({
           value === 'modify' ||
           value === 'change' ||
           value === 'changed' ||
-          value === 'diff'
+          value === 'diff' ||
+          value === 'likely_same' ||
+          value === 'comment_changed' ||
+          value === 'dnp_changed' ||
+          value === 'rotated' ||
+          value === 'moved_xy'
         ) {
           return 'modified';
         }
@@ -1,4 +1,4 @@
-import { useCallback, useEffect, useMemo, useState } from 'react';
+import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
 import type { ProjectPayload, ProjectRecord } from '../types';
 import {
   loadActiveProjectId,
@@ -19,6 +19,7 @@ import {
 } from '../core/project-manager';
 import type { UseBOMDataResult } from './useBOMData';
 import { ProjectStorage, PROJECT_STORAGE_KEY } from '../core/storage';
+import { listenForProjectTransfers } from '../services/window';
 
 const ACTIVE_PROJECT_KEY = 'bomsync_active_project';
 const FAVORITE_PROJECTS_KEY = 'bomsync_favorite_projects';
@@ -332,6 +333,56 @@ export function useProjects(bomA?: UseBOMDataResult, bomB?: UseBOMDataResult): U
     };
   }, [activeProjectId, bomA, bomB, favoriteProjects, projects, setActiveProject]);
 
+  // 転送の受け取りを張り直さないよう、最新のBOMデータ・プロジェクト一覧は参照で渡す
+  const bomRef = useRef({ bomA, bomB });
+  bomRef.current = { bomA, bomB };
+  const projectsRef = useRef(projects);
+  projectsRef.current = projects;
+
+  // 他のウィンドウから転送されたプロジェクトを取り込み、表示する
+  // 転送元は受信確認を受けてプロジェクトを削除するため、保存が終わるまで確認を返さない
+  useEffect(() => {
+    if (!isTauriEnvironment()) {
+      return;
+    }
+
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+    void listenForProjectTransfers(async record => {
+      const prev = projectsRef.current;
+      const exists = prev.some(project => project.id === record.id);
+      const updated = exists
+        ? prev.map(project => (project.id === record.id ? record : project))
+        : [...prev, record];
+      if (!(await ProjectStorage.saveAll(updated))) {
+        throw new Error('転送されたプロジェクトを保存できませんでした。');
+      }
+      projectsRef.current = updated;
+      setProjects(updated);
+      // 保存待ちの古い一覧で上書きされないよう、待機中の保存も最新の一覧に置き換える
+      debouncedSaveProjects(updated);
+
+      const { bomA: currentA, bomB: currentB } = bomRef.current;
+      if (currentA && currentB) {
+        applyProjectPayload(record, currentA, currentB);
+      }
+      setActiveProject(record.id);
+      const displayName = record.name?.trim() || '未命名タブ';
+      logActivity(`別のウィンドウからプロジェクト「${displayName}」を受け取りました。`);
+    }).then(stop => {
+      if (disposed) {
+        stop();
+      } else {
+        unlisten = stop;
+      }
+    });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [debouncedSaveProjects, setActiveProject]);
+
   useEffect(() => {
     const handleBeforeUnload = () => {
       flushSaveProjects();
@@ -3,18 +3,42 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { ParseResult } from '../types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type {
+  BatchReport,
+  ExportOptions,
+  GapReport,
+  ParseError,
+  ParseOptions,
+  ParseResult,
+  PartSummary,
+  Progress
+} from '../types';
 
 /**
  * BOMファイルをパースする
  *
  * @param path - ファイルパス
+ * @param options - 読み込みオプション（省略時は既定値）
  * @returns パース結果（rows, column_roles, column_order含む）
  */
-export async function parseBomFile(path: string): Promise<ParseResult> {
+export async function parseBomFile(path: string, options?: ParseOptions): Promise<ParseResult> {
+  if (options) {
+    return await invoke<ParseResult>('parse_bom_file_with_options', { path, options });
+  }
   return await invoke<ParseResult>('parse_bom_file', { path });
 }
 
+/**
+ * BOMファイルの形式を判定する（拡張子がない・未知の場合は内容から判定）
+ *
+ * @param path - ファイルパス
+ * @returns 形式名（'csv', 'xlsx', 'json', 'eco', 'msf', 'ccf', 'pws', 'bd', 'rpt' など）
+ */
+export async function detectFormat(path: string): Promise<string> {
+  return await invoke<string>('detect_format', { path });
+}
+
 /**
  * セッションファイルを読み込む
  */
@@ -42,3 +66,120 @@ export async function loadDictionary(dictionaryName: string): Promise<string> {
 export async function saveDictionary(dictionaryName: string, content: string): Promise<void> {
   await invoke('save_dictionary', { dictionaryName, content });
 }
+
+/**
+ * 現在の辞書ディレクトリを取得する
+ */
+export async function getDictionaryDir(): Promise<string> {
+  return await invoke<string>('get_dictionary_dir');
+}
+
+/**
+ * 辞書ディレクトリを変更する（null の場合は既定の場所に戻す）
+ *
+ * 書き込めないディレクトリを指定した場合はエラーになる
+ *
+ * @returns 変更後に使われる辞書ディレクトリ
+ */
+export async function setDictionaryDir(path: string | null): Promise<string> {
+  return await invoke<string>('set_dictionary_dir', { path });
+}
+
+/**
+ * Referenceの接頭辞が許可リストに含まれるか検証
+ *
+ * @param parse - BOMデータ
+ * @param allowed - 許可する接頭辞（例: ['C', 'R', 'L', 'U', 'IC']）
+ * @returns 許可されていない接頭辞のReferenceごとの警告
+ */
+export async function validateRefPrefixes(parse: ParseResult, allowed: string[]): Promise<ParseError[]> {
+  return await invoke<ParseError[]>('validate_ref_prefixes', { parse, allowed });
+}
+
+/**
+ * Referenceの欠番を接頭辞ごとに検出する
+ *
+ * @param parse - BOMデータ
+ * @returns 欠番がある接頭辞ごとの報告（使用数が3未満の接頭辞は除く）
+ */
+export async function findReferenceGaps(parse: ParseResult): Promise<GapReport[]> {
+  return await invoke<GapReport[]>('find_reference_gaps', { parse });
+}
+
+/**
+ * 部品型番ごとに実装数を集計する（範囲指定は展開して数える）
+ *
+ * @param parse - BOMデータ
+ * @returns 部品型番ごとの実装数とReferenceのリスト（型番がない場合は "(未指定)"）
+ */
+export async function summarizeByPart(parse: ParseResult): Promise<PartSummary[]> {
+  return await invoke<PartSummary[]>('summarize_by_part', { parse });
+}
+
+/**
+ * BOMデータの内容を表すハッシュ値を取得する（エラー・行番号などの付随情報は含めない）
+ *
+ * @param parse - BOMデータ
+ * @returns SHA-256の16進文字列（内容が同じBOMは同じ値）
+ */
+export async function getBomFingerprint(parse: ParseResult): Promise<string> {
+  return await invoke<string>('bom_fingerprint', { parse });
+}
+
+/**
+ * BOMをエクスポートしてファイルに直接書き込む（XLSXにも対応）
+ *
+ * 一時ファイル経由で置き換えるため、書き込みに失敗しても既存ファイルは壊れない
+ *
+ * @param parse - エクスポートするBOMデータ
+ * @param format - 出力形式（'csv', 'xlsx' など）
+ * @param options - エクスポートオプション
+ * @param path - 書き込み先のファイルパス
+ * @returns 書き込んだバイト数
+ */
+export async function exportBomToPath(
+  parse: ParseResult,
+  format: string,
+  options: ExportOptions,
+  path: string
+): Promise<number> {
+  return await invoke<number>('export_bom_to_path', { parse, format, options, path });
+}
+
+/**
+ * フォルダ内のBOMを一括で変換する（1ファイルの失敗では中断しない）
+ *
+ * @param inputDir - 入力フォルダ（サブフォルダも含めて走査）
+ * @param outputDir - 出力フォルダ
+ * @param targetFormat - 出力形式（'csv', 'eco', 'ccf' など）
+ * @returns ファイルごとの成功・失敗・スキップの結果
+ */
+export async function batchConvert(
+  inputDir: string,
+  outputDir: string,
+  targetFormat: string
+): Promise<BatchReport> {
+  return await invoke<BatchReport>('batch_convert', { inputDir, outputDir, targetFormat });
+}
+
+/**
+ * 時間のかかる処理（比較・IPC登録名の適用）の進捗を受け取る
+ *
+ * @param handler - 進捗を受け取る関数
+ * @returns 受信を解除する関数
+ */
+export async function onBomProgress(handler: (progress: Progress) => void): Promise<UnlistenFn> {
+  return await listen<Progress>('bom-progress', event => handler(event.payload));
+}
+
+/**
+ * 実行中の処理（比較・IPC登録名の適用）に中断を指示する
+ *
+ * 中断された処理は kind が 'cancelled' のエラー（AppError）で終了する
+ *
+ * @param operationId - 処理の呼び出し時に指定した処理ID
+ * @returns 処理が実行中だった場合は true
+ */
+export async function cancelOperation(operationId: string): Promise<boolean> {
+  return await invoke<boolean>('cancel_operation', { operationId });
+}
@@ -3,17 +3,74 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { ParseResult, DiffRow } from '../types';
+import type { ParseResult, CompareResult, DiffRow, NormalizeOptions } from '../types';
 
 /**
  * 2つのBOMを比較して差分を取得
  *
  * @param parseA - BOM A（比較元）
  * @param parseB - BOM B（比較先）
+ * @param normalize - 対応付けキー・比較値の正規化（マージと同じ値を渡す）
+ * @param operationId - 中断に使う処理ID（`cancelOperation` に渡す）
  * @returns 差分情報リスト（行インデックスベース）
  */
-export async function compareBoms(parseA: ParseResult, parseB: ParseResult): Promise<DiffRow[]> {
-  return await invoke<DiffRow[]>('compare_boms', { parseA, parseB });
+export async function compareBoms(
+  parseA: ParseResult,
+  parseB: ParseResult,
+  normalize?: NormalizeOptions,
+  operationId?: string
+): Promise<DiffRow[]> {
+  const options = normalize ? { normalize } : undefined;
+  return await invoke<DiffRow[]>('compare_boms', { parseA, parseB, options, operationId });
+}
+
+/**
+ * 列構成を確認してから2つのBOMを比較（引数は compareBoms と同じ）
+ *
+ * @returns 差分情報リストと、片方のBOMにしかない役割などの列構成の警告
+ */
+export async function compareBomsWithReport(
+  parseA: ParseResult,
+  parseB: ParseResult,
+  normalize?: NormalizeOptions,
+  operationId?: string
+): Promise<CompareResult> {
+  const options = normalize ? { normalize } : undefined;
+  return await invoke<CompareResult>('compare_boms_with_report', {
+    parseA,
+    parseB,
+    options,
+    operationId
+  });
+}
+
+/**
+ * 編集された行のみを比較し直して差分を更新（編集中の再比較用）
+ *
+ * 行の追加・削除で行の並びが変わった場合は compareBoms で全体を比較すること
+ *
+ * @param previous - 前回の比較結果（同じ normalize で比較したもの）
+ * @param parseA - BOM A（比較元）
+ * @param parseB - BOM B（比較先）
+ * @param changedRefs - 編集された行のReference（変更した場合は変更前・変更後の両方）
+ * @param normalize - 対応付けキー・比較値の正規化
+ * @returns 更新後の差分情報リスト
+ */
+export async function updateDiff(
+  previous: DiffRow[],
+  parseA: ParseResult,
+  parseB: ParseResult,
+  changedRefs: string[],
+  normalize?: NormalizeOptions
+): Promise<DiffRow[]> {
+  const options = normalize ? { normalize } : undefined;
+  return await invoke<DiffRow[]>('update_diff', {
+    previous,
+    parseA,
+    parseB,
+    changedRefs,
+    options
+  });
 }
 
 /**
@@ -21,8 +78,71 @@ export async function compareBoms(parseA: ParseResult, parseB: ParseResult): Pro
  *
  * @param parseA - 更新元のBOM
  * @param parseB - 更新内容のBOM
+ * @param normalize - 対応付けキーの正規化（比較と同じ値を渡す）
  * @returns マージ後のParseResult
  */
-export async function updateAndAppendBoms(parseA: ParseResult, parseB: ParseResult): Promise<ParseResult> {
-  return await invoke<ParseResult>('update_and_append_boms', { parseA, parseB });
+export async function updateAndAppendBoms(
+  parseA: ParseResult,
+  parseB: ParseResult,
+  normalize?: NormalizeOptions
+): Promise<ParseResult> {
+  return await invoke<ParseResult>('update_and_append_boms', { parseA, parseB, normalize });
+}
+
+/**
+ * 複数のBOMの行をそのまま縦に連結（Referenceでの対応付けは行わない）
+ *
+ * @param parts - 連結するBOM（この順に行を並べる）
+ * @returns 連結したParseResult（列は役割・ヘッダー名でまとめる）
+ */
+export async function concatBoms(parts: ParseResult[]): Promise<ParseResult> {
+  return await invoke<ParseResult>('concat_boms', { parts });
+}
+
+/**
+ * 差分レポート（変更された項目ごとに Ref, Status, Field, A, B の1行）をCSVで取得
+ *
+ * @param parseA - BOM A（比較元）
+ * @param parseB - BOM B（比較先）
+ * @param diffs - compareBoms の結果
+ * @returns CSV形式のレポート
+ */
+export async function exportDiffReport(
+  parseA: ParseResult,
+  parseB: ParseResult,
+  diffs: DiffRow[]
+): Promise<string> {
+  return await invoke<string>('export_diff_report', { parseA, parseB, diffs });
+}
+
+/**
+ * 差分レポートを色分けしたHTMLの表（単体で表示できる断片）として取得
+ *
+ * @param parseA - BOM A（比較元）
+ * @param parseB - BOM B（比較先）
+ * @param diffs - compareBoms の結果
+ * @returns `<table>` 要素のHTML
+ */
+export async function exportDiffHtml(
+  parseA: ParseResult,
+  parseB: ParseResult,
+  diffs: DiffRow[]
+): Promise<string> {
+  return await invoke<string>('export_diff_html', { parseA, parseB, diffs });
+}
+
+/**
+ * 差分レポートをMarkdownの表として取得
+ *
+ * @param parseA - BOM A（比較元）
+ * @param parseB - BOM B（比較先）
+ * @param diffs - compareBoms の結果
+ * @returns Markdown形式の表
+ */
+export async function exportDiffMarkdown(
+  parseA: ParseResult,
+  parseB: ParseResult,
+  diffs: DiffRow[]
+): Promise<string> {
+  return await invoke<string>('export_diff_markdown', { parseA, parseB, diffs });
 }
@@ -3,16 +3,43 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { ParseResult, FormatOptions } from '../types';
+import type {
+  CellEdit,
+  CleanseOptions,
+  DedupResult,
+  ExpandOptions,
+  ManufacturerAliasEntry,
+  ParseResult,
+  PipelineStep,
+  RenumberResult,
+  FormatOptions,
+  Transform,
+  TransformResult
+} from '../types';
 
 /**
  * Referenceを展開（C1-C4 → C1, C2, C3, C4）
  *
  * @param parse - 元のBOMデータ
+ * @param options - 展開オプション（降順の範囲の扱い、1セルあたりの展開数の上限）
  * @returns 展開後のBOMデータ
  */
-export async function expandReference(parse: ParseResult): Promise<ParseResult> {
-  return await invoke<ParseResult>('expand_reference', { parse });
+export async function expandReference(parse: ParseResult, options?: ExpandOptions): Promise<ParseResult> {
+  return await invoke<ParseResult>('expand_reference', { parse, options });
+}
+
+/**
+ * Referenceを展開し、元に戻すための変更記録も取得
+ *
+ * @param parse - 元のBOMデータ
+ * @param options - 展開オプション（expandReference と同じ）
+ * @returns 展開後のBOMデータと変更記録
+ */
+export async function expandReferenceWithTransform(
+  parse: ParseResult,
+  options?: ExpandOptions
+): Promise<TransformResult> {
+  return await invoke<TransformResult>('expand_reference_with_transform', { parse, options });
 }
 
 /**
@@ -25,6 +52,64 @@ export async function splitReferenceRows(parse: ParseResult): Promise<ParseResul
   return await invoke<ParseResult>('split_reference_rows', { parse });
 }
 
+/**
+ * 指定した接頭辞のReferenceを連番で付け直す（C1, C3, C4 → C1, C2, C3）
+ *
+ * @param parse - 元のBOMデータ
+ * @param prefix - 付け直す接頭辞（ほかの接頭辞のReferenceは変更しない）
+ * @param start - 最初に割り当てる番号
+ * @returns 付け直したBOMデータと、変更したReferenceの一覧
+ */
+export async function renumberReferences(
+  parse: ParseResult,
+  prefix: string,
+  start = 1
+): Promise<RenumberResult> {
+  return await invoke<RenumberResult>('renumber_references', { parse, prefix, start });
+}
+
+/**
+ * キーが重複する行を削除
+ *
+ * @param parse - 元のBOMデータ
+ * @param keyRoles - キーに使う役割（例: ['ref']、空の場合は行全体の値で判定）
+ * @param preferComplete - 値のあるセルが最も多い行を残すか（省略時は最初の行を残す）
+ * @returns 重複を削除したBOMデータと削除した行数
+ */
+export async function deduplicateRows(
+  parse: ParseResult,
+  keyRoles: string[],
+  preferComplete?: boolean
+): Promise<DedupResult> {
+  return await invoke<DedupResult>('deduplicate_rows', { parse, keyRoles, preferComplete });
+}
+
+/**
+ * 指定した役割の列の値で行を並べ替える（値が空の行は末尾）
+ *
+ * @param parse - 元のBOMデータ
+ * @param byRole - 並べ替えに使う役割（'ref', 'part_no' など）
+ * @param natural - Referenceを自然順（C2 < C10）で並べるか
+ * @returns 並べ替えたBOMデータ（列構造は変更しない）
+ */
+export async function sortRows(parse: ParseResult, byRole: string, natural: boolean): Promise<ParseResult> {
+  return await invoke<ParseResult>('sort_rows', { parse, byRole, natural });
+}
+
+/**
+ * メーカー列の表記ゆれを正式名に置き換える（辞書にない値はそのまま）
+ *
+ * @param parse - 元のBOMデータ
+ * @param aliases - メーカー名エイリアス（辞書 'manufacturer_alias' の内容）
+ * @returns メーカー列を置き換えたBOMデータ
+ */
+export async function applyManufacturerAliases(
+  parse: ParseResult,
+  aliases: ManufacturerAliasEntry[]
+): Promise<ParseResult> {
+  return await invoke<ParseResult>('apply_manufacturer_aliases', { parse, aliases });
+}
+
 /**
  * 空白セルを埋める
  *
@@ -35,14 +120,59 @@ export async function fillBlankCells(parse: ParseResult): Promise<ParseResult> {
   return await invoke<ParseResult>('fill_blank_cells', { parse });
 }
 
+/**
+ * 空白セルを埋め、元に戻すための変更記録も取得
+ *
+ * @param parse - 元のBOMデータ
+ * @returns 空欄補完後のBOMデータと変更記録
+ */
+export async function fillBlankCellsWithTransform(parse: ParseResult): Promise<TransformResult> {
+  return await invoke<TransformResult>('fill_blank_cells_with_transform', { parse });
+}
+
+/**
+ * 変更記録を使って処理前の状態に戻す
+ *
+ * @param parse - 処理後のBOMデータ
+ * @param transform - 処理が返した変更記録
+ * @returns 処理前のBOMデータ
+ */
+export async function undoTransform(parse: ParseResult, transform: Transform): Promise<ParseResult> {
+  return await invoke<ParseResult>('undo_transform', { parse, transform });
+}
+
 /**
  * テキストデータのクレンジング
  *
  * @param parse - 元のBOMデータ
+ * @param options - クレンジングオプション（省略時は括弧の削除と半角変換の両方を行う）
  * @returns クレンジング後のBOMデータ
  */
-export async function cleanseTextData(parse: ParseResult): Promise<ParseResult> {
-  return await invoke<ParseResult>('cleanse_text_data', { parse });
+export async function cleanseTextData(parse: ParseResult, options?: CleanseOptions): Promise<ParseResult> {
+  return await invoke<ParseResult>('cleanse_text_data', { parse, options });
+}
+
+/**
+ * テキストクレンジングで変更されるセルを取得（データは変更しない）
+ *
+ * @param parse - 元のBOMデータ
+ * @param options - クレンジングオプション（cleanseTextData と同じ既定値）
+ * @returns 値が変わるセルの一覧
+ */
+export async function previewCleanse(parse: ParseResult, options?: CleanseOptions): Promise<CellEdit[]> {
+  return await invoke<CellEdit[]>('preview_cleanse', { parse, options });
+}
+
+/**
+ * 1つのセルを読み込み時の値に戻す
+ *
+ * @param parse - BOMデータ（keepOriginalRows を指定して読み込んだもの）
+ * @param row - 行インデックス
+ * @param column - 列インデックス
+ * @returns セルを戻したBOMデータ
+ */
+export async function revertCell(parse: ParseResult, row: number, column: number): Promise<ParseResult> {
+  return await invoke<ParseResult>('revert_cell', { parse, row, column });
 }
 
 /**
@@ -55,3 +185,19 @@ export async function cleanseTextData(parse: ParseResult): Promise<ParseResult>
 export async function applyFormatRules(parse: ParseResult, options: FormatOptions): Promise<ParseResult> {
   return await invoke<ParseResult>('apply_format_rules', { parse, options });
 }
+
+/**
+ * 複数の前処理をまとめて順に適用（処理ごとのBOMデータのやり取りを省く）
+ *
+ * @param parse - 元のBOMデータ
+ * @param steps - 適用する処理（先頭から順に実行）
+ * @param operationId - 中断に使う処理ID（cancel_operation に渡す）
+ * @returns 全ての処理を適用したBOMデータ
+ */
+export async function runPipeline(
+  parse: ParseResult,
+  steps: PipelineStep[],
+  operationId?: string
+): Promise<ParseResult> {
+  return await invoke<ParseResult>('run_pipeline', { parse, steps, operationId });
+}
@@ -5,15 +5,23 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { emit, type UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
 import type { ProjectRecord } from '../types';
+import { logger } from '../utils/logger';
 
 export interface WindowPosition {
   x: number;
   y: number;
 }
 
+export interface ProjectWindowInfo {
+  label: string;
+  created: boolean;
+}
+
 /**
- * 新しいウィンドウでプロジェクトを開く
+ * 新しいウィンドウでプロジェクトを開く（既に開いている場合はそのウィンドウを前面に出す）
  *
  * @param projectId - 開くプロジェクトのID
  * @param position - ウィンドウの位置（オプション）
@@ -28,9 +36,71 @@ export async function openProjectInNewWindow(
   });
 }
 
+/**
+ * プロジェクトのウィンドウを前面に出し、開いていない場合は新しく開く
+ *
+ * @param projectId - 開くプロジェクトのID
+ * @param position - 新しく開く場合のウィンドウの位置（オプション）
+ * @returns ウィンドウのラベルと、新しく開いたかどうか
+ */
+export async function focusOrCreateProjectWindow(
+  projectId: string,
+  position?: WindowPosition
+): Promise<ProjectWindowInfo> {
+  return invoke<ProjectWindowInfo>('focus_or_create_project_window', {
+    projectId,
+    position: position || null
+  });
+}
+
+/**
+ * 現在のウィンドウの位置とサイズをプロジェクトに対応付けて保存
+ *
+ * 次に同じプロジェクトを別ウィンドウで開くときに復元される
+ *
+ * @param projectId - プロジェクトID
+ */
+export async function saveWindowState(projectId: string): Promise<void> {
+  await invoke('save_window_state', { projectId });
+}
+
+/**
+ * 現在のウィンドウの移動・リサイズを監視し、落ち着いた時点で配置を保存
+ *
+ * @param projectId - プロジェクトID
+ * @param delayMs - 最後の移動・リサイズから保存までの待ち時間（ミリ秒）
+ * @returns 監視を解除する関数
+ */
+export async function watchWindowState(
+  projectId: string,
+  delayMs = 500
+): Promise<() => void> {
+  const currentWindow = getCurrentWindow();
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  const schedule = () => {
+    if (timer) clearTimeout(timer);
+    timer = setTimeout(() => {
+      saveWindowState(projectId).catch(error => {
+        logger.warn('[watchWindowState] Failed to save window state', error);
+      });
+    }, delayMs);
+  };
+
+  const unlistenMoved = await currentWindow.onMoved(schedule);
+  const unlistenResized = await currentWindow.onResized(schedule);
+  return () => {
+    if (timer) clearTimeout(timer);
+    unlistenMoved();
+    unlistenResized();
+  };
+}
+
 /**
  * プロジェクトデータを別ウィンドウに転送
  *
+ * 転送先が受信を確認するまで待つ。確認が届かない場合はエラーになるため、
+ * 元ウィンドウのプロジェクトは成功した場合にのみ削除すること
+ *
  * @param targetLabel - 転送先ウィンドウのラベル
  * @param project - 転送するプロジェクトデータ
  */
@@ -44,3 +114,42 @@ export async function transferProjectToWindow(
     projectData
   });
 }
+
+/** 対応しているプロジェクト転送データの形式のバージョン */
+export const PROJECT_TRANSFER_VERSION = 1;
+
+/** 別ウィンドウから転送されるプロジェクト（`merge-project` イベントの内容） */
+export interface ProjectTransfer {
+  transfer_id: number;
+  project_id: string;
+  data_version: number;
+  payload: ProjectRecord;
+}
+
+/**
+ * 他のウィンドウから転送されたプロジェクトを受け取り、受信確認を返す
+ *
+ * 受信確認はハンドラーが取り込みを終えてから返す。ハンドラーが例外を投げた場合や、
+ * 対応していない形式のバージョンの場合は、その内容を転送元に返す（転送元はプロジェクトを残す）
+ *
+ * @param handler - 転送されたプロジェクトを取り込む処理
+ * @returns 受け取りを解除する関数
+ */
+export async function listenForProjectTransfers(
+  handler: (project: ProjectRecord) => void | Promise<void>
+): Promise<UnlistenFn> {
+  return getCurrentWindow().listen<ProjectTransfer>('merge-project', async event => {
+    const { transfer_id: transferId, data_version: dataVersion, payload } = event.payload;
+    let error: string | null = null;
+    try {
+      if (dataVersion > PROJECT_TRANSFER_VERSION) {
+        throw new Error(`未対応の転送データ形式です（バージョン ${dataVersion}）`);
+      }
+      await handler(payload);
+    } catch (err) {
+      logger.error('[listenForProjectTransfers] Failed to import transferred project', err);
+      error = err instanceof Error ? err.message : String(err);
+    }
+    await emit('merge-project-ack', { transferId, error });
+  });
+}
@@ -2,6 +2,8 @@
  * BOM（部品表）関連の型定義
  */
 
+import type { DiffRow } from './diff';
+
 /**
  * 列メタデータ
  */
@@ -20,6 +22,93 @@ export interface ParseError {
   severity: 'error' | 'warning' | 'info';
 }
 
+/**
+ * バックエンドのエラーの種類
+ */
+export type ErrorKind =
+  | 'not_found'
+  | 'unsupported_format'
+  | 'parse'
+  | 'io'
+  | 'validation'
+  | 'internal'
+  | 'cancelled';
+
+/**
+ * バックエンドのコマンドが返すエラー
+ */
+export interface AppError {
+  message: string;
+  kind: ErrorKind;
+}
+
+/**
+ * Reference番号の欠番（接頭辞ごと）
+ */
+export interface GapReport {
+  prefix: string;
+  min: number;
+  max: number;
+  count: number;
+  missing: number[];
+}
+
+/**
+ * 部品型番ごとの実装数
+ */
+export interface PartSummary {
+  part_no: string;
+  ref_count: number;
+  refs: string[];
+  /** 未実装（DNP）のReference（実装数には含めない） */
+  dnp_refs: string[];
+}
+
+/**
+ * 一括変換の1ファイルの結果
+ */
+export interface BatchFileResult {
+  input_path: string;
+  output_path: string | null;
+  /** "success" | "error" | "info"（対応していない形式のためスキップ） */
+  severity: 'success' | 'error' | 'info';
+  message: string | null;
+  warning_count: number;
+}
+
+/**
+ * フォルダ内のBOMの一括変換結果
+ */
+export interface BatchReport {
+  files: BatchFileResult[];
+  succeeded: number;
+  failed: number;
+  skipped: number;
+}
+
+/**
+ * 時間のかかる処理の進捗（"bom-progress" イベント）
+ */
+export interface Progress {
+  /** 処理の種類（"compare_boms", "apply_ipc_names" など） */
+  stage: string;
+  processed: number;
+  total: number;
+}
+
+/**
+ * 読み込み時の検証結果の集計
+ */
+export interface ValidationSummary {
+  total_rows: number;
+  missing_reference_rows: number;
+  duplicate_reference_rows: number;
+  missing_part_no_rows: number;
+  invalid_char_rows: number;
+  /** 未実装（DNP）の行数 */
+  dnp_rows: number;
+}
+
 /**
  * パース結果
  *
@@ -59,6 +148,91 @@ export interface ParseResult {
 
   /** 構造化エラー情報 */
   structured_errors?: ParseError[];
+
+  /** 検証結果の集計（CSV/Excel読み込み時のみ） */
+  validation_summary?: ValidationSummary | null;
+
+  /** 検出したヘッダー行の位置（元ファイルの行インデックス、0始まり） */
+  header_row_index?: number | null;
+
+  /** データ開始行の位置（元ファイルの行インデックス、0始まり） */
+  data_start_index?: number;
+
+  /** セルの書式（rows と同じ並び、Excel読み込み時のみ） */
+  cell_styles?: CellStyle[][] | null;
+
+  /** 読み込んだPADS-ECOファイルの部品データ以外の行（ECO出力時に再現） */
+  eco_template?: EcoTemplate | null;
+
+  /** 読み込み時のセルの値（keepOriginalRows を指定して読み込んだ場合のみ） */
+  original_rows?: string[][] | null;
+}
+
+/**
+ * BOMファイル読み込み時のオプション
+ */
+export interface ParseOptions {
+  /** 無効文字の警告対象外とする非ASCII文字（"±" や "ぁ-ん" のような範囲） */
+  allowedChars?: string[];
+  /** 読み込み時のセルの値を original_rows に保持する（セル単位で元に戻す場合） */
+  keepOriginalRows?: boolean;
+  /** 読み込み前に読み飛ばす先頭の行数（CSV・Excel、開始行の自動判定より優先） */
+  skipLines?: number;
+  /** CSVの引用符（既定は '"'） */
+  quote?: string;
+  /** CSVのエスケープ文字（'\\' など、省略時はエスケープなし） */
+  escape?: string | null;
+  /** CSVの引用符内で "" を引用符1文字として扱う（既定は true） */
+  doubleQuote?: boolean;
+  /** ヘッダー行の扱い（auto: 自動判定、first_row: 先頭行をヘッダー、none: ヘッダーなし） */
+  headerMode?: 'auto' | 'first_row' | 'none';
+}
+
+/**
+ * PADS-ECO出力のテンプレート（部品データの前後に出力する行）
+ */
+export interface EcoTemplate {
+  /** 部品データの前の行（*PART* 行まで） */
+  header: string[];
+  /** 部品データの後の行（*END* 行まで） */
+  footer: string[];
+}
+
+/**
+ * エクスポートオプション
+ */
+export interface ExportOptions {
+  /** 出力フォーマット（'csv', 'eco', 'ccf', 'xlsx' など） */
+  format?: string;
+  includeDiffComments?: boolean;
+  /** 差分ステータスでの絞り込み（'all', 'added', 'removed', 'modified'） */
+  filter?: string | null;
+  /** 出力する列名または列ID（空の場合は全列） */
+  headers?: string[];
+  diffs?: DiffRow[] | null;
+  /** 出力するBOMが差分情報のどちら側か（指定時は a_index / b_index で行に対応付ける） */
+  diffSide?: 'a' | 'b' | null;
+  writeBom?: boolean;
+  lineEnding?: 'lf' | 'crlf';
+  delimiter?: string;
+  groupBy?: 'part_no' | 'value' | 'manufacturer';
+  ecoTemplate?: EcoTemplate | null;
+  extraRoles?: string[];
+  /** 未実装（DNP）の行を出力しない */
+  excludeDnp?: boolean;
+  /** CCF/MSF出力時の字下げ（空白の数、既定: entry 16・continuation 25） */
+  cadIndent?: { entry?: number; continuation?: number };
+  /** 出力する行のインデックス（指定した順に出力、省略時は全行。filter はこの行に適用） */
+  rowIndices?: number[] | null;
+}
+
+/**
+ * セルの書式情報
+ */
+export interface CellStyle {
+  /** 塗りつぶし色（"#rrggbb"） */
+  fill_color: string | null;
+  strikethrough: boolean;
 }
 
 /**
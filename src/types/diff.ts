@@ -1,3 +1,5 @@
+import type { ParseResult } from './bom';
+
 /**
  * BOM差分比較関連の型定義
  */
@@ -11,7 +13,7 @@
  * - 実際のデータはParseResultから行インデックスで取得
  */
 export interface DiffRow {
-  /** 差分ステータス: "added", "deleted", "changed", "unchanged" */
+  /** 差分ステータス: "added", "deleted", "changed", "likely_same", "comment_changed", "dnp_changed", "rotated", "moved_xy", "unchanged" */
   status: string;
 
   /** BOM Aの行インデックス（削除または変更の場合に設定） */
@@ -25,4 +27,87 @@ export interface DiffRow {
 
   /** 変更された列のリスト */
   changed_columns: string[];
+
+  /** 変更された列ごとの変更前・変更後の値（changed_columns と同じ順） */
+  changes?: FieldChange[];
+}
+
+/**
+ * 変更された列の値
+ */
+export interface FieldChange {
+  /** BOM Aの列ID */
+  column_id: string;
+
+  /** 列の役割（役割のない列は null） */
+  role: string | null;
+
+  /** BOM Aの値 */
+  a_value: string;
+
+  /** BOM Bの値（対応する列がない場合は空） */
+  b_value: string;
+}
+
+/**
+ * 列構成の確認付きの比較結果
+ */
+export interface CompareResult {
+  /** 差分情報リスト */
+  diffs: DiffRow[];
+
+  /** 列構成の警告（片方のBOMにしかない役割など） */
+  warnings: string[];
+}
+
+/**
+ * 対応付けキー・比較値の正規化オプション（比較・マージで共通）
+ */
+export interface NormalizeOptions {
+  /** 大文字・小文字を区別しない */
+  caseInsensitive?: boolean;
+
+  /** 全角英数字・記号・スペースを半角とみなす */
+  widthFold?: boolean;
+
+  /** 前後の空白を無視する（既定: true） */
+  trim?: boolean;
+
+  /** 連続する空白を1つとみなす */
+  collapseSpace?: boolean;
+}
+
+/**
+ * 3方向マージの競合（Reference + 列IDで特定）
+ */
+export interface MergeConflict {
+  /** 競合の種類: "modified", "added_both", "deleted_in_a", "deleted_in_b" */
+  kind: string;
+
+  /** Reference値 */
+  ref_value: string;
+
+  /** マージ結果での行インデックス */
+  merged_index: number;
+
+  /** 列ID（行単位の競合の場合はnull） */
+  column_id: string | null;
+
+  /** 列名 */
+  column_name: string | null;
+
+  base_value: string | null;
+  a_value: string | null;
+  b_value: string | null;
+}
+
+/**
+ * 3方向マージの結果
+ */
+export interface MergeResult {
+  /** マージ後のBOM（BOM Aの列構造） */
+  merged: ParseResult;
+
+  /** 自動でマージできなかった箇所 */
+  conflicts: MergeConflict[];
 }
@@ -2,6 +2,8 @@
  * 前処理パイプライン関連の型定義
  */
 
+import type { CellStyle, ParseError, ParseResult, ValidationSummary } from './bom';
+
 export interface PreprocessBlock {
   id: string;
   type: string;
@@ -10,7 +12,133 @@ export interface PreprocessBlock {
   icon: string;
 }
 
+/**
+ * テキストクレンジングのオプション（省略した項目は既定値）
+ */
+export interface CleanseOptions {
+  /** 括弧 "()" "（）" を削除するか（既定: false） */
+  removeParentheses?: boolean;
+  /** 全角英数字・記号・スペースを半角に変換するか（既定: true） */
+  normalizeWidth?: boolean;
+  /** 前後の空白を削除し、連続する空白を1つにまとめるか（既定: true） */
+  normalizeWhitespace?: boolean;
+}
+
+/**
+ * 重複行の削除の結果
+ */
+export interface DedupResult {
+  /** 重複を削除したBOMデータ */
+  result: ParseResult;
+  /** 削除した行数 */
+  removed_count: number;
+}
+
+/**
+ * Reference展開のオプション
+ */
+export interface ExpandOptions {
+  /** 降順の範囲指定（"C5-C1"）を昇順に展開するか（既定: false、エラーにする） */
+  allowDescending?: boolean;
+  /** 1つのセルから展開するReferenceの上限（既定: 10000） */
+  maxExpansion?: number;
+}
+
 export interface FormatOptions {
   use_strikethrough: boolean;
   use_cell_color: boolean;
 }
+
+/**
+ * 一括処理（runPipeline）の処理ステップ（オプションは type と同じオブジェクトに指定）
+ */
+export type PipelineStep =
+  | ({ type: 'expand' } & ExpandOptions)
+  | { type: 'split_refs' }
+  | { type: 'fill_blanks' }
+  | ({ type: 'cleanse' } & CleanseOptions)
+  | {
+      type: 'apply_ipc';
+      /** IPC登録名ルール（辞書 "ipc_master" の内容） */
+      rules: Record<string, unknown>[];
+      /** 例外マスタ（辞書 "exception_master" の内容） */
+      exceptions?: Record<string, unknown>[];
+    }
+  | ({ type: 'format' } & FormatOptions);
+
+/**
+ * セルの値の変更（行インデックスは処理後の位置）
+ */
+export interface CellEdit {
+  row: number;
+  column: number;
+  before: string;
+  after: string;
+}
+
+/**
+ * 行の置き換え（処理前の removed_rows が index から inserted_count 行に置き換わった）
+ */
+export interface RowSplice {
+  index: number;
+  inserted_count: number;
+  removed_rows: string[][];
+}
+
+/**
+ * 行データ以外の付随情報（処理前の値）
+ */
+export interface ParseMetadata {
+  errors: string[];
+  row_numbers: number[];
+  structured_errors: ParseError[] | null;
+  validation_summary: ValidationSummary | null;
+  header_row_index: number | null;
+  data_start_index: number;
+  cell_styles: CellStyle[][] | null;
+  original_rows?: string[][] | null;
+}
+
+/**
+ * 処理による変更の記録（BOM全体のスナップショットの代わりにUndo履歴として保持する）
+ */
+export interface Transform {
+  operation: string;
+  cell_edits: CellEdit[];
+  row_splices: RowSplice[];
+  previous_metadata: ParseMetadata | null;
+}
+
+/**
+ * 処理結果と変更の記録
+ */
+export interface TransformResult {
+  result: ParseResult;
+  transform: Transform;
+}
+
+/**
+ * Referenceの変更（"C3" → "C2" など）
+ */
+export interface ReferenceRename {
+  old: string;
+  new: string;
+}
+
+/**
+ * Referenceの付け直しの結果
+ */
+export interface RenumberResult {
+  /** Referenceを付け直したBOMデータ */
+  result: ParseResult;
+  /** 変更したReference（出現順） */
+  mapping: ReferenceRename[];
+}
+
+/**
+ * メーカー名の表記ゆれ（'Murata Mfg.'、'村田製作所' など → 正式名）
+ */
+export interface ManufacturerAliasEntry {
+  alias: string;
+  canonical: string;
+}
@@ -23,7 +23,7 @@ import { setProcessing, logActivity } from '../utils';
 export async function openFileDialog(dataset: DatasetKey): Promise<void> {
   const filePath = await open({
     filters: [
-      { name: 'BOM Files', extensions: ['csv', 'xlsx', 'xls', 'eco', 'ccf', 'msf'] }
+      { name: 'BOM Files', extensions: ['csv', 'xlsx', 'xlsm', 'xlsb', 'xls', 'eco', 'ccf', 'msf'] }
     ],
     multiple: false
   });
@@ -17,9 +17,10 @@ export function registerFilePickerButtons(onFileSelected: (dataset: DatasetKey,
     button.addEventListener('click', async () => {
       const file = await open({
         filters: [
-          { name: 'BOM Files', extensions: ['csv', 'xlsx'] },
+          { name: 'BOM Files', extensions: ['csv', 'xlsx', 'xlsm', 'xlsb', 'xls'] },
           { name: 'CAD Netlist', extensions: ['eco', 'ccf', 'msf', 'pws', 'bd', 'rpt', 'net'] },
           { name: 'Text Files', extensions: ['txt'] },
+          { name: 'JSON', extensions: ['json'] },
           { name: 'すべてのファイル', extensions: ['*'] }
         ]
       });
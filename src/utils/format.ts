@@ -67,6 +67,8 @@ export function displayNameForColumn(column: string): string {
       return 'コメント';
     case 'manufacturer':
       return 'メーカー名';
+    case 'dnp':
+      return 'DNP（未実装）';
     default:
       return column;
   }